@@ -1,26 +1,635 @@
-use crate::api::{create_new_task, fetch_task_detail, fetch_tasks};
-use crate::models::{Task, TaskDetail};
-use crate::parser::parse_task_input;
-use crossterm::event::KeyCode;
+use crate::auth::open_browser;
+use crate::board::{bucket_tasks, position_for_move, Board};
+use crate::cache::Lru;
+use crate::calendar::{to_api_datetime, to_api_datetime_at, DatePicker};
+use crate::color::{hex_color, Theme, TASK_COLORS};
+use crate::config::{Profile, ProjectSettings, QuickFilter, BUILTIN_FILTERS, MINE_FILTER};
+use crate::editor::{edit_text, single_line, to_html};
+use crate::event::{AppEvent, Key, KeyCode};
+use crate::export::{task_markdown, ExportTarget};
+use crate::fuzzy::{filter_tasks, fuzzy_filter};
+use crate::history::History;
+use crate::html::extract_links;
+use crate::ics::{CalendarFeed, ExternalEvent};
+use crate::members::{Member, Members};
+use crate::parser::{completion_token, parse_task_input, parse_task_ref, ParsedTask, TaskRef};
+use crate::picker::Picker;
+use crate::row_format::RowPart;
+use crate::state::{Draft, State, TaskIndex};
+use crate::teams::{TeamInput, Teams};
+use crate::timer::{
+    append_timesheet, expand_home, format_elapsed, time_comment, timesheet_row, Timer,
+};
+use crate::worker::{
+    Action, Change, LabelsFor, Message, ProjectsFor, Reload, Then, UserFor, Worker,
+};
+use chrono::{Local, NaiveDate, NaiveTime};
+use ratatui::text::Line;
 use ratatui::widgets::ListState;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::error::Error;
 use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicUsize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use vikunja_client::api::{
+    is_unauthorized, ApiResult, HttpClient, LoadProgress, NewTask, ReactionTarget, TaskPage,
+    TaskQuery, TotpRequired, VikunjaApi,
+};
+use vikunja_client::models::{
+    Bucket, Capabilities, Comment, Label, Notification, Project, ProjectView, Reactions,
+    ServerInfo, Task, User, MIN_SUPPORTED_VERSION,
+};
+
+// How often notifications are checked in the background
+const NOTIFICATION_INTERVAL: Duration = Duration::from_secs(60);
+
+// How long a deleted task can be brought back with u
+const UNDO_WINDOW: Duration = Duration::from_secs(10);
+
+// How many opened tasks are kept to show again without fetching them
+const DETAIL_CACHE_SIZE: usize = 50;
+
+// Columns code blocks scroll by with `<` and `>`
+const DETAIL_SCROLL_STEP: usize = 8;
+
+// Days the done review looks back when the config doesn't say
+pub const DEFAULT_REVIEW_DAYS: u32 = 7;
+
+// What the reaction picker offers
+pub const REACTIONS: &[&str] = &["👍", "👎", "❤️", "🎉", "😄", "😕", "🚀", "👀"];
 
 pub struct App {
+    pub api: Arc<dyn VikunjaApi>,
+    // Runs the API calls, the key handler only sends it actions
+    worker: Worker,
+    // Numbers the loads of the list and the board, results of older ones are
+    // dropped when they arrive
+    generation: u64,
+    // The task whose details are being loaded
+    opening: Option<u64>,
     pub tasks: Vec<Task>,
+    // Rows of the list after the `/` filter, as indices into tasks. The list
+    // selection indexes this.
+    pub visible_rows: Vec<usize>,
+    // Styled list rows by task index, built as they scroll into view and
+    // dropped whenever the tasks or the filter change
+    pub task_lines: Vec<Option<Line<'static>>>,
+    pub list_filter: Option<String>,
+    // The `:` command line being typed
+    pub command: String,
     pub state: ListState,
-    pub task_detail: Option<TaskDetail>,
+    pub task_detail: Option<Task>,
+    pub task_comments: Vec<Comment>,
+    // Whether code blocks in the details wrap, and how far they are
+    // scrolled sideways when they don't
+    pub detail_wrap: bool,
+    pub detail_scroll_x: usize,
     pub input_mode: InputMode,
     pub active_input: ActiveInput,
     pub new_task_title: String,
     pub new_task_description: String,
+    pub new_task_due: Option<NaiveDate>,
+    pub new_task_reminder: Option<NaiveDate>,
+    pub new_task_start: Option<NaiveDate>,
+    pub new_task_end: Option<NaiveDate>,
+    // Index into TASK_COLORS
+    pub new_task_color: Option<usize>,
+    // The project picked for the new task's +project, when it was ambiguous
+    new_task_project: Option<Project>,
+    // The projects offered by the ProjectChoice and DuplicateParent pickers
+    project_choices: Vec<Project>,
+    // The project being duplicated while its parent is picked
+    duplicating: Option<Project>,
+    // Labels of the new task that don't exist yet, and whether creating
+    // them was agreed to
+    pub new_labels: Vec<Label>,
+    new_labels_agreed: bool,
+    // The task the add popup is about to create, shown for a last look
+    // before it goes out unless confirm_new_task is off
+    pub new_task_preview: Option<NewTaskPreview>,
+    pub confirm_new_task: bool,
+    // How the last task sent to be created came out
+    created: Option<Creation>,
+    // Kept between sessions when there is a path to keep it at
+    pub saved: State,
+    state_path: Option<PathBuf>,
+    // Open tasks seen, for the shell to complete --task from
+    task_index: TaskIndex,
+    task_index_path: Option<PathBuf>,
+    // The draft of the task being created, dropped once it is
+    submitted_draft: Option<Draft>,
+    pub label_color: Option<String>,
+    pub date_picker: Option<DatePicker>,
+    pub date_target: DateTarget,
     pub page: usize,
+    // Pages of the current list and tasks on this one, as the server counts
+    // them
+    pub total_pages: usize,
+    result_count: usize,
+    // Tasks on every page but the last, once a page before the last is seen
+    page_size: Option<usize>,
     pub show_done_tasks: bool,
+    // The done toggle as last set in each project, None for all tasks
+    show_done_by_view: HashMap<Option<u64>, bool>,
+    // With done tasks shown in the list they come last under a "Completed"
+    // separator. These are how many, and whether they are folded away.
+    pub completed_rows: usize,
+    pub collapse_completed: bool,
+    pub projects: Vec<Project>,
+    pub current_project: Option<Project>,
+    pub picker: Option<Picker>,
+    pub quick_filters: Vec<QuickFilter>,
+    pub active_filter: Option<QuickFilter>,
+    pub view_kind: ViewKind,
+    pub board: Option<Board>,
+    // The list view of the current project, whose manual order the list shows
+    pub list_view_id: Option<u64>,
+    pub read_only: bool,
+    pub message: Option<String>,
+    pub all_tasks: Vec<Task>,
+    pub all_tasks_load: Option<AllTasksLoad>,
+    pub server_info: Option<ServerInfo>,
+    pub auth_form: AuthForm,
+    pub pending_retry: Option<Retry>,
+    pub profiles: Vec<Profile>,
+    pub profile: String,
+    // By project id or lowercased title, as the config names them
+    project_settings: HashMap<String, ProjectSettings>,
+    pub confirm_done_bucket: bool,
+    pub show_done_at: bool,
+    // Whether the window title follows the view
+    pub terminal_title: bool,
+    // Time of day for due dates given without one, noon when unset
+    pub default_due_time: Option<NaiveTime>,
+    // How far back the done review looks
+    pub done_review_days: u32,
+    // Text the done review searches every done task for instead, empty for
+    // all of them
+    pub archive: Option<String>,
+    // Tasks picked in the done review for reopening together
+    pub marked: BTreeSet<u64>,
+    // The title being edited in its list row
+    pub title_edit: Option<TitleEdit>,
+    // Tasks showing their subtasks under them in the list
+    pub expanded: HashSet<u64>,
+    pub bulk_label: Option<BulkLabel>,
+    pub bulk_move: Option<BulkMove>,
+    prefetch: Option<Prefetch>,
+    // Asked before quitting would drop work
+    pub quit_question: Option<String>,
+    pub theme: Theme,
+    // The latest API requests shown over the view, toggled with F12
+    pub show_api_log: bool,
+    pub pending_move: Option<CardMove>,
+    pub plain: bool,
+    pub completion: Option<Completion>,
+    pub members: Option<Members>,
+    pub teams: Option<Teams>,
+    label_cache: Option<Vec<Label>>,
+    history: History<Location>,
+    // One slot per tab. The active tab's state lives in the fields above and
+    // its slot is empty until another tab is opened.
+    tabs: Vec<Workspace>,
+    pub active_tab: usize,
+    // Tasks opened before with their comments, reused while the task's
+    // updated timestamp in the list stays the same
+    detail_cache: Lru<u64, (Task, Vec<Comment>)>,
+    // Urls of the open task offered by the links popup
+    links: Vec<String>,
+    // Set by `g`, waiting for the second key of gt, gT, gn, gc or gx
+    pending_g: bool,
+    pub notifications: Vec<Notification>,
+    pub notification_state: ListState,
+    notifications_loading: bool,
+    notifications_checked: Option<Instant>,
+    // Labels or projects being loaded for the completion list
+    completion_loading: bool,
+    // A card or task move on its way to the server. Further moves wait for
+    // it, their positions would be worked out from the old order.
+    moving: bool,
+    // A repeating task just done, whose next due date is told once the view
+    // has reloaded
+    next_occurrence: Option<u64>,
+    // When the list or the board was last loaded from the server
+    pub loaded_at: Option<Instant>,
+    // When the running reload started, and how long the last one took
+    sync_started: Option<Instant>,
+    pub last_sync: Option<Duration>,
+    // The task x asked to delete, waiting for confirmation
+    pub pending_delete: Option<Task>,
+    // The last deleted task and when, kept to create it again on undo
+    recently_deleted: Option<(Task, Instant)>,
+    // Time being tracked on a task, and the CSV file it is also logged to
+    pub timer: Option<Timer>,
+    pub timesheet: Option<PathBuf>,
+    // A task to export once its details are in, and text for the terminal to
+    // put on the clipboard
+    pending_export: Option<(u64, ExportTarget)>,
+    pub clipboard: Option<String>,
+    // How list rows are laid out, the built-in layout when None
+    pub row_format: Option<Vec<RowPart>>,
+    // Calendars outside Vikunja and their events, shown in the date picker
+    pub calendar_feeds: Vec<CalendarFeed>,
+    pub external_events: Vec<ExternalEvent>,
+    // Results held back while the sign-in form is open
+    deferred: Vec<Message>,
+    // Signed in user, looked up the first time a reaction is toggled
+    pub current_user: Option<User>,
+    // Set after an external editor had the terminal, which then needs a
+    // full redraw
+    pub redraw: bool,
+    reaction_target: Option<ReactionTarget>,
+}
+
+// The view state each tab keeps for itself
+#[derive(Default)]
+struct Workspace {
+    tasks: Vec<Task>,
+    visible_rows: Vec<usize>,
+    list_filter: Option<String>,
+    state: ListState,
+    task_detail: Option<Task>,
+    task_comments: Vec<Comment>,
+    page: usize,
+    total_pages: usize,
+    result_count: usize,
+    show_done_tasks: bool,
+    current_project: Option<Project>,
+    active_filter: Option<QuickFilter>,
+    view_kind: ViewKind,
+    board: Option<Board>,
+    list_view_id: Option<u64>,
+    history: History<Location>,
+}
+
+// A place navigation history can return to: what was listed, what was
+// selected and which task was open
+#[derive(Clone)]
+pub struct Location {
+    project: Option<Project>,
+    filter: Option<QuickFilter>,
+    view_kind: ViewKind,
+    archive: Option<String>,
+    page: usize,
+    selected: Option<u64>,
+    task_id: Option<u64>,
+}
+
+impl PartialEq for Location {
+    fn eq(&self, other: &Location) -> bool {
+        self.project.as_ref().map(|p| p.id) == other.project.as_ref().map(|p| p.id)
+            && self.filter == other.filter
+            && self.view_kind == other.view_kind
+            && self.archive == other.archive
+            && self.page == other.page
+            && self.selected == other.selected
+            && self.task_id == other.task_id
+    }
+}
+
+// Completion list for the quick add token being typed in the title
+pub struct Completion {
+    pub kind: CompletionKind,
+    // Byte offset of the token's sigil in the title
+    pub start: usize,
+    pub picker: Picker,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CompletionKind {
+    Label,
+    Project,
+    User,
+}
+
+impl CompletionKind {
+    fn from_sigil(sigil: char) -> CompletionKind {
+        match sigil {
+            '*' => CompletionKind::Label,
+            '+' => CompletionKind::Project,
+            _ => CompletionKind::User,
+        }
+    }
+
+    fn sigil(self) -> char {
+        match self {
+            CompletionKind::Label => '*',
+            CompletionKind::Project => '+',
+            CompletionKind::User => '@',
+        }
+    }
+}
+
+// Which date of the new task the date picker sets
+#[derive(Clone, Copy, PartialEq)]
+pub enum DateTarget {
+    Due,
+    Reminder,
+    Start,
+    End,
+}
+
+// A board card on its way to another bucket
+pub struct CardMove {
+    pub task_id: u64,
+    pub title: String,
+    pub bucket_id: u64,
+    // Set when the move enters or leaves the done bucket
+    pub done: Option<bool>,
+}
+
+// A load of every task page running in the background
+pub struct AllTasksLoad {
+    pub progress: Arc<LoadProgress>,
+}
+
+// Requests that failed because the session expired, run again after signing in
+#[derive(Clone)]
+pub enum Retry {
+    Refresh,
+    OpenTask(u64),
+    // Reacting to the open task or one of its comments
+    React(u64),
+    Comment(u64),
+    ProjectSwitcher,
+    FindTask,
+    CreateTask {
+        parsed: ParsedTask,
+        task: Box<NewTask>,
+        new_labels: Vec<Label>,
+    },
+    Reopen(Vec<u64>),
+    Rename {
+        task_id: u64,
+        title: String,
+    },
+    BulkLabel {
+        name: String,
+        remove: bool,
+    },
+    AssignToMe(u64),
+    MineFilter,
+    Members,
+    Share {
+        member: Member,
+        right: u8,
+    },
+    Unshare(Member),
+    Teams,
+    CreateTeam(String),
+    AddTeamMember {
+        team_id: u64,
+        username: String,
+    },
+    DuplicateProject {
+        project: Project,
+        parent_id: u64,
+    },
+    Delete(Box<Task>),
+    LogTime,
+    BulkMove(String),
+}
+
+impl Retry {
+    fn context(&self) -> &'static str {
+        match self {
+            Retry::Refresh | Retry::FindTask => "Error fetching tasks",
+            Retry::OpenTask(_) => "Error fetching task details",
+            Retry::React(_) => "Error updating reactions",
+            Retry::Comment(_) => "Error adding comment",
+            Retry::ProjectSwitcher => "Error fetching projects",
+            Retry::CreateTask { .. } => "Error creating new task",
+            Retry::Reopen(_) => "Error reopening tasks",
+            Retry::Rename { .. } => "Error renaming task",
+            Retry::BulkLabel { .. } => "Error labeling tasks",
+            Retry::AssignToMe(_) => "Error assigning task",
+            Retry::MineFilter => "Error fetching the current user",
+            Retry::Members => "Error fetching project members",
+            Retry::Share { .. } => "Error sharing project",
+            Retry::Unshare(_) => "Error unsharing project",
+            Retry::Teams => "Error fetching teams",
+            Retry::CreateTeam(_) => "Error creating team",
+            Retry::AddTeamMember { .. } => "Error adding team member",
+            Retry::DuplicateProject { .. } => "Error duplicating project",
+            Retry::Delete(_) => "Error deleting task",
+            Retry::LogTime => "Error logging time, W tries again",
+            Retry::BulkMove(_) => "Error moving tasks",
+        }
+    }
+}
+
+// Sign-in form shown when the server rejects the token mid-session
+#[derive(Default)]
+pub struct AuthForm {
+    pub token: String,
+    pub username: String,
+    pub password: String,
+    // Only asked for once the server says the account needs it
+    pub totp_passcode: Option<String>,
+    pub field: AuthField,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum AuthField {
+    #[default]
+    Token,
+    Username,
+    Password,
+    Totp,
+}
+
+impl AuthForm {
+    pub fn fields(&self) -> Vec<AuthField> {
+        let mut fields = vec![AuthField::Token, AuthField::Username, AuthField::Password];
+        if self.totp_passcode.is_some() {
+            fields.push(AuthField::Totp);
+        }
+        fields
+    }
+
+    fn input_mut(&mut self) -> &mut String {
+        match self.field {
+            AuthField::Token => &mut self.token,
+            AuthField::Username => &mut self.username,
+            AuthField::Password => &mut self.password,
+            AuthField::Totp => self.totp_passcode.get_or_insert_with(String::new),
+        }
+    }
+
+    fn next_field(&mut self) {
+        let fields = self.fields();
+        let i = fields.iter().position(|f| *f == self.field).unwrap_or(0);
+        self.field = fields[(i + 1) % fields.len()];
+    }
+
+    fn previous_field(&mut self) {
+        let fields = self.fields();
+        let i = fields.iter().position(|f| *f == self.field).unwrap_or(0);
+        self.field = fields[(i + fields.len() - 1) % fields.len()];
+    }
+}
+
+// A label added to or removed from every task matching the view, waiting
+// for confirmation and then for the changes to go through
+pub struct BulkLabel {
+    pub label: Label,
+    pub remove: bool,
+    pub task_ids: Vec<u64>,
+    // Tasks changed so far, set once the changes are sent
+    pub progress: Option<Arc<AtomicUsize>>,
+}
+
+// The page after the one in view, loaded ahead for the query it was asked
+// with. The result is None while it is loading, and otherwise kept with
+// when it arrived.
+struct Prefetch {
+    query: TaskQuery,
+    page: usize,
+    result: Option<(TaskPage, Instant)>,
+}
+
+// Tasks matching the view moved to another project, waiting for
+// confirmation and then for the moves to go through
+pub struct BulkMove {
+    pub project: Project,
+    pub task_ids: Vec<u64>,
+    // Tasks moved so far, set once the moves are sent
+    pub progress: Option<Arc<AtomicUsize>>,
+}
+
+// A created task with a note on the labels or assignees that couldn't be
+// added, or why the task wasn't created
+pub type Creation = Result<Option<String>, String>;
+
+// A task from the add popup, ready to be sent
+struct ReadyTask {
+    parsed: ParsedTask,
+    task: NewTask,
+    new_labels: Vec<Label>,
+    project: Option<Project>,
+}
+
+// Why a task from the add popup isn't ready to be sent
+enum Held {
+    Load(Missing),
+    Refused(String),
+    // A question was put up, the task goes once it is answered
+    NewLabels,
+    ProjectChoice,
+}
+
+// What has to be loaded before a new task can be made
+#[derive(Clone, Copy, PartialEq)]
+enum Missing {
+    Projects,
+    Labels,
+}
+
+// What quick add will send, with the names the server only sees as ids
+pub struct NewTaskPreview {
+    // None for the server's default project
+    pub project: Option<String>,
+    pub labels: Vec<String>,
+    pub assignees: Vec<String>,
+    pub task: NewTask,
+}
+
+// A task title edited in place, with the cursor as a byte offset into it
+pub struct TitleEdit {
+    pub task_id: u64,
+    pub text: String,
+    pub cursor: usize,
+}
+
+impl TitleEdit {
+    fn new(task: &Task) -> TitleEdit {
+        TitleEdit {
+            task_id: task.id,
+            text: task.title.clone(),
+            cursor: task.title.len(),
+        }
+    }
+
+    fn insert(&mut self, c: char) {
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+    }
+
+    fn backspace(&mut self) {
+        if let Some(c) = self.text[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+            self.text.remove(self.cursor);
+        }
+    }
+
+    fn delete(&mut self) {
+        if self.cursor < self.text.len() {
+            self.text.remove(self.cursor);
+        }
+    }
+
+    fn left(&mut self) {
+        if let Some(c) = self.text[..self.cursor].chars().next_back() {
+            self.cursor -= c.len_utf8();
+        }
+    }
+
+    fn right(&mut self) {
+        if let Some(c) = self.text[self.cursor..].chars().next() {
+            self.cursor += c.len_utf8();
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum)]
+pub enum ViewKind {
+    #[default]
+    List,
+    Kanban,
+    // Tasks done in the last done_review_days, most recently done first
+    Done,
 }
 
 pub enum InputMode {
     Normal,
     Editing,
     Insert,
+    ProjectSwitcher,
+    TaskFinder,
+    FilterMenu,
+    ProfileSwitcher,
+    Reauth,
+    ConfirmMove,
+    DatePicker,
+    ListFilter,
+    Command,
+    Notifications,
+    ReactionTargets,
+    ReactionPicker,
+    Links,
+    RenameTask,
+    ConfirmBulkLabel,
+    ConfirmBulkMove,
+    ConfirmQuit,
+    // Asks before creating the labels of a new task that don't exist yet
+    ConfirmNewLabels,
+    ConfirmNewTask,
+    RestoreDraft,
+    // Picks between the projects a +project in quick add could mean
+    ProjectChoice,
+    // Picks where a duplicated project goes
+    DuplicateParent,
+    // Who the current project is shared with
+    Members,
+    AddMember,
+    ConfirmUnshare,
+    ConfirmDelete,
+    // The teams of the signed in user and their members
+    Teams,
+    TeamInput,
 }
 #[derive(PartialEq)]
 pub enum ActiveInput {
@@ -29,55 +638,835 @@ pub enum ActiveInput {
 }
 
 impl App {
-    pub fn new(tasks: Vec<Task>) -> App {
-        let mut state = ListState::default();
-        if !tasks.is_empty() {
-            state.select(Some(0));
-        } else {
-            state.select(None);
-        }
+    pub fn new(api: Arc<dyn VikunjaApi>, quick_filters: Vec<QuickFilter>) -> App {
         App {
-            tasks,
-            state,
+            worker: Worker::spawn(api.clone()),
+            api,
+            generation: 0,
+            opening: None,
+            tasks: Vec::new(),
+            visible_rows: Vec::new(),
+            task_lines: Vec::new(),
+            list_filter: None,
+            command: String::new(),
+            state: ListState::default(),
             task_detail: None,
+            task_comments: Vec::new(),
+            detail_wrap: true,
+            detail_scroll_x: 0,
             input_mode: InputMode::Normal,
             active_input: ActiveInput::Title,
             new_task_title: String::new(),
             new_task_description: String::new(),
+            new_task_due: None,
+            new_task_reminder: None,
+            new_task_start: None,
+            new_task_end: None,
+            new_task_color: None,
+            new_task_project: None,
+            project_choices: Vec::new(),
+            duplicating: None,
+            new_labels: Vec::new(),
+            new_labels_agreed: false,
+            new_task_preview: None,
+            confirm_new_task: false,
+            created: None,
+            saved: State::default(),
+            state_path: None,
+            task_index: TaskIndex::default(),
+            task_index_path: None,
+            submitted_draft: None,
+            label_color: None,
+            date_picker: None,
+            date_target: DateTarget::Due,
             page: 1,
+            total_pages: 1,
+            result_count: 0,
+            page_size: None,
             show_done_tasks: false,
+            show_done_by_view: HashMap::new(),
+            completed_rows: 0,
+            collapse_completed: false,
+            projects: Vec::new(),
+            current_project: None,
+            picker: None,
+            quick_filters,
+            active_filter: None,
+            view_kind: ViewKind::List,
+            board: None,
+            list_view_id: None,
+            read_only: false,
+            message: None,
+            all_tasks: Vec::new(),
+            all_tasks_load: None,
+            server_info: None,
+            auth_form: AuthForm::default(),
+            pending_retry: None,
+            profiles: Vec::new(),
+            profile: String::new(),
+            project_settings: HashMap::new(),
+            confirm_done_bucket: false,
+            show_done_at: false,
+            terminal_title: false,
+            default_due_time: None,
+            done_review_days: DEFAULT_REVIEW_DAYS,
+            archive: None,
+            marked: BTreeSet::new(),
+            expanded: HashSet::new(),
+            title_edit: None,
+            bulk_label: None,
+            bulk_move: None,
+            prefetch: None,
+            quit_question: None,
+            theme: Theme::default(),
+            show_api_log: false,
+            pending_move: None,
+            plain: false,
+            completion: None,
+            members: None,
+            teams: None,
+            label_cache: None,
+            history: History::new(),
+            tabs: vec![Workspace::default()],
+            active_tab: 0,
+            detail_cache: Lru::new(DETAIL_CACHE_SIZE),
+            links: Vec::new(),
+            pending_g: false,
+            notifications: Vec::new(),
+            notification_state: ListState::default(),
+            notifications_loading: false,
+            notifications_checked: None,
+            completion_loading: false,
+            moving: false,
+            next_occurrence: None,
+            loaded_at: None,
+            sync_started: None,
+            last_sync: None,
+            pending_delete: None,
+            recently_deleted: None,
+            timer: None,
+            timesheet: None,
+            pending_export: None,
+            clipboard: None,
+            row_format: None,
+            calendar_feeds: Vec::new(),
+            external_events: Vec::new(),
+            deferred: Vec::new(),
+            current_user: None,
+            redraw: false,
+            reaction_target: None,
         }
     }
 
-    pub async fn refresh_tasks(
-        &mut self,
-        instance_url: &str,
-        api_key: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let new_tasks = fetch_tasks(instance_url, api_key, self.page).await?;
-        if self.show_done_tasks {
-            self.tasks = new_tasks;
+    // Fetches the projects and who is signed in alongside the first page, for
+    // the switcher, completions and assigning that would wait on them later,
+    // and the events of the calendars set up in the config
+    pub fn warm_up(&mut self) {
+        if self.projects.is_empty() {
+            self.worker.send(Action::LoadProjects(ProjectsFor::Startup));
+        }
+        if self.current_user.is_none() {
+            self.worker.send(Action::LoadCurrentUser(UserFor::Startup));
+        }
+        for feed in &self.calendar_feeds {
+            self.worker.send(Action::LoadCalendar(feed.clone()));
+        }
+    }
+
+    // The external events on a day, which the date picker lists under the
+    // month
+    pub fn events_on(&self, date: NaiveDate) -> Vec<&ExternalEvent> {
+        self.external_events
+            .iter()
+            .filter(|event| event.date == date)
+            .collect()
+    }
+
+    pub fn set_server_info(&mut self, info: ServerInfo) {
+        if !info.is_supported() {
+            self.message = Some(format!(
+                "Vikunja {} is older than {}.{}.{}, some features will not work",
+                info.version,
+                MIN_SUPPORTED_VERSION.0,
+                MIN_SUPPORTED_VERSION.1,
+                MIN_SUPPORTED_VERSION.2
+            ));
+        }
+        self.server_info = Some(info);
+    }
+
+    pub fn capabilities(&self) -> Capabilities {
+        self.server_info
+            .as_ref()
+            .map(ServerInfo::capabilities)
+            .unwrap_or(Capabilities {
+                project_views: true,
+                reactions: true,
+            })
+    }
+
+    // Loads the view again keeping the selected task selected, or the row
+    // it was on when it is gone
+    pub fn refresh_tasks(&mut self) {
+        let select = self.selected_task().map(|task| task.id);
+        self.reload(select, None);
+    }
+
+    // Loads a different view, which starts at its first task
+    fn load_from_top(&mut self) {
+        self.cancel_opening();
+        self.reload(None, None);
+    }
+
+    // Loads the page before, which is entered at its last task
+    fn load_from_bottom(&mut self) {
+        self.cancel_opening();
+        self.generation += 1;
+        self.load(Reload {
+            generation: self.generation,
+            last_row: true,
+            ..Default::default()
+        });
+    }
+
+    // Calls off loading the details of a task the user has moved on from, so
+    // they don't show up late over what is selected now
+    fn cancel_opening(&mut self) {
+        if self.opening.take().is_some() {
+            self.worker.cancel_detail_load();
+        }
+    }
+
+    // Loads the view and the open task again, keeping the selection
+    fn refresh_view(&mut self) {
+        let open = self.task_detail.as_ref().map(|task| task.id);
+        if let Some(task_id) = open {
+            self.detail_cache.remove(&task_id);
+        }
+        let select = self.selected_task().map(|task| task.id);
+        self.reload(select, open);
+    }
+
+    // Loads the list or the board of the current view, then selects and opens
+    // the given tasks
+    fn reload(&mut self, select: Option<u64>, open: Option<u64>) {
+        self.generation += 1;
+        self.load(Reload {
+            generation: self.generation,
+            select,
+            open,
+            last_row: false,
+        });
+    }
+
+    fn load(&mut self, reload: Reload) {
+        self.prefetch = None;
+        self.sync_started = Some(Instant::now());
+        if self.view_kind == ViewKind::Kanban {
+            // Boards belong to a project, so there is nothing to show without one
+            match &self.current_project {
+                Some(project) => self.worker.send(Action::LoadBoard {
+                    reload,
+                    project_id: project.id,
+                }),
+                None => {
+                    self.board = None;
+                    self.moving = false;
+                    if let Some(task_id) = reload.open {
+                        self.open_task(task_id);
+                    }
+                }
+            }
+            return;
+        }
+
+        let list_view_of = self
+            .current_project
+            .as_ref()
+            .filter(|_| self.view_kind == ViewKind::List)
+            .filter(|_| self.list_view_id.is_none() && self.capabilities().project_views)
+            .map(|project| project.id);
+        self.worker.send(Action::LoadTasks {
+            reload,
+            page: self.page,
+            query: self.task_query(),
+            list_view_of,
+        });
+    }
+
+    fn show_tasks(&mut self, reload: Reload, list_view_id: Option<u64>, tasks: Vec<Task>) {
+        if list_view_id.is_some() {
+            self.list_view_id = list_view_id;
+        }
+        self.tasks = tasks;
+        self.marked
+            .retain(|id| self.tasks.iter().any(|task| task.id == *id));
+        let previous_row = self.state.selected();
+        self.update_visible_rows();
+        if reload.last_row {
+            self.state.select(self.visible_rows.len().checked_sub(1));
+        } else if let Some(task_id) = reload.select {
+            self.reselect(task_id, previous_row);
+        }
+        if let Some(task_id) = reload.open {
+            self.open_task(task_id);
+        }
+    }
+
+    // Applies the `/` filter to the loaded tasks and selects the first row
+    fn update_visible_rows(&mut self) {
+        self.task_lines.clear();
+        let rows: Vec<usize> = match &self.list_filter {
+            Some(query) => filter_tasks(query, &self.tasks),
+            None => (0..self.tasks.len()).collect(),
+        };
+        self.completed_rows = 0;
+        self.visible_rows = if self.groups_completed() {
+            let (mut open, done): (Vec<usize>, Vec<usize>) =
+                rows.into_iter().partition(|&i| !self.tasks[i].done);
+            self.completed_rows = done.len();
+            if !self.collapse_completed {
+                open.extend(done);
+            }
+            open
+        } else {
+            rows
+        };
+        if self.visible_rows.is_empty() {
+            self.state.select(None);
         } else {
-            self.tasks = new_tasks.into_iter().filter(|task| !task.done).collect();
+            self.state.select(Some(0));
         }
-        self.state.select(Some(0));
-        Ok(())
+    }
+
+    // Subtasks as of the task's details when they were opened, which are
+    // newer than the list's when the subtasks were changed since
+    pub fn subtasks<'a>(&'a self, task: &'a Task) -> &'a [Task] {
+        let detail = self.detail_cache.peek(&task.id).map(|(detail, _)| detail);
+        detail.unwrap_or(task).related("subtask")
+    }
+
+    fn toggle_subtasks(&mut self) {
+        let Some(task) = self.selected_task() else {
+            return;
+        };
+        let task_id = task.id;
+        if self.subtasks(task).is_empty() {
+            self.message = Some("No subtasks".to_string());
+        } else if !self.expanded.remove(&task_id) {
+            self.expanded.insert(task_id);
+        }
+    }
+
+    // Selects the task again after the rows changed, or else the row nearest
+    // to where it was
+    fn reselect(&mut self, task_id: u64, previous_row: Option<usize>) {
+        let row = self.visible_tasks().position(|t| t.id == task_id);
+        let last = self.visible_rows.len().checked_sub(1);
+        let near = previous_row.zip(last).map(|(row, last)| row.min(last));
+        self.state.select(row.or(near));
+    }
+
+    pub fn groups_completed(&self) -> bool {
+        self.show_done_tasks && self.view_kind == ViewKind::List
+    }
+
+    fn toggle_completed(&mut self) {
+        let selected = self.selected_task().map(|task| task.id);
+        let previous_row = self.state.selected();
+        self.collapse_completed = !self.collapse_completed;
+        self.update_visible_rows();
+        if let Some(task_id) = selected {
+            self.reselect(task_id, previous_row);
+        }
+    }
+
+    pub fn visible_tasks(&self) -> impl Iterator<Item = &Task> {
+        self.visible_rows.iter().filter_map(|&i| self.tasks.get(i))
+    }
+
+    fn clear_list_filter(&mut self) {
+        self.list_filter = None;
+        self.update_visible_rows();
+    }
+
+    fn show_board(&mut self, reload: Reload, view: &ProjectView, buckets: Vec<Bucket>) {
+        // A new board starts on the bucket from the project settings
+        let column = match &self.board {
+            Some(board) => board.column,
+            None => self
+                .project_settings()
+                .and_then(|s| s.bucket.as_ref())
+                .and_then(|name| {
+                    buckets
+                        .iter()
+                        .position(|b| b.title.eq_ignore_ascii_case(name))
+                })
+                .unwrap_or(0),
+        };
+        let mut board = Board::new(view, buckets);
+        board.column = column.min(board.buckets.len().saturating_sub(1));
+        if let Some(task_id) = reload.select {
+            board.select_task(task_id);
+        }
+        self.board = Some(board);
+        if let Some(task_id) = reload.open {
+            self.open_task(task_id);
+        }
+    }
+
+    // Moves the selected card to the bucket `offset` columns away. Moves into
+    // or out of the done bucket wait for confirmation when the config asks so.
+    pub fn move_card(&mut self, offset: isize) {
+        let Some(board) = &self.board else {
+            return;
+        };
+        let Some(task) = board.selected_task() else {
+            return;
+        };
+        let Some(bucket) = board
+            .column
+            .checked_add_signed(offset)
+            .and_then(|column| board.buckets.get(column))
+        else {
+            return;
+        };
+        let done = if board.is_done_bucket(bucket.id) {
+            Some(true)
+        } else if board.is_done_bucket(board.buckets[board.column].id) {
+            Some(false)
+        } else {
+            None
+        };
+        let card_move = CardMove {
+            task_id: task.id,
+            title: task.title.clone(),
+            bucket_id: bucket.id,
+            done,
+        };
+
+        if done.is_some() && self.confirm_done_bucket {
+            self.pending_move = Some(card_move);
+            self.input_mode = InputMode::ConfirmMove;
+            return;
+        }
+        self.apply_move(card_move);
+    }
+
+    fn apply_move(&mut self, card_move: CardMove) {
+        let (Some(board), Some(project)) = (&self.board, &self.current_project) else {
+            return;
+        };
+        let task_id = card_move.task_id;
+        let repeats = card_move.done == Some(true)
+            && board.selected_task().is_some_and(|task| task.repeats());
+        let change = Change::MoveCard {
+            project_id: project.id,
+            view_id: board.view_id,
+            card_move,
+        };
+        if repeats {
+            self.moving = true;
+            self.worker.send(Action::Save {
+                change,
+                then: Then::NextOccurrence(task_id),
+                retry: Retry::Refresh,
+            });
+            return;
+        }
+        self.save_move(change, task_id);
+    }
+
+    // Tells when the repeating task just done is due next, the reload keeps
+    // it selected
+    fn report_next_occurrence(&mut self) {
+        let Some(task_id) = self.next_occurrence.take() else {
+            return;
+        };
+        let Some(task) = self.selected_task().filter(|task| task.id == task_id) else {
+            return;
+        };
+        self.message = Some(match task.due_at() {
+            Some(due) => format!("Next occurrence: {}", due.format("%b %-d")),
+            None => format!("{} repeats", task.title),
+        });
+    }
+
+    // Moves the selected card `offset` rows up or down within its bucket
+    pub fn reorder_card(&mut self, offset: isize) {
+        let Some(board) = &self.board else {
+            return;
+        };
+        let (Some(bucket), Some(row)) = (board.buckets.get(board.column), board.selected_row())
+        else {
+            return;
+        };
+        let tasks = bucket_tasks(bucket);
+        let Some(target) = row
+            .checked_add_signed(offset)
+            .filter(|target| *target < tasks.len())
+        else {
+            return;
+        };
+        let task_id = tasks[row].id;
+        let change = Change::SetPosition {
+            task_id,
+            view_id: board.view_id,
+            position: position_for_move(tasks, row, target),
+        };
+        self.save_move(change, task_id);
+    }
+
+    // Moves the selected task up or down the list and saves its position in
+    // the project's list view, the manual order of the web interface
+    pub fn reorder_task(&mut self, offset: isize) {
+        let Some(view_id) = self.list_view_id else {
+            self.message = Some("Switch to a project (s) to reorder its tasks".to_string());
+            return;
+        };
+        if let Some(sort) = self.project_settings().and_then(|s| s.sort.as_ref()) {
+            self.message = Some(format!(
+                "The list is sorted by {}, remove the sort setting to reorder",
+                sort
+            ));
+            return;
+        }
+        // Done tasks moved to the end aren't next to their neighbours on the
+        // server, so positions worked out from this order would scramble it
+        if self.groups_completed() {
+            self.message = Some("Hide the done tasks (t) to reorder".to_string());
+            return;
+        }
+        let Some(row) = self.state.selected() else {
+            return;
+        };
+        let tasks: Vec<Task> = self.visible_tasks().cloned().collect();
+        let Some(target) = row
+            .checked_add_signed(offset)
+            .filter(|target| *target < tasks.len())
+        else {
+            return;
+        };
+        let task_id = tasks[row].id;
+        let change = Change::SetPosition {
+            task_id,
+            view_id,
+            position: position_for_move(&tasks, row, target),
+        };
+        self.save_move(change, task_id);
+    }
+
+    // Saves a move, then reloads the view keeping the moved task selected
+    fn save_move(&mut self, change: Change, task_id: u64) {
+        self.moving = true;
+        self.worker.send(Action::Save {
+            change,
+            then: Then::ReloadAt(task_id),
+            retry: Retry::Refresh,
+        });
+    }
+
+    pub fn selected_task(&self) -> Option<&Task> {
+        match self.view_kind {
+            ViewKind::List | ViewKind::Done => self
+                .state
+                .selected()
+                .and_then(|i| self.visible_rows.get(i))
+                .and_then(|&i| self.tasks.get(i)),
+            ViewKind::Kanban => self.board.as_ref().and_then(|b| b.selected_task()),
+        }
+    }
+
+    // The project given on the command line, by its exact title in any case
+    // or its id. Nothing is guessed, as a typo would open another project;
+    // the error lists what could have been meant.
+    pub fn find_project(&self, name: &str) -> Result<Project, String> {
+        let listed = |projects: Vec<&Project>| -> String {
+            let names: Vec<String> = projects
+                .iter()
+                .map(|p| format!("{} ({})", p.title, p.id))
+                .collect();
+            names.join(", ")
+        };
+        let named: Vec<&Project> = self
+            .projects
+            .iter()
+            .filter(|p| p.title.eq_ignore_ascii_case(name))
+            .collect();
+        match named.as_slice() {
+            [project] => return Ok((*project).clone()),
+            [] => {}
+            _ => {
+                return Err(format!(
+                    "Several projects are named '{}', give the id instead: {}",
+                    name,
+                    listed(named)
+                ))
+            }
+        }
+        let by_id = name.parse::<u64>().ok();
+        if let Some(project) = self.projects.iter().find(|p| Some(p.id) == by_id) {
+            return Ok(project.clone());
+        }
+        let titles: Vec<&str> = self.projects.iter().map(|p| p.title.as_str()).collect();
+        let close: Vec<&Project> = fuzzy_filter(name, &titles)
+            .into_iter()
+            .take(5)
+            .map(|i| &self.projects[i])
+            .collect();
+        if close.is_empty() {
+            Err(format!("No project named '{}'", name))
+        } else {
+            Err(format!(
+                "No project named '{}', did you mean: {}",
+                name,
+                listed(close)
+            ))
+        }
+    }
+
+    // Looks up a quick filter from the config, then the built-in ones, and
+    // otherwise treats the argument as a raw filter query
+    pub fn resolve_filter(&self, name: &str) -> QuickFilter {
+        if let Some(quick_filter) = self.quick_filters.iter().find(|f| f.name == name) {
+            return quick_filter.clone();
+        }
+        if let Some(mine) = self.mine_filter().filter(|_| name == MINE_FILTER) {
+            return mine;
+        }
+        if let Some((builtin, filter)) = BUILTIN_FILTERS.iter().find(|(n, _)| *n == name) {
+            return QuickFilter {
+                name: builtin.to_string(),
+                filter: filter.to_string(),
+            };
+        }
+        QuickFilter {
+            name: name.to_string(),
+            filter: name.to_string(),
+        }
+    }
+
+    // Tasks assigned to the current user, once they are known. They are
+    // matched by id, which stays the same when the username changes.
+    pub fn mine_filter(&self) -> Option<QuickFilter> {
+        let me = self.current_user.as_ref()?;
+        Some(QuickFilter {
+            name: "Assigned to me".to_string(),
+            filter: format!("assignees in {}", me.id),
+        })
+    }
+
+    fn apply_mine_filter(&mut self) {
+        match self.mine_filter() {
+            Some(mine) => self.apply_filter(Some(mine)),
+            None => self
+                .worker
+                .send(Action::LoadCurrentUser(UserFor::MineFilter)),
+        }
+    }
+
+    // Combines the project scope and the active quick filter into one filter query
+    pub fn task_filter(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if let Some(project) = &self.current_project {
+            parts.push(format!("project = {}", project.id));
+        }
+        if let Some(quick_filter) = &self.active_filter {
+            parts.push(format!("({})", quick_filter.filter));
+        }
+        if self.view_kind == ViewKind::Done && self.archive.is_some() {
+            parts.push("done = true".to_string());
+        } else if self.view_kind == ViewKind::Done {
+            parts.push(format!(
+                "done = true && done_at > now-{}d",
+                self.done_review_days
+            ));
+        } else if !self.show_done_tasks {
+            // Left to the server, so every page comes back full
+            parts.push("done = false".to_string());
+        }
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join(" && "))
+        }
+    }
+
+    pub fn set_project_settings(&mut self, settings: HashMap<String, ProjectSettings>) {
+        self.project_settings = settings
+            .into_iter()
+            .map(|(key, settings)| (key.to_lowercase(), settings))
+            .collect();
+    }
+
+    // Overrides from the config for the active project
+    pub fn project_settings(&self) -> Option<&ProjectSettings> {
+        self.settings_for(self.current_project.as_ref()?.id)
+    }
+
+    // Overrides from the config for a project, set under its id or its title
+    fn settings_for(&self, project_id: u64) -> Option<&ProjectSettings> {
+        if let Some(settings) = self.project_settings.get(&project_id.to_string()) {
+            return Some(settings);
+        }
+        let project = self
+            .current_project
+            .iter()
+            .chain(&self.projects)
+            .find(|p| p.id == project_id)?;
+        self.project_settings.get(&project.title.to_lowercase())
+    }
+
+    pub fn task_query(&self) -> TaskQuery {
+        if self.view_kind == ViewKind::Done {
+            return TaskQuery {
+                filter: self.task_filter(),
+                sort: Some("done_at desc".to_string()),
+                search: self.archive.clone().filter(|text| !text.is_empty()),
+                ..Default::default()
+            };
+        }
+        TaskQuery {
+            filter: self.task_filter(),
+            sort: self.project_settings().and_then(|s| s.sort.clone()),
+            view: self
+                .current_project
+                .as_ref()
+                .zip(self.list_view_id)
+                .map(|(project, view_id)| (project.id, view_id)),
+            search: None,
+        }
+    }
+
+    // Makes a project the active view with the done toggle it was left with,
+    // or else its show_done override, or else hiding done tasks
+    pub fn enter_project(&mut self, project: Option<Project>) {
+        self.current_project = project;
+        self.board = None;
+        self.list_view_id = None;
+        let key = self.current_project.as_ref().map(|p| p.id);
+        self.show_done_tasks = match self.show_done_by_view.get(&key) {
+            Some(show_done) => *show_done,
+            None => self
+                .project_settings()
+                .and_then(|s| s.show_done)
+                .unwrap_or(false),
+        };
+    }
+
+    fn toggle_done_tasks(&mut self) {
+        self.show_done_tasks = !self.show_done_tasks;
+        let key = self.current_project.as_ref().map(|p| p.id);
+        self.show_done_by_view.insert(key, self.show_done_tasks);
+        self.page = 1;
+        self.refresh_tasks();
     }
 
     pub fn next_page(&mut self) {
         self.page += 1;
     }
 
+    // Tasks the server sent in a shape that couldn't be read are left out of
+    // the list, and told about next to whatever message is already shown
+    fn report_skipped(&mut self, skipped: usize) {
+        let note = match skipped {
+            0 => return,
+            1 => "1 task couldn't be read and is left out".to_string(),
+            _ => format!("{} tasks couldn't be read and are left out", skipped),
+        };
+        self.message = Some(match self.message.take() {
+            Some(message) => format!("{}; {}", message, note),
+            None => note,
+        });
+    }
+
+    fn count_pages(&mut self, page: &TaskPage) {
+        self.total_pages = page.total_pages;
+        self.result_count = page.result_count;
+        if self.page < page.total_pages {
+            self.page_size = Some(page.result_count);
+        }
+    }
+
+    // How many tasks match the view, and whether that is exact. Pages before
+    // the last are full, so it is exact on the last page once the page size
+    // is known, and otherwise the least there can be.
+    pub fn matching_tasks(&self) -> (usize, bool) {
+        let pages = self.total_pages.max(self.page);
+        if pages == 1 {
+            return (self.result_count, true);
+        }
+        match self.page_size {
+            Some(size) if self.page == pages => ((pages - 1) * size + self.result_count, true),
+            Some(size) => ((pages - 1) * size + 1, false),
+            None => (pages - 1 + self.result_count, false),
+        }
+    }
+
+    fn prefetch_next_page(&mut self) {
+        if self.page >= self.total_pages {
+            return;
+        }
+        let query = self.task_query();
+        let page = self.page + 1;
+        self.worker.send(Action::PrefetchPage {
+            query: query.clone(),
+            page,
+        });
+        self.prefetch = Some(Prefetch {
+            query,
+            page,
+            result: None,
+        });
+    }
+
+    // Shows the page loaded ahead when it is the one asked for, still for
+    // the same filter and sort
+    fn show_prefetched(&mut self) -> bool {
+        let query = self.task_query();
+        let (tasks, arrived) = match self.prefetch.take() {
+            Some(Prefetch {
+                query: for_query,
+                page,
+                result: Some((tasks, arrived)),
+            }) if page == self.page && for_query == query => (tasks, arrived),
+            _ => return false,
+        };
+        if tasks.tasks.is_empty() {
+            self.page -= 1;
+            self.message = Some("No more pages".to_string());
+            return true;
+        }
+        // Loads still on their way are for the page before
+        self.generation += 1;
+        let reload = Reload {
+            generation: self.generation,
+            ..Default::default()
+        };
+        self.count_pages(&tasks);
+        self.show_tasks(reload, None, tasks.tasks);
+        // How old the page is counts from when it was loaded, not shown
+        self.loaded_at = Some(arrived);
+        self.report_skipped(tasks.skipped);
+        self.prefetch_next_page();
+        true
+    }
+
+    // The first page has none before it, so it stays as it is
     pub fn previous_page(&mut self) {
         if self.page > 1 {
             self.page -= 1;
+            self.load_from_bottom();
         }
     }
 
     pub fn next(&mut self) {
+        if self.visible_rows.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
-                if i >= self.tasks.len() - 1 {
+                if i >= self.visible_rows.len() - 1 {
                     0
                 } else {
                     i + 1
@@ -89,10 +1478,13 @@ impl App {
     }
 
     pub fn previous(&mut self) {
+        if self.visible_rows.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i == 0 {
-                    self.tasks.len() - 1
+                    self.visible_rows.len() - 1
                 } else {
                     i - 1
                 }
@@ -102,126 +1494,3088 @@ impl App {
         self.state.select(Some(i));
     }
 
-    pub async fn select_task(
+    pub fn open_task(&mut self, task_id: u64) {
+        if let Some((task, comments)) = self.cached_detail(task_id) {
+            self.task_detail = Some(task);
+            self.task_comments = comments;
+            self.opening = None;
+            return;
+        }
+        self.opening = Some(task_id);
+        self.worker.send(Action::LoadTask(task_id));
+    }
+
+    // The details loaded before, while the list says the task hasn't changed
+    // since
+    fn cached_detail(&mut self, task_id: u64) -> Option<(Task, Vec<Comment>)> {
+        let updated = self.listed_task(task_id).and_then(|t| t.updated.clone());
+        let (task, comments) = self.detail_cache.get(&task_id)?;
+        (updated.is_some() && task.updated == updated).then(|| (task.clone(), comments.clone()))
+    }
+
+    // A task as it was last loaded into the list, the board or the finder
+    fn listed_task(&self, task_id: u64) -> Option<&Task> {
+        let board_tasks = self
+            .board
+            .iter()
+            .flat_map(|board| &board.buckets)
+            .flat_map(|bucket| bucket.tasks.iter().flatten());
+        self.tasks
+            .iter()
+            .chain(board_tasks)
+            .chain(&self.all_tasks)
+            .find(|task| task.id == task_id)
+    }
+
+    // Lists the links in the open task's description and comments to pick
+    // one to open in the browser
+    fn open_links(&mut self) {
+        let Some(task) = &self.task_detail else {
+            return;
+        };
+        let mut html = task.description.clone().unwrap_or_default();
+        for comment in &self.task_comments {
+            html.push_str(&comment.comment);
+        }
+        let links = extract_links(&html);
+        if links.is_empty() {
+            self.message = Some("No links in this task".to_string());
+            return;
+        }
+        let candidates = links
+            .iter()
+            .enumerate()
+            .map(|(i, (text, url))| {
+                if text.is_empty() || text == url {
+                    format!("{}. {}", i + 1, url)
+                } else {
+                    format!("{}. {} {}", i + 1, text, url)
+                }
+            })
+            .collect();
+        self.links = links.into_iter().map(|(_, url)| url).collect();
+        self.picker = Some(Picker::new(candidates));
+        self.input_mode = InputMode::Links;
+    }
+
+    // Runs the external editor on the text, reporting why when it fails
+    fn external_edit(&mut self, text: &str, extension: &str) -> Option<String> {
+        self.redraw = true;
+        match edit_text(text, extension) {
+            Ok(text) => Some(text),
+            Err(err) => {
+                self.message = Some(err.to_string());
+                None
+            }
+        }
+    }
+
+    // Writes a comment on the open task in the external editor
+    fn write_comment(&mut self) {
+        let Some(task_id) = self.task_detail.as_ref().map(|t| t.id) else {
+            return;
+        };
+        if self.read_only {
+            self.message = Some("Commenting is disabled in read-only mode".to_string());
+            return;
+        }
+        let Some(text) = self.external_edit("", "md") else {
+            return;
+        };
+        if text.trim().is_empty() {
+            self.message = Some("Empty comment, nothing was added".to_string());
+            return;
+        }
+        self.worker.send(Action::Save {
+            change: Change::AddComment {
+                task_id,
+                html: to_html(&text),
+            },
+            then: Then::Reopen(task_id),
+            retry: Retry::Comment(task_id),
+        });
+    }
+
+    // Asks what to react to when the open task has comments, otherwise goes
+    // straight to the reactions. Who I am is looked up first, to tell which
+    // reactions are mine.
+    fn open_reactions(&mut self) {
+        let Some(task) = &self.task_detail else {
+            return;
+        };
+        if !self.capabilities().reactions {
+            self.message = Some("Reactions need Vikunja 0.24".to_string());
+            return;
+        }
+        if self.read_only {
+            self.message = Some("Reacting is disabled in read-only mode".to_string());
+            return;
+        }
+        let task_id = task.id;
+        if self.current_user.is_none() {
+            self.worker
+                .send(Action::LoadCurrentUser(UserFor::Reactions));
+            return;
+        }
+        if self.task_comments.is_empty() {
+            self.open_reaction_picker(ReactionTarget::Task(task_id));
+            return;
+        }
+        let mut candidates = vec!["This task".to_string()];
+        candidates.extend(self.task_comments.iter().map(|comment| {
+            let text = html2text::from_read(comment.comment.as_bytes(), usize::MAX);
+            format!("Comment by {}: {}", comment.author.username, text.trim())
+        }));
+        self.picker = Some(Picker::new(candidates));
+        self.input_mode = InputMode::ReactionTargets;
+    }
+
+    fn reactions_on(&self, target: ReactionTarget) -> Option<&Reactions> {
+        match target {
+            ReactionTarget::Task(_) => self.task_detail.as_ref()?.reactions.as_ref(),
+            ReactionTarget::Comment(id) => self
+                .task_comments
+                .iter()
+                .find(|c| c.id == id)?
+                .reactions
+                .as_ref(),
+        }
+    }
+
+    fn reacted(&self, target: ReactionTarget, value: &str) -> bool {
+        let me = self.current_user.as_ref().map(|u| u.id);
+        self.reactions_on(target)
+            .and_then(|reactions| reactions.get(value))
+            .is_some_and(|users| users.iter().any(|u| Some(u.id) == me))
+    }
+
+    fn open_reaction_picker(&mut self, target: ReactionTarget) {
+        let candidates = REACTIONS
+            .iter()
+            .map(|value| {
+                if self.reacted(target, value) {
+                    format!("{} (remove)", value)
+                } else {
+                    value.to_string()
+                }
+            })
+            .collect();
+        self.reaction_target = Some(target);
+        self.picker = Some(Picker::new(candidates));
+        self.input_mode = InputMode::ReactionPicker;
+    }
+
+    // Takes my reaction back when I already gave it, adds it otherwise
+    fn toggle_reaction(&mut self, target: ReactionTarget, value: &str) {
+        let Some(task_id) = self.task_detail.as_ref().map(|t| t.id) else {
+            return;
+        };
+        self.worker.send(Action::Save {
+            change: Change::React {
+                target,
+                value: value.to_string(),
+                remove: self.reacted(target, value),
+            },
+            then: Then::Reopen(task_id),
+            retry: Retry::React(task_id),
+        });
+    }
+
+    // Shows the error on the status line, unless the session has expired, in
+    // which case the sign-in form opens and the request is kept to run again
+    pub fn handle_error(&mut self, err: Box<dyn Error + Send + Sync>, retry: Retry) {
+        if is_unauthorized(err.as_ref()) {
+            // Requests running side by side can all be turned away, the
+            // first one is run again
+            if let InputMode::Reauth = self.input_mode {
+                return;
+            }
+            self.auth_form = AuthForm::default();
+            self.pending_retry = Some(retry);
+            self.input_mode = InputMode::Reauth;
+        } else {
+            self.message = Some(format!("{}: {}", retry.context(), err));
+        }
+    }
+
+    fn open_task_or_report(&mut self, task_id: u64) {
+        self.remember();
+        self.open_task(task_id);
+    }
+
+    // Runs a `:` command: `label <name>` and `unlabel <name>` change every
+    // task matching the view and `move <project>` moves them, `archive
+    // <text>` searches the done tasks, `export [path]` copies or saves the
+    // selected task as Markdown, anything else is a task to go to, with
+    // or without `goto` in front
+    fn run_command(&mut self, command: &str) {
+        let command = command.trim();
+        if let Some(name) = command.strip_prefix("label ") {
+            return self.plan_bulk_label(name, false);
+        }
+        if let Some(name) = command.strip_prefix("unlabel ") {
+            return self.plan_bulk_label(name, true);
+        }
+        if let Some(name) = command.strip_prefix("move ") {
+            return self.plan_bulk_move(name);
+        }
+        if let Some(text) = command.strip_prefix("archive") {
+            if text.is_empty() || text.starts_with(' ') {
+                return self.search_archive(text.trim());
+            }
+        }
+        if let Some(path) = command.strip_prefix("export") {
+            match path.trim() {
+                "" => return self.export_task(ExportTarget::Clipboard),
+                _ if path.starts_with(' ') => {
+                    return self.export_task(ExportTarget::File(PathBuf::from(path.trim())))
+                }
+                _ => {}
+            }
+        }
+        let reference = command.strip_prefix("goto ").unwrap_or(command);
+        match parse_task_ref(reference) {
+            Some(task_ref) => self.goto_task(task_ref),
+            None if command.is_empty() => {}
+            None => self.message = Some(format!("Unknown command: {}", command)),
+        }
+    }
+
+    // Opens a task by its identifier. `#12` is the index in the current
+    // project, or the task id outside of one like in the web interface's links.
+    fn goto_task(&mut self, task_ref: TaskRef) {
+        let (project, index) = match &task_ref {
+            TaskRef::Index(index) => match &self.current_project {
+                Some(project) => (project.clone(), *index),
+                None => return self.show_task(*index),
+            },
+            TaskRef::Identifier(prefix, index) => {
+                if self.projects.is_empty() {
+                    self.worker
+                        .send(Action::LoadProjects(ProjectsFor::Goto(task_ref.clone())));
+                    return;
+                }
+                let project = self.projects.iter().find(|p| {
+                    !p.identifier.is_empty() && p.identifier.eq_ignore_ascii_case(prefix)
+                });
+                match project {
+                    Some(project) => (project.clone(), *index),
+                    None => {
+                        self.message = Some(format!("No project has the identifier {}", prefix));
+                        return;
+                    }
+                }
+            }
+        };
+        self.worker.send(Action::FindTask { project, index });
+    }
+
+    // Opens a task, selecting it too when it is in the list
+    fn show_task(&mut self, task_id: u64) {
+        let row = self.visible_tasks().position(|t| t.id == task_id);
+        if let Some(row) = row {
+            self.state.select(Some(row));
+        }
+        self.open_task_or_report(task_id);
+    }
+
+    // The default labels of the project the task goes to and the typed ones
+    fn new_task_labels(&self, parsed: &ParsedTask, project_id: Option<u64>) -> Vec<String> {
+        let mut labels = project_id
+            .and_then(|id| self.settings_for(id))
+            .map(|s| s.labels.clone())
+            .unwrap_or_default();
+        for title in &parsed.labels {
+            if !labels.iter().any(|t| t.eq_ignore_ascii_case(title)) {
+                labels.push(title.clone());
+            }
+        }
+        labels
+    }
+
+    // Creates the task with the labels and assignees from the quick add
+    // title, and the labels and bucket from the settings of its project
+    fn create_task(&mut self, parsed: ParsedTask, task: NewTask, new_labels: Vec<Label>) {
+        let labels = self.new_task_labels(&parsed, task.project_id);
+        // A bucket picked on the board wins over the configured one
+        let bucket = task
+            .project_id
+            .filter(|_| task.bucket_id.is_none())
+            .and_then(|id| self.settings_for(id))
+            .and_then(|s| s.bucket.clone());
+        if !new_labels.is_empty() {
+            // Fetched again for completion once they exist
+            self.label_cache = None;
+        }
+        self.worker.send(Action::CreateTask {
+            parsed,
+            task,
+            labels,
+            new_labels,
+            bucket,
+            known_users: self.known_users(),
+        });
+    }
+
+    pub fn load_state(&mut self, path: PathBuf) {
+        self.saved = State::load(&path);
+        self.state_path = Some(path);
+    }
+
+    pub fn load_task_index(&mut self, path: PathBuf) {
+        self.task_index = TaskIndex::load(&path);
+        self.task_index_path = Some(path);
+    }
+
+    fn index_tasks(&mut self) {
+        let Some(path) = &self.task_index_path else {
+            return;
+        };
+        if self.task_index.update(&self.tasks) {
+            if let Err(err) = self.task_index.save(path) {
+                self.message = Some(format!("Could not save the task index: {}", err));
+            }
+        }
+    }
+
+    fn save_state(&mut self) {
+        if let Some(path) = &self.state_path {
+            if let Err(err) = self.saved.save(path) {
+                self.message = Some(format!("Could not save state: {}", err));
+            }
+        }
+    }
+
+    // Keeps what is typed into the add popup, so a stray Esc or a crash
+    // doesn't lose a long description
+    fn stash_draft(&mut self) {
+        let draft = (!self.new_task_title.trim().is_empty()
+            || !self.new_task_description.trim().is_empty())
+        .then(|| Draft {
+            title: self.new_task_title.clone(),
+            description: self.new_task_description.clone(),
+        });
+        if draft != self.saved.draft {
+            self.saved.draft = draft;
+            self.save_state();
+        }
+    }
+
+    fn open_add_popup(&mut self) {
+        self.clear_new_task();
+        self.active_input = ActiveInput::Title;
+        self.input_mode = match self.saved.draft {
+            Some(_) => InputMode::RestoreDraft,
+            None => InputMode::Editing,
+        };
+    }
+
+    fn clear_new_task(&mut self) {
+        self.new_task_title.clear();
+        self.new_task_description.clear();
+        self.new_task_due = None;
+        self.new_task_reminder = None;
+        self.new_task_start = None;
+        self.new_task_end = None;
+        self.new_task_color = None;
+        self.new_task_project = None;
+        self.new_labels.clear();
+        self.new_labels_agreed = false;
+        self.new_task_preview = None;
+        self.saved.title_history.reset();
+    }
+
+    // Creates a task from each line as if typed into the add popup, for `add`
+    // on the command line. What the popup would ask about can't be answered,
+    // so those lines are left out with the reason.
+    pub async fn add_lines(
         &mut self,
-        instance_url: &str,
-        api_key: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(selected) = self.state.selected() {
-            let task = &self.tasks[selected];
-            let task_detail = fetch_task_detail(instance_url, api_key, task.id).await?;
-            self.task_detail = Some(task_detail);
+        lines: &[String],
+        create_labels: bool,
+    ) -> Vec<(String, Creation)> {
+        let mut outcomes = Vec::new();
+        for line in lines.iter().map(|line| line.trim()) {
+            if line.is_empty() {
+                continue;
+            }
+            self.new_task_title = line.to_string();
+            self.new_labels_agreed = create_labels;
+            let outcome = self.add_line().await;
+            self.input_mode = InputMode::Normal;
+            self.picker = None;
+            self.clear_new_task();
+            outcomes.push((line.to_string(), outcome));
+        }
+        outcomes
+    }
+
+    async fn add_line(&mut self) -> Creation {
+        let mut loaded = Vec::new();
+        let ready = loop {
+            match self.prepare_new_task() {
+                Ok(ready) => break ready,
+                // Loaded once, and only into the cache so nothing else is sent
+                Err(Held::Load(missing)) if !loaded.contains(&missing) => {
+                    let action = match missing {
+                        Missing::Projects => Action::LoadProjects(ProjectsFor::Startup),
+                        Missing::Labels => Action::LoadLabels(LabelsFor::Startup),
+                    };
+                    self.worker.send(action);
+                    self.settle().await;
+                    loaded.push(missing);
+                }
+                Err(Held::Load(Missing::Projects)) => {
+                    return Err("the projects couldn't be loaded".to_string())
+                }
+                Err(Held::Load(Missing::Labels)) => {
+                    return Err("the labels couldn't be loaded".to_string())
+                }
+                Err(Held::Refused(reason)) => return Err(reason),
+                Err(Held::NewLabels) => {
+                    let titles: Vec<&str> =
+                        self.new_labels.iter().map(|l| l.title.as_str()).collect();
+                    return Err(format!(
+                        "new labels {}, add --create-labels to create them",
+                        titles.join(", ")
+                    ));
+                }
+                Err(Held::ProjectChoice) => return Err("more than one project matches".to_string()),
+            }
+        };
+        self.create_task(ready.parsed, ready.task, ready.new_labels);
+        self.settle().await;
+        match self.created.take() {
+            _ if matches!(self.input_mode, InputMode::Reauth) => {
+                Err("the session has expired".to_string())
+            }
+            Some(creation) => creation,
+            None => Err("the server didn't answer".to_string()),
+        }
+    }
+
+    // The labels typed in quick add that don't exist yet, to be created with
+    // the task. They are asked about first.
+    fn labels_to_create(&mut self, parsed: &ParsedTask) -> Result<Vec<Label>, Held> {
+        if parsed.labels.is_empty() {
+            return Ok(Vec::new());
+        }
+        let Some(existing) = &self.label_cache else {
+            return Err(Held::Load(Missing::Labels));
+        };
+        let mut missing: Vec<Label> = Vec::new();
+        for title in &parsed.labels {
+            let known = existing.iter().chain(&missing);
+            if !known.clone().any(|l| l.title.eq_ignore_ascii_case(title)) {
+                missing.push(Label {
+                    title: title.clone(),
+                    hex_color: Some(self.new_label_color(title)),
+                    ..Default::default()
+                });
+            }
+        }
+        if missing.is_empty() || self.new_labels_agreed {
+            return Ok(missing);
+        }
+        self.new_labels = missing;
+        self.input_mode = InputMode::ConfirmNewLabels;
+        Err(Held::NewLabels)
+    }
+
+    // The configured color, or one of the task colors picked by the title
+    fn new_label_color(&self, title: &str) -> String {
+        match &self.label_color {
+            Some(color) => color.trim_start_matches('#').to_string(),
+            None => {
+                let i = title.bytes().map(usize::from).sum::<usize>() % TASK_COLORS.len();
+                TASK_COLORS[i].1.to_string()
+            }
         }
-        Ok(())
     }
 
-    pub async fn handle_input(
+    // The project a +project in quick add means: an exact title, or the only
+    // fuzzy match. Several matches open a picker and the task is added once
+    // one is picked.
+    fn resolve_new_task_project(&mut self, name: &str) -> Result<Project, Held> {
+        if let Some(project) = self.new_task_project.clone() {
+            return Ok(project);
+        }
+        if self.projects.is_empty() {
+            return Err(Held::Load(Missing::Projects));
+        }
+        if let Some(project) = self
+            .projects
+            .iter()
+            .find(|p| p.title.eq_ignore_ascii_case(name))
+        {
+            return Ok(project.clone());
+        }
+        let titles: Vec<&str> = self.projects.iter().map(|p| p.title.as_str()).collect();
+        let matches = fuzzy_filter(name, &titles);
+        match matches[..] {
+            [] => Err(Held::Refused(format!("No project matching '{}'", name))),
+            [i] => Ok(self.projects[i].clone()),
+            _ => {
+                self.project_choices = matches.iter().map(|&i| self.projects[i].clone()).collect();
+                let candidates = self
+                    .project_choices
+                    .iter()
+                    .map(|p| p.title.clone())
+                    .collect();
+                self.picker = Some(Picker::new(candidates));
+                self.input_mode = InputMode::ProjectChoice;
+                Err(Held::ProjectChoice)
+            }
+        }
+    }
+
+    // New tasks go into the project in view, and on the board into the
+    // bucket in view
+    pub fn new_task_bucket(&self) -> Option<&Bucket> {
+        if self.view_kind != ViewKind::Kanban {
+            return None;
+        }
+        let board = self.board.as_ref()?;
+        board.buckets.get(board.column)
+    }
+
+    // Steps through the task colors and back to none
+    fn cycle_new_task_color(&mut self) {
+        self.new_task_color = match self.new_task_color {
+            None => Some(0),
+            Some(i) if i + 1 < TASK_COLORS.len() => Some(i + 1),
+            Some(_) => None,
+        };
+    }
+
+    fn open_date_picker(&mut self, target: DateTarget) {
+        let current = match target {
+            DateTarget::Due => self.new_task_due,
+            DateTarget::Reminder => self.new_task_reminder,
+            DateTarget::Start => self.new_task_start,
+            DateTarget::End => self.new_task_end,
+        };
+        self.date_target = target;
+        self.date_picker = Some(DatePicker::new(current));
+        self.input_mode = InputMode::DatePicker;
+    }
+
+    fn close_date_picker(&mut self, picked: Option<Option<NaiveDate>>) {
+        if let Some(date) = picked {
+            match self.date_target {
+                DateTarget::Due => self.new_task_due = date,
+                DateTarget::Reminder => self.new_task_reminder = date,
+                DateTarget::Start => self.new_task_start = date,
+                DateTarget::End => self.new_task_end = date,
+            }
+        }
+        self.date_picker = None;
+        self.input_mode = InputMode::Editing;
+    }
+
+    // Users seen on loaded tasks, as assignees or creators
+    fn known_users(&self) -> Vec<User> {
+        let mut users: Vec<User> = Vec::new();
+        for task in self.tasks.iter().chain(&self.all_tasks) {
+            let assignees = task.assignees.iter().flatten();
+            for user in assignees.chain(&task.created_by) {
+                if !users.iter().any(|u| u.id == user.id) {
+                    users.push(user.clone());
+                }
+            }
+        }
+        users.sort_by(|a, b| a.username.cmp(&b.username));
+        users
+    }
+
+    // Opens, narrows or closes the completion list after the title changed
+    fn update_completion(&mut self) {
+        let Some((start, sigil, query)) = completion_token(&self.new_task_title) else {
+            self.completion = None;
+            return;
+        };
+        let kind = CompletionKind::from_sigil(sigil);
+        let query = query.to_string();
+        if let Some(completion) = self.completion.as_mut() {
+            if completion.start == start && completion.kind == kind {
+                completion.picker.set_query(&query);
+                return;
+            }
+        }
+
+        // Picked up again once the labels or projects are in
+        let Some(candidates) = self.completion_candidates(kind) else {
+            self.completion = None;
+            return;
+        };
+        self.completion = (!candidates.is_empty()).then(|| {
+            let mut picker = Picker::new(candidates);
+            picker.set_query(&query);
+            Completion {
+                kind,
+                start,
+                picker,
+            }
+        });
+    }
+
+    // Labels and projects are fetched once, users come from the loaded tasks.
+    // None while they are still loading.
+    fn completion_candidates(&mut self, kind: CompletionKind) -> Option<Vec<String>> {
+        let missing = match kind {
+            CompletionKind::Label if self.label_cache.is_none() => {
+                Some(Action::LoadLabels(LabelsFor::Completion))
+            }
+            CompletionKind::Project if self.projects.is_empty() => {
+                Some(Action::LoadProjects(ProjectsFor::Completion))
+            }
+            _ => None,
+        };
+        if let Some(action) = missing {
+            if !self.completion_loading {
+                self.completion_loading = true;
+                self.worker.send(action);
+            }
+            return None;
+        }
+        Some(match kind {
+            CompletionKind::Label => {
+                let labels = self.label_cache.iter().flatten();
+                labels.map(|l| l.title.clone()).collect()
+            }
+            CompletionKind::Project => self.projects.iter().map(|p| p.title.clone()).collect(),
+            CompletionKind::User => self.known_users().into_iter().map(|u| u.username).collect(),
+        })
+    }
+
+    // Shows the completions that were waiting for labels or projects
+    fn completions_loaded(&mut self) {
+        self.completion_loading = false;
+        if let InputMode::Insert = self.input_mode {
+            self.update_completion();
+        }
+    }
+
+    // Replaces the token being typed with the selected completion, quoted
+    // when it contains spaces
+    fn accept_completion(&mut self) {
+        let Some(completion) = self.completion.take() else {
+            return;
+        };
+        let Some(i) = completion.picker.selected() else {
+            return;
+        };
+        let value = &completion.picker.candidates[i];
+        let value = if value.contains(char::is_whitespace) {
+            format!("\"{}\"", value)
+        } else {
+            value.clone()
+        };
+        self.new_task_title.truncate(completion.start);
+        self.new_task_title.push(completion.kind.sigil());
+        self.new_task_title.push_str(&value);
+        self.new_task_title.push(' ');
+    }
+
+    fn run_retry(&mut self, retry: Retry) {
+        match retry {
+            Retry::Refresh => self.refresh_tasks(),
+            Retry::OpenTask(task_id) | Retry::React(task_id) | Retry::Comment(task_id) => {
+                self.open_task_or_report(task_id)
+            }
+            Retry::ProjectSwitcher => self.open_project_switcher(),
+            Retry::FindTask => self.open_task_finder(),
+            Retry::CreateTask {
+                parsed,
+                task,
+                new_labels,
+            } => self.create_task(parsed, *task, new_labels),
+            Retry::Reopen(task_ids) => self.reopen_tasks(task_ids),
+            Retry::Rename { task_id, title } => self.rename_task(task_id, title),
+            Retry::AssignToMe(task_id) => self.assign_to_me(task_id),
+            Retry::MineFilter => self.apply_mine_filter(),
+            Retry::Members => self.open_members(),
+            Retry::Share { member, right } => self.share_project(member, right),
+            Retry::Unshare(member) => self.unshare_project(member),
+            Retry::Teams => self.worker.send(Action::LoadTeams),
+            Retry::CreateTeam(name) => self.create_team(name),
+            Retry::AddTeamMember { team_id, username } => self.add_team_member(team_id, username),
+            Retry::DuplicateProject { project, parent_id } => {
+                self.duplicate_project(project, parent_id)
+            }
+            Retry::Delete(task) => self.delete_task(*task),
+            Retry::LogTime => self.log_time(),
+            Retry::BulkMove(name) => self.plan_bulk_move(&name),
+            Retry::BulkLabel { name, remove } => self.plan_bulk_label(&name, remove),
+        }
+    }
+
+    // Uses a pasted token when one is given, otherwise logs in with the
+    // username and password, then picks up where the session left off
+    fn submit_auth(&mut self) {
+        let form = &self.auth_form;
+        if !form.token.trim().is_empty() {
+            let token = form.token.trim().to_string();
+            self.signed_in(&token);
+        } else if form.username.is_empty() {
+            self.auth_form.error =
+                Some("Enter an API token, or a username and password".to_string());
+        } else {
+            self.worker.send(Action::Login {
+                username: form.username.clone(),
+                password: form.password.clone(),
+                passcode: form.totp_passcode.clone(),
+            });
+        }
+    }
+
+    fn logged_in(&mut self, result: ApiResult<String>) {
+        match result {
+            Ok(token) => self.signed_in(&token),
+            // Two-factor accounts get a second step for the passcode
+            Err(err) if err.downcast_ref::<TotpRequired>().is_some() => {
+                let form = &mut self.auth_form;
+                form.error = Some(if form.totp_passcode.is_some() {
+                    "Invalid TOTP passcode, try again".to_string()
+                } else {
+                    "Enter the passcode from your authenticator app".to_string()
+                });
+                form.totp_passcode = Some(String::new());
+                form.field = AuthField::Totp;
+            }
+            Err(err) => self.auth_form.error = Some(err.to_string()),
+        }
+    }
+
+    fn signed_in(&mut self, token: &str) {
+        self.api.set_token(token);
+        self.auth_form = AuthForm::default();
+        self.input_mode = InputMode::Normal;
+        if let Some(retry) = self.pending_retry.take() {
+            self.run_retry(retry);
+        }
+    }
+
+    pub fn toggle_view(&mut self) {
+        if self.view_kind != ViewKind::Kanban && !self.capabilities().project_views {
+            let version = self.server_info.as_ref().map_or("", |i| i.version.as_str());
+            self.message = Some(format!(
+                "The board needs project views from Vikunja 0.24 (server is {})",
+                version
+            ));
+            return;
+        }
+        self.view_kind = match self.view_kind {
+            ViewKind::List | ViewKind::Done => ViewKind::Kanban,
+            ViewKind::Kanban => ViewKind::List,
+        };
+        self.cancel_opening();
+        self.refresh_tasks();
+    }
+
+    // Switches between the done review and the list
+    pub fn toggle_done_review(&mut self) {
+        self.remember();
+        self.view_kind = match self.view_kind {
+            ViewKind::Done => ViewKind::List,
+            _ => ViewKind::Done,
+        };
+        self.archive = None;
+        self.open_done_review();
+    }
+
+    // Puts the selected task as Markdown on the clipboard or in a file,
+    // loading its description and comments first. The view stays as it is.
+    fn export_task(&mut self, target: ExportTarget) {
+        let Some(task_id) = self.selected_task().map(|task| task.id) else {
+            self.message = Some("No task to export".to_string());
+            return;
+        };
+        match self.cached_detail(task_id) {
+            Some((task, comments)) => self.finish_export(&task, &comments, target),
+            None => {
+                self.pending_export = Some((task_id, target));
+                self.worker.send(Action::LoadTask(task_id));
+            }
+        }
+    }
+
+    fn finish_export(&mut self, task: &Task, comments: &[Comment], target: ExportTarget) {
+        let project = self.projects.iter().find(|p| p.id == task.project_id);
+        let markdown = task_markdown(task, comments, project.map(|p| p.title.as_str()));
+        let title = task.title.clone();
+        self.message = Some(match target {
+            ExportTarget::Clipboard => {
+                self.clipboard = Some(markdown);
+                format!("Copied \"{}\" as Markdown", title)
+            }
+            ExportTarget::File(path) => match std::fs::write(expand_home(&path), markdown) {
+                Ok(()) => format!("Exported \"{}\" to {}", title, path.display()),
+                Err(err) => format!("Error exporting to {}: {}", path.display(), err),
+            },
+        });
+    }
+
+    // Searches every done task in view, not only the recent ones, most
+    // recently done first
+    fn search_archive(&mut self, text: &str) {
+        self.remember();
+        self.view_kind = ViewKind::Done;
+        self.archive = Some(text.to_string());
+        self.open_done_review();
+    }
+
+    fn open_done_review(&mut self) {
+        self.marked.clear();
+        self.page = 1;
+        self.task_detail = None;
+        self.load_from_top();
+    }
+
+    fn toggle_mark(&mut self) {
+        let Some(task_id) = self.selected_task().map(|task| task.id) else {
+            return;
+        };
+        if !self.marked.remove(&task_id) {
+            self.marked.insert(task_id);
+        }
+        self.task_lines.clear();
+        self.next();
+    }
+
+    // Whether quitting is fine right away. Changes still being saved and a
+    // new task not yet added are asked about first.
+    fn request_quit(&mut self) -> bool {
+        let question = match self.worker.saving() {
+            0 if self.typing_new_task() => {
+                "The new task hasn't been added. Quit and discard it?".to_string()
+            }
+            0 if self.has_unsent_input() => {
+                "The tracked time hasn't been logged. Quit and discard it?".to_string()
+            }
+            0 => return true,
+            1 => "A change is still being saved. Quit anyway?".to_string(),
+            saving => format!("{} changes are still being saved. Quit anyway?", saving),
+        };
+        self.quit_question = Some(question);
+        self.input_mode = InputMode::ConfirmQuit;
+        false
+    }
+
+    fn typing_new_task(&self) -> bool {
+        !self.new_task_title.trim().is_empty() || !self.new_task_description.trim().is_empty()
+    }
+
+    // Typed text not sent yet, or time tracked and not logged yet
+    fn has_unsent_input(&self) -> bool {
+        self.typing_new_task() || self.timer.is_some()
+    }
+
+    // Finds what the label would change before asking to go ahead
+    fn plan_bulk_label(&mut self, name: &str, remove: bool) {
+        if self.read_only {
+            self.message = Some("Labeling tasks is disabled in read-only mode".to_string());
+            return;
+        }
+        if self.bulk_label.is_some() {
+            self.message = Some("Still labeling the last tasks".to_string());
+            return;
+        }
+        let name = name.trim().trim_start_matches('*').trim_matches('"');
+        if name.is_empty() {
+            self.message = Some("Which label? Like :label stale".to_string());
+            return;
+        }
+        self.worker.send(Action::PlanBulkLabel {
+            query: self.task_query(),
+            name: name.to_string(),
+            remove,
+        });
+    }
+
+    fn bulk_label_planned(
         &mut self,
-        key: crossterm::event::KeyEvent,
-        instance_url: &str,
-        api_key: &str,
-    ) -> io::Result<bool> {
-        match self.input_mode {
-            InputMode::Normal => match key.code {
-                KeyCode::Char('q') => return Ok(true),
-                KeyCode::Char('j') => self.next(),
-                KeyCode::Char('k') => self.previous(),
-                KeyCode::Char('n') => {
-                    // Next page
-                    self.next_page();
-                    if let Err(err) = self.refresh_tasks(instance_url, api_key).await {
-                        eprintln!("Error fetching tasks: {}", err);
+        name: String,
+        remove: bool,
+        result: ApiResult<Option<(Label, Vec<u64>)>>,
+    ) {
+        let (label, task_ids) = match result {
+            Ok(Some(plan)) => plan,
+            Ok(None) => {
+                self.message = Some(format!("No label named {}", name));
+                return;
+            }
+            Err(err) => return self.handle_error(err, Retry::BulkLabel { name, remove }),
+        };
+        if task_ids.is_empty() {
+            self.message = Some(if remove {
+                format!("No matching task has the label {}", label.title)
+            } else {
+                format!("Every matching task has the label {}", label.title)
+            });
+            return;
+        }
+        self.bulk_label = Some(BulkLabel {
+            label,
+            remove,
+            task_ids,
+            progress: None,
+        });
+        self.input_mode = InputMode::ConfirmBulkLabel;
+    }
+
+    fn start_bulk_label(&mut self) {
+        let Some(bulk) = self.bulk_label.as_mut() else {
+            return;
+        };
+        let progress = Arc::new(AtomicUsize::new(0));
+        bulk.progress = Some(progress.clone());
+        self.worker.send(Action::BulkLabel {
+            label: bulk.label.clone(),
+            remove: bulk.remove,
+            task_ids: bulk.task_ids.clone(),
+            progress,
+        });
+    }
+
+    fn bulk_labeled(&mut self, label: Label, remove: bool, changed: usize, result: ApiResult<()>) {
+        self.bulk_label = None;
+        // Labels show in the list and the details, both are loaded again
+        self.detail_cache.clear();
+        self.refresh_tasks();
+        match result {
+            Ok(()) => {
+                let done = if remove { "Removed" } else { "Added" };
+                self.message = Some(format!(
+                    "{} the label {} on {} tasks",
+                    done, label.title, changed
+                ));
+            }
+            Err(err) => self.handle_error(
+                err,
+                Retry::BulkLabel {
+                    name: label.title,
+                    remove,
+                },
+            ),
+        }
+    }
+
+    // Finds the project and the tasks that would move before asking to go ahead
+    fn plan_bulk_move(&mut self, name: &str) {
+        if self.read_only {
+            self.message = Some("Moving tasks is disabled in read-only mode".to_string());
+            return;
+        }
+        if self.bulk_move.is_some() {
+            self.message = Some("Still moving the last tasks".to_string());
+            return;
+        }
+        let name = name.trim().trim_start_matches('+').trim_matches('"');
+        if name.is_empty() {
+            self.message = Some("Which project? Like :move Archive".to_string());
+            return;
+        }
+        self.worker.send(Action::PlanBulkMove {
+            query: self.task_query(),
+            name: name.to_string(),
+            marked: self.marked.iter().copied().collect(),
+        });
+    }
+
+    fn bulk_move_planned(&mut self, name: String, result: ApiResult<Option<(Project, Vec<u64>)>>) {
+        let (project, task_ids) = match result {
+            Ok(Some(plan)) => plan,
+            Ok(None) => {
+                self.message = Some(format!("No project named {}", name));
+                return;
+            }
+            Err(err) => return self.handle_error(err, Retry::BulkMove(name)),
+        };
+        if task_ids.is_empty() {
+            self.message = Some(format!("No matching task to move to {}", project.title));
+            return;
+        }
+        self.bulk_move = Some(BulkMove {
+            project,
+            task_ids,
+            progress: None,
+        });
+        self.input_mode = InputMode::ConfirmBulkMove;
+    }
+
+    fn start_bulk_move(&mut self) {
+        let Some(bulk) = self.bulk_move.as_mut() else {
+            return;
+        };
+        let progress = Arc::new(AtomicUsize::new(0));
+        bulk.progress = Some(progress.clone());
+        self.worker.send(Action::BulkMove {
+            project: bulk.project.clone(),
+            task_ids: bulk.task_ids.clone(),
+            progress,
+        });
+    }
+
+    fn bulk_moved(&mut self, project: Project, moved: usize, result: ApiResult<()>) {
+        self.bulk_move = None;
+        self.marked.clear();
+        self.detail_cache.clear();
+        self.refresh_tasks();
+        match result {
+            Ok(()) => {
+                let tasks = if moved == 1 { "task" } else { "tasks" };
+                self.message = Some(format!("Moved {} {} to {}", moved, tasks, project.title));
+            }
+            Err(err) => self.handle_error(err, Retry::BulkMove(project.title)),
+        }
+    }
+
+    fn start_title_edit(&mut self) {
+        let Some(task) = self.selected_task() else {
+            return;
+        };
+        self.title_edit = Some(TitleEdit::new(task));
+        self.input_mode = InputMode::RenameTask;
+    }
+
+    // Shows the new title right away and saves it in the background
+    fn save_title_edit(&mut self) {
+        let Some(edit) = self.title_edit.as_ref() else {
+            self.input_mode = InputMode::Normal;
+            return;
+        };
+        let title = edit.text.trim().to_string();
+        if title.is_empty() {
+            self.message = Some("Task title cannot be empty".to_string());
+            return;
+        }
+        let task_id = edit.task_id;
+        self.title_edit = None;
+        self.input_mode = InputMode::Normal;
+        let Some(task) = self.tasks.iter_mut().find(|task| task.id == task_id) else {
+            return;
+        };
+        if task.title == title {
+            return;
+        }
+        task.title = title.clone();
+        self.task_lines.clear();
+        if let Some(detail) = self.task_detail.as_mut().filter(|t| t.id == task_id) {
+            detail.title = title.clone();
+        }
+        self.rename_task(task_id, title);
+    }
+
+    // Adds the current user to the task's assignees, fetching who that is
+    // the first time
+    fn assign_to_me(&mut self, task_id: u64) {
+        let Some(me) = self.current_user.clone() else {
+            self.worker
+                .send(Action::LoadCurrentUser(UserFor::AssignToMe(task_id)));
+            return;
+        };
+        let assigned = self
+            .tasks
+            .iter()
+            .chain(self.task_detail.as_ref())
+            .filter(|task| task.id == task_id)
+            .flat_map(|task| task.assignees.iter().flatten())
+            .any(|user| user.id == me.id);
+        if assigned {
+            self.message = Some("Already assigned to you".to_string());
+            return;
+        }
+        self.message = Some(format!("Assigned to {}", me.username));
+        self.worker.send(Action::Save {
+            change: Change::Assign {
+                task_id,
+                user_id: me.id,
+            },
+            then: Then::ReloadAt(task_id),
+            retry: Retry::AssignToMe(task_id),
+        });
+    }
+
+    fn rename_task(&mut self, task_id: u64, title: String) {
+        self.worker.send(Action::Save {
+            change: Change::Rename {
+                task_id,
+                title: title.clone(),
+            },
+            then: Then::ReloadAt(task_id),
+            retry: Retry::Rename { task_id, title },
+        });
+    }
+
+    fn delete_task(&mut self, task: Task) {
+        self.worker.send(Action::Save {
+            change: Change::Delete(task.id),
+            then: Then::Deleted(Box::new(task.clone())),
+            retry: Retry::Delete(Box::new(task)),
+        });
+    }
+
+    // Starts tracking time on the selected task, or stops the running timer
+    // and logs the time on its task as a comment
+    fn toggle_timer(&mut self) {
+        if self.read_only {
+            self.message = Some("Tracking time is disabled in read-only mode".to_string());
+            return;
+        }
+        let Some(timer) = self.timer.as_mut() else {
+            if let Some(task) = self.selected_task() {
+                self.timer = Some(Timer::new(task.id, &task.title, Local::now().date_naive()));
+            }
+            return;
+        };
+        if timer.sending {
+            self.message = Some("Still logging the time".to_string());
+            return;
+        }
+        if timer.stopped.is_none() {
+            let elapsed = timer.elapsed();
+            if elapsed.as_secs() < 60 {
+                self.timer = None;
+                self.message = Some("Stopped the timer, under a minute isn't logged".to_string());
+                return;
+            }
+            timer.stopped = Some(elapsed);
+        }
+        self.log_time();
+    }
+
+    // Posts the stopped timer as a comment. The timer stays until the comment
+    // is saved, so a failed post can be sent again with W.
+    fn log_time(&mut self) {
+        let Some(timer) = self.timer.as_mut() else {
+            return;
+        };
+        timer.sending = true;
+        let elapsed = timer.elapsed();
+        let task_id = timer.task_id;
+        self.worker.send(Action::Save {
+            change: Change::AddComment {
+                task_id,
+                html: to_html(&time_comment(elapsed, timer.date)),
+            },
+            then: Then::Tracked {
+                task_id,
+                title: timer.title.clone(),
+                logged: format_elapsed(elapsed),
+                row: timesheet_row(timer, elapsed),
+            },
+            retry: Retry::LogTime,
+        });
+    }
+
+    // In the done view u reopens tasks, so there is no undo there
+    pub fn can_undo_delete(&self) -> bool {
+        self.view_kind != ViewKind::Done
+            && self
+                .recently_deleted
+                .as_ref()
+                .is_some_and(|(_, at)| at.elapsed() < UNDO_WINDOW)
+    }
+
+    // Creates the deleted task again with its title, description, labels
+    // and due date. Comments, attachments and the rest are gone with it.
+    fn restore_deleted(&mut self) {
+        let Some((task, _)) = self.recently_deleted.take() else {
+            return;
+        };
+        let new_task = NewTask {
+            title: task.title.clone(),
+            description: task.description.clone().filter(|d| d != "<p></p>"),
+            due_date: task.due_at().and(task.due_date.clone()),
+            project_id: Some(task.project_id),
+            ..Default::default()
+        };
+        let labels = task
+            .labels
+            .iter()
+            .flatten()
+            .map(|label| label.title.clone())
+            .collect();
+        self.message = Some(format!("Restoring \"{}\"", task.title));
+        self.worker.send(Action::CreateTask {
+            parsed: ParsedTask::default(),
+            task: new_task,
+            labels,
+            new_labels: Vec::new(),
+            bucket: None,
+            known_users: Vec::new(),
+        });
+    }
+
+    // Marks the marked tasks undone, or the selected one when none are marked
+    fn reopen_tasks(&mut self, task_ids: Vec<u64>) {
+        let task_ids = if task_ids.is_empty() {
+            self.selected_task()
+                .map(|task| task.id)
+                .into_iter()
+                .collect()
+        } else {
+            task_ids
+        };
+        if task_ids.is_empty() {
+            return;
+        }
+        self.worker.send(Action::Save {
+            change: Change::Reopen(task_ids.clone()),
+            then: Then::Reload,
+            retry: Retry::Reopen(task_ids),
+        });
+    }
+
+    pub fn open_project_switcher(&mut self) {
+        if self.projects.is_empty() {
+            self.worker
+                .send(Action::LoadProjects(ProjectsFor::Switcher));
+            return;
+        }
+        // The first entry returns to the unscoped task list
+        let mut candidates = vec!["All Projects".to_string()];
+        candidates.extend(self.projects.iter().map(|p| p.title.clone()));
+        let mut colors = vec![None];
+        colors.extend(self.projects.iter().map(|p| hex_color(&p.hex_color)));
+        self.picker = Some(Picker::new(candidates).with_colors(colors));
+        self.input_mode = InputMode::ProjectSwitcher;
+    }
+
+    // Picks where the copy of the project goes, the top level first
+    fn pick_duplicate_parent(&mut self, project: Project) {
+        self.project_choices = self
+            .projects
+            .iter()
+            .filter(|p| p.id != project.id)
+            .cloned()
+            .collect();
+        let mut candidates = vec!["Top Level".to_string()];
+        candidates.extend(self.project_choices.iter().map(|p| p.title.clone()));
+        self.picker = Some(Picker::new(candidates));
+        self.duplicating = Some(project);
+        self.input_mode = InputMode::DuplicateParent;
+    }
+
+    fn duplicate_project(&mut self, project: Project, parent_id: u64) {
+        self.worker.send(Action::Save {
+            change: Change::DuplicateProject {
+                project_id: project.id,
+                parent_id,
+            },
+            then: Then::Duplicated(project.title.clone()),
+            retry: Retry::DuplicateProject { project, parent_id },
+        });
+    }
+
+    // The finder searches every task, not just the current page, so all
+    // pages are loaded first and the finder opens once they have arrived
+    pub fn open_task_finder(&mut self) {
+        if self.all_tasks_load.is_some() {
+            return;
+        }
+        let progress = Arc::new(LoadProgress::default());
+        self.worker.send(Action::LoadAllTasks {
+            query: self.task_query(),
+            progress: progress.clone(),
+        });
+        self.all_tasks_load = Some(AllTasksLoad { progress });
+    }
+
+    // Picks up the results of background work, called on every tick of the UI loop
+    pub fn poll_background(&mut self) {
+        self.poll_notifications();
+        if !matches!(self.input_mode, InputMode::Reauth) {
+            for message in std::mem::take(&mut self.deferred) {
+                self.update(message);
+            }
+        }
+        while let Some(message) = self.worker.try_recv() {
+            self.update(message);
+        }
+    }
+
+    // Whether requests the view waits for are still running
+    pub fn loading(&self) -> bool {
+        self.worker.loading()
+    }
+
+    // Waits for the requests in flight and takes in their results, for running
+    // without the UI loop
+    pub async fn settle(&mut self) {
+        while self.worker.busy() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            self.poll_background();
+        }
+    }
+
+    fn synced(&mut self) {
+        self.loaded_at = Some(Instant::now());
+        self.last_sync = self.sync_started.take().map(|started| started.elapsed());
+    }
+
+    // Applies the result of an action
+    fn update(&mut self, message: Message) {
+        match message {
+            Message::ServerInfo(result) => {
+                if let Ok(info) = result {
+                    self.set_server_info(info);
+                }
+            }
+            Message::Tasks { reload, .. } | Message::Board { reload, .. }
+                if reload.generation != self.generation => {}
+            Message::Tasks {
+                reload,
+                list_view_id,
+                result,
+            } => {
+                self.moving = false;
+                match result {
+                    // Past the last page, the page before is loaded instead of
+                    // showing nothing
+                    Ok(page) if page.tasks.is_empty() && self.page > 1 => {
+                        self.page -= 1;
+                        self.message = Some("No more pages".to_string());
+                        self.refresh_tasks();
+                    }
+                    Ok(page) => {
+                        self.count_pages(&page);
+                        self.show_tasks(reload, list_view_id, page.tasks);
+                        self.index_tasks();
+                        self.synced();
+                        self.report_skipped(page.skipped);
+                        self.report_next_occurrence();
+                        self.prefetch_next_page();
+                    }
+                    Err(err) => self.handle_error(err, Retry::Refresh),
+                }
+            }
+            Message::Board { reload, result } => {
+                self.moving = false;
+                match result {
+                    Ok(Some((view, buckets))) => {
+                        let skipped = buckets.iter().map(|b| b.skipped).sum();
+                        self.show_board(reload, &view, buckets);
+                        self.synced();
+                        self.report_skipped(skipped);
+                        self.report_next_occurrence();
+                    }
+                    Ok(None) => {
+                        self.board = None;
+                        let title = self.current_project.as_ref().map_or("", |p| &p.title);
+                        let err = format!("Project {} has no kanban view", title);
+                        self.handle_error(err.into(), Retry::Refresh);
+                    }
+                    Err(err) => self.handle_error(err, Retry::Refresh),
+                }
+            }
+            // Don't pop the finder up over the sign-in form
+            message @ Message::AllTasks(_) if matches!(self.input_mode, InputMode::Reauth) => {
+                self.deferred.push(message);
+            }
+            Message::AllTasks(result) => {
+                self.all_tasks_load = None;
+                match result {
+                    Ok(tasks) => {
+                        self.all_tasks = tasks;
+                        let candidates = self.all_tasks.iter().map(|t| t.title.clone()).collect();
+                        self.picker = Some(Picker::new(candidates));
+                        self.input_mode = InputMode::TaskFinder;
+                    }
+                    Err(err) => self.handle_error(err, Retry::FindTask),
+                }
+            }
+            // Loaded to be exported, not shown
+            Message::Task { task_id, result }
+                if self.opening != Some(task_id)
+                    && self
+                        .pending_export
+                        .as_ref()
+                        .is_some_and(|(id, _)| *id == task_id) =>
+            {
+                let target = self.pending_export.take().map(|(_, target)| target);
+                match (result, target) {
+                    (Ok((task, comments)), Some(target)) => {
+                        self.finish_export(&task, &comments, target);
+                        self.detail_cache.insert(task_id, (*task, comments));
+                    }
+                    (Err(err), _) => {
+                        self.message = Some(format!("Error loading the task to export: {}", err))
+                    }
+                    _ => {}
+                }
+            }
+            // Another task was opened in the meantime
+            Message::Task { task_id, .. } if self.opening != Some(task_id) => {}
+            Message::Task { task_id, result } => {
+                self.opening = None;
+                match result {
+                    Ok((task, comments)) => {
+                        self.detail_cache
+                            .insert(task_id, (*task.clone(), comments.clone()));
+                        // The subtask progress in the list may have changed
+                        self.task_lines.clear();
+                        if let Some((_, target)) =
+                            self.pending_export.take_if(|(id, _)| *id == task_id)
+                        {
+                            self.finish_export(&task, &comments, target);
+                        }
+                        self.task_detail = Some(*task);
+                        self.task_comments = comments;
+                    }
+                    Err(err) => self.handle_error(err, Retry::OpenTask(task_id)),
+                }
+            }
+            Message::Projects { wanted_for, result } => {
+                let projects = match result {
+                    Ok(projects) => projects,
+                    Err(err) => {
+                        return match wanted_for {
+                            ProjectsFor::Switcher => self.handle_error(err, Retry::ProjectSwitcher),
+                            ProjectsFor::Completion => {
+                                self.completion_loading = false;
+                                self.message = Some(format!("Error loading completions: {}", err));
+                            }
+                            ProjectsFor::Goto(_) => self.handle_error(err, Retry::Refresh),
+                            ProjectsFor::NewTask => {
+                                self.message = Some(format!("Error fetching projects: {}", err));
+                            }
+                            // Fetched again when something needs them
+                            ProjectsFor::Startup => {}
+                        };
+                    }
+                };
+                self.projects = projects;
+                match wanted_for {
+                    ProjectsFor::Switcher => self.open_project_switcher(),
+                    ProjectsFor::Completion => self.completions_loaded(),
+                    ProjectsFor::Goto(task_ref) => self.goto_task(task_ref),
+                    ProjectsFor::NewTask if self.projects.is_empty() => {
+                        self.message = Some("There are no projects to add the task to".to_string());
+                    }
+                    ProjectsFor::NewTask => self.submit_new_task(),
+                    ProjectsFor::Startup => {}
+                }
+            }
+            Message::Labels { wanted_for, result } => match (result, wanted_for) {
+                (Ok(labels), wanted_for) => {
+                    self.label_cache = Some(labels);
+                    match wanted_for {
+                        LabelsFor::Completion => self.completions_loaded(),
+                        LabelsFor::NewTask => self.submit_new_task(),
+                        LabelsFor::Startup => {}
+                    }
+                }
+                (Err(err), LabelsFor::Completion) => {
+                    self.completion_loading = false;
+                    self.message = Some(format!("Error loading completions: {}", err));
+                }
+                (Err(err), LabelsFor::NewTask | LabelsFor::Startup) => {
+                    self.message = Some(format!("Error fetching labels: {}", err));
+                }
+            },
+            Message::CurrentUser { wanted_for, result } => match result {
+                Ok(user) => {
+                    self.current_user = Some(user);
+                    match wanted_for {
+                        UserFor::Reactions => self.open_reactions(),
+                        UserFor::AssignToMe(task_id) => self.assign_to_me(task_id),
+                        UserFor::MineFilter => self.apply_mine_filter(),
+                        UserFor::Startup => {}
+                    }
+                }
+                Err(err) => match wanted_for {
+                    UserFor::Reactions => {
+                        let task_id = self.task_detail.as_ref().map_or(0, |t| t.id);
+                        self.handle_error(err, Retry::React(task_id));
+                    }
+                    UserFor::AssignToMe(task_id) => {
+                        self.handle_error(err, Retry::AssignToMe(task_id))
+                    }
+                    UserFor::MineFilter => self.handle_error(err, Retry::MineFilter),
+                    UserFor::Startup => {}
+                },
+            },
+            // Failures wait for the next check, link shares and some servers
+            // have no notifications
+            Message::Notifications(result) => {
+                self.notifications_loading = false;
+                if let Ok(notifications) = result {
+                    self.notifications = notifications;
+                    if self.notification_state.selected().is_none() || self.notifications.is_empty()
+                    {
+                        self.notification_state
+                            .select((!self.notifications.is_empty()).then_some(0));
+                    }
+                }
+            }
+            Message::Members { project, result } => match result {
+                Ok((users, teams, your_teams)) => {
+                    // A reload after a change keeps the row and the permission
+                    let (row, right) = match self.members.take() {
+                        Some(members) if members.project.id == project.id => {
+                            (members.state.selected(), members.right)
+                        }
+                        _ => (Some(0), 0),
+                    };
+                    let mut members = Members::new(project, users, teams, your_teams);
+                    members.right = right;
+                    members.select(row);
+                    self.members = Some(members);
+                    if let InputMode::Normal = self.input_mode {
+                        self.input_mode = InputMode::Members;
+                    }
+                }
+                Err(err) => self.handle_error(err, Retry::Members),
+            },
+            Message::Teams(result) => match result {
+                Ok(teams) => {
+                    let selected = self
+                        .teams
+                        .as_ref()
+                        .and_then(Teams::selected)
+                        .map(|team| team.id);
+                    let mut view = Teams::new(teams);
+                    if let Some(team_id) = selected {
+                        view.select_team(team_id);
+                    }
+                    self.teams = Some(view);
+                    if let InputMode::Normal = self.input_mode {
+                        self.input_mode = InputMode::Teams;
+                    }
+                }
+                Err(err) => self.handle_error(err, Retry::Teams),
+            },
+            Message::Found {
+                project,
+                index,
+                result,
+            } => match result {
+                Ok(Some(task_id)) => self.show_task(task_id),
+                Ok(None) => self.message = Some(format!("No task #{} in {}", index, project.title)),
+                Err(err) => self.handle_error(err, Retry::Refresh),
+            },
+            Message::Created {
+                parsed,
+                task,
+                new_labels,
+                result,
+            } => match result {
+                Ok(tagged) => {
+                    if self.submitted_draft.is_some() && self.saved.draft == self.submitted_draft {
+                        self.saved.draft = None;
+                        self.save_state();
+                    }
+                    self.submitted_draft = None;
+                    let note = match tagged {
+                        Ok(missing) if !missing.is_empty() => {
+                            Some(format!("Not found: {}", missing.join(", ")))
+                        }
+                        Ok(_) => None,
+                        Err(err) => Some(format!("Error tagging the new task: {}", err)),
+                    };
+                    if note.is_some() {
+                        self.message = note.clone();
+                    }
+                    self.created = Some(Ok(note));
+                    self.refresh_tasks();
+                }
+                Err(err) => {
+                    self.created = Some(Err(err.to_string()));
+                    self.handle_error(
+                        err,
+                        Retry::CreateTask {
+                            parsed,
+                            task: Box::new(task),
+                            new_labels,
+                        },
+                    )
+                }
+            },
+            Message::Saved {
+                result,
+                then,
+                retry,
+            } => {
+                if let Err(err) = result {
+                    self.moving = false;
+                    if let (Retry::LogTime, Some(timer)) = (&retry, self.timer.as_mut()) {
+                        timer.sending = false;
+                    }
+                    return self.handle_error(err, retry);
+                }
+                match then {
+                    Then::ReloadAt(task_id) => {
+                        self.detail_cache.remove(&task_id);
+                        self.reload(Some(task_id), None);
+                    }
+                    // Comments and reactions leave the task's updated
+                    // timestamp alone
+                    Then::Reopen(task_id) => {
+                        self.detail_cache.remove(&task_id);
+                        self.open_task(task_id);
+                    }
+                    Then::Reload => {
+                        self.marked.clear();
+                        self.refresh_tasks();
+                    }
+                    Then::Members(project) => self.worker.send(Action::LoadMembers(project)),
+                    Then::Teams => self.worker.send(Action::LoadTeams),
+                    Then::Deleted(task) => {
+                        self.detail_cache.remove(&task.id);
+                        if self.task_detail.as_ref().is_some_and(|t| t.id == task.id) {
+                            self.task_detail = None;
+                        }
+                        self.message = Some(format!("Deleted \"{}\", u to undo", task.title));
+                        self.recently_deleted = Some((*task, Instant::now()));
+                        self.refresh_tasks();
+                    }
+                    Then::Tracked {
+                        task_id,
+                        title,
+                        logged,
+                        row,
+                    } => {
+                        self.timer = None;
+                        self.detail_cache.remove(&task_id);
+                        if self.task_detail.as_ref().is_some_and(|t| t.id == task_id) {
+                            self.open_task(task_id);
+                        }
+                        let written = match &self.timesheet {
+                            Some(path) => append_timesheet(path, &row),
+                            None => Ok(()),
+                        };
+                        self.message = Some(match written {
+                            Ok(()) => format!("Logged {} on {}", logged, title),
+                            Err(err) => {
+                                format!("Logged {}, error writing the timesheet: {}", logged, err)
+                            }
+                        });
+                    }
+                    Then::Duplicated(title) => {
+                        self.message = Some(format!("Duplicated {}", title));
+                        self.projects.clear();
+                        // An open switcher gets the copy, otherwise it shows
+                        // the next time
+                        if let InputMode::ProjectSwitcher = self.input_mode {
+                            self.open_project_switcher();
+                        }
+                    }
+                    Then::NextOccurrence(task_id) => {
+                        self.detail_cache.remove(&task_id);
+                        self.next_occurrence = Some(task_id);
+                        self.reload(Some(task_id), None);
+                    }
+                }
+            }
+            Message::MarkedRead { read, open, result } => {
+                self.notifications_read(&read);
+                match result {
+                    Ok(()) => {
+                        if let Some(task_id) = open {
+                            self.input_mode = InputMode::Normal;
+                            self.open_task_or_report(task_id);
+                        }
+                    }
+                    Err(err) => self.handle_error(err, Retry::Refresh),
+                }
+            }
+            Message::BulkLabelPlan {
+                name,
+                remove,
+                result,
+            } => self.bulk_label_planned(name, remove, result),
+            Message::BulkLabeled {
+                label,
+                remove,
+                changed,
+                result,
+            } => self.bulk_labeled(label, remove, changed, result),
+            Message::BulkMovePlan { name, result } => self.bulk_move_planned(name, result),
+            Message::BulkMoved {
+                project,
+                moved,
+                result,
+            } => self.bulk_moved(project, moved, result),
+            Message::Prefetched {
+                query,
+                page,
+                result,
+            } => {
+                // Dropped when the view changed meanwhile, and on errors, as
+                // `n` loads the page itself then
+                let current = self
+                    .prefetch
+                    .as_mut()
+                    .filter(|p| p.query == query && p.page == page);
+                match (current, result) {
+                    (Some(prefetch), Ok(tasks)) => prefetch.result = Some((tasks, Instant::now())),
+                    (Some(_), Err(_)) => self.prefetch = None,
+                    (None, _) => {}
+                }
+            }
+            Message::LoggedIn(result) => self.logged_in(result),
+            Message::ProfileResolved { profile, result } => match result {
+                Ok(api_key) => self.use_profile(profile, &api_key),
+                Err(err) => self.handle_error(err, Retry::Refresh),
+            },
+            Message::Calendar { feed, result } => match result {
+                Ok(events) => {
+                    self.external_events
+                        .retain(|event| event.calendar != feed.name);
+                    self.external_events.extend(events);
+                    self.external_events.sort_by_key(|event| event.date);
+                }
+                Err(err) => {
+                    self.message =
+                        Some(format!("Error loading the calendar {}: {}", feed.name, err))
+                }
+            },
+            Message::Cancelled => {}
+        }
+    }
+
+    // Checks for notifications every NOTIFICATION_INTERVAL
+    fn poll_notifications(&mut self) {
+        if self.notifications_loading {
+            return;
+        }
+        let due = self
+            .notifications_checked
+            .is_none_or(|checked| checked.elapsed() >= NOTIFICATION_INTERVAL);
+        if due {
+            self.worker.send(Action::LoadNotifications);
+            self.notifications_loading = true;
+            self.notifications_checked = Some(Instant::now());
+        }
+    }
+
+    pub fn unread_notifications(&self) -> usize {
+        self.notifications.iter().filter(|n| n.is_unread()).count()
+    }
+
+    // Lists who the current project is shared with once they are loaded
+    fn open_members(&mut self) {
+        let Some(project) = self.current_project.clone() else {
+            self.message = Some("Switch to a project (s) to see who it is shared with".to_string());
+            return;
+        };
+        self.worker.send(Action::LoadMembers(project));
+    }
+
+    fn share_project(&mut self, member: Member, right: u8) {
+        let Some(members) = &self.members else {
+            return;
+        };
+        self.worker.send(Action::Save {
+            change: Change::Share {
+                project_id: members.project.id,
+                member: member.clone(),
+                right,
+            },
+            then: Then::Members(members.project.clone()),
+            retry: Retry::Share { member, right },
+        });
+    }
+
+    fn unshare_project(&mut self, member: Member) {
+        let Some(members) = &self.members else {
+            return;
+        };
+        self.worker.send(Action::Save {
+            change: Change::Unshare {
+                project_id: members.project.id,
+                member: member.clone(),
+            },
+            then: Then::Members(members.project.clone()),
+            retry: Retry::Unshare(member),
+        });
+    }
+
+    fn create_team(&mut self, name: String) {
+        self.worker.send(Action::Save {
+            change: Change::CreateTeam(name.clone()),
+            then: Then::Teams,
+            retry: Retry::CreateTeam(name),
+        });
+    }
+
+    fn add_team_member(&mut self, team_id: u64, username: String) {
+        self.worker.send(Action::Save {
+            change: Change::AddTeamMember {
+                team_id,
+                username: username.clone(),
+            },
+            then: Then::Teams,
+            retry: Retry::AddTeamMember { team_id, username },
+        });
+    }
+
+    // Takes what was typed in the teams view, a new team or a member
+    fn submit_team_input(&mut self) {
+        let Some(teams) = self.teams.as_mut() else {
+            return;
+        };
+        let Some(input) = teams.input.take() else {
+            return;
+        };
+        self.input_mode = InputMode::Teams;
+        let text = input.text().trim().to_string();
+        if text.is_empty() {
+            return;
+        }
+        match input {
+            TeamInput::NewTeam(_) => self.create_team(text),
+            TeamInput::AddMember(_) => {
+                if let Some(team_id) = teams.selected().map(|team| team.id) {
+                    self.add_team_member(team_id, text);
+                }
+            }
+        }
+    }
+
+    fn open_notifications(&mut self) {
+        if self.notifications.is_empty() {
+            self.message = Some("No notifications".to_string());
+            return;
+        }
+        self.notification_state.select(Some(0));
+        self.input_mode = InputMode::Notifications;
+    }
+
+    // Marks the notifications read, then opens the task if one is given
+    fn mark_notifications_read(&mut self, indices: Vec<usize>, open: Option<u64>) {
+        let ids: Vec<u64> = indices
+            .into_iter()
+            .filter_map(|i| self.notifications.get(i))
+            .filter(|n| n.is_unread())
+            .map(|n| n.id)
+            .collect();
+        if ids.is_empty() {
+            self.update(Message::MarkedRead {
+                read: ids,
+                open,
+                result: Ok(()),
+            });
+            return;
+        }
+        self.worker.send(Action::MarkRead { ids, open });
+    }
+
+    fn notifications_read(&mut self, ids: &[u64]) {
+        for notification in &mut self.notifications {
+            if ids.contains(&notification.id) {
+                notification.read_at = Some(chrono::Local::now().to_rfc3339());
+            }
+        }
+    }
+
+    pub fn open_filter_menu(&mut self) {
+        let mut candidates = vec!["No Filter".to_string(), "Assigned to me".to_string()];
+        candidates.extend(self.quick_filters.iter().enumerate().map(|(i, f)| {
+            if i < 9 {
+                format!("{}: {}", i + 1, f.name)
+            } else {
+                f.name.clone()
+            }
+        }));
+        self.picker = Some(Picker::new(candidates));
+        self.input_mode = InputMode::FilterMenu;
+    }
+
+    pub fn open_profile_switcher(&mut self) {
+        if self.profiles.len() < 2 {
+            self.message =
+                Some("Add [profiles.<name>] to the config to switch instances".to_string());
+            return;
+        }
+        let candidates = self.profiles.iter().map(|p| p.name.clone()).collect();
+        self.picker = Some(Picker::new(candidates));
+        self.input_mode = InputMode::ProfileSwitcher;
+    }
+
+    // Connects to another instance and starts over, dropping everything that
+    // was loaded from the previous one. The API key is resolved by the worker,
+    // as api_key_cmd may wait on a password manager.
+    pub fn switch_profile(&mut self, profile: Profile) -> ApiResult<()> {
+        if profile.vikunja.oidc_provider.is_some() {
+            return Err(format!(
+                "{} signs in with OpenID Connect, start with --profile {} instead",
+                profile.name, profile.name
+            )
+            .into());
+        }
+        self.worker.send(Action::ResolveProfile(profile));
+        Ok(())
+    }
+
+    // Points the app at the profile's instance once its API key is known, and
+    // starts over there
+    fn use_profile(&mut self, profile: Profile, api_key: &str) {
+        self.api = Arc::new(HttpClient::new(&profile.vikunja.instance_url, api_key));
+        // Whatever the old instance still sends back is dropped with its worker
+        self.worker = Worker::spawn(self.api.clone());
+        self.profile = profile.name;
+
+        self.all_tasks_load = None;
+        self.deferred.clear();
+        self.opening = None;
+        self.moving = false;
+        self.completion_loading = false;
+        self.all_tasks.clear();
+        self.tasks.clear();
+        self.visible_rows.clear();
+        self.task_lines.clear();
+        self.list_filter = None;
+        self.state.select(None);
+        self.task_detail = None;
+        self.page = 1;
+        self.projects.clear();
+        self.current_project = None;
+        self.active_filter = None;
+        self.board = None;
+        self.list_view_id = None;
+        self.history.clear();
+        self.tabs = vec![Workspace::default()];
+        self.active_tab = 0;
+        self.notifications_loading = false;
+        self.notifications.clear();
+        self.notifications_checked = None;
+        self.server_info = None;
+        self.pending_retry = None;
+        self.label_cache = None;
+        self.current_user = None;
+        self.detail_cache.clear();
+
+        self.worker.send(Action::LoadServerInfo);
+        self.refresh_tasks();
+    }
+
+    pub fn apply_filter(&mut self, quick_filter: Option<QuickFilter>) {
+        self.remember();
+        self.active_filter = quick_filter;
+        self.page = 1;
+        self.task_detail = None;
+        self.load_from_top();
+    }
+
+    pub fn switch_project(&mut self, project: Option<Project>) {
+        self.remember();
+        self.enter_project(project);
+        self.page = 1;
+        self.task_detail = None;
+        self.load_from_top();
+    }
+
+    fn location(&self) -> Location {
+        Location {
+            project: self.current_project.clone(),
+            filter: self.active_filter.clone(),
+            view_kind: self.view_kind,
+            archive: self.archive.clone(),
+            page: self.page,
+            selected: self.selected_task().map(|task| task.id),
+            task_id: self.task_detail.as_ref().map(|task| task.id),
+        }
+    }
+
+    // Records the current place before going somewhere else
+    fn remember(&mut self) {
+        let here = self.location();
+        self.history.record(here);
+    }
+
+    pub fn go_back(&mut self) {
+        let here = self.location();
+        match self.history.back(here) {
+            Some(location) => self.restore(location),
+            None => self.message = Some("Nothing to go back to".to_string()),
+        }
+    }
+
+    pub fn go_forward(&mut self) {
+        let here = self.location();
+        match self.history.forward(here) {
+            Some(location) => self.restore(location),
+            None => self.message = Some("Nothing to go forward to".to_string()),
+        }
+    }
+
+    // Moves the active tab's state out of the app fields
+    fn take_workspace(&mut self) -> Workspace {
+        Workspace {
+            tasks: std::mem::take(&mut self.tasks),
+            visible_rows: std::mem::take(&mut self.visible_rows),
+            list_filter: self.list_filter.take(),
+            state: std::mem::take(&mut self.state),
+            task_detail: self.task_detail.take(),
+            task_comments: std::mem::take(&mut self.task_comments),
+            page: self.page,
+            total_pages: self.total_pages,
+            result_count: self.result_count,
+            show_done_tasks: self.show_done_tasks,
+            current_project: self.current_project.take(),
+            active_filter: self.active_filter.take(),
+            view_kind: self.view_kind,
+            board: self.board.take(),
+            list_view_id: self.list_view_id.take(),
+            history: std::mem::take(&mut self.history),
+        }
+    }
+
+    fn put_workspace(&mut self, workspace: Workspace) {
+        self.tasks = workspace.tasks;
+        self.visible_rows = workspace.visible_rows;
+        self.task_lines.clear();
+        self.list_filter = workspace.list_filter;
+        self.state = workspace.state;
+        self.task_detail = workspace.task_detail;
+        self.task_comments = workspace.task_comments;
+        self.page = workspace.page;
+        self.total_pages = workspace.total_pages;
+        self.result_count = workspace.result_count;
+        self.show_done_tasks = workspace.show_done_tasks;
+        self.current_project = workspace.current_project;
+        self.active_filter = workspace.active_filter;
+        self.view_kind = workspace.view_kind;
+        self.board = workspace.board;
+        self.list_view_id = workspace.list_view_id;
+        self.history = workspace.history;
+        // What was still loading belongs to the tab that was left
+        self.generation += 1;
+        self.cancel_opening();
+        self.moving = false;
+    }
+
+    // Cycles through the tabs, -1 for the previous one. Tabs come back as
+    // they were left, without reloading.
+    pub fn cycle_tab(&mut self, offset: isize) {
+        let count = self.tabs.len();
+        if count < 2 {
+            self.message = Some("Only one tab is open, gn opens another".to_string());
+            return;
+        }
+        let next = (self.active_tab as isize + offset).rem_euclid(count as isize) as usize;
+        self.tabs[self.active_tab] = self.take_workspace();
+        let workspace = std::mem::take(&mut self.tabs[next]);
+        self.put_workspace(workspace);
+        self.active_tab = next;
+    }
+
+    // Opens a tab next to the active one on the same project and filter
+    pub fn new_tab(&mut self) {
+        let workspace = Workspace {
+            page: 1,
+            show_done_tasks: self.show_done_tasks,
+            current_project: self.current_project.clone(),
+            active_filter: self.active_filter.clone(),
+            view_kind: self.view_kind,
+            ..Default::default()
+        };
+        self.tabs[self.active_tab] = self.take_workspace();
+        self.active_tab += 1;
+        self.tabs.insert(self.active_tab, Workspace::default());
+        self.put_workspace(workspace);
+        self.refresh_tasks();
+    }
+
+    pub fn close_tab(&mut self) {
+        if self.tabs.len() < 2 {
+            self.message = Some("The last tab can't be closed".to_string());
+            return;
+        }
+        self.tabs.remove(self.active_tab);
+        self.active_tab = self.active_tab.min(self.tabs.len() - 1);
+        let workspace = std::mem::take(&mut self.tabs[self.active_tab]);
+        self.put_workspace(workspace);
+    }
+
+    // Titles for the tab bar, in tab order
+    pub fn tab_titles(&self) -> Vec<String> {
+        let title = |project: Option<&Project>, filter: Option<&QuickFilter>| {
+            let mut title = project
+                .map_or("All Tasks", |p| p.title.as_str())
+                .to_string();
+            if let Some(filter) = filter {
+                title.push_str(&format!(" [{}]", filter.name));
+            }
+            title
+        };
+        self.tabs
+            .iter()
+            .enumerate()
+            .map(|(i, tab)| {
+                if i == self.active_tab {
+                    title(self.current_project.as_ref(), self.active_filter.as_ref())
+                } else {
+                    title(tab.current_project.as_ref(), tab.active_filter.as_ref())
+                }
+            })
+            .collect()
+    }
+
+    // Window title naming the view with counts of the tasks shown in it, like
+    // "vikunja: Work › 12 open, 3 overdue"
+    pub fn window_title(&self) -> String {
+        let view = self.tab_titles().swap_remove(self.active_tab);
+        let tasks: Vec<&Task> = match (&self.view_kind, &self.board) {
+            (ViewKind::Kanban, Some(board)) => {
+                board.buckets.iter().flat_map(bucket_tasks).collect()
+            }
+            _ => self.tasks.iter().collect(),
+        };
+        let now = Local::now();
+        let open = tasks.iter().filter(|task| !task.done).count();
+        let overdue = tasks.iter().filter(|task| task.is_overdue(now)).count();
+        let mut title = format!("vikunja: {} › {} open", view, open);
+        if overdue > 0 {
+            title.push_str(&format!(", {} overdue", overdue));
+        }
+        title
+    }
+
+    fn restore(&mut self, location: Location) {
+        self.enter_project(location.project);
+        self.active_filter = location.filter;
+        self.view_kind = location.view_kind;
+        self.archive = location.archive;
+        self.page = location.page;
+        self.task_detail = None;
+        self.reload(location.selected, location.task_id);
+    }
+
+    // Creates the task from the add popup
+    fn submit_new_task(&mut self) {
+        let ready = match self.prepare_new_task() {
+            Ok(ready) => ready,
+            Err(Held::Load(missing)) => {
+                let action = match missing {
+                    Missing::Projects => Action::LoadProjects(ProjectsFor::NewTask),
+                    Missing::Labels => Action::LoadLabels(LabelsFor::NewTask),
+                };
+                return self.worker.send(action);
+            }
+            Err(Held::Refused(reason)) => {
+                if self.read_only {
+                    self.input_mode = InputMode::Normal;
+                }
+                self.message = Some(reason);
+                return;
+            }
+            // The question is up
+            Err(Held::NewLabels | Held::ProjectChoice) => return,
+        };
+        if self.confirm_new_task && self.new_task_preview.is_none() {
+            self.new_task_preview = Some(NewTaskPreview {
+                project: ready.project.map(|p| p.title),
+                labels: self.new_task_labels(&ready.parsed, ready.task.project_id),
+                assignees: ready.parsed.assignees.clone(),
+                task: ready.task,
+            });
+            self.input_mode = InputMode::ConfirmNewTask;
+            return;
+        }
+        // The draft is kept until the server has the task
+        self.stash_draft();
+        self.submitted_draft = self.saved.draft.clone();
+        self.saved.title_history.add(&self.new_task_title);
+        self.save_state();
+        self.clear_new_task();
+        self.input_mode = InputMode::Normal;
+        self.create_task(ready.parsed, ready.task, ready.new_labels);
+    }
+
+    // Makes the task typed into the add popup, for the popup and for `add` on
+    // the command line
+    fn prepare_new_task(&mut self) -> Result<ReadyTask, Held> {
+        if self.read_only {
+            return Err(Held::Refused(
+                "Read-only mode, task was not created".to_string(),
+            ));
+        }
+        if self.new_task_title.trim().is_empty() {
+            return Err(Held::Refused("Task title cannot be empty".to_string()));
+        }
+        let parsed = parse_task_input(&self.new_task_title);
+        let project = match &parsed.project {
+            Some(name) => Some(self.resolve_new_task_project(name)?),
+            None => self.current_project.clone(),
+        };
+        // The bucket in view only applies to its own project
+        let bucket_id = self
+            .new_task_bucket()
+            .filter(|_| {
+                project.as_ref().map(|p| p.id) == self.current_project.as_ref().map(|p| p.id)
+            })
+            .map(|b| b.id);
+        let new_labels = self.labels_to_create(&parsed)?;
+
+        // The description box adds to the one typed in braces
+        let typed = self.new_task_description.trim();
+        let description = match (parsed.description.clone(), typed.is_empty()) {
+            (Some(braced), false) => Some(format!("{}\n\n{}", braced, typed)),
+            (Some(braced), true) => Some(braced),
+            (None, false) => Some(self.new_task_description.clone()),
+            (None, true) => None,
+        };
+
+        let task = NewTask {
+            title: parsed.title.clone(),
+            description,
+            priority: parsed.priority,
+            // Dates typed in the title win over the picked ones
+            due_date: parsed
+                .due_date
+                .map(|due| (due.date, due.time))
+                .or(self.new_task_due.map(|date| (date, None)))
+                .map(|(date, time)| to_api_datetime_at(date, time.or(self.default_due_time))),
+            start_date: parsed
+                .start_date
+                .map(|start| to_api_datetime_at(start.date, start.time))
+                .or(self.new_task_start.map(to_api_datetime)),
+            end_date: parsed
+                .end_date
+                .map(|end| to_api_datetime_at(end.date, end.time))
+                .or(self.new_task_end.map(to_api_datetime)),
+            reminders: parsed
+                .reminders
+                .iter()
+                .map(|reminder| to_api_datetime_at(reminder.date, reminder.time))
+                .chain(self.new_task_reminder.map(to_api_datetime))
+                .collect(),
+            hex_color: self.new_task_color.map(|i| TASK_COLORS[i].1.to_string()),
+            project_id: project.as_ref().map(|p| p.id),
+            bucket_id,
+        };
+        Ok(ReadyTask {
+            parsed,
+            task,
+            new_labels,
+            project,
+        })
+    }
+
+    // Takes the next event, returns whether the app should quit
+    pub fn handle_event(&mut self, event: AppEvent) -> io::Result<bool> {
+        match event {
+            AppEvent::Key(key) => self.handle_key(key),
+            AppEvent::Tick => {
+                if matches!(self.input_mode, InputMode::Editing | InputMode::Insert) {
+                    self.stash_draft();
+                }
+                self.poll_background();
+                Ok(false)
+            }
+        }
+    }
+
+    fn handle_key(&mut self, key: Key) -> io::Result<bool> {
+        // Messages stay on the status line until the next key press
+        self.message = None;
+        // The log covers the view, keys don't reach what is under it
+        if self.show_api_log {
+            if matches!(key.code, KeyCode::F(12) | KeyCode::Esc) {
+                self.show_api_log = false;
+            }
+            return Ok(false);
+        }
+        if key.code == KeyCode::F(12) {
+            self.show_api_log = true;
+            return Ok(false);
+        }
+
+        match self.input_mode {
+            InputMode::Normal if self.pending_g => {
+                self.pending_g = false;
+                match key.code {
+                    KeyCode::Char('t') => self.cycle_tab(1),
+                    KeyCode::Char('T') => self.cycle_tab(-1),
+                    KeyCode::Char('n') => self.new_tab(),
+                    KeyCode::Char('c') => self.close_tab(),
+                    KeyCode::Char('x') => self.open_links(),
+                    _ => {}
+                }
+            }
+            InputMode::Normal => match key.code {
+                KeyCode::Char('q') => return Ok(self.request_quit()),
+                KeyCode::Char('g') => self.pending_g = true,
+                KeyCode::Char('o') if key.modifiers.ctrl => self.go_back(),
+                // Ctrl-i comes as itself with the kitty keyboard protocol,
+                // other terminals send it as Tab
+                KeyCode::Char('i') if key.modifiers.ctrl => self.go_forward(),
+                KeyCode::Tab => self.go_forward(),
+                KeyCode::Char('/') if self.view_kind != ViewKind::Kanban => {
+                    self.list_filter.get_or_insert_with(String::new);
+                    self.input_mode = InputMode::ListFilter;
+                }
+                KeyCode::Esc if self.list_filter.is_some() => self.clear_list_filter(),
+                KeyCode::Char(c @ (':' | '#')) => {
+                    self.command.clear();
+                    if c == '#' {
+                        self.command.push(c);
+                    }
+                    self.input_mode = InputMode::Command;
+                }
+                KeyCode::Char('j') => {
+                    self.cancel_opening();
+                    match self.board.as_mut() {
+                        Some(board) if self.view_kind == ViewKind::Kanban => board.down(),
+                        _ => self.next(),
+                    }
+                }
+                KeyCode::Char('k') => {
+                    self.cancel_opening();
+                    match self.board.as_mut() {
+                        Some(board) if self.view_kind == ViewKind::Kanban => board.up(),
+                        _ => self.previous(),
+                    }
+                }
+                KeyCode::Char('h') => {
+                    self.cancel_opening();
+                    if let Some(board) = self.board.as_mut() {
+                        board.left();
+                    }
+                }
+                KeyCode::Char('l') => {
+                    self.cancel_opening();
+                    if let Some(board) = self.board.as_mut() {
+                        board.right();
+                    }
+                }
+                KeyCode::Char('H' | 'J' | 'K' | 'L') if self.read_only => {
+                    self.message = Some("Moving tasks is disabled in read-only mode".to_string());
+                }
+                KeyCode::Char('H' | 'J' | 'K' | 'L') if self.moving => {
+                    self.message = Some("Still saving the last move".to_string());
+                }
+                KeyCode::Char(c @ ('J' | 'K')) if self.view_kind == ViewKind::List => {
+                    self.reorder_task(if c == 'K' { -1 } else { 1 });
+                }
+                KeyCode::Char('H' | 'J' | 'K' | 'L') if self.view_kind != ViewKind::Kanban => {}
+                KeyCode::Char(c @ ('H' | 'J' | 'K' | 'L')) => match c {
+                    'H' => self.move_card(-1),
+                    'L' => self.move_card(1),
+                    'K' => self.reorder_card(-1),
+                    _ => self.reorder_card(1),
+                },
+                KeyCode::Char('b') => self.toggle_view(),
+                KeyCode::Char('z') if self.view_kind != ViewKind::Kanban => self.toggle_subtasks(),
+                KeyCode::Char('D') => self.toggle_done_review(),
+                KeyCode::Char('i') if self.view_kind != ViewKind::Kanban && self.read_only => {
+                    self.message = Some("Editing tasks is disabled in read-only mode".to_string());
+                }
+                KeyCode::Char('i') if self.view_kind != ViewKind::Kanban => self.start_title_edit(),
+                KeyCode::Char('m') if self.read_only => {
+                    self.message =
+                        Some("Assigning tasks is disabled in read-only mode".to_string());
+                }
+                KeyCode::Char('m') => {
+                    if let Some(task_id) = self.selected_task().map(|task| task.id) {
+                        self.assign_to_me(task_id);
+                    }
+                }
+                KeyCode::Char('W') => self.toggle_timer(),
+                KeyCode::Char('C') if self.groups_completed() => self.toggle_completed(),
+                KeyCode::Char('x') if self.read_only => {
+                    self.message = Some("Deleting tasks is disabled in read-only mode".to_string());
+                }
+                KeyCode::Char('x') => {
+                    if let Some(task) = self.selected_task().cloned() {
+                        self.pending_delete = Some(task);
+                        self.input_mode = InputMode::ConfirmDelete;
+                    }
+                }
+                KeyCode::Char('u') if self.can_undo_delete() => self.restore_deleted(),
+                KeyCode::Char(' ') if self.view_kind == ViewKind::Done => self.toggle_mark(),
+                KeyCode::Char('u') if self.view_kind == ViewKind::Done && self.read_only => {
+                    self.message =
+                        Some("Reopening tasks is disabled in read-only mode".to_string());
+                }
+                KeyCode::Char('u') if self.view_kind == ViewKind::Done => {
+                    let marked = self.marked.iter().copied().collect();
+                    self.reopen_tasks(marked);
+                }
+                KeyCode::Char('n') => {
+                    // Next page
+                    self.next_page();
+                    if !self.show_prefetched() {
+                        self.load_from_top();
+                    }
+                }
+                KeyCode::Char('p') => {
+                    // Previous page
+                    self.previous_page();
+                }
+                KeyCode::Char('t') => self.toggle_done_tasks(),
+                KeyCode::Char('r') => self.refresh_view(),
+                KeyCode::Char('s') => self.open_project_switcher(),
+                KeyCode::Char('f') => self.open_task_finder(),
+                KeyCode::Char('N') => self.open_notifications(),
+                KeyCode::Char('U') => self.open_members(),
+                KeyCode::Char('T') => self.worker.send(Action::LoadTeams),
+                KeyCode::Char('w') if self.task_detail.is_some() => {
+                    self.detail_wrap = !self.detail_wrap;
+                    self.detail_scroll_x = 0;
+                }
+                KeyCode::Char('<') if !self.detail_wrap => {
+                    self.detail_scroll_x = self.detail_scroll_x.saturating_sub(DETAIL_SCROLL_STEP);
+                }
+                KeyCode::Char('>') if !self.detail_wrap => {
+                    self.detail_scroll_x += DETAIL_SCROLL_STEP;
+                }
+                KeyCode::Char('c') if self.task_detail.is_some() => self.write_comment(),
+                KeyCode::Char('e') => self.open_reactions(),
+                KeyCode::Char('F') => self.open_filter_menu(),
+                KeyCode::Char('P') => self.open_profile_switcher(),
+                KeyCode::Char(c @ '0'..='9') => {
+                    // 0 clears the filter, 1-9 apply the quick filters in config order
+                    let index = c.to_digit(10).unwrap_or(0) as usize;
+                    let quick_filter = match index {
+                        0 => None,
+                        i => match self.quick_filters.get(i - 1) {
+                            Some(f) => Some(f.clone()),
+                            None => return Ok(false),
+                        },
+                    };
+                    self.apply_filter(quick_filter);
+                }
+                KeyCode::Char('a') if self.read_only => {
+                    self.message = Some("Adding tasks is disabled in read-only mode".to_string());
+                }
+                KeyCode::Char('a') => self.open_add_popup(),
+                KeyCode::Enter => {
+                    if let Some(task_id) = self.selected_task().map(|task| task.id) {
+                        self.open_task_or_report(task_id);
+                    }
+                }
+                _ => {}
+            },
+
+            InputMode::Editing | InputMode::Insert if key == Key::ctrl(KeyCode::Char('e')) => {
+                let text = match self.active_input {
+                    ActiveInput::Title => self.new_task_title.clone(),
+                    ActiveInput::Description => self.new_task_description.clone(),
+                };
+                if let Some(text) = self.external_edit(&text, "md") {
+                    match self.active_input {
+                        ActiveInput::Title => {
+                            self.new_task_title = single_line(&text);
+                            self.update_completion();
+                        }
+                        ActiveInput::Description => {
+                            self.new_task_description = text.trim_end().to_string()
+                        }
+                    }
+                }
+            }
+            InputMode::ListFilter if key == Key::ctrl(KeyCode::Char('e')) => {
+                let query = self.list_filter.clone().unwrap_or_default();
+                if let Some(text) = self.external_edit(&query, "txt") {
+                    self.list_filter = Some(single_line(&text));
+                    self.update_visible_rows();
+                }
+            }
+            InputMode::Command if key == Key::ctrl(KeyCode::Char('e')) => {
+                let command = self.command.clone();
+                if let Some(text) = self.external_edit(&command, "txt") {
+                    self.command = single_line(&text);
+                }
+            }
+            InputMode::Editing => match key.code {
+                KeyCode::Char('i') => {
+                    self.input_mode = InputMode::Insert;
+                }
+                KeyCode::Tab => {
+                    self.active_input = match self.active_input {
+                        ActiveInput::Title => ActiveInput::Description,
+                        ActiveInput::Description => ActiveInput::Title,
+                    };
+                }
+                KeyCode::Enter => self.submit_new_task(),
+                KeyCode::Char('q') => return Ok(self.request_quit()),
+                KeyCode::Char('d') => self.open_date_picker(DateTarget::Due),
+                KeyCode::Char('r') => self.open_date_picker(DateTarget::Reminder),
+                KeyCode::Char('s') => self.open_date_picker(DateTarget::Start),
+                KeyCode::Char('e') => self.open_date_picker(DateTarget::End),
+                KeyCode::Char('c') => self.cycle_new_task_color(),
+                KeyCode::Esc => {
+                    self.stash_draft();
+                    self.clear_new_task();
+                    self.input_mode = InputMode::Normal;
+                }
+                _ => {}
+            },
+            InputMode::Insert if self.completion.is_some() => match key.code {
+                KeyCode::Tab | KeyCode::Enter => self.accept_completion(),
+                KeyCode::Down => {
+                    if let Some(completion) = self.completion.as_mut() {
+                        completion.picker.next();
+                    }
+                }
+                KeyCode::Up => {
+                    if let Some(completion) = self.completion.as_mut() {
+                        completion.picker.previous();
+                    }
+                }
+                KeyCode::Esc => self.completion = None,
+                KeyCode::Char(c) => {
+                    self.new_task_title.push(c);
+                    self.update_completion();
+                }
+                KeyCode::Backspace => {
+                    self.new_task_title.pop();
+                    self.update_completion();
+                }
+                _ => {}
+            },
+            // Shift+Enter only reaches terminals speaking the kitty keyboard
+            // protocol, Alt+Enter works everywhere
+            InputMode::Insert => match key.code {
+                KeyCode::Enter if key.modifiers.ctrl => self.submit_new_task(),
+                KeyCode::Up if self.active_input == ActiveInput::Title => {
+                    if let Some(title) = self.saved.title_history.older(&self.new_task_title) {
+                        self.new_task_title = title;
+                    }
+                }
+                KeyCode::Down if self.active_input == ActiveInput::Title => {
+                    if let Some(title) = self.saved.title_history.newer() {
+                        self.new_task_title = title;
+                    }
+                }
+                KeyCode::Enter
+                    if (key.modifiers.shift || key.modifiers.alt)
+                        && self.active_input == ActiveInput::Description =>
+                {
+                    self.new_task_description.push('\n')
+                }
+                KeyCode::Char(c) => match self.active_input {
+                    ActiveInput::Title => {
+                        self.new_task_title.push(c);
+                        self.update_completion();
+                    }
+                    ActiveInput::Description => self.new_task_description.push(c),
+                },
+                KeyCode::Backspace => match self.active_input {
+                    ActiveInput::Title => {
+                        self.new_task_title.pop();
+                        self.update_completion();
+                    }
+                    ActiveInput::Description => {
+                        self.new_task_description.pop();
+                    }
+                },
+                KeyCode::Esc => {
+                    self.input_mode = InputMode::Editing;
+                }
+                _ => {}
+            },
+            InputMode::ProjectSwitcher
+            | InputMode::TaskFinder
+            | InputMode::FilterMenu
+            | InputMode::ProfileSwitcher
+            | InputMode::ReactionTargets
+            | InputMode::ReactionPicker
+            | InputMode::Links
+            | InputMode::ProjectChoice
+            | InputMode::DuplicateParent => {
+                let Some(picker) = self.picker.as_mut() else {
+                    self.input_mode = InputMode::Normal;
+                    return Ok(false);
+                };
+                let duplicate = key == Key::ctrl(KeyCode::Char('d'))
+                    && matches!(self.input_mode, InputMode::ProjectSwitcher);
+                match key.code {
+                    KeyCode::Char(_) if duplicate && self.read_only => {
+                        self.message =
+                            Some("Duplicating projects is disabled in read-only mode".to_string());
+                    }
+                    // Index 0 is the "All Projects" entry
+                    KeyCode::Char(_) if duplicate => {
+                        if let Some(i) = picker.selected().and_then(|i| i.checked_sub(1)) {
+                            self.picker = None;
+                            self.pick_duplicate_parent(self.projects[i].clone());
+                        }
+                    }
+                    KeyCode::Char(c) => picker.push(c),
+                    KeyCode::Backspace => picker.pop(),
+                    KeyCode::Down | KeyCode::Tab => picker.next(),
+                    KeyCode::Up | KeyCode::BackTab => picker.previous(),
+                    // Back to the add form, which is still filled in
+                    KeyCode::Esc if matches!(self.input_mode, InputMode::ProjectChoice) => {
+                        self.picker = None;
+                        self.new_labels_agreed = false;
+                        self.input_mode = InputMode::Editing;
+                    }
+                    KeyCode::Esc => {
+                        self.picker = None;
+                        self.input_mode = InputMode::Normal;
+                    }
+                    KeyCode::Enter => {
+                        let selected = picker.selected();
+                        let mode = std::mem::replace(&mut self.input_mode, InputMode::Normal);
+                        self.picker = None;
+
+                        match (mode, selected) {
+                            (InputMode::TaskFinder, Some(i)) => {
+                                self.show_task(self.all_tasks[i].id);
+                            }
+                            // The quick filters follow "No Filter" and "Assigned to me"
+                            (InputMode::FilterMenu, Some(0)) => self.apply_filter(None),
+                            (InputMode::FilterMenu, Some(1)) => self.apply_mine_filter(),
+                            (InputMode::FilterMenu, Some(i)) => {
+                                self.apply_filter(Some(self.quick_filters[i - 2].clone()));
+                            }
+                            (InputMode::Links, Some(i)) => {
+                                if let Err(err) = open_browser(&self.links[i]) {
+                                    self.message =
+                                        Some(format!("Failed to open the link: {}", err));
+                                }
+                            }
+                            (InputMode::ReactionTargets, Some(i)) => {
+                                let target = match i.checked_sub(1) {
+                                    Some(i) => ReactionTarget::Comment(self.task_comments[i].id),
+                                    None => match &self.task_detail {
+                                        Some(task) => ReactionTarget::Task(task.id),
+                                        None => return Ok(false),
+                                    },
+                                };
+                                self.open_reaction_picker(target);
+                            }
+                            (InputMode::ReactionPicker, Some(i)) => {
+                                let Some(target) = self.reaction_target.take() else {
+                                    return Ok(false);
+                                };
+                                self.toggle_reaction(target, REACTIONS[i]);
+                            }
+                            (InputMode::ProjectChoice, Some(i)) => {
+                                self.input_mode = InputMode::Editing;
+                                self.new_task_project = Some(self.project_choices[i].clone());
+                                self.submit_new_task();
+                            }
+                            (InputMode::ProjectChoice, None) => {
+                                self.input_mode = InputMode::Editing;
+                            }
+                            (InputMode::DuplicateParent, Some(i)) => {
+                                let parent_id =
+                                    i.checked_sub(1).map_or(0, |i| self.project_choices[i].id);
+                                if let Some(project) = self.duplicating.take() {
+                                    self.duplicate_project(project, parent_id);
+                                }
+                            }
+                            (InputMode::DuplicateParent, None) => self.duplicating = None,
+                            (InputMode::ProfileSwitcher, Some(i)) => {
+                                let profile = self.profiles[i].clone();
+                                if let Err(err) = self.switch_profile(profile) {
+                                    self.handle_error(err, Retry::Refresh);
+                                }
+                            }
+                            (_, Some(i)) => {
+                                // Index 0 is the "All Projects" entry
+                                let project = i.checked_sub(1).map(|i| self.projects[i].clone());
+                                self.switch_project(project);
+                            }
+                            (_, None) => {}
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            InputMode::Members => {
+                let Some(members) = self.members.as_mut() else {
+                    self.input_mode = InputMode::Normal;
+                    return Ok(false);
+                };
+                match key.code {
+                    KeyCode::Char('j') | KeyCode::Down => members.down(),
+                    KeyCode::Char('k') | KeyCode::Up => members.up(),
+                    KeyCode::Char('a' | 'd') if self.read_only => {
+                        self.message =
+                            Some("Sharing projects is disabled in read-only mode".to_string());
+                    }
+                    KeyCode::Char('a') => {
+                        members.adding = Some(String::new());
+                        self.input_mode = InputMode::AddMember;
+                    }
+                    KeyCode::Char('d') if members.selected().is_some() => {
+                        self.input_mode = InputMode::ConfirmUnshare;
+                    }
+                    KeyCode::Esc | KeyCode::Char('q') => {
+                        self.members = None;
+                        self.input_mode = InputMode::Normal;
+                    }
+                    _ => {}
+                }
+            }
+            InputMode::AddMember => {
+                let Some(members) = self.members.as_mut() else {
+                    self.input_mode = InputMode::Normal;
+                    return Ok(false);
+                };
+                let adding = members.adding.get_or_insert_with(String::new);
+                match key.code {
+                    KeyCode::Char(c) => adding.push(c),
+                    KeyCode::Backspace => {
+                        adding.pop();
+                    }
+                    KeyCode::Tab => members.cycle_right(),
+                    KeyCode::Enter => match members.typed_member() {
+                        Ok(member) => {
+                            let right = members.right;
+                            members.adding = None;
+                            self.input_mode = InputMode::Members;
+                            self.share_project(member, right);
+                        }
+                        Err(err) => self.message = Some(err),
+                    },
+                    KeyCode::Esc => {
+                        members.adding = None;
+                        self.input_mode = InputMode::Members;
+                    }
+                    _ => {}
+                }
+            }
+            InputMode::Teams => {
+                let Some(teams) = self.teams.as_mut() else {
+                    self.input_mode = InputMode::Normal;
+                    return Ok(false);
+                };
+                match key.code {
+                    KeyCode::Char('j') | KeyCode::Down => teams.down(),
+                    KeyCode::Char('k') | KeyCode::Up => teams.up(),
+                    KeyCode::Char('n' | 'a') if self.read_only => {
+                        self.message =
+                            Some("Changing teams is disabled in read-only mode".to_string());
+                    }
+                    KeyCode::Char('n') => {
+                        teams.input = Some(TeamInput::NewTeam(String::new()));
+                        self.input_mode = InputMode::TeamInput;
+                    }
+                    KeyCode::Char('a') if teams.selected().is_some() => {
+                        teams.input = Some(TeamInput::AddMember(String::new()));
+                        self.input_mode = InputMode::TeamInput;
+                    }
+                    KeyCode::Esc | KeyCode::Char('q') => {
+                        self.teams = None;
+                        self.input_mode = InputMode::Normal;
+                    }
+                    _ => {}
+                }
+            }
+            InputMode::TeamInput => {
+                let Some(input) = self.teams.as_mut().and_then(|t| t.input.as_mut()) else {
+                    self.input_mode = InputMode::Teams;
+                    return Ok(false);
+                };
+                match key.code {
+                    KeyCode::Char(c) => input.text_mut().push(c),
+                    KeyCode::Backspace => {
+                        input.text_mut().pop();
+                    }
+                    KeyCode::Enter => self.submit_team_input(),
+                    KeyCode::Esc => {
+                        if let Some(teams) = self.teams.as_mut() {
+                            teams.input = None;
+                        }
+                        self.input_mode = InputMode::Teams;
+                    }
+                    _ => {}
+                }
+            }
+            InputMode::ConfirmDelete => match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    self.input_mode = InputMode::Normal;
+                    if let Some(task) = self.pending_delete.take() {
+                        self.delete_task(task);
                     }
                 }
-                KeyCode::Char('p') => {
-                    // Previous page
-                    self.previous_page();
-                    if let Err(err) = self.refresh_tasks(instance_url, api_key).await {
-                        eprintln!("Error fetching tasks: {}", err);
+                KeyCode::Char('n') | KeyCode::Esc => {
+                    self.pending_delete = None;
+                    self.input_mode = InputMode::Normal;
+                }
+                _ => {}
+            },
+            InputMode::ConfirmUnshare => match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    self.input_mode = InputMode::Members;
+                    let selected = self.members.as_ref().and_then(Members::selected);
+                    if let Some((member, _)) = selected {
+                        self.unshare_project(member);
                     }
                 }
-                KeyCode::Char('t') => {
-                    self.show_done_tasks = !self.show_done_tasks;
-                    if let Err(err) = self.refresh_tasks(instance_url, api_key).await {
-                        eprintln!("Error fetching tasks: {}", err);
+                KeyCode::Char('n') | KeyCode::Esc => self.input_mode = InputMode::Members,
+                _ => {}
+            },
+            InputMode::RestoreDraft => match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    if let Some(draft) = self.saved.draft.clone() {
+                        self.new_task_title = draft.title;
+                        self.new_task_description = draft.description;
                     }
+                    self.input_mode = InputMode::Editing;
                 }
-                KeyCode::Char('a') => {
+                KeyCode::Char('n') => {
+                    self.saved.draft = None;
+                    self.save_state();
                     self.input_mode = InputMode::Editing;
-                    self.new_task_title.clear();
-                    self.new_task_description.clear();
-                    self.active_input = ActiveInput::Title;
                 }
-                KeyCode::Enter => {
-                    if let Err(err) = self.select_task(instance_url, api_key).await {
-                        eprintln!("Error fetching task details: {}", err);
+                KeyCode::Esc => self.input_mode = InputMode::Normal,
+                _ => {}
+            },
+            InputMode::ConfirmNewTask => match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    self.input_mode = InputMode::Editing;
+                    self.submit_new_task();
+                }
+                // The task may be edited to other labels, asked about again
+                KeyCode::Char('n') | KeyCode::Esc => {
+                    self.new_task_preview = None;
+                    self.new_labels_agreed = false;
+                    self.input_mode = InputMode::Editing;
+                }
+                _ => {}
+            },
+            InputMode::ConfirmNewLabels => match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    self.new_labels_agreed = true;
+                    self.input_mode = InputMode::Editing;
+                    self.submit_new_task();
+                }
+                KeyCode::Char('n') | KeyCode::Esc => {
+                    self.new_labels.clear();
+                    self.new_labels_agreed = false;
+                    self.input_mode = InputMode::Editing;
+                }
+                _ => {}
+            },
+            InputMode::ConfirmQuit => match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => return Ok(true),
+                KeyCode::Char('n') | KeyCode::Esc => {
+                    self.quit_question = None;
+                    self.input_mode = if self.typing_new_task() {
+                        InputMode::Editing
+                    } else {
+                        InputMode::Normal
+                    };
+                }
+                _ => {}
+            },
+            InputMode::ConfirmBulkLabel => match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    self.input_mode = InputMode::Normal;
+                    self.start_bulk_label();
+                }
+                KeyCode::Char('n') | KeyCode::Esc => {
+                    self.input_mode = InputMode::Normal;
+                    self.bulk_label = None;
+                }
+                _ => {}
+            },
+            InputMode::ConfirmBulkMove => match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    self.input_mode = InputMode::Normal;
+                    self.start_bulk_move();
+                }
+                KeyCode::Char('n') | KeyCode::Esc => {
+                    self.input_mode = InputMode::Normal;
+                    self.bulk_move = None;
+                }
+                _ => {}
+            },
+            InputMode::ConfirmMove => match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    self.input_mode = InputMode::Normal;
+                    if let Some(card_move) = self.pending_move.take() {
+                        self.apply_move(card_move);
                     }
                 }
+                KeyCode::Char('n') | KeyCode::Esc => {
+                    self.input_mode = InputMode::Normal;
+                    self.pending_move = None;
+                }
                 _ => {}
             },
 
-            InputMode::Editing => match key.code {
-                KeyCode::Char('i') => {
-                    self.input_mode = InputMode::Insert;
+            // Up and Down move through the narrowed list, so earlier searches
+            // are on Ctrl-p and Ctrl-n
+            InputMode::ListFilter => match key.code {
+                KeyCode::Char('p') if key.modifiers.ctrl => {
+                    let typed = self.list_filter.clone().unwrap_or_default();
+                    if let Some(query) = self.saved.search_history.older(&typed) {
+                        self.list_filter = Some(query);
+                        self.update_visible_rows();
+                    }
                 }
-                KeyCode::Tab => {
-                    self.active_input = match self.active_input {
-                        ActiveInput::Title => ActiveInput::Description,
-                        ActiveInput::Description => ActiveInput::Title,
-                    };
+                KeyCode::Char('n') if key.modifiers.ctrl => {
+                    if let Some(query) = self.saved.search_history.newer() {
+                        self.list_filter = Some(query);
+                        self.update_visible_rows();
+                    }
+                }
+                KeyCode::Char(c) => {
+                    self.list_filter.get_or_insert_with(String::new).push(c);
+                    self.update_visible_rows();
                 }
+                KeyCode::Backspace => {
+                    if let Some(query) = self.list_filter.as_mut() {
+                        query.pop();
+                    }
+                    self.update_visible_rows();
+                }
+                KeyCode::Down => self.next(),
+                KeyCode::Up => self.previous(),
+                // Keeps the narrowed list for moving around with j/k
                 KeyCode::Enter => {
-                    if self.new_task_title.trim().is_empty() {
-                        eprintln!("Task title cannot be empty.");
-                    } else {
-                        let parsed_task = parse_task_input(&self.new_task_title);
-
-                        let description = if self.new_task_description.trim().is_empty() {
-                            None
-                        } else {
-                            Some(self.new_task_description.as_str())
-                        };
+                    if self.list_filter.as_deref().is_some_and(str::is_empty) {
+                        self.clear_list_filter();
+                    }
+                    if let Some(query) = &self.list_filter {
+                        self.saved.search_history.add(query);
+                        self.save_state();
+                    }
+                    self.saved.search_history.reset();
+                    self.input_mode = InputMode::Normal;
+                }
+                KeyCode::Esc => {
+                    self.saved.search_history.reset();
+                    self.clear_list_filter();
+                    self.input_mode = InputMode::Normal;
+                }
+                _ => {}
+            },
 
-                        if let Err(err) = create_new_task(
-                            instance_url,
-                            api_key,
-                            &parsed_task.title,
-                            description,
-                            parsed_task.priority,
-                        )
-                        .await
-                        {
-                            eprintln!("Error creating new task: {}", err);
-                        } else if let Err(err) = self.refresh_tasks(instance_url, api_key).await {
-                            eprintln!("Error fetching tasks: {}", err);
-                        }
-                        self.new_task_title.clear();
-                        self.new_task_description.clear();
+            InputMode::RenameTask => {
+                let Some(edit) = self.title_edit.as_mut() else {
+                    self.input_mode = InputMode::Normal;
+                    return Ok(false);
+                };
+                match key.code {
+                    KeyCode::Char(c) => edit.insert(c),
+                    KeyCode::Backspace => edit.backspace(),
+                    KeyCode::Delete => edit.delete(),
+                    KeyCode::Left => edit.left(),
+                    KeyCode::Right => edit.right(),
+                    KeyCode::Home => edit.cursor = 0,
+                    KeyCode::End => edit.cursor = edit.text.len(),
+                    KeyCode::Enter => self.save_title_edit(),
+                    KeyCode::Esc => {
+                        self.title_edit = None;
                         self.input_mode = InputMode::Normal;
                     }
+                    _ => {}
+                }
+            }
+
+            InputMode::Command => match key.code {
+                KeyCode::Up => {
+                    if let Some(command) = self.saved.command_history.older(&self.command) {
+                        self.command = command;
+                    }
+                }
+                KeyCode::Down => {
+                    if let Some(command) = self.saved.command_history.newer() {
+                        self.command = command;
+                    }
+                }
+                KeyCode::Char(c) => self.command.push(c),
+                // Deleting past the start closes the command line, like in vim
+                KeyCode::Backspace if self.command.is_empty() => {
+                    self.saved.command_history.reset();
+                    self.input_mode = InputMode::Normal;
+                }
+                KeyCode::Backspace => {
+                    self.command.pop();
+                }
+                KeyCode::Enter => {
+                    self.input_mode = InputMode::Normal;
+                    let command = std::mem::take(&mut self.command);
+                    self.saved.command_history.add(&command);
+                    self.save_state();
+                    self.run_command(&command);
                 }
                 KeyCode::Esc => {
-                    self.new_task_title.clear();
-                    self.new_task_description.clear();
+                    self.saved.command_history.reset();
+                    self.command.clear();
                     self.input_mode = InputMode::Normal;
                 }
                 _ => {}
             },
-            InputMode::Insert => match key.code {
-                KeyCode::Char(c) => match self.active_input {
-                    ActiveInput::Title => self.new_task_title.push(c),
-                    ActiveInput::Description => self.new_task_description.push(c),
-                },
-                KeyCode::Backspace => match self.active_input {
-                    ActiveInput::Title => {
-                        self.new_task_title.pop();
+
+            InputMode::Notifications => {
+                let len = self.notifications.len();
+                let selected = self.notification_state.selected();
+                match key.code {
+                    KeyCode::Char('j') | KeyCode::Down if len > 0 => {
+                        let i = selected.map_or(0, |i| (i + 1) % len);
+                        self.notification_state.select(Some(i));
                     }
-                    ActiveInput::Description => {
-                        self.new_task_description.pop();
+                    KeyCode::Char('k') | KeyCode::Up if len > 0 => {
+                        let i = selected.map_or(0, |i| (i + len - 1) % len);
+                        self.notification_state.select(Some(i));
                     }
-                },
-                KeyCode::Esc => {
+                    KeyCode::Char('m' | 'M') if self.read_only => {
+                        self.message = Some(
+                            "Marking notifications read is disabled in read-only mode".to_string(),
+                        );
+                    }
+                    KeyCode::Char('m') => {
+                        self.mark_notifications_read(selected.into_iter().collect(), None)
+                    }
+                    KeyCode::Char('M') => self.mark_notifications_read((0..len).collect(), None),
+                    // Opens the task it is about and counts as reading it
+                    KeyCode::Enter => {
+                        let task_id = selected
+                            .and_then(|i| self.notifications.get(i))
+                            .and_then(Notification::task_id);
+                        let read = if self.read_only {
+                            Vec::new()
+                        } else {
+                            selected.into_iter().collect()
+                        };
+                        self.mark_notifications_read(read, task_id);
+                    }
+                    KeyCode::Esc | KeyCode::Char('q') => self.input_mode = InputMode::Normal,
+                    _ => {}
+                }
+            }
+
+            InputMode::DatePicker => {
+                let Some(picker) = self.date_picker.as_mut() else {
                     self.input_mode = InputMode::Editing;
+                    return Ok(false);
+                };
+                match key.code {
+                    KeyCode::Left | KeyCode::Char('h') => picker.move_days(-1),
+                    KeyCode::Right | KeyCode::Char('l') => picker.move_days(1),
+                    KeyCode::Up | KeyCode::Char('k') => picker.move_days(-7),
+                    KeyCode::Down | KeyCode::Char('j') => picker.move_days(7),
+                    KeyCode::PageUp | KeyCode::Char('[') => picker.move_months(-1),
+                    KeyCode::PageDown | KeyCode::Char(']') => picker.move_months(1),
+                    KeyCode::Char('t') => picker.today(),
+                    KeyCode::Enter => {
+                        let date = picker.selected;
+                        self.close_date_picker(Some(Some(date)));
+                    }
+                    KeyCode::Char('x') => self.close_date_picker(Some(None)),
+                    KeyCode::Esc => self.close_date_picker(None),
+                    _ => {}
+                }
+            }
+
+            InputMode::Reauth => match key.code {
+                KeyCode::Char(c) => self.auth_form.input_mut().push(c),
+                KeyCode::Backspace => {
+                    self.auth_form.input_mut().pop();
+                }
+                KeyCode::Down | KeyCode::Tab => self.auth_form.next_field(),
+                KeyCode::Up | KeyCode::BackTab => self.auth_form.previous_field(),
+                KeyCode::Enter => self.submit_auth(),
+                KeyCode::Esc => {
+                    self.pending_retry = None;
+                    self.auth_form = AuthForm::default();
+                    self.input_mode = InputMode::Normal;
+                    self.message = Some(
+                        "Not signed in, requests will fail until the token is replaced".to_string(),
+                    );
                 }
                 _ => {}
             },
@@ -229,3 +4583,857 @@ impl App {
         Ok(false)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::demo::DemoApi;
+    use crate::event::parse_keys;
+
+    // Plays the keys to the app, letting the requests they send finish after
+    // each one
+    async fn play(app: &mut App, keys: &str) {
+        app.settle().await;
+        for key in parse_keys(keys).unwrap() {
+            app.handle_event(AppEvent::Key(key)).unwrap();
+            app.settle().await;
+        }
+    }
+
+    // The task with the title as the server has it now, the newest one when
+    // there are several
+    async fn saved_task(api: &DemoApi, title: &str) -> Task {
+        let tasks = api.fetch_tasks(1, &TaskQuery::default()).await.unwrap();
+        tasks.into_iter().rev().find(|t| t.title == title).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_ctrl_o_and_ctrl_i_go_back_and_forward() {
+        let mut app = App::new(Arc::new(DemoApi::new()), Vec::new());
+        app.refresh_tasks();
+        play(&mut app, "sWork<Enter><C-o>").await;
+        assert!(app.current_project.is_none());
+        play(&mut app, "<C-i>").await;
+        assert_eq!(app.current_project.as_ref().unwrap().title, "Work");
+        assert!(app.title_edit.is_none());
+        play(&mut app, "<C-o><Tab>").await;
+        assert_eq!(app.current_project.as_ref().unwrap().title, "Work");
+    }
+
+    #[tokio::test]
+    async fn test_switch_project_and_open_a_task() {
+        let mut app = App::new(Arc::new(DemoApi::new()), Vec::new());
+        app.refresh_tasks();
+        play(&mut app, "sWork<Enter>").await;
+        assert_eq!(app.current_project.as_ref().unwrap().title, "Work");
+        assert!(app
+            .tasks
+            .iter()
+            .all(|task| task.identifier.starts_with("WRK")));
+
+        play(&mut app, "j<Enter>").await;
+        let selected = app.selected_task().unwrap().id;
+        assert_eq!(app.task_detail.as_ref().map(|task| task.id), Some(selected));
+    }
+
+    #[tokio::test]
+    async fn test_project_from_the_command_line_must_match_exactly() {
+        let mut app = App::new(Arc::new(DemoApi::new()), Vec::new());
+        let project = |id: u64, title: &str| Project {
+            id,
+            title: title.to_string(),
+            ..Default::default()
+        };
+        app.projects = vec![
+            project(2, "Work"),
+            project(7, "Workshop"),
+            project(9, "Home"),
+        ];
+        assert_eq!(app.find_project("workshop").unwrap().id, 7);
+        assert_eq!(app.find_project("9").unwrap().title, "Home");
+
+        // A prefix matching two projects opens neither
+        let err = app.find_project("wor").unwrap_err();
+        assert!(err.contains("Work (2)") && err.contains("Workshop (7)"));
+        app.projects.push(project(12, "Home"));
+        let err = app.find_project("home").unwrap_err();
+        assert!(err.contains("Home (9)") && err.contains("Home (12)"));
+        assert_eq!(
+            app.find_project("zzz").unwrap_err(),
+            "No project named 'zzz'"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_done_toggle_is_kept_per_project() {
+        let mut app = App::new(Arc::new(DemoApi::new()), Vec::new());
+        app.refresh_tasks();
+        play(&mut app, "sWork<Enter>").await;
+        assert!(app.tasks.iter().all(|task| !task.done));
+        play(&mut app, "t").await;
+        assert!(app.tasks.iter().any(|task| task.done));
+
+        play(&mut app, "s<Enter>").await;
+        assert!(app.current_project.is_none());
+        assert!(!app.show_done_tasks);
+        assert!(app.tasks.iter().all(|task| !task.done));
+        play(&mut app, "sWork<Enter>").await;
+        assert!(app.show_done_tasks);
+    }
+
+    #[tokio::test]
+    async fn test_quitting_asks_before_dropping_work() {
+        let mut app = App::new(Arc::new(DemoApi::new()), Vec::new());
+        app.refresh_tasks();
+        play(&mut app, "aiBuy milk<Esc>").await;
+        let q = parse_keys("q").unwrap()[0];
+        assert!(!app.handle_event(AppEvent::Key(q)).unwrap());
+        assert!(app.quit_question.as_ref().unwrap().contains("new task"));
+        play(&mut app, "n").await;
+        assert!(matches!(app.input_mode, InputMode::Editing));
+
+        // The rename is still on its way when q is pressed
+        play(&mut app, "<Esc>").await;
+        for key in parse_keys("iX<Enter>").unwrap() {
+            app.handle_event(AppEvent::Key(key)).unwrap();
+        }
+        assert!(!app.handle_event(AppEvent::Key(q)).unwrap());
+        assert!(app.quit_question.as_ref().unwrap().contains("being saved"));
+        play(&mut app, "<Esc>").await;
+        assert!(app.handle_event(AppEvent::Key(q)).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_edit_a_title_in_its_row() {
+        let api = Arc::new(DemoApi::new());
+        let mut app = App::new(api.clone(), Vec::new());
+        app.refresh_tasks();
+        play(&mut app, "sWork<Enter>").await;
+        let task = app.selected_task().unwrap().clone();
+        play(&mut app, "i<Home><Del>X<End><BS>!<Left>é<Enter>").await;
+        let saved = api.fetch_task_detail(task.id).await.unwrap();
+        let expected = format!("X{}é!", &task.title[1..task.title.len() - 1]);
+        assert_eq!(saved.title, expected);
+        assert_eq!(app.selected_task().unwrap().title, saved.title);
+    }
+
+    #[tokio::test]
+    async fn test_assign_the_selected_task_to_me() {
+        let mut app = App::new(Arc::new(DemoApi::new()), Vec::new());
+        app.refresh_tasks();
+        play(&mut app, "sWork<Enter>m").await;
+        let mine = |app: &App| {
+            let task = app.selected_task().unwrap();
+            task.assignees
+                .iter()
+                .flatten()
+                .filter(|u| u.username == "demo")
+                .count()
+        };
+        assert_eq!(mine(&app), 1);
+        play(&mut app, "m").await;
+        assert_eq!(app.message.as_deref(), Some("Already assigned to you"));
+        assert_eq!(mine(&app), 1);
+    }
+
+    #[tokio::test]
+    async fn test_assigned_to_me_view() {
+        let mut app = App::new(Arc::new(DemoApi::new()), Vec::new());
+        app.refresh_tasks();
+        play(&mut app, "F<Down><Enter>").await;
+        assert_eq!(app.active_filter.as_ref().unwrap().name, "Assigned to me");
+        let mine = |task: &Task| {
+            task.assignees
+                .iter()
+                .flatten()
+                .any(|u| u.username == "demo")
+        };
+        assert!(app.tasks.iter().all(mine));
+
+        // Assigning a task brings it into the view
+        let count = app.tasks.len();
+        play(&mut app, "0m").await;
+        play(&mut app, "F<Down><Enter>").await;
+        assert_eq!(app.tasks.len(), count + 1);
+    }
+
+    #[tokio::test]
+    async fn test_add_a_task_with_a_color() {
+        let api = Arc::new(DemoApi::new());
+        let mut app = App::new(api.clone(), Vec::new());
+        play(&mut app, "aiPaint the fence<Esc>cc").await;
+        assert_eq!(app.new_task_color, Some(1));
+        play(&mut app, "<Enter>").await;
+        let task = saved_task(&api, "Paint the fence").await;
+        assert_eq!(task.hex_color.as_deref(), Some(TASK_COLORS[1].1));
+        assert_eq!(app.new_task_color, None);
+    }
+
+    #[tokio::test]
+    async fn test_expand_subtasks_under_their_parent() {
+        let mut app = App::new(Arc::new(DemoApi::new()), Vec::new());
+        app.refresh_tasks();
+        play(&mut app, "sInbox<Enter>z").await;
+        assert_eq!(app.message.as_deref(), Some("No subtasks"));
+
+        play(&mut app, "jjz").await;
+        let parent = app.selected_task().unwrap().clone();
+        assert_eq!(parent.title, "Weekly shop");
+        assert!(app.expanded.contains(&parent.id));
+        let done = app.subtasks(&parent).iter().filter(|t| t.done).count();
+        assert_eq!((done, app.subtasks(&parent).len()), (1, 3));
+        play(&mut app, "z").await;
+        assert!(app.expanded.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_add_into_the_bucket_in_view() {
+        let api = Arc::new(DemoApi::new());
+        let mut app = App::new(api.clone(), Vec::new());
+        app.refresh_tasks();
+        play(&mut app, "sWork<Enter>bl").await;
+        let bucket_id = app.new_task_bucket().unwrap().id;
+        play(&mut app, "aiBook the venue<Esc><Enter>").await;
+        let task = saved_task(&api, "Book the venue").await;
+        assert_eq!(task.project_id, 2);
+        assert_eq!(task.bucket_id, bucket_id);
+    }
+
+    #[tokio::test]
+    async fn test_project_settings_apply_to_the_project_added_to() {
+        let api = Arc::new(DemoApi::new());
+        let mut app = App::new(api.clone(), Vec::new());
+        app.set_project_settings(Config::demo().projects);
+        app.refresh_tasks();
+        play(&mut app, "sWork<Enter>").await;
+        play(&mut app, "aiFix the login<Esc><Enter>").await;
+        play(&mut app, "ai+gro Buy eggs<Esc><Enter>").await;
+
+        let labels = |task: &Task| -> Vec<String> {
+            task.labels
+                .iter()
+                .flatten()
+                .map(|l| l.title.clone())
+                .collect()
+        };
+        let views = api.fetch_project_views(2).await.unwrap();
+        let kanban = views.iter().find(|v| v.view_kind == "kanban").unwrap();
+        let buckets = api.fetch_buckets(2, kanban.id).await.unwrap();
+        let in_progress = buckets.iter().find(|b| b.title == "In Progress").unwrap();
+        let login = saved_task(&api, "Fix the login").await;
+        assert_eq!(login.bucket_id, in_progress.id);
+        assert_eq!(labels(&login), ["code"]);
+        assert!(labels(&saved_task(&api, "Buy eggs").await).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_add_into_a_typed_project() {
+        let api = Arc::new(DemoApi::new());
+        let mut app = App::new(api.clone(), Vec::new());
+        play(&mut app, "ai+gro Buy eggs<Esc><Enter>").await;
+        play(&mut app, "ai+o Call back<Esc><Enter>").await;
+        assert!(matches!(app.input_mode, InputMode::ProjectChoice));
+        play(&mut app, "<Esc>").await;
+        assert!(matches!(app.input_mode, InputMode::Editing));
+        play(&mut app, "<Enter>work<Enter>").await;
+        assert!(matches!(app.input_mode, InputMode::Normal));
+
+        assert_eq!(saved_task(&api, "Buy eggs").await.project_id, 3);
+        assert_eq!(saved_task(&api, "Call back").await.project_id, 2);
+    }
+
+    #[tokio::test]
+    async fn test_confirm_what_quick_add_creates() {
+        let api = Arc::new(DemoApi::new());
+        let mut app = App::new(api.clone(), Vec::new());
+        app.confirm_new_task = true;
+        play(&mut app, "aiPay rent !4 +work @sam<Esc><Enter>").await;
+        assert!(matches!(app.input_mode, InputMode::ConfirmNewTask));
+        let preview = app.new_task_preview.as_ref().unwrap();
+        assert_eq!(preview.project.as_deref(), Some("Work"));
+        assert_eq!(preview.task.title, "Pay rent");
+        assert_eq!(preview.task.priority, Some(4));
+        assert_eq!(preview.assignees, ["sam"]);
+
+        play(&mut app, "<Esc>").await;
+        assert!(matches!(app.input_mode, InputMode::Editing));
+        assert!(app.new_task_preview.is_none());
+        assert_eq!(app.new_task_title, "Pay rent !4 +work @sam");
+        play(&mut app, "<Enter><Enter>").await;
+        assert!(matches!(app.input_mode, InputMode::Normal));
+        let tasks = api.fetch_tasks(1, &TaskQuery::default()).await.unwrap();
+        assert!(tasks.iter().any(|t| t.title == "Pay rent"));
+    }
+
+    #[tokio::test]
+    async fn test_restore_a_cancelled_draft() {
+        let api = Arc::new(DemoApi::new());
+        let mut app = App::new(api.clone(), Vec::new());
+        play(
+            &mut app,
+            "aiCall the bank<Esc><Tab>iAbout the card<Esc><Esc>",
+        )
+        .await;
+        assert!(matches!(app.input_mode, InputMode::Normal));
+        let draft = app.saved.draft.clone().unwrap();
+        assert_eq!(draft.title, "Call the bank");
+        assert_eq!(draft.description, "About the card");
+
+        play(&mut app, "a").await;
+        assert!(matches!(app.input_mode, InputMode::RestoreDraft));
+        play(&mut app, "y").await;
+        assert_eq!(app.new_task_title, "Call the bank");
+        assert_eq!(app.new_task_description, "About the card");
+        play(&mut app, "<Enter>").await;
+        assert!(app.saved.draft.is_none());
+        play(&mut app, "a").await;
+        assert!(matches!(app.input_mode, InputMode::Editing));
+    }
+
+    #[tokio::test]
+    async fn test_recall_earlier_input() {
+        let api = Arc::new(DemoApi::new());
+        let mut app = App::new(api.clone(), Vec::new());
+        app.refresh_tasks();
+        play(&mut app, ":archive milk<Enter>:#1<Enter>:<Up><Up>").await;
+        assert_eq!(app.command, "archive milk");
+        play(&mut app, "<Down><Down>").await;
+        assert_eq!(app.command, "");
+        play(&mut app, "<Esc>/ship<Enter><Esc>/<C-p>").await;
+        assert_eq!(app.list_filter.as_deref(), Some("ship"));
+        play(&mut app, "<Esc>aiPay rent<Esc><Enter>aiPay<Up>").await;
+        assert_eq!(app.new_task_title, "Pay rent");
+        play(&mut app, "<Down>").await;
+        assert_eq!(app.new_task_title, "Pay");
+    }
+
+    #[tokio::test]
+    async fn test_create_missing_labels_from_quick_add() {
+        let api = Arc::new(DemoApi::new());
+        let mut app = App::new(api.clone(), Vec::new());
+        app.label_color = Some("#e8e8e8".to_string());
+        play(&mut app, "aiShip it *home *urgent <Esc><Enter>").await;
+        assert!(matches!(app.input_mode, InputMode::ConfirmNewLabels));
+        assert_eq!(app.new_labels.len(), 1);
+        play(&mut app, "n").await;
+        assert!(matches!(app.input_mode, InputMode::Editing));
+        play(&mut app, "<Enter>y").await;
+        assert!(matches!(app.input_mode, InputMode::Normal));
+
+        let labels = api.fetch_labels().await.unwrap();
+        let urgent = labels.iter().find(|l| l.title == "urgent").unwrap();
+        assert_eq!(urgent.hex_color.as_deref(), Some("e8e8e8"));
+        let task = saved_task(&api, "Ship it").await;
+        let titles: Vec<&str> = task
+            .labels
+            .iter()
+            .flatten()
+            .map(|l| l.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["home", "urgent"]);
+    }
+
+    #[tokio::test]
+    async fn test_new_labels_are_asked_about_again_after_going_back() {
+        let mut app = App::new(Arc::new(DemoApi::new()), Vec::new());
+        app.confirm_new_task = true;
+        play(&mut app, "aiShip it *urgent <Esc><Enter>y").await;
+        assert!(matches!(app.input_mode, InputMode::ConfirmNewTask));
+        play(&mut app, "n").await;
+        play(&mut app, "i*later <Esc><Enter>").await;
+        assert!(matches!(app.input_mode, InputMode::ConfirmNewLabels));
+    }
+
+    #[tokio::test]
+    async fn test_add_a_task_with_typed_reminders() {
+        let api = Arc::new(DemoApi::new());
+        let mut app = App::new(api.clone(), Vec::new());
+        play(
+            &mut app,
+            "aiPay rent remind: 2024-05-01 9am remind:2024-05-02<Esc>r<Enter>",
+        )
+        .await;
+        play(&mut app, "<Enter>").await;
+        let task = saved_task(&api, "Pay rent").await;
+        assert_eq!(task.reminders.as_ref().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_description_in_braces_comes_first() {
+        let api = Arc::new(DemoApi::new());
+        let mut app = App::new(api.clone(), Vec::new());
+        play(
+            &mut app,
+            "aiRenew passport {bring photos}<Esc><Tab>iand the form<Esc><Enter>",
+        )
+        .await;
+        // The new one, after the demo's own
+        let task = saved_task(&api, "Renew passport").await;
+        let description = task.description.unwrap();
+        assert_eq!(description, "<p>bring photos\n\nand the form</p>");
+    }
+
+    #[tokio::test]
+    async fn test_done_repeating_task_tells_its_next_occurrence() {
+        let api = Arc::new(DemoApi::new());
+        let mut app = App::new(api.clone(), Vec::new());
+        app.confirm_done_bucket = false;
+        app.refresh_tasks();
+        play(&mut app, "sInbox<Enter>bjj").await;
+        assert_eq!(app.selected_task().unwrap().title, "Weekly shop");
+        play(&mut app, "LL").await;
+        assert_eq!(app.message.as_deref(), Some("Next occurrence: May 11"));
+        let task = saved_task(&api, "Weekly shop").await;
+        assert!(!task.done);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_picks_up_changes_from_elsewhere() {
+        let api = Arc::new(DemoApi::new());
+        let mut app = App::new(api.clone(), Vec::new());
+        app.refresh_tasks();
+        play(&mut app, "j<Enter>").await;
+        let mut task = app.task_detail.clone().unwrap();
+        task.title = "Call the dentist today".to_string();
+        api.update_task(&task).await.unwrap();
+
+        play(&mut app, "r").await;
+        assert_eq!(app.selected_task().unwrap().title, "Call the dentist today");
+        assert_eq!(app.task_detail.unwrap().title, "Call the dentist today");
+        assert!(app.loaded_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_share_the_project_and_remove_a_member() {
+        let api = Arc::new(DemoApi::new());
+        let mut app = App::new(api.clone(), Vec::new());
+        app.refresh_tasks();
+        play(&mut app, "sWork<Enter>U").await;
+        assert!(matches!(app.input_mode, InputMode::Members));
+        assert_eq!(app.members.as_ref().unwrap().len(), 3);
+
+        play(&mut app, "ateam:design<Tab><Enter>").await;
+        let teams = api.fetch_project_teams(2).await.unwrap();
+        assert!(teams
+            .iter()
+            .any(|t| t.team.name == "Design" && t.right == 1));
+
+        play(&mut app, "jdy").await;
+        let users = api.fetch_project_users(2).await.unwrap();
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0].user.username, "alex");
+        assert_eq!(app.members.as_ref().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_create_a_team_and_add_a_member() {
+        let api = Arc::new(DemoApi::new());
+        let mut app = App::new(api.clone(), Vec::new());
+        play(&mut app, "T").await;
+        assert!(matches!(app.input_mode, InputMode::Teams));
+        assert_eq!(app.teams.as_ref().unwrap().teams.len(), 2);
+
+        play(&mut app, "nOps<Enter>jjasam<Enter>").await;
+        let teams = api.fetch_teams().await.unwrap();
+        let ops = teams.iter().find(|team| team.name == "Ops").unwrap();
+        let members: Vec<&str> = ops
+            .members
+            .iter()
+            .flatten()
+            .map(|member| member.user.username.as_str())
+            .collect();
+        assert_eq!(members, ["demo", "sam"]);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_a_project_from_the_switcher() {
+        let api = Arc::new(DemoApi::new());
+        let mut app = App::new(api.clone(), Vec::new());
+        play(&mut app, "sGroceries<C-d>").await;
+        assert!(matches!(app.input_mode, InputMode::DuplicateParent));
+        play(&mut app, "<Enter>").await;
+        assert!(matches!(app.input_mode, InputMode::Normal));
+        assert!(app.projects.is_empty());
+
+        let projects = api.fetch_projects().await.unwrap();
+        let copy = projects
+            .iter()
+            .find(|p| p.title == "Groceries - duplicate")
+            .unwrap();
+        let query = TaskQuery {
+            filter: Some(format!("project = {}", copy.id)),
+            ..Default::default()
+        };
+        assert_eq!(api.fetch_tasks(1, &query).await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_undo_a_delete_creates_the_task_again() {
+        let api = Arc::new(DemoApi::new());
+        let mut app = App::new(api.clone(), Vec::new());
+        app.refresh_tasks();
+        play(&mut app, "x").await;
+        assert!(matches!(app.input_mode, InputMode::ConfirmDelete));
+        play(&mut app, "n").await;
+        assert!(app.pending_delete.is_none());
+        assert_eq!(app.selected_task().unwrap().title, "Renew passport");
+        play(&mut app, "xy").await;
+        assert_eq!(
+            app.message.as_deref(),
+            Some("Deleted \"Renew passport\", u to undo")
+        );
+        let titles =
+            |tasks: Vec<Task>| -> Vec<String> { tasks.into_iter().map(|t| t.title).collect() };
+        let tasks = api.fetch_tasks(1, &TaskQuery::default()).await.unwrap();
+        assert!(!titles(tasks).contains(&"Renew passport".to_string()));
+
+        play(&mut app, "u").await;
+        let restored = saved_task(&api, "Renew passport").await;
+        assert_eq!(restored.project_id, 1);
+        assert_eq!(restored.due_date.as_deref(), Some("2024-05-10T17:00:00Z"));
+        assert_eq!(restored.labels.as_ref().unwrap()[0].title, "errands");
+        assert_eq!(
+            restored.description.as_deref(),
+            Some("<p>Bring the old passport and two photos.</p>")
+        );
+        assert!(!app.can_undo_delete());
+    }
+
+    #[tokio::test]
+    async fn test_moving_on_calls_off_the_details_loading() {
+        let mut app = App::new(Arc::new(DemoApi::new()), Vec::new());
+        app.refresh_tasks();
+        app.settle().await;
+        // No waiting between the keys, the details are still loading on `j`
+        for key in parse_keys("<Enter>j").unwrap() {
+            app.handle_event(AppEvent::Key(key)).unwrap();
+        }
+        app.settle().await;
+        assert!(app.task_detail.is_none());
+        assert_eq!(app.state.selected(), Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_matching_tasks_are_counted_from_the_pages() {
+        let api = Arc::new(DemoApi::new());
+        for i in 0..50 {
+            let task = NewTask {
+                title: format!("Chore {}", i),
+                ..Default::default()
+            };
+            api.create_new_task(&task).await.unwrap();
+        }
+        let mut app = App::new(api.clone(), Vec::new());
+        app.refresh_tasks();
+        app.settle().await;
+        assert_eq!(app.matching_tasks(), (51, false));
+
+        play(&mut app, "n").await;
+        assert_eq!(app.page, 2);
+        assert_eq!(app.matching_tasks(), (50 + app.tasks.len(), true));
+    }
+
+    #[tokio::test]
+    async fn test_a_profile_key_that_fails_keeps_the_current_instance() {
+        let mut app = App::new(Arc::new(DemoApi::new()), Vec::new());
+        app.profile = "home".to_string();
+        let profile = Profile {
+            name: "work".to_string(),
+            vikunja: crate::config::VikunjaConfig {
+                instance_url: "https://example.com".to_string(),
+                api_key: String::new(),
+                api_key_cmd: Some("echo locked >&2; exit 3".to_string()),
+                oidc_provider: None,
+            },
+        };
+        app.switch_profile(profile).unwrap();
+        app.settle().await;
+        assert_eq!(app.profile, "home");
+        assert!(app.message.as_deref().unwrap_or("").contains("locked"));
+    }
+
+    #[tokio::test]
+    async fn test_api_log_takes_the_keys_while_it_is_shown() {
+        let mut app = App::new(Arc::new(DemoApi::new()), Vec::new());
+        app.refresh_tasks();
+        app.settle().await;
+        let selected = app.state.selected();
+        play(&mut app, "<F12>jja").await;
+        assert!(app.show_api_log);
+        assert_eq!(app.state.selected(), selected);
+        assert!(matches!(app.input_mode, InputMode::Normal));
+        play(&mut app, "<Esc>j").await;
+        assert!(!app.show_api_log);
+        assert_ne!(app.state.selected(), selected);
+    }
+
+    #[tokio::test]
+    async fn test_skipped_tasks_are_told_next_to_the_message() {
+        let mut app = App::new(Arc::new(DemoApi::new()), Vec::new());
+        app.message = Some("Next on 2026-11-01".to_string());
+        app.report_skipped(2);
+        assert_eq!(
+            app.message.as_deref(),
+            Some("Next on 2026-11-01; 2 tasks couldn't be read and are left out")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_next_page_is_loaded_ahead() {
+        let api = Arc::new(DemoApi::new());
+        for i in 0..50 {
+            let task = NewTask {
+                title: format!("Chore {}", i),
+                ..Default::default()
+            };
+            api.create_new_task(&task).await.unwrap();
+        }
+        let mut app = App::new(api.clone(), Vec::new());
+        app.refresh_tasks();
+        app.settle().await;
+        assert!(app
+            .prefetch
+            .as_ref()
+            .is_some_and(|p| p.page == 2 && p.result.is_some()));
+        let arrived = app.prefetch.as_ref().unwrap().result.as_ref().unwrap().1;
+
+        // Shown without waiting for a request
+        for key in parse_keys("n").unwrap() {
+            app.handle_event(AppEvent::Key(key)).unwrap();
+        }
+        assert!(!app.loading());
+        assert_eq!(app.page, 2);
+        assert_eq!(app.loaded_at, Some(arrived));
+        assert!(!app.tasks.is_empty() && app.tasks.len() < 50);
+
+        // A page loaded ahead for another filter isn't used
+        play(&mut app, "p").await;
+        assert!(app.prefetch.is_some());
+        play(&mut app, "t").await;
+        assert_eq!(app.prefetch.as_ref().unwrap().query, app.task_query());
+    }
+
+    #[tokio::test]
+    async fn test_pages_are_entered_at_the_row_next_to_the_one_left() {
+        let api = Arc::new(DemoApi::new());
+        for i in 0..50 {
+            let task = NewTask {
+                title: format!("Chore {}", i),
+                ..Default::default()
+            };
+            api.create_new_task(&task).await.unwrap();
+        }
+        let mut app = App::new(api, Vec::new());
+        app.refresh_tasks();
+        play(&mut app, "jjn").await;
+        assert_eq!(app.page, 2);
+        assert_eq!(app.state.selected(), Some(0));
+        play(&mut app, "jp").await;
+        assert_eq!(app.page, 1);
+        assert_eq!(app.state.selected(), Some(app.visible_rows.len() - 1));
+
+        // Without a page before, the selection stays
+        play(&mut app, "kp").await;
+        assert_eq!(app.state.selected(), Some(app.visible_rows.len() - 2));
+    }
+
+    #[tokio::test]
+    async fn test_projects_and_user_load_with_the_first_page() {
+        let mut app = App::new(Arc::new(DemoApi::new()), Vec::new());
+        // Only the tasks show as loading
+        app.warm_up();
+        assert!(app.worker.busy() && !app.loading());
+        app.refresh_tasks();
+        app.settle().await;
+        assert!(!app.tasks.is_empty());
+        assert!(!app.projects.is_empty());
+        assert!(app.current_user.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_next_page_past_the_end_stays_on_the_last() {
+        let mut app = App::new(Arc::new(DemoApi::new()), Vec::new());
+        app.refresh_tasks();
+        play(&mut app, "jn").await;
+        assert_eq!(app.page, 1);
+        assert_eq!(app.message.as_deref(), Some("No more pages"));
+        assert_eq!(app.state.selected(), Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_reloads_keep_the_selected_task() {
+        let mut app = App::new(Arc::new(DemoApi::new()), Vec::new());
+        app.refresh_tasks();
+        play(&mut app, "jj").await;
+        let task_id = app.selected_task().unwrap().id;
+        play(&mut app, "t").await;
+        assert_eq!(app.selected_task().unwrap().id, task_id);
+        play(&mut app, "r").await;
+        assert_eq!(app.selected_task().unwrap().id, task_id);
+
+        // A deleted task leaves its row to the next one
+        let row = app.state.selected();
+        play(&mut app, "xy").await;
+        assert_eq!(app.state.selected(), row);
+        assert_ne!(app.selected_task().unwrap().id, task_id);
+    }
+
+    #[tokio::test]
+    async fn test_done_tasks_come_last_and_fold_away() {
+        let mut app = App::new(Arc::new(DemoApi::new()), Vec::new());
+        app.refresh_tasks();
+        play(&mut app, "t").await;
+        let done: Vec<bool> = app.visible_tasks().map(|task| task.done).collect();
+        assert_eq!(app.completed_rows, 3);
+        assert!(done.ends_with(&[true; 3]) && !done[..done.len() - 3].contains(&true));
+
+        play(&mut app, "C").await;
+        assert_eq!(app.visible_tasks().count(), done.len() - 3);
+        assert!(app.visible_tasks().all(|task| !task.done));
+        play(&mut app, "C").await;
+        assert_eq!(app.visible_tasks().count(), done.len());
+
+        play(&mut app, "sWork<Enter>tJ").await;
+        assert!(app.groups_completed());
+        assert_eq!(
+            app.message.as_deref(),
+            Some("Hide the done tasks (t) to reorder")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stopping_the_timer_logs_a_comment() {
+        let api = Arc::new(DemoApi::new());
+        let mut app = App::new(api.clone(), Vec::new());
+        app.refresh_tasks();
+        play(&mut app, "Wq").await;
+        assert!(matches!(app.input_mode, InputMode::ConfirmQuit));
+        play(&mut app, "n").await;
+        assert!(matches!(app.input_mode, InputMode::Normal));
+        let timer = app.timer.as_mut().unwrap();
+        let task_id = timer.task_id;
+        timer.started -= Duration::from_secs(42 * 60);
+        play(&mut app, "W").await;
+        assert!(app.timer.is_none());
+
+        let comments = api.fetch_comments(task_id).await.unwrap();
+        let today = Local::now().format("%Y-%m-%d");
+        let logged = format!("⏱ 0:42 on {}", today);
+        assert!(comments.last().unwrap().comment.contains(&logged));
+        assert!(app
+            .message
+            .as_deref()
+            .unwrap()
+            .starts_with("Logged 0:42 on "));
+    }
+
+    #[tokio::test]
+    async fn test_move_every_task_in_the_view() {
+        let api = Arc::new(DemoApi::new());
+        let mut app = App::new(api.clone(), Vec::new());
+        app.refresh_tasks();
+        play(&mut app, "sWork<Enter>").await;
+        let ids: Vec<u64> = app.tasks.iter().map(|task| task.id).collect();
+        play(&mut app, ":move groc<Enter>").await;
+        assert_eq!(app.bulk_move.as_ref().unwrap().task_ids, ids);
+        play(&mut app, "y").await;
+        assert!(app.bulk_move.is_none());
+        assert!(app.tasks.is_empty());
+
+        let tasks = api.fetch_tasks(1, &TaskQuery::default()).await.unwrap();
+        assert!(tasks
+            .iter()
+            .filter(|task| ids.contains(&task.id))
+            .all(|task| task.project_id == 3));
+    }
+
+    #[tokio::test]
+    async fn test_label_every_task_in_the_view() {
+        let api = Arc::new(DemoApi::new());
+        let mut app = App::new(api.clone(), Vec::new());
+        app.refresh_tasks();
+        play(&mut app, "sWork<Enter>:label *code<Enter>").await;
+        assert_eq!(app.bulk_label.as_ref().unwrap().task_ids.len(), 2);
+        play(&mut app, "y").await;
+        assert!(app.bulk_label.is_none());
+        let labeled = |task: &Task| task.labels.iter().flatten().any(|l| l.title == "code");
+        assert!(app.tasks.iter().all(labeled));
+
+        play(&mut app, ":unlabel code<Enter><Esc>").await;
+        assert!(app.bulk_label.is_none());
+        assert!(app.tasks.iter().all(labeled));
+    }
+
+    #[tokio::test]
+    async fn test_reopen_marked_tasks_from_the_done_review() {
+        let mut app = App::new(Arc::new(DemoApi::new()), Vec::new());
+        app.refresh_tasks();
+        play(&mut app, "D").await;
+        assert_eq!(app.tasks.len(), 3);
+        assert!(app.tasks.iter().all(|task| task.done));
+
+        let marked: Vec<u64> = app.tasks[..2].iter().map(|task| task.id).collect();
+        play(&mut app, "<Space><Space>u").await;
+        assert!(app.marked.is_empty());
+        let ids: Vec<u64> = app.tasks.iter().map(|task| task.id).collect();
+        assert!(marked.iter().all(|id| !ids.contains(id)));
+        assert_eq!(ids.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_add_lines_from_the_command_line() {
+        let api = Arc::new(DemoApi::new());
+        let mut app = App::new(api.clone(), Vec::new());
+        // Nothing is previewed, and the setting is left as it is
+        app.confirm_new_task = true;
+        let lines = ["buy milk", "", "call +Nope", "fix bike *bikes"].map(String::from);
+        let outcomes = app.add_lines(&lines, false).await;
+        assert_eq!(outcomes.len(), 3);
+        assert_eq!(outcomes[0], ("buy milk".to_string(), Ok(None)));
+        assert_eq!(outcomes[1].1, Err("No project matching 'Nope'".to_string()));
+        assert!(outcomes[2]
+            .1
+            .as_ref()
+            .is_err_and(|err| err.contains("bikes")));
+        let tasks = api.fetch_tasks(1, &TaskQuery::default()).await.unwrap();
+        assert!(tasks.iter().any(|task| task.title == "buy milk"));
+        assert!(!tasks.iter().any(|task| task.title == "fix bike"));
+
+        let outcomes = app.add_lines(&lines[3..], true).await;
+        assert!(outcomes[0].1.is_ok());
+        assert!(app.confirm_new_task);
+    }
+
+    #[tokio::test]
+    async fn test_export_the_selected_task_as_markdown() {
+        let mut app = App::new(Arc::new(DemoApi::new()), Vec::new());
+        app.refresh_tasks();
+        let path = std::env::temp_dir().join(format!("export-{}.md", std::process::id()));
+        play(&mut app, &format!(":export {}<Enter>", path.display())).await;
+        let markdown = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let heading = markdown.lines().next().unwrap();
+        assert!(heading.starts_with("# ") && heading.ends_with(&app.tasks[0].title));
+        assert!(markdown.contains("- Status: open"));
+        assert!(app.task_detail.is_none());
+
+        play(&mut app, ":export<Enter>").await;
+        assert_eq!(app.clipboard.as_deref(), Some(markdown.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_search_the_archive_of_done_tasks() {
+        let mut app = App::new(Arc::new(DemoApi::new()), Vec::new());
+        app.refresh_tasks();
+        play(&mut app, ":archive laptop<Enter>").await;
+        assert_eq!(app.view_kind, ViewKind::Done);
+        let titles: Vec<&str> = app.tasks.iter().map(|task| task.title.as_str()).collect();
+        assert_eq!(titles, ["Back up the laptop"]);
+
+        play(&mut app, "D").await;
+        assert_eq!(app.view_kind, ViewKind::List);
+        assert!(app.archive.is_none());
+        assert!(app.tasks.iter().all(|task| !task.done));
+    }
+}
@@ -0,0 +1,290 @@
+use dirs::data_dir;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::timeout;
+use vikunja_client::auth::same_instance;
+use vikunja_client::models::{OpenIdProvider, ServerInfo};
+use vikunja_client::{ApiResult, HttpClient, VikunjaApi};
+
+// How long to wait for the browser to come back from the provider
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(300);
+
+// A login saved for the instance that issued it
+#[derive(Serialize, Deserialize)]
+struct SavedToken {
+    instance_url: String,
+    token: String,
+}
+
+fn tokens_path() -> Option<PathBuf> {
+    let mut path = data_dir()?;
+    path.push("vikunja-tui/tokens.json");
+    Some(path)
+}
+
+fn load_tokens(path: &Path) -> Vec<SavedToken> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+// Only a token the same instance issued is used, so profiles on different
+// servers never send each other's logins
+fn load_token(path: &Path, instance_url: &str) -> Option<String> {
+    load_tokens(path)
+        .into_iter()
+        .find(|saved| same_instance(&saved.instance_url, instance_url))
+        .map(|saved| saved.token)
+        .filter(|token| !token.is_empty())
+}
+
+// The token grants full access to the account, so only the user can read it
+fn save_token(path: &Path, instance_url: &str, token: &str) -> io::Result<()> {
+    let mut tokens = load_tokens(path);
+    tokens.retain(|saved| !same_instance(&saved.instance_url, instance_url));
+    tokens.push(SavedToken {
+        instance_url: instance_url.to_string(),
+        token: token.to_string(),
+    });
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let text = serde_json::to_string(&tokens)?;
+    io::Write::write_all(&mut options.open(path)?, text.as_bytes())
+}
+
+// Signs in through the configured OpenID Connect provider. A saved token is
+// renewed while it is still valid, otherwise the browser flow runs again.
+pub async fn oidc_token(
+    api: &HttpClient,
+    instance_url: &str,
+    info: &ServerInfo,
+    provider_key: &str,
+) -> ApiResult<String> {
+    let path = tokens_path();
+    if let Some(token) = path
+        .as_deref()
+        .and_then(|path| load_token(path, instance_url))
+    {
+        api.set_token(&token);
+        if let Ok(token) = api.renew_token().await {
+            if let Some(path) = &path {
+                save_token(path, instance_url, &token)?;
+            }
+            return Ok(token);
+        }
+    }
+
+    let openid = &info.auth.openid_connect;
+    if !openid.enabled {
+        return Err("OpenID Connect login is not enabled on this instance".into());
+    }
+    let Some(provider) = openid
+        .providers
+        .iter()
+        .find(|p| p.key == provider_key || p.name.eq_ignore_ascii_case(provider_key))
+    else {
+        let keys: Vec<&str> = openid.providers.iter().map(|p| p.key.as_str()).collect();
+        return Err(format!(
+            "No OpenID Connect provider '{}', available: {}",
+            provider_key,
+            keys.join(", ")
+        )
+        .into());
+    };
+
+    let token = browser_login(api, provider).await?;
+    if let Some(path) = &path {
+        save_token(path, instance_url, &token)?;
+    }
+    Ok(token)
+}
+
+// The value the provider hands back with the code, so a redirect that
+// wasn't started here is turned away. It comes from the system's random
+// source as it has to be unguessable.
+fn login_state() -> ApiResult<String> {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes)?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+// Sends the user to the provider with a redirect back to a one-shot listener
+// on localhost, which receives the authorization code
+async fn browser_login(api: &HttpClient, provider: &OpenIdProvider) -> ApiResult<String> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let redirect_url = format!(
+        "http://127.0.0.1:{}/auth/openid/",
+        listener.local_addr()?.port()
+    );
+    let state = login_state()?;
+
+    let auth_url = Url::parse_with_params(
+        &provider.auth_url,
+        &[
+            ("client_id", provider.client_id.as_str()),
+            ("redirect_uri", &format!("{}{}", redirect_url, provider.key)),
+            ("response_type", "code"),
+            ("scope", provider.scope.as_str()),
+            ("state", state.as_str()),
+        ],
+    )?;
+    println!(
+        "Sign in with {} in your browser. If it does not open, visit:\n{}",
+        provider.name, auth_url
+    );
+    // The url has been printed too, so a missing opener is not an error
+    let _ = open_browser(auth_url.as_str());
+
+    let code = timeout(LOGIN_TIMEOUT, wait_for_code(&listener, &state))
+        .await
+        .map_err(|_| "Timed out waiting for the OpenID Connect login")??;
+    api.oidc_callback(&provider.key, &code, &redirect_url).await
+}
+
+// Links come from task descriptions and comments anyone on the project can
+// edit, so only plain web addresses are handed on, and never to a shell
+fn web_url(url: &str) -> io::Result<Url> {
+    match Url::parse(url.trim()) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") && url.has_host() => Ok(url),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("not a web address: {}", url),
+        )),
+    }
+}
+
+pub fn open_browser(url: &str) -> io::Result<()> {
+    let url = web_url(url)?;
+    let (program, args) = if cfg!(target_os = "macos") {
+        ("open", vec![url.as_str()])
+    } else if cfg!(windows) {
+        (
+            "rundll32",
+            vec!["url.dll,FileProtocolHandler", url.as_str()],
+        )
+    } else {
+        ("xdg-open", vec![url.as_str()])
+    };
+    Command::new(program)
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    Ok(())
+}
+
+async fn wait_for_code(listener: &TcpListener, state: &str) -> ApiResult<String> {
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let mut buffer = vec![0; 8192];
+        let read = stream.read(&mut buffer).await?;
+        let request = String::from_utf8_lossy(&buffer[..read]);
+
+        // Anything else the browser asks for, like a favicon, is ignored
+        let Some(params) = callback_params(&request) else {
+            respond(&mut stream, "404 Not Found", "").await;
+            continue;
+        };
+        if params.get("state").map(String::as_str) != Some(state) {
+            respond(&mut stream, "400 Bad Request", "Unexpected login response.").await;
+            continue;
+        }
+        if let Some(error) = params.get("error") {
+            respond(&mut stream, "200 OK", "Sign-in failed, see the terminal.").await;
+            return Err(format!("OpenID Connect login failed: {}", error).into());
+        }
+        if let Some(code) = params.get("code") {
+            respond(&mut stream, "200 OK", "Signed in, you can close this tab.").await;
+            return Ok(code.clone());
+        }
+    }
+}
+
+// Query parameters of a request for the redirect path
+fn callback_params(request: &str) -> Option<HashMap<String, String>> {
+    let target = request.lines().next()?.split_whitespace().nth(1)?;
+    let url = Url::parse(&format!("http://127.0.0.1{}", target)).ok()?;
+    if !url.path().starts_with("/auth/openid/") {
+        return None;
+    }
+    Some(url.query_pairs().into_owned().collect())
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_callback_params_reads_code_and_state() {
+        let request =
+            "GET /auth/openid/keycloak?state=abc&code=x%2Fy HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n";
+        let params = callback_params(request).unwrap();
+        assert_eq!(params.get("code").map(String::as_str), Some("x/y"));
+        assert_eq!(params.get("state").map(String::as_str), Some("abc"));
+        assert!(callback_params("GET /favicon.ico HTTP/1.1\r\n\r\n").is_none());
+    }
+
+    #[test]
+    fn test_only_web_urls_are_opened() {
+        assert!(web_url("https://vikunja.io/docs").is_ok());
+        assert!(web_url("http://127.0.0.1:8080/?a=1&b=2").is_ok());
+        assert!(web_url("file:///etc/passwd").is_err());
+        assert!(web_url("javascript:alert(1)").is_err());
+        assert!(web_url("https://x.io\" & calc").is_err());
+        assert!(web_url("ms-settings:").is_err());
+    }
+
+    #[test]
+    fn test_saved_tokens_are_kept_per_instance() {
+        let dir = std::env::temp_dir().join(format!("vikunja-tui-tokens-{}", std::process::id()));
+        let path = dir.join("tokens.json");
+        save_token(&path, "https://a.example.com", "token-a").unwrap();
+        save_token(&path, "https://b.example.com/", "token-b").unwrap();
+        save_token(&path, "https://A.example.com/", "token-a2").unwrap();
+
+        assert_eq!(
+            load_token(&path, "https://a.example.com").as_deref(),
+            Some("token-a2")
+        );
+        assert_eq!(
+            load_token(&path, "https://b.example.com").as_deref(),
+            Some("token-b")
+        );
+        assert_eq!(load_token(&path, "https://c.example.com"), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_login_state_differs_each_time() {
+        let state = login_state().unwrap();
+        assert_eq!(state.len(), 32);
+        assert_ne!(state, login_state().unwrap());
+    }
+}
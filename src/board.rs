@@ -0,0 +1,137 @@
+use crate::picker::{select_next, select_previous};
+use ratatui::widgets::ListState;
+use vikunja_client::models::{Bucket, ProjectView, Task};
+
+// Gap Vikunja leaves between the positions of neighbouring tasks
+const POSITION_SPACING: f64 = 65536.0;
+
+// Kanban board of a project, with one selection per bucket
+pub struct Board {
+    pub view_id: u64,
+    pub done_bucket_id: Option<u64>,
+    pub buckets: Vec<Bucket>,
+    pub column: usize,
+    pub states: Vec<ListState>,
+}
+
+impl Board {
+    pub fn new(view: &ProjectView, buckets: Vec<Bucket>) -> Board {
+        let states = buckets
+            .iter()
+            .map(|bucket| {
+                let mut state = ListState::default();
+                if !bucket_tasks(bucket).is_empty() {
+                    state.select(Some(0));
+                }
+                state
+            })
+            .collect();
+        Board {
+            view_id: view.id,
+            done_bucket_id: (view.done_bucket_id != 0).then_some(view.done_bucket_id),
+            buckets,
+            column: 0,
+            states,
+        }
+    }
+
+    pub fn is_done_bucket(&self, bucket_id: u64) -> bool {
+        self.done_bucket_id == Some(bucket_id)
+    }
+
+    pub fn left(&mut self) {
+        if self.column > 0 {
+            self.column -= 1;
+        }
+    }
+
+    pub fn right(&mut self) {
+        if self.column + 1 < self.buckets.len() {
+            self.column += 1;
+        }
+    }
+
+    pub fn down(&mut self) {
+        let len = self.column_len();
+        if let Some(state) = self.states.get_mut(self.column) {
+            select_next(state, len);
+        }
+    }
+
+    pub fn up(&mut self) {
+        let len = self.column_len();
+        if let Some(state) = self.states.get_mut(self.column) {
+            select_previous(state, len);
+        }
+    }
+
+    fn column_len(&self) -> usize {
+        self.buckets
+            .get(self.column)
+            .map(|bucket| bucket_tasks(bucket).len())
+            .unwrap_or(0)
+    }
+
+    pub fn selected_row(&self) -> Option<usize> {
+        self.states.get(self.column)?.selected()
+    }
+
+    pub fn selected_task(&self) -> Option<&Task> {
+        let bucket = self.buckets.get(self.column)?;
+        bucket_tasks(bucket).get(self.selected_row()?)
+    }
+
+    // Selects the card of a task in whichever bucket it is
+    pub fn select_task(&mut self, task_id: u64) {
+        for (column, bucket) in self.buckets.iter().enumerate() {
+            if let Some(row) = bucket_tasks(bucket).iter().position(|t| t.id == task_id) {
+                self.column = column;
+                self.states[column].select(Some(row));
+                return;
+            }
+        }
+    }
+}
+
+// The position that moves the task at index `from` to index `to` of the same
+// bucket, halfway between the tasks that become its neighbours
+pub fn position_for_move(tasks: &[Task], from: usize, to: usize) -> f64 {
+    let others: Vec<f64> = tasks
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != from)
+        .map(|(_, task)| task.position)
+        .collect();
+    let before = to.checked_sub(1).and_then(|i| others.get(i).copied());
+    let after = others.get(to).copied();
+    match (before, after) {
+        (Some(before), Some(after)) => (before + after) / 2.0,
+        (None, Some(after)) => after / 2.0,
+        (Some(before), None) => before + POSITION_SPACING,
+        (None, None) => POSITION_SPACING,
+    }
+}
+
+pub fn bucket_tasks(bucket: &Bucket) -> &[Task] {
+    bucket.tasks.as_deref().unwrap_or(&[])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_for_move_lands_between_neighbours() {
+        let tasks: Vec<Task> = [100.0, 200.0, 300.0]
+            .iter()
+            .map(|&position| Task {
+                position,
+                ..Default::default()
+            })
+            .collect();
+        assert_eq!(position_for_move(&tasks, 2, 1), 150.0);
+        assert_eq!(position_for_move(&tasks, 1, 0), 50.0);
+        assert_eq!(position_for_move(&tasks, 0, 1), 250.0);
+        assert_eq!(position_for_move(&tasks, 1, 2), 300.0 + POSITION_SPACING);
+    }
+}
@@ -0,0 +1,233 @@
+use chrono::{
+    Datelike, Days, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Weekday,
+};
+
+// Picked dates are set to noon, so they land on the same day in any nearby
+// time zone
+const PICKED_HOUR: u32 = 12;
+
+// Month calendar for choosing a date with the keyboard
+pub struct DatePicker {
+    pub selected: NaiveDate,
+}
+
+impl DatePicker {
+    pub fn new(selected: Option<NaiveDate>) -> DatePicker {
+        DatePicker {
+            selected: selected.unwrap_or_else(today),
+        }
+    }
+
+    pub fn move_days(&mut self, days: i64) {
+        let moved = if days < 0 {
+            self.selected
+                .checked_sub_days(Days::new(days.unsigned_abs()))
+        } else {
+            self.selected.checked_add_days(Days::new(days as u64))
+        };
+        if let Some(date) = moved {
+            self.selected = date;
+        }
+    }
+
+    // Keeps the day of the month where possible, Jan 31 goes to the end of
+    // February
+    pub fn move_months(&mut self, months: i32) {
+        let moved = if months < 0 {
+            self.selected
+                .checked_sub_months(Months::new(months.unsigned_abs()))
+        } else {
+            self.selected.checked_add_months(Months::new(months as u32))
+        };
+        if let Some(date) = moved {
+            self.selected = date;
+        }
+    }
+
+    pub fn today(&mut self) {
+        self.selected = today();
+    }
+
+    // The weeks of the selected month, Monday first, with None outside it
+    pub fn weeks(&self) -> Vec<[Option<NaiveDate>; 7]> {
+        let first = self.selected.with_day(1).unwrap_or(self.selected);
+        let offset = first.weekday().num_days_from_monday() as usize;
+        let mut weeks = Vec::new();
+        let mut week = [None; 7];
+        let mut date = first;
+        let mut column = offset;
+        while date.month() == first.month() {
+            week[column] = Some(date);
+            column += 1;
+            if column == 7 {
+                weeks.push(week);
+                week = [None; 7];
+                column = 0;
+            }
+            match date.succ_opt() {
+                Some(next) => date = next,
+                None => break,
+            }
+        }
+        if column > 0 {
+            weeks.push(week);
+        }
+        weeks
+    }
+}
+
+// A date typed in quick add, with the time of day when one was given
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TypedDate {
+    pub date: NaiveDate,
+    pub time: Option<NaiveTime>,
+}
+
+// A date followed by an optional time, like "friday 9am", "today 17:00" or
+// "2024-05-01T09:00"
+pub fn parse_date_time(text: &str, today: NaiveDate) -> Option<TypedDate> {
+    if let Ok(datetime) = NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M") {
+        return Some(TypedDate {
+            date: datetime.date(),
+            time: Some(datetime.time()),
+        });
+    }
+    let (date, time) = match text.split_once(char::is_whitespace) {
+        Some((date, time)) => (date, Some(parse_time(time.trim())?)),
+        None => (text, None),
+    };
+    Some(TypedDate {
+        date: parse_date(date, today)?,
+        time,
+    })
+}
+
+// A time of day: 17:00, 9am, 9:30pm or 9 am
+pub fn parse_time(text: &str) -> Option<NaiveTime> {
+    let text = text.to_lowercase();
+    let (clock, afternoon) = if let Some(clock) = text.strip_suffix("am") {
+        (clock.trim_end(), Some(false))
+    } else if let Some(clock) = text.strip_suffix("pm") {
+        (clock.trim_end(), Some(true))
+    } else {
+        (text.as_str(), None)
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => (hour.parse().ok()?, minute.parse().ok()?),
+        None if afternoon.is_some() => (clock.parse::<u32>().ok()?, 0),
+        _ => return None,
+    };
+    let hour = match afternoon {
+        // 12am is midnight and 12pm noon
+        Some(afternoon) if (1..=12).contains(&hour) => hour % 12 + if afternoon { 12 } else { 0 },
+        Some(_) => return None,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+// A date as typed in quick add: 2024-05-01, today, tomorrow or a weekday like
+// fri or friday, which is the next one after today
+pub fn parse_date(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match text.to_lowercase().as_str() {
+        "today" => Some(today),
+        "tomorrow" => today.succ_opt(),
+        text => {
+            if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+                return Some(date);
+            }
+            let weekday = text.parse::<Weekday>().ok()?;
+            let ahead =
+                (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+            today.checked_add_days(Days::new(if ahead == 0 { 7 } else { ahead as u64 }))
+        }
+    }
+}
+
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+// The date as the API expects it, at the picked time in the local time zone
+pub fn to_api_datetime(date: NaiveDate) -> String {
+    to_api_datetime_at(date, None)
+}
+
+// The date at the given local time of day, noon when there is none
+pub fn to_api_datetime_at(date: NaiveDate, time: Option<NaiveTime>) -> String {
+    let time = time
+        .or(NaiveTime::from_hms_opt(PICKED_HOUR, 0, 0))
+        .unwrap_or(NaiveTime::MIN);
+    let local = date.and_time(time);
+    match Local.from_local_datetime(&local).earliest() {
+        Some(datetime) => datetime.to_rfc3339(),
+        None => local.and_utc().to_rfc3339(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_weeks_start_on_monday() {
+        // May 2024 starts on a Wednesday and spans five weeks
+        let picker = DatePicker::new(Some(date(2024, 5, 17)));
+        let weeks = picker.weeks();
+        assert_eq!(weeks.len(), 5);
+        assert_eq!(weeks[0][..2], [None, None]);
+        assert_eq!(weeks[0][2], Some(date(2024, 5, 1)));
+        assert_eq!(weeks[4][4], Some(date(2024, 5, 31)));
+        assert_eq!(weeks[4][5], None);
+    }
+
+    #[test]
+    fn test_move_months_clamps_the_day() {
+        let mut picker = DatePicker::new(Some(date(2024, 1, 31)));
+        picker.move_months(1);
+        assert_eq!(picker.selected, date(2024, 2, 29));
+        picker.move_days(-30);
+        assert_eq!(picker.selected, date(2024, 1, 30));
+    }
+
+    #[test]
+    fn test_parse_date() {
+        // A Friday
+        let today = date(2024, 5, 17);
+        assert_eq!(parse_date("2024-06-01", today), Some(date(2024, 6, 1)));
+        assert_eq!(parse_date("Tomorrow", today), Some(date(2024, 5, 18)));
+        assert_eq!(parse_date("mon", today), Some(date(2024, 5, 20)));
+        assert_eq!(parse_date("friday", today), Some(date(2024, 5, 24)));
+        assert_eq!(parse_date("someday", today), None);
+    }
+
+    #[test]
+    fn test_parse_date_time() {
+        let today = date(2024, 5, 17);
+        let at = |h, m| NaiveTime::from_hms_opt(h, m, 0);
+        assert_eq!(
+            parse_date_time("2024-05-01T09:00", today),
+            Some(TypedDate {
+                date: date(2024, 5, 1),
+                time: at(9, 0)
+            })
+        );
+        assert_eq!(
+            parse_date_time("friday 9:30pm", today),
+            Some(TypedDate {
+                date: date(2024, 5, 24),
+                time: at(21, 30)
+            })
+        );
+        assert_eq!(parse_date_time("today", today).unwrap().time, None);
+        assert_eq!(parse_time("12am"), at(0, 0));
+        assert_eq!(parse_time("17:00"), at(17, 0));
+        assert_eq!(parse_time("13pm"), None);
+        assert_eq!(parse_time("9"), None);
+        assert_eq!(parse_date_time("today 25:00", today), None);
+    }
+}
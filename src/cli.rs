@@ -0,0 +1,115 @@
+use crate::app::ViewKind;
+use crate::state::{task_index_path, TaskIndex};
+use clap::{Parser, Subcommand};
+use clap_complete::engine::{ArgValueCompleter, CompletionCandidate};
+use clap_complete::Shell;
+use std::ffi::OsStr;
+use std::path::PathBuf;
+
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Args {
+    /// Link to a task to open, like https://vikunja.example.com/tasks/42, on a
+    /// configured instance
+    #[arg(conflicts_with_all = ["demo", "share", "task"])]
+    pub link: Option<String>,
+
+    /// Config file to use instead of the default locations
+    #[arg(long, env = "VIKUNJA_TUI_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// Connect with one of the [profiles.<name>] from the config instead of [vikunja]
+    #[arg(long)]
+    pub profile: Option<String>,
+
+    /// Open the task view scoped to this project (its exact title or id)
+    #[arg(long)]
+    pub project: Option<String>,
+
+    /// Apply a quick filter from the config, a built-in filter (overdue, today), or a raw filter query
+    #[arg(long)]
+    pub filter: Option<String>,
+
+    /// Initial view to show
+    #[arg(long, value_enum, default_value_t = ViewKind::List)]
+    pub view: ViewKind,
+
+    /// Open the details of this task id
+    #[arg(long, add = ArgValueCompleter::new(complete_task_id))]
+    pub task: Option<u64>,
+
+    /// Run against built-in sample data instead of a Vikunja instance
+    #[arg(long, conflicts_with = "share")]
+    pub demo: bool,
+
+    /// Open a shared project with a link share hash, or the full share link
+    #[arg(long, conflicts_with = "project")]
+    pub share: Option<String>,
+
+    /// Disable every action that changes data on the server
+    #[arg(long)]
+    pub read_only: bool,
+
+    /// Screen reader friendly output: no box drawing or color, with the current
+    /// selection read out on the status line
+    #[arg(long)]
+    pub plain: bool,
+
+    /// Print the screen as text at this size, like 80x24, instead of taking
+    /// over the terminal
+    #[arg(long, value_name = "COLSxROWS", value_parser = parse_size)]
+    pub render: Option<(u16, u16)>,
+
+    /// Keys to press before --render prints the screen, in vim notation like
+    /// "sWork<Enter>j<C-o>"
+    #[arg(long, requires = "render")]
+    pub keys: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Print a Markdown review of the tasks done and created lately and the
+    /// ones overdue, by project, without opening the interface
+    Report {
+        /// How many days back to look
+        #[arg(long, default_value_t = 7)]
+        days: u32,
+    },
+    /// Add a task written like in the add popup, like "buy milk due:tomorrow",
+    /// or one task per line read from stdin with -
+    Add {
+        /// Create labels that don't exist yet instead of leaving the task out
+        #[arg(long)]
+        create_labels: bool,
+        #[arg(required = true)]
+        words: Vec<String>,
+    },
+    /// Print the shell script that completes the flags and commands, and
+    /// --task with the open tasks seen lately, like
+    /// `source <(vikunja-tui completions bash)` in ~/.bashrc
+    Completions { shell: Shell },
+}
+
+// The open tasks the interface has shown, by id with the title alongside
+fn complete_task_id(current: &OsStr) -> Vec<CompletionCandidate> {
+    let (Some(current), Some(path)) = (current.to_str(), task_index_path()) else {
+        return Vec::new();
+    };
+    TaskIndex::load(&path)
+        .tasks
+        .into_iter()
+        .filter(|task| task.id.to_string().starts_with(current))
+        .map(|task| CompletionCandidate::new(task.id.to_string()).help(Some(task.title.into())))
+        .collect()
+}
+
+fn parse_size(size: &str) -> Result<(u16, u16), String> {
+    let (width, height) = size
+        .split_once('x')
+        .ok_or_else(|| format!("expected COLSxROWS, got '{}'", size))?;
+    let parse = |n: &str| n.trim().parse::<u16>().map_err(|err| err.to_string());
+    Ok((parse(width)?, parse(height)?))
+}
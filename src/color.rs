@@ -0,0 +1,491 @@
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier, Style};
+use serde::{Deserialize, Deserializer};
+use std::env;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+// Steps of the 6x6x6 color cube in the 256 color palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+// The 16 basic colors as xterm shows them, in palette order
+const ANSI_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+// How many colors the terminal can show
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorSupport {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+    // NO_COLOR is set, or the terminal is dumb
+    Monochrome,
+}
+
+impl ColorSupport {
+    fn detect() -> ColorSupport {
+        let var = |name| env::var(name).unwrap_or_default();
+        let term = var("TERM");
+        if !var("NO_COLOR").is_empty() || term == "dumb" {
+            ColorSupport::Monochrome
+        } else if matches!(var("COLORTERM").as_str(), "truecolor" | "24bit")
+            || env::var_os("WT_SESSION").is_some()
+        {
+            ColorSupport::TrueColor
+        } else if term.contains("256") {
+            ColorSupport::Ansi256
+        } else {
+            ColorSupport::Ansi16
+        }
+    }
+}
+
+pub fn color_support() -> ColorSupport {
+    static SUPPORT: OnceLock<ColorSupport> = OnceLock::new();
+    *SUPPORT.get_or_init(ColorSupport::detect)
+}
+
+// Vikunja stores colors as six hex digits, with or without a leading #, and
+// an empty string when none is set
+pub fn parse_hex(hex: &str) -> Option<(u8, u8, u8)> {
+    let hex = hex.trim_start_matches('#');
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    Some(((value >> 16) as u8, (value >> 8) as u8, value as u8))
+}
+
+// A color from the API, reduced to what the terminal supports when the frame
+// is adapted
+pub fn hex_color(hex: &str) -> Option<Color> {
+    parse_hex(hex).map(|(r, g, b)| Color::Rgb(r, g, b))
+}
+
+// The colors the web interface offers for tasks, cycled through in the add
+// form
+pub const TASK_COLORS: &[(&str, &str)] = &[
+    ("yellow", "ffbe0b"),
+    ("orange", "fd8a09"),
+    ("red", "fb5607"),
+    ("pink", "ff006e"),
+    ("lilac", "efbdeb"),
+    ("purple", "8338ec"),
+    ("indigo", "5f33e1"),
+    ("blue", "3a86ff"),
+    ("sky", "4c91ff"),
+    ("green", "0ead69"),
+    ("teal", "25be8b"),
+    ("navy", "073b4c"),
+    ("slate", "373f47"),
+];
+
+// Label badges use the label color as background, with black or white text
+// depending on how light it is. Labels without a color stay yellow.
+pub fn label_style(hex: Option<&str>) -> Style {
+    let Some(rgb) = hex.and_then(parse_hex) else {
+        return Style::default().bg(Color::Yellow).fg(Color::Black);
+    };
+    let (r, g, b) = rgb;
+    let luma = (299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000;
+    let text = if luma > 140 {
+        Color::Black
+    } else {
+        Color::White
+    };
+    Style::default().bg(Color::Rgb(r, g, b)).fg(text)
+}
+
+// Colors from the [theme] table of the config. Urgency follows the web
+// interface, which flags priority 4 (urgent) and 5 (do now).
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Theme {
+    #[serde(deserialize_with = "deserialize_color")]
+    pub urgent: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub do_now: Color,
+    // The selected row, with the done and overdue styles merged over it for
+    // rows that are
+    #[serde(deserialize_with = "deserialize_style")]
+    pub selected: Style,
+    #[serde(deserialize_with = "deserialize_style")]
+    pub selected_done: Style,
+    #[serde(deserialize_with = "deserialize_style")]
+    pub selected_overdue: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            urgent: Color::Rgb(0xff, 0x9f, 0x43),
+            do_now: Color::Rgb(0xff, 0x38, 0x60),
+            selected: Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+            selected_done: Style::default().add_modifier(Modifier::DIM),
+            selected_overdue: Style::default().fg(Color::Red),
+        }
+    }
+}
+
+impl Theme {
+    // Darker shades that stay readable on a light background
+    pub fn light() -> Theme {
+        Theme {
+            urgent: Color::Rgb(0xb3, 0x5c, 0x00),
+            do_now: Color::Rgb(0xc4, 0x00, 0x2f),
+            ..Theme::default()
+        }
+    }
+
+    pub fn selection_style(&self, done: bool, overdue: bool) -> Style {
+        let mut style = self.selected;
+        if done {
+            style = style.patch(self.selected_done);
+        }
+        if overdue {
+            style = style.patch(self.selected_overdue);
+        }
+        style
+    }
+
+    // How a task title shows its priority, None below urgent
+    pub fn priority_style(&self, priority: i32) -> Option<Style> {
+        match priority {
+            4 => Some(Style::default().fg(self.urgent)),
+            p if p >= 5 => Some(
+                Style::default()
+                    .fg(self.do_now)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            _ => None,
+        }
+    }
+}
+
+// Which of the light and dark themes to use, auto asks the terminal
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Background {
+    Auto,
+    Light,
+    Dark,
+}
+
+// `theme` in the config: one [theme] table for any background, or a
+// background to pick from [themes.light] and [themes.dark] by
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum ThemeChoice {
+    Background(Background),
+    Theme(Theme),
+}
+
+impl Default for ThemeChoice {
+    fn default() -> ThemeChoice {
+        ThemeChoice::Theme(Theme::default())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Themes {
+    pub light: Theme,
+    pub dark: Theme,
+}
+
+impl Default for Themes {
+    fn default() -> Themes {
+        Themes {
+            light: Theme::light(),
+            dark: Theme::default(),
+        }
+    }
+}
+
+impl ThemeChoice {
+    // `light_background` is only asked for auto, it may query the terminal
+    pub fn resolve(self, themes: Themes, light_background: impl FnOnce() -> bool) -> Theme {
+        let light = match self {
+            ThemeChoice::Theme(theme) => return theme,
+            ThemeChoice::Background(Background::Light) => true,
+            ThemeChoice::Background(Background::Dark) => false,
+            ThemeChoice::Background(Background::Auto) => light_background(),
+        };
+        if light {
+            themes.light
+        } else {
+            themes.dark
+        }
+    }
+}
+
+// Whether a background color like the terminal's answer to OSC 11,
+// "rgb:ffff/ffff/ffff", is light. Each part has one to four hex digits.
+pub fn is_light_answer(answer: &str) -> Option<bool> {
+    let rgb = answer.split("rgb:").nth(1)?;
+    let rgb = rgb
+        .trim_end_matches(['\x07', '\\'])
+        .trim_end_matches('\x1b');
+    let parts: Vec<f64> = rgb
+        .split('/')
+        .map(|part| {
+            let digits = part.len().clamp(1, 4);
+            let value = u32::from_str_radix(part, 16)
+                .ok()
+                .filter(|_| digits == part.len())?;
+            Some(value as f64 / (16f64.powi(digits as i32) - 1.0))
+        })
+        .collect::<Option<_>>()?;
+    let [r, g, b] = parts[..] else {
+        return None;
+    };
+    Some(0.299 * r + 0.587 * g + 0.114 * b > 0.5)
+}
+
+// A hex color like "#ff9f43", or a name like "red" or "lightblue"
+pub fn parse_color(text: &str) -> Option<Color> {
+    hex_color(text).or_else(|| Color::from_str(text).ok())
+}
+
+// Words like "bold yellow on blue": colors and hex colors for the text, a
+// color after "on" for the background, and modifiers. "none" leaves the row
+// as it is.
+pub fn parse_style(text: &str) -> Option<Style> {
+    let mut style = Style::default();
+    let mut words = text.split_whitespace();
+    while let Some(word) = words.next() {
+        let modifier = match word.to_lowercase().as_str() {
+            "none" => continue,
+            "bold" => Modifier::BOLD,
+            "dim" => Modifier::DIM,
+            "italic" => Modifier::ITALIC,
+            "underlined" => Modifier::UNDERLINED,
+            "reversed" => Modifier::REVERSED,
+            "crossed_out" => Modifier::CROSSED_OUT,
+            "on" => {
+                style = style.bg(parse_color(words.next()?)?);
+                continue;
+            }
+            _ => {
+                style = style.fg(parse_color(word)?);
+                continue;
+            }
+        };
+        style = style.add_modifier(modifier);
+    }
+    Some(style)
+}
+
+fn deserialize_style<'de, D>(deserializer: D) -> Result<Style, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let text = String::deserialize(deserializer)?;
+    parse_style(&text).ok_or_else(|| {
+        serde::de::Error::custom(format!(
+            "'{}' is not a style like \"bold yellow on blue\"",
+            text
+        ))
+    })
+}
+
+fn deserialize_color<'de, D>(deserializer: D) -> Result<Color, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let text = String::deserialize(deserializer)?;
+    parse_color(&text).ok_or_else(|| serde::de::Error::custom(format!("'{}' is not a color", text)))
+}
+
+// Rewrites every cell of a drawn frame to colors the terminal can show. In
+// monochrome, colored text turns bold (dim for dark gray) and colored
+// backgrounds turn into reversed text, so highlights stay visible.
+pub fn adapt_buffer(buffer: &mut Buffer, support: ColorSupport) {
+    if support == ColorSupport::TrueColor {
+        return;
+    }
+    for cell in buffer.content.iter_mut() {
+        if support == ColorSupport::Monochrome {
+            match cell.fg {
+                Color::Reset | Color::White => {}
+                Color::DarkGray => cell.modifier |= Modifier::DIM,
+                _ => cell.modifier |= Modifier::BOLD,
+            }
+            if cell.bg != Color::Reset {
+                cell.modifier |= Modifier::REVERSED;
+            }
+            cell.fg = Color::Reset;
+            cell.bg = Color::Reset;
+        } else {
+            cell.fg = reduce(cell.fg, support);
+            cell.bg = reduce(cell.bg, support);
+        }
+    }
+}
+
+fn reduce(color: Color, support: ColorSupport) -> Color {
+    match (color, support) {
+        (Color::Rgb(r, g, b), ColorSupport::Ansi256) => Color::Indexed(nearest_256((r, g, b))),
+        (Color::Rgb(r, g, b), ColorSupport::Ansi16) => nearest_16((r, g, b)),
+        (Color::Indexed(i), ColorSupport::Ansi16) if i >= 16 => nearest_16(indexed_rgb(i)),
+        _ => color,
+    }
+}
+
+fn nearest_16(rgb: (u8, u8, u8)) -> Color {
+    ANSI_COLORS
+        .iter()
+        .min_by_key(|(_, ansi)| distance(rgb, *ansi))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::Reset)
+}
+
+// The color of a 256 palette entry, from the cube or the grayscale ramp
+fn indexed_rgb(i: u8) -> (u8, u8, u8) {
+    match i {
+        0..=15 => ANSI_COLORS[i as usize].1,
+        16..=231 => {
+            let i = i - 16;
+            (
+                CUBE_LEVELS[(i / 36) as usize],
+                CUBE_LEVELS[(i / 6 % 6) as usize],
+                CUBE_LEVELS[(i % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (i - 232);
+            (level, level, level)
+        }
+    }
+}
+
+// The closest entry of the 256 color palette, either from the color cube or
+// the grayscale ramp
+fn nearest_256(rgb: (u8, u8, u8)) -> u8 {
+    let cube_index = |value: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - value as i32).abs())
+            .unwrap_or(0)
+    };
+    let (ri, gi, bi) = (cube_index(rgb.0), cube_index(rgb.1), cube_index(rgb.2));
+    let cube = (CUBE_LEVELS[ri], CUBE_LEVELS[gi], CUBE_LEVELS[bi]);
+
+    // The ramp runs from 8 to 238 in steps of 10
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let gray_index = (average.saturating_sub(3) / 10).min(23) as u8;
+    let gray_level = 8 + 10 * gray_index;
+
+    if distance(rgb, (gray_level, gray_level, gray_level)) < distance(rgb, cube) {
+        232 + gray_index
+    } else {
+        16 + 36 * ri as u8 + 6 * gi as u8 + bi as u8
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> i32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2);
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex() {
+        assert_eq!(parse_hex("1973ff"), Some((0x19, 0x73, 0xff)));
+        assert_eq!(parse_hex("#E8E8E8"), Some((0xe8, 0xe8, 0xe8)));
+        assert_eq!(parse_hex(""), None);
+        assert_eq!(parse_hex("+12345"), None);
+    }
+
+    #[test]
+    fn test_theme_colors() {
+        let theme: Theme = toml::from_str("urgent = \"yellow\"\ndo_now = \"#E53935\"").unwrap();
+        assert_eq!(theme.urgent, Color::Yellow);
+        assert_eq!(theme.do_now, Color::Rgb(0xe5, 0x39, 0x35));
+        assert_eq!(theme.priority_style(3), None);
+        assert!(toml::from_str::<Theme>("urgent = \"nope\"").is_err());
+    }
+
+    #[test]
+    fn test_selection_styles_merge() {
+        let theme: Theme = toml::from_str(
+            "selected = \"bold white on blue\"\nselected_overdue = \"#ff0000 underlined\"",
+        )
+        .unwrap();
+        let style = theme.selection_style(true, true);
+        assert_eq!(style.fg, Some(Color::Rgb(0xff, 0, 0)));
+        assert_eq!(style.bg, Some(Color::Blue));
+        assert!(style
+            .add_modifier
+            .contains(Modifier::BOLD | Modifier::DIM | Modifier::UNDERLINED));
+        assert_eq!(theme.selection_style(false, false).fg, Some(Color::White));
+        assert_eq!(parse_style("none"), Some(Style::default()));
+        assert_eq!(parse_style("bold on"), None);
+        assert!(toml::from_str::<Theme>("selected = \"blinking\"").is_err());
+    }
+
+    #[test]
+    fn test_light_and_dark_themes() {
+        #[derive(Deserialize)]
+        struct Config {
+            theme: ThemeChoice,
+        }
+        let auto: Config = toml::from_str("theme = \"auto\"").unwrap();
+        let palette: Config = toml::from_str("[theme]\nurgent = \"red\"").unwrap();
+        assert_eq!(auto.theme, ThemeChoice::Background(Background::Auto));
+        assert_eq!(
+            auto.theme.clone().resolve(Themes::default(), || true),
+            Theme::light()
+        );
+        assert_eq!(
+            palette
+                .theme
+                .resolve(Themes::default(), || unreachable!())
+                .urgent,
+            Color::Red
+        );
+
+        assert_eq!(
+            is_light_answer("\x1b]11;rgb:ffff/fefe/f0f0\x1b\\"),
+            Some(true)
+        );
+        assert_eq!(is_light_answer("\x1b]11;rgb:1e/1e/2e\x07"), Some(false));
+        assert_eq!(is_light_answer("\x1b]11;rgb:zz/00/00\x07"), None);
+    }
+
+    #[test]
+    fn test_nearest_256() {
+        assert_eq!(nearest_256((255, 0, 0)), 196);
+        assert_eq!(nearest_256((0, 0, 0)), 16);
+        assert_eq!(nearest_256((128, 128, 128)), 244);
+        assert_eq!(nearest_256((0x19, 0x73, 0xff)), 27);
+    }
+
+    #[test]
+    fn test_reduce_to_16_colors() {
+        let support = ColorSupport::Ansi16;
+        assert_eq!(reduce(Color::Rgb(0xe5, 0x39, 0x35), support), Color::Red);
+        assert_eq!(reduce(Color::Indexed(27), support), Color::LightBlue);
+        assert_eq!(reduce(Color::Yellow, support), Color::Yellow);
+    }
+}
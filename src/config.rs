@@ -0,0 +1,461 @@
+use crate::app::DEFAULT_REVIEW_DAYS;
+use crate::calendar::parse_time;
+use crate::color::{parse_color, ThemeChoice, Themes};
+use crate::ics::CalendarFeed;
+use crate::row_format::{parse_row_format, RowPart};
+use chrono::NaiveTime;
+use dirs::{config_dir, home_dir};
+use ratatui::style::Color;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct VikunjaConfig {
+    pub instance_url: String,
+    #[serde(default)]
+    pub api_key: String,
+    // Command whose output is the API key, to keep it in a password manager
+    #[serde(default)]
+    pub api_key_cmd: Option<String>,
+    // Key of the OpenID Connect provider to sign in with instead of an API key
+    #[serde(default)]
+    pub oidc_provider: Option<String>,
+}
+
+impl VikunjaConfig {
+    // The configured API key, or the output of api_key_cmd when that is set
+    pub fn resolve_api_key(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(command) = &self.api_key_cmd else {
+            return Ok(self.api_key.clone());
+        };
+
+        let output = if cfg!(windows) {
+            Command::new("cmd").args(["/C", command]).output()
+        } else {
+            Command::new("sh").args(["-c", command]).output()
+        }
+        .map_err(|err| format!("Failed to run api_key_cmd '{}': {}", command, err))?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "api_key_cmd '{}' failed: {}",
+                command,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into());
+        }
+        // Password managers print the secret followed by a newline
+        let api_key = String::from_utf8(output.stdout)?.trim().to_string();
+        if api_key.is_empty() {
+            return Err(format!("api_key_cmd '{}' printed nothing", command).into());
+        }
+        Ok(api_key)
+    }
+}
+
+// Name of the profile defined by the [vikunja] table
+pub const DEFAULT_PROFILE: &str = "default";
+
+// An instance and the credentials for it. [vikunja] is the default profile and
+// [profiles.<name>] tables add more, to switch between at runtime.
+#[derive(Clone, Debug)]
+pub struct Profile {
+    pub name: String,
+    pub vikunja: VikunjaConfig,
+}
+
+// Overrides applied while a project is the active view. The config key is the
+// project title or id.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ProjectSettings {
+    pub show_done: Option<bool>,
+    // Field to sort by, optionally followed by asc or desc
+    pub sort: Option<String>,
+    // Labels added to tasks created while the project is active
+    pub labels: Vec<String>,
+    // Bucket the board starts on
+    pub bucket: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuickFilter {
+    pub name: String,
+    pub filter: String,
+}
+
+// Filters available by name without any configuration
+pub const BUILTIN_FILTERS: &[(&str, &str)] = &[
+    ("overdue", "due_date < now && done = false"),
+    ("today", "due_date < now/d+1d && done = false"),
+];
+
+// The built-in filter on the signed-in user's tasks, which needs to know who
+// that is first
+pub const MINE_FILTER: &str = "mine";
+
+#[derive(Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub read_only: bool,
+    // Ask before a board move marks a task done or undone
+    #[serde(default)]
+    pub confirm_done_bucket: bool,
+    // Show when done tasks were completed in the task list
+    #[serde(default)]
+    pub show_done_at: bool,
+    // Show the current view in the terminal's window title
+    #[serde(default = "enabled")]
+    pub terminal_title: bool,
+    // Show what quick add will create and wait for Enter before creating it
+    #[serde(default = "enabled")]
+    pub confirm_new_task: bool,
+    // Time of day for due dates given without one, like "17:00" or "5pm"
+    #[serde(default, deserialize_with = "deserialize_time")]
+    pub default_due_time: Option<NaiveTime>,
+    // How many days back the done review (`D`) lists completed tasks
+    #[serde(default = "review_days")]
+    pub done_review_days: u32,
+    // Color of labels created from quick add, like "#e8e8e8". Without it each
+    // gets one of the task colors.
+    #[serde(default, deserialize_with = "deserialize_label_color")]
+    pub label_color: Option<String>,
+    // Template of the list rows, like "{identifier} {title} {due:%m-%d}"
+    #[serde(default, deserialize_with = "deserialize_row_format")]
+    pub row_format: Option<Vec<RowPart>>,
+    // CSV file each stopped timer adds a row to, besides the comment
+    #[serde(default)]
+    pub timesheet: Option<PathBuf>,
+    #[serde(default)]
+    pub theme: ThemeChoice,
+    #[serde(default)]
+    pub themes: Themes,
+    pub vikunja: VikunjaConfig,
+    // Kept in the order they appear in the file, so the first nine map to 1-9
+    #[serde(default, deserialize_with = "deserialize_filters")]
+    pub filters: Vec<QuickFilter>,
+    #[serde(default, deserialize_with = "deserialize_profiles")]
+    pub profiles: Vec<Profile>,
+    #[serde(default)]
+    pub projects: HashMap<String, ProjectSettings>,
+    // iCal feeds whose events the date picker shows, read-only
+    #[serde(default)]
+    pub calendars: Vec<CalendarFeed>,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+fn review_days() -> u32 {
+    DEFAULT_REVIEW_DAYS
+}
+
+fn deserialize_profiles<'de, D>(deserializer: D) -> Result<Vec<Profile>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let table = toml::value::Table::deserialize(deserializer)?;
+    table
+        .into_iter()
+        .map(|(name, value)| {
+            let vikunja = value
+                .try_into::<VikunjaConfig>()
+                .map_err(|err| serde::de::Error::custom(format!("profile '{}': {}", name, err)))?;
+            Ok(Profile { name, vikunja })
+        })
+        .collect()
+}
+
+fn deserialize_time<'de, D>(deserializer: D) -> Result<Option<NaiveTime>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let text = String::deserialize(deserializer)?;
+    parse_time(&text)
+        .map(Some)
+        .ok_or_else(|| serde::de::Error::custom(format!("'{}' is not a time like \"17:00\"", text)))
+}
+
+// Kept as the six hex digits the server takes
+fn deserialize_label_color<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let text = String::deserialize(deserializer)?;
+    match parse_color(&text) {
+        Some(Color::Rgb(r, g, b)) => Ok(Some(format!("{:02x}{:02x}{:02x}", r, g, b))),
+        _ => Err(serde::de::Error::custom(format!(
+            "label_color: '{}' is not a hex color like \"#e8e8e8\"",
+            text
+        ))),
+    }
+}
+
+fn deserialize_row_format<'de, D>(deserializer: D) -> Result<Option<Vec<RowPart>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let template = String::deserialize(deserializer)?;
+    parse_row_format(&template)
+        .map(Some)
+        .map_err(|err| serde::de::Error::custom(format!("row_format: {}", err)))
+}
+
+fn deserialize_filters<'de, D>(deserializer: D) -> Result<Vec<QuickFilter>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let table = toml::value::Table::deserialize(deserializer)?;
+    table
+        .into_iter()
+        .map(|(name, value)| match value {
+            toml::Value::String(filter) => Ok(QuickFilter { name, filter }),
+            _ => Err(serde::de::Error::custom(format!(
+                "filter '{}' must be a string",
+                name
+            ))),
+        })
+        .collect()
+}
+
+impl Config {
+    // Default settings for an instance, when there is no config file
+    pub fn for_instance(instance_url: &str) -> Config {
+        Config {
+            read_only: false,
+            confirm_done_bucket: false,
+            show_done_at: false,
+            terminal_title: true,
+            confirm_new_task: true,
+            default_due_time: None,
+            done_review_days: DEFAULT_REVIEW_DAYS,
+            label_color: None,
+            timesheet: None,
+            row_format: None,
+            theme: ThemeChoice::default(),
+            themes: Themes::default(),
+            vikunja: VikunjaConfig {
+                instance_url: instance_url.to_string(),
+                api_key: String::new(),
+                api_key_cmd: None,
+                oidc_provider: None,
+            },
+            filters: Vec::new(),
+            profiles: Vec::new(),
+            projects: HashMap::new(),
+            calendars: Vec::new(),
+        }
+    }
+
+    // Every profile, the default one first
+    pub fn all_profiles(&self) -> Vec<Profile> {
+        let mut profiles = vec![Profile {
+            name: DEFAULT_PROFILE.to_string(),
+            vikunja: self.vikunja.clone(),
+        }];
+        profiles.extend(self.profiles.iter().cloned());
+        profiles
+    }
+
+    // Settings used by demo mode in place of a config file
+    pub fn demo() -> Config {
+        Config {
+            show_done_at: true,
+            filters: vec![QuickFilter {
+                name: "urgent".to_string(),
+                filter: "priority >= 4".to_string(),
+            }],
+            projects: HashMap::from([(
+                "Work".to_string(),
+                ProjectSettings {
+                    sort: Some("priority desc".to_string()),
+                    labels: vec!["code".to_string()],
+                    bucket: Some("In Progress".to_string()),
+                    ..Default::default()
+                },
+            )]),
+            ..Config::for_instance("https://demo.invalid")
+        }
+    }
+}
+
+// Where the config file is looked for, in order. An explicit path is the only
+// candidate. Otherwise the platform config directory is tried (~/.config on
+// Linux, ~/Library/Application Support on macOS, %APPDATA% on Windows), and
+// then ~/.config, which many people use on macOS as well.
+pub fn config_paths(explicit: Option<&Path>) -> Vec<PathBuf> {
+    if let Some(path) = explicit {
+        return vec![path.to_path_buf()];
+    }
+
+    let mut paths = Vec::new();
+    let dirs = [config_dir(), home_dir().map(|home| home.join(".config"))];
+    for dir in dirs.into_iter().flatten() {
+        let path = dir.join("vikunja-tui").join("config.toml");
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    paths
+}
+
+// Returned by load_config when there is no file in any of the places it
+// looks, which modes that can do without a config tell from a broken one
+#[derive(Debug)]
+pub struct NoConfigFile {
+    tried: Vec<PathBuf>,
+}
+
+impl fmt::Display for NoConfigFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "No config file found, tried:")?;
+        for path in &self.tried {
+            write!(f, "\n  {}", path.display())?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for NoConfigFile {}
+
+pub fn load_config(
+    explicit: Option<&Path>,
+) -> Result<Config, Box<dyn std::error::Error + Send + Sync>> {
+    let paths = config_paths(explicit);
+    let Some(config_path) = paths.iter().find(|path| path.is_file()) else {
+        return Err(NoConfigFile { tried: paths }.into());
+    };
+
+    // Read the config file
+    let config_content = fs::read_to_string(config_path)?;
+
+    // Parse the TOML content
+    let config: Config = toml::from_str(&config_content)
+        .map_err(|err| format!("Invalid config file {}: {}", config_path.display(), err))?;
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filters_keep_file_order() {
+        let input = r#"
+            [vikunja]
+            instance_url = "https://example.com"
+            api_key = "key"
+
+            [filters]
+            work = "project in 3, 4 && done = false"
+            errands = "labels in 2"
+        "#;
+        let config: Config = toml::from_str(input).unwrap();
+        let names: Vec<&str> = config.filters.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["work", "errands"]);
+        assert_eq!(config.filters[0].filter, "project in 3, 4 && done = false");
+    }
+
+    #[test]
+    fn test_explicit_config_path_is_the_only_candidate() {
+        let path = Path::new("/tmp/vikunja.toml");
+        assert_eq!(config_paths(Some(path)), vec![path.to_path_buf()]);
+        assert!(!config_paths(None).is_empty());
+    }
+
+    #[test]
+    fn test_project_settings() {
+        let input = r#"
+            [vikunja]
+            instance_url = "https://example.com"
+            api_key = "key"
+
+            [projects.Groceries]
+            show_done = true
+            labels = ["errands"]
+
+            [projects.4]
+            sort = "due_date desc"
+        "#;
+        let config: Config = toml::from_str(input).unwrap();
+        assert_eq!(config.projects["Groceries"].show_done, Some(true));
+        assert_eq!(config.projects["Groceries"].labels, vec!["errands"]);
+        assert_eq!(config.projects["4"].sort.as_deref(), Some("due_date desc"));
+    }
+
+    #[test]
+    fn test_profiles_follow_the_default_one() {
+        let input = r#"
+            [vikunja]
+            instance_url = "https://home.example.com"
+            api_key = "key"
+
+            [profiles.work]
+            instance_url = "https://work.example.com"
+            api_key_cmd = "pass show work"
+        "#;
+        let config: Config = toml::from_str(input).unwrap();
+        let profiles = config.all_profiles();
+        let names: Vec<&str> = profiles.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec![DEFAULT_PROFILE, "work"]);
+        assert_eq!(profiles[1].vikunja.instance_url, "https://work.example.com");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_api_key_cmd_output_is_used() {
+        let input = r#"
+            [vikunja]
+            instance_url = "https://example.com"
+            api_key_cmd = "printf 'secret\n'"
+        "#;
+        let config: Config = toml::from_str(input).unwrap();
+        assert_eq!(config.vikunja.resolve_api_key().unwrap(), "secret");
+    }
+
+    #[test]
+    fn test_filters_are_optional() {
+        let input = r#"
+            [vikunja]
+            instance_url = "https://example.com"
+            api_key = "key"
+        "#;
+        let config: Config = toml::from_str(input).unwrap();
+        assert!(config.filters.is_empty());
+    }
+
+    #[test]
+    fn test_label_color_must_be_a_hex_color() {
+        let input = |color: &str| {
+            format!(
+                "label_color = \"{}\"\n[vikunja]\ninstance_url = \"https://example.com\"\n",
+                color
+            )
+        };
+        let config: Config = toml::from_str(&input("#E8E8E8")).unwrap();
+        assert_eq!(config.label_color.as_deref(), Some("e8e8e8"));
+        assert!(toml::from_str::<Config>(&input("e8e8")).is_err());
+        assert!(toml::from_str::<Config>(&input("red")).is_err());
+    }
+
+    #[test]
+    fn test_missing_config_is_told_from_a_broken_one() {
+        let dir = std::env::temp_dir().join(format!("vikunja-tui-config-{}", std::process::id()));
+        let path = dir.join("config.toml");
+        let missing = load_config(Some(&path));
+        assert!(missing.is_err_and(|err| err.is::<NoConfigFile>()));
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, "[vikunja\n").unwrap();
+        let broken = load_config(Some(&path));
+        assert!(broken.is_err_and(|err| !err.is::<NoConfigFile>()));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,1177 @@
+use async_trait::async_trait;
+use serde_json::json;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use vikunja_client::api::{ApiResult, NewTask, ReactionTarget, TaskPage, TaskQuery, VikunjaApi};
+use vikunja_client::models::{
+    Attachment, AttachmentFile, Bucket, Comment, Label, Notification, Project, ProjectTeam,
+    ProjectUser, ProjectView, Reactions, Reminder, ServerInfo, Task, Team, TeamMember, User,
+};
+
+const PAGE_SIZE: usize = 50;
+const INBOX_PROJECT_ID: u64 = 1;
+const LIST_VIEW_ID: u64 = 9;
+const KANBAN_VIEW_ID: u64 = 10;
+// Label ids are the position in this list plus one
+const LABELS: &[(&str, &str)] = &[
+    ("errands", "f5a623"),
+    ("home", "4caf50"),
+    ("reporting", "9013fe"),
+    ("code", "1973ff"),
+    ("bug", "e53935"),
+    ("docs", ""),
+];
+
+// User ids are the position in this list plus one
+const USERS: &[(&str, &str)] = &[
+    ("alex", "Alex Kim"),
+    ("sam", "Sam Rivera"),
+    ("demo", "Demo User"),
+];
+// The teams the demo user is in and their members, the first one an admin
+const TEAMS: &[(&str, &[&str])] = &[
+    ("Design", &["demo", "alex"]),
+    ("Engineering", &["demo", "alex", "sam"]),
+];
+// Who the demo is signed in as
+const CURRENT_USER: &str = "demo";
+
+#[derive(Clone, Default)]
+struct DemoTask {
+    id: u64,
+    title: String,
+    done: bool,
+    done_at: Option<String>,
+    project_id: u64,
+    bucket: usize,
+    position: f64,
+    priority: i32,
+    due_date: Option<String>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    reminders: Vec<String>,
+    // Seconds between occurrences
+    repeat_after: i64,
+    labels: Vec<String>,
+    assignees: Vec<&'static str>,
+    // Ids of the tasks that have to be done first
+    blocked_by: Vec<u64>,
+    // Id of the task this is a subtask of
+    parent: Option<u64>,
+    description: String,
+    hex_color: String,
+    // File names
+    attachments: Vec<&'static str>,
+    // Counts changes, for the updated timestamp
+    revision: u32,
+}
+
+// The Inbox has none, its tasks go by #index like on a real instance
+fn project_identifier(project_id: u64) -> &'static str {
+    match project_id {
+        2 => "WRK",
+        3 => "GRC",
+        _ => "",
+    }
+}
+
+impl DemoTask {
+    // Tasks are numbered per project in the order they were created
+    fn index(&self, tasks: &[DemoTask]) -> u64 {
+        tasks
+            .iter()
+            .filter(|task| task.project_id == self.project_id && task.id <= self.id)
+            .count() as u64
+    }
+
+    fn identifier(&self, tasks: &[DemoTask]) -> String {
+        match project_identifier(self.project_id) {
+            "" => format!("#{}", self.index(tasks)),
+            prefix => format!("{}-{}", prefix, self.index(tasks)),
+        }
+    }
+
+    // Titles containing the text, or the task with that `#index`
+    fn matches_search(&self, tasks: &[DemoTask], search: &str) -> bool {
+        match search.strip_prefix('#').and_then(|i| i.parse::<u64>().ok()) {
+            Some(index) => self.index(tasks) == index,
+            None => self.title.to_lowercase().contains(&search.to_lowercase()),
+        }
+    }
+
+    // Done on a repeating task moves its due date on instead, like Vikunja
+    // does, and puts it back in the first bucket
+    fn complete(&mut self) {
+        if self.repeat_after == 0 {
+            self.done = true;
+            self.done_at = Some(chrono::Utc::now().to_rfc3339());
+            return;
+        }
+        self.due_date = self.due_date.as_deref().and_then(|due| {
+            let due = chrono::DateTime::parse_from_rfc3339(due).ok()?;
+            let next = due + chrono::Duration::seconds(self.repeat_after);
+            Some(next.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+        });
+        self.bucket = 0;
+    }
+
+    fn to_task(&self, data: &DemoData) -> Task {
+        let tasks = &data.tasks;
+        let related = |ids: Vec<u64>| -> Vec<Task> {
+            tasks
+                .iter()
+                .filter(|task| ids.contains(&task.id))
+                .map(|task| Task {
+                    id: task.id,
+                    title: task.title.clone(),
+                    done: task.done,
+                    project_id: task.project_id,
+                    identifier: task.identifier(tasks),
+                    ..Default::default()
+                })
+                .collect()
+        };
+        let blocking: Vec<u64> = tasks
+            .iter()
+            .filter(|task| task.blocked_by.contains(&self.id))
+            .map(|task| task.id)
+            .collect();
+        let mut related_tasks = HashMap::new();
+        if !self.blocked_by.is_empty() {
+            related_tasks.insert("blocked".to_string(), related(self.blocked_by.clone()));
+        }
+        if !blocking.is_empty() {
+            related_tasks.insert("blocking".to_string(), related(blocking));
+        }
+        let subtasks: Vec<u64> = tasks
+            .iter()
+            .filter(|task| task.parent == Some(self.id))
+            .map(|task| task.id)
+            .collect();
+        if !subtasks.is_empty() {
+            related_tasks.insert("subtask".to_string(), related(subtasks));
+        }
+        if let Some(parent) = self.parent {
+            related_tasks.insert("parenttask".to_string(), related(vec![parent]));
+        }
+
+        Task {
+            id: self.id,
+            title: self.title.clone(),
+            done: self.done,
+            done_at: Some(
+                self.done_at
+                    .clone()
+                    .unwrap_or_else(|| "0001-01-01T00:00:00Z".to_string()),
+            ),
+            due_date: Some(
+                self.due_date
+                    .clone()
+                    .unwrap_or_else(|| "0001-01-01T00:00:00Z".to_string()),
+            ),
+            start_date: Some(
+                self.start_date
+                    .clone()
+                    .unwrap_or_else(|| "0001-01-01T00:00:00Z".to_string()),
+            ),
+            end_date: Some(
+                self.end_date
+                    .clone()
+                    .unwrap_or_else(|| "0001-01-01T00:00:00Z".to_string()),
+            ),
+            reminders: Some(
+                self.reminders
+                    .iter()
+                    .map(|reminder| Reminder {
+                        reminder: Some(reminder.clone()),
+                        ..Default::default()
+                    })
+                    .collect(),
+            ),
+            labels: Some(
+                self.labels
+                    .iter()
+                    .filter_map(|title| data.labels.iter().find(|l| l.title == *title))
+                    .cloned()
+                    .collect(),
+            ),
+            assignees: Some(
+                self.assignees
+                    .iter()
+                    .filter_map(|username| user(username))
+                    .collect(),
+            ),
+            priority: Some(self.priority),
+            description: Some(if self.description.is_empty() {
+                "<p></p>".to_string()
+            } else {
+                self.description.clone()
+            }),
+            hex_color: Some(self.hex_color.clone()),
+            repeat_after: self.repeat_after,
+            attachments: Some(
+                self.attachments
+                    .iter()
+                    .enumerate()
+                    .map(|(i, name)| Attachment {
+                        id: self.id * 100 + i as u64,
+                        task_id: self.id,
+                        file: AttachmentFile {
+                            name: name.to_string(),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+                    .collect(),
+            ),
+            comment_count: Some(
+                data.comments
+                    .iter()
+                    .filter(|(task, _, _)| *task == self.id)
+                    .count() as u64,
+            ),
+            identifier: self.identifier(tasks),
+            index: self.index(tasks),
+            project_id: self.project_id,
+            bucket_id: self.bucket as u64 + 1,
+            position: self.position,
+            related_tasks: Some(related_tasks),
+            updated: Some(format!(
+                "2024-05-01T10:{:02}:{:02}Z",
+                self.revision / 60 % 60,
+                self.revision % 60
+            )),
+            ..Default::default()
+        }
+    }
+
+    // Orders by one of the fields the demo data has, unknown fields keep the
+    // original order
+    fn compare(&self, other: &DemoTask, field: &str) -> Ordering {
+        match field {
+            "title" => self.title.cmp(&other.title),
+            "priority" => self.priority.cmp(&other.priority),
+            "done" => self.done.cmp(&other.done),
+            "due_date" => self.due_date.cmp(&other.due_date),
+            "done_at" => self.done_at.cmp(&other.done_at),
+            "id" => self.id.cmp(&other.id),
+            "position" => self.position.total_cmp(&other.position),
+            _ => Ordering::Equal,
+        }
+    }
+
+    fn field(&self, name: &str) -> Option<i64> {
+        match name {
+            "project" | "project_id" => Some(self.project_id as i64),
+            "done" => Some(self.done as i64),
+            "priority" => Some(self.priority as i64),
+            _ => None,
+        }
+    }
+}
+
+fn user(username: &str) -> Option<User> {
+    let i = USERS.iter().position(|(u, _)| *u == username)?;
+    Some(User {
+        id: i as u64 + 1,
+        username: username.to_string(),
+        name: USERS[i].1.to_string(),
+    })
+}
+
+fn label(title: &str) -> Option<Label> {
+    let i = LABELS.iter().position(|(l, _)| *l == title)?;
+    let hex_color = LABELS[i].1;
+    Some(Label {
+        id: i as u64 + 1,
+        title: title.to_string(),
+        hex_color: (!hex_color.is_empty()).then(|| hex_color.to_string()),
+        ..Default::default()
+    })
+}
+
+// Team ids are the position in the list plus one
+fn team(i: usize, name: &str, usernames: &[&str]) -> Team {
+    let members = usernames
+        .iter()
+        .enumerate()
+        .filter_map(|(j, username)| {
+            Some(TeamMember {
+                user: user(username)?,
+                admin: j == 0,
+            })
+        })
+        .collect();
+    Team {
+        id: i as u64 + 1,
+        name: name.to_string(),
+        members: Some(members),
+        ..Default::default()
+    }
+}
+
+struct DemoData {
+    projects: Vec<Project>,
+    labels: Vec<Label>,
+    buckets: Vec<&'static str>,
+    tasks: Vec<DemoTask>,
+    notifications: Vec<Notification>,
+    // Task id, author and text
+    comments: Vec<(u64, &'static str, String)>,
+    // What was reacted to, the emoji and who reacted
+    reactions: Vec<(ReactionTarget, String, &'static str)>,
+    // Project id, username or team id and the permission
+    user_shares: Vec<(u64, String, u8)>,
+    team_shares: Vec<(u64, u64, u8)>,
+    teams: Vec<Team>,
+}
+
+impl DemoData {
+    fn reactions(&self, target: ReactionTarget) -> Reactions {
+        let mut reactions = Reactions::new();
+        for (reacted_to, value, username) in &self.reactions {
+            if *reacted_to == target {
+                let users: &mut Vec<User> = reactions.entry(value.clone()).or_default();
+                users.extend(user(username));
+            }
+        }
+        reactions
+    }
+}
+
+// In-process stand-in for a Vikunja instance with fixed sample data, so the
+// interface can be tried out and rendered deterministically without a server
+pub struct DemoApi {
+    data: Mutex<DemoData>,
+}
+
+impl DemoApi {
+    pub fn new() -> DemoApi {
+        let projects = vec![
+            Project {
+                id: INBOX_PROJECT_ID,
+                title: "Inbox".to_string(),
+                ..Default::default()
+            },
+            Project {
+                id: 2,
+                title: "Work".to_string(),
+                description: "<p>Everything for the day job.</p>".to_string(),
+                hex_color: "1973ff".to_string(),
+                identifier: project_identifier(2).to_string(),
+            },
+            Project {
+                id: 3,
+                title: "Groceries".to_string(),
+                description: "<p>Shared with the household, check before buying.</p>".to_string(),
+                hex_color: "4caf50".to_string(),
+                identifier: project_identifier(3).to_string(),
+            },
+        ];
+
+        let tasks = vec![
+            DemoTask {
+                project_id: 1,
+                title: "Renew passport".to_string(),
+                priority: 3,
+                due_date: Some("2024-05-10T17:00:00Z".to_string()),
+                labels: vec!["errands".into()],
+                description: "<p>Bring the old passport and two photos.</p>".to_string(),
+                ..Default::default()
+            },
+            DemoTask {
+                project_id: 1,
+                title: "Call the dentist".to_string(),
+                priority: 2,
+                ..Default::default()
+            },
+            DemoTask {
+                project_id: 1,
+                title: "Back up the laptop".to_string(),
+                done: true,
+                done_at: Some("2024-04-27T09:15:00Z".to_string()),
+                bucket: 2,
+                priority: 1,
+                labels: vec!["home".into()],
+                ..Default::default()
+            },
+            DemoTask {
+                project_id: 2,
+                title: "Write quarterly report".to_string(),
+                bucket: 1,
+                priority: 4,
+                due_date: Some("2024-05-03T12:00:00Z".to_string()),
+                labels: vec!["reporting".into()],
+                assignees: vec!["alex"],
+                hex_color: "3a86ff".to_string(),
+                attachments: vec!["q1-figures.xlsx", "hiring-plan.pdf"],
+                description: concat!(
+                    "<p>Sections: <strong>revenue</strong>, hiring, roadmap.</p>",
+                    "<table><tbody>",
+                    "<tr><th><p>Quarter</p></th><th><p>Revenue</p></th><th><p>Hires</p></th></tr>",
+                    "<tr><td><p>Q1</p></td><td><p>1.2M</p></td><td><p>3</p></td></tr>",
+                    "<tr><td><p>Q2</p></td><td><p>1.5M, ahead of the plan</p></td><td><p>5</p></td></tr>",
+                    "</tbody></table>",
+                )
+                .to_string(),
+                ..Default::default()
+            },
+            DemoTask {
+                project_id: 2,
+                title: "Review pull requests".to_string(),
+                priority: 3,
+                labels: vec!["code".into()],
+                description: concat!(
+                    "<ul><li>API client</li><li>Parser tests</li></ul>",
+                    "<p>From the <a href=\"https://example.com/pulls\">review queue</a>, ",
+                    "CI at https://ci.example.com/builds.</p>",
+                )
+                .to_string(),
+                ..Default::default()
+            },
+            DemoTask {
+                project_id: 2,
+                title: "Fix login timeout bug".to_string(),
+                bucket: 1,
+                priority: 5,
+                due_date: Some("2024-05-01T09:00:00Z".to_string()),
+                labels: vec!["bug".into(), "code".into()],
+                assignees: vec!["sam"],
+                description: concat!(
+                    "<p>Sessions expire after 5 minutes instead of 30.</p>",
+                    "<pre><code class=\"language-rust\">",
+                    "const SESSION_TIMEOUT: Duration = Duration::from_secs(5 * 60); // should be 30 minutes\n",
+                    "fn refresh(session: &amp;mut Session) {\n",
+                    "    session.expires_at = Instant::now() + SESSION_TIMEOUT;\n",
+                    "}</code></pre>",
+                )
+                .to_string(),
+                ..Default::default()
+            },
+            DemoTask {
+                project_id: 2,
+                title: "Plan team offsite".to_string(),
+                priority: 2,
+                // Waits for the quarterly report
+                blocked_by: vec![4],
+                due_date: Some("2024-06-14T17:00:00Z".to_string()),
+                start_date: Some("2024-06-12T09:00:00Z".to_string()),
+                end_date: Some("2024-06-14T17:00:00Z".to_string()),
+                ..Default::default()
+            },
+            DemoTask {
+                project_id: 2,
+                title: "Update onboarding docs".to_string(),
+                done: true,
+                done_at: Some("2024-04-29T16:40:00Z".to_string()),
+                bucket: 2,
+                priority: 1,
+                labels: vec!["docs".into()],
+                description: "<p>Done in the wiki.</p>".to_string(),
+                ..Default::default()
+            },
+            DemoTask {
+                project_id: 3,
+                title: "Milk".to_string(),
+                parent: Some(12),
+                ..Default::default()
+            },
+            DemoTask {
+                project_id: 3,
+                title: "Coffee beans".to_string(),
+                description: "<p>The light roast.</p>".to_string(),
+                parent: Some(12),
+                ..Default::default()
+            },
+            DemoTask {
+                project_id: 3,
+                title: "Bread".to_string(),
+                done: true,
+                done_at: Some("2024-04-30T18:05:00Z".to_string()),
+                bucket: 2,
+                parent: Some(12),
+                ..Default::default()
+            },
+            // The routine lives in the Inbox, what to buy in Groceries
+            DemoTask {
+                project_id: 1,
+                title: "Weekly shop".to_string(),
+                due_date: Some("2024-05-04T10:00:00Z".to_string()),
+                repeat_after: 7 * 24 * 3600,
+                ..Default::default()
+            },
+        ];
+        // Ids follow the order above, like a freshly seeded instance
+        let tasks = tasks
+            .into_iter()
+            .enumerate()
+            .map(|(i, task)| DemoTask {
+                id: i as u64 + 1,
+                position: (i + 1) as f64 * 65536.0,
+                ..task
+            })
+            .collect();
+
+        let notification =
+            |id: u64, name: &str, notification, read_at: Option<&str>| Notification {
+                id,
+                name: name.to_string(),
+                notification,
+                read_at: read_at.map(str::to_string),
+                created: Some("2024-05-01T08:00:00Z".to_string()),
+            };
+        let notifications = vec![
+            notification(
+                1,
+                "task.assigned",
+                json!({
+                    "doer": {"username": "sam", "name": "Sam Rivera"},
+                    "assignee": {"username": "demo", "name": "you"},
+                    "task": {"id": 6, "title": "Fix login timeout bug"}
+                }),
+                None,
+            ),
+            notification(
+                2,
+                "task.comment",
+                json!({
+                    "doer": {"username": "alex", "name": "Alex Kim"},
+                    "task": {"id": 4, "title": "Write quarterly report"},
+                    "comment": {"comment": "<p>Hiring numbers are in.</p>"}
+                }),
+                None,
+            ),
+            notification(
+                3,
+                "task.reminder",
+                json!({"task": {"id": 1, "title": "Renew passport"}}),
+                Some("2024-04-30T09:00:00Z"),
+            ),
+        ];
+
+        DemoApi {
+            data: Mutex::new(DemoData {
+                projects,
+                labels: LABELS
+                    .iter()
+                    .filter_map(|(title, _)| label(title))
+                    .collect(),
+                buckets: vec!["To Do", "In Progress", "Done"],
+                tasks,
+                notifications,
+                comments: vec![
+                    (4, "alex", "<p>Hiring numbers are in.</p>".to_string()),
+                    (
+                        4,
+                        "sam",
+                        "<p>Roadmap section needs the Q3 dates.</p>".to_string(),
+                    ),
+                ],
+                reactions: vec![
+                    (ReactionTarget::Task(4), "🎉".to_string(), "sam"),
+                    (ReactionTarget::Comment(1), "👍".to_string(), "sam"),
+                    (ReactionTarget::Comment(1), "👍".to_string(), "demo"),
+                ],
+                user_shares: vec![(2, "alex".to_string(), 1), (2, "sam".to_string(), 0)],
+                team_shares: vec![(2, 2, 2)],
+                teams: TEAMS
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (name, usernames))| team(i, name, usernames))
+                    .collect(),
+            }),
+        }
+    }
+}
+
+// Supports the subset of the filter syntax the app itself generates:
+// `field op value` clauses on project, done and priority joined with `&&`.
+// Unknown clauses are ignored rather than rejected.
+fn matches_filter(task: &DemoTask, filter: &str) -> bool {
+    filter.split("&&").all(|clause| {
+        let clause = clause.trim().trim_start_matches('(').trim_end_matches(')');
+        // Assignees are given by id or username
+        if let Some(users) = clause.strip_prefix("assignees in ") {
+            return users.split(',').map(str::trim).any(|wanted| {
+                task.assignees.iter().any(|username| {
+                    *username == wanted
+                        || user(username).is_some_and(|u| u.id.to_string() == wanted)
+                })
+            });
+        }
+        for op in ["!=", ">=", "<=", "=", ">", "<"] {
+            if let Some((field, value)) = clause.split_once(op) {
+                let value = match value.trim() {
+                    "true" => 1,
+                    "false" => 0,
+                    v => match v.parse::<i64>() {
+                        Ok(v) => v,
+                        Err(_) => return true,
+                    },
+                };
+                let Some(actual) = task.field(field.trim()) else {
+                    return true;
+                };
+                return match op {
+                    "!=" => actual != value,
+                    ">=" => actual >= value,
+                    "<=" => actual <= value,
+                    "=" => actual == value,
+                    ">" => actual > value,
+                    _ => actual < value,
+                };
+            }
+        }
+        true
+    })
+}
+
+#[async_trait]
+impl VikunjaApi for DemoApi {
+    async fn fetch_info(&self) -> ApiResult<ServerInfo> {
+        Ok(ServerInfo {
+            version: "v0.24.6".to_string(),
+            ..Default::default()
+        })
+    }
+
+    async fn fetch_task_page(&self, page: usize, query: &TaskQuery) -> ApiResult<TaskPage> {
+        let data = self.data.lock().unwrap();
+        let mut matching: Vec<&DemoTask> = data
+            .tasks
+            .iter()
+            .filter(|task| {
+                query
+                    .filter
+                    .as_deref()
+                    .is_none_or(|f| matches_filter(task, f))
+                    && query
+                        .search
+                        .as_deref()
+                        .is_none_or(|s| task.matches_search(&data.tasks, s))
+            })
+            .collect();
+        // One position per task serves both views here, views keep their
+        // own on a real instance
+        let sort = match (&query.sort, query.view) {
+            (Some(sort), _) => Some(sort.as_str()),
+            (None, Some(_)) => Some("position"),
+            (None, None) => None,
+        };
+        if let Some(sort) = sort {
+            let mut parts = sort.split_whitespace();
+            let field = parts.next().unwrap_or_default();
+            let descending = parts.next() == Some("desc");
+            matching.sort_by(|a, b| {
+                let ordering = a.compare(b, field);
+                if descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            });
+        }
+        let on_page = matching
+            .len()
+            .saturating_sub(page.saturating_sub(1) * PAGE_SIZE)
+            .min(PAGE_SIZE);
+        Ok(TaskPage {
+            total_pages: matching.len().div_ceil(PAGE_SIZE).max(1),
+            result_count: on_page,
+            tasks: matching
+                .into_iter()
+                .skip(page.saturating_sub(1) * PAGE_SIZE)
+                .take(PAGE_SIZE)
+                .map(|task| task.to_task(&data))
+                .collect(),
+            skipped: 0,
+        })
+    }
+
+    async fn fetch_projects(&self) -> ApiResult<Vec<Project>> {
+        Ok(self.data.lock().unwrap().projects.clone())
+    }
+
+    async fn fetch_project_views(&self, _project_id: u64) -> ApiResult<Vec<ProjectView>> {
+        Ok(vec![
+            ProjectView {
+                id: LIST_VIEW_ID,
+                view_kind: "list".to_string(),
+                done_bucket_id: 0,
+            },
+            ProjectView {
+                id: KANBAN_VIEW_ID,
+                view_kind: "kanban".to_string(),
+                // The "Done" bucket
+                done_bucket_id: 3,
+            },
+        ])
+    }
+
+    // Projects have no parents here, the copy goes next to the original
+    async fn duplicate_project(&self, project_id: u64, _parent_id: u64) -> ApiResult<Project> {
+        let mut data = self.data.lock().unwrap();
+        let original = data
+            .projects
+            .iter()
+            .find(|project| project.id == project_id)
+            .cloned()
+            .ok_or_else(|| format!("No project {}", project_id))?;
+        let copy = Project {
+            id: data.projects.iter().map(|p| p.id).max().unwrap_or(0) + 1,
+            title: format!("{} - duplicate", original.title),
+            identifier: String::new(),
+            ..original
+        };
+        data.projects.push(copy.clone());
+        let tasks: Vec<DemoTask> = data
+            .tasks
+            .iter()
+            .filter(|task| task.project_id == project_id)
+            .cloned()
+            .collect();
+        for task in tasks {
+            let id = data.tasks.iter().map(|task| task.id).max().unwrap_or(0) + 1;
+            data.tasks.push(DemoTask {
+                id,
+                project_id: copy.id,
+                position: id as f64 * 65536.0,
+                blocked_by: Vec::new(),
+                parent: None,
+                revision: 0,
+                ..task
+            });
+        }
+        Ok(copy)
+    }
+
+    async fn fetch_buckets(&self, project_id: u64, _view_id: u64) -> ApiResult<Vec<Bucket>> {
+        let data = self.data.lock().unwrap();
+        Ok(data
+            .buckets
+            .iter()
+            .enumerate()
+            .map(|(i, title)| {
+                let mut tasks: Vec<Task> = data
+                    .tasks
+                    .iter()
+                    .filter(|task| task.project_id == project_id && task.bucket == i)
+                    .map(|task| task.to_task(&data))
+                    .collect();
+                tasks.sort_by(|a, b| a.position.total_cmp(&b.position));
+                // Bucket ids are the index plus one
+                Bucket {
+                    id: i as u64 + 1,
+                    title: title.to_string(),
+                    tasks: Some(tasks),
+                    skipped: 0,
+                }
+            })
+            .collect())
+    }
+
+    async fn fetch_task_detail(&self, task_id: u64) -> ApiResult<Task> {
+        let data = self.data.lock().unwrap();
+        match data.tasks.iter().find(|task| task.id == task_id) {
+            Some(task) => Ok(Task {
+                reactions: Some(data.reactions(ReactionTarget::Task(task_id))),
+                ..task.to_task(&data)
+            }),
+            None => Err(format!("Error fetching task detail: task {} not found", task_id).into()),
+        }
+    }
+
+    async fn create_new_task(&self, task: &NewTask) -> ApiResult<Task> {
+        let mut data = self.data.lock().unwrap();
+        let id = data.tasks.iter().map(|task| task.id).max().unwrap_or(0) + 1;
+        let description = task
+            .description
+            .as_ref()
+            .map(|desc| {
+                if desc.starts_with('<') {
+                    desc.clone()
+                } else {
+                    format!("<p>{}</p>", desc)
+                }
+            })
+            .unwrap_or_default();
+        data.tasks.push(DemoTask {
+            id,
+            position: id as f64 * 65536.0,
+            title: task.title.clone(),
+            done: false,
+            done_at: None,
+            project_id: task.project_id.unwrap_or(INBOX_PROJECT_ID),
+            // Bucket ids are one past the index here
+            bucket: task.bucket_id.map_or(0, |id| id.saturating_sub(1) as usize),
+            priority: task.priority.map(i32::from).unwrap_or(0),
+            due_date: task.due_date.clone(),
+            start_date: task.start_date.clone(),
+            end_date: task.end_date.clone(),
+            reminders: task.reminders.clone(),
+            repeat_after: 0,
+            labels: Vec::new(),
+            assignees: Vec::new(),
+            blocked_by: Vec::new(),
+            parent: None,
+            description,
+            hex_color: task.hex_color.clone().unwrap_or_default(),
+            attachments: Vec::new(),
+            revision: 0,
+        });
+        Ok(data.tasks[data.tasks.len() - 1].to_task(&data))
+    }
+
+    async fn update_task(&self, task: &Task) -> ApiResult<Task> {
+        self.with_task(task.id, |demo_task| {
+            demo_task.title = task.title.clone();
+            if task.done && !demo_task.done {
+                demo_task.complete();
+            } else if !task.done {
+                demo_task.done = false;
+                demo_task.done_at = None;
+            }
+            demo_task.priority = task.priority.unwrap_or_default();
+            demo_task.description = task.description.clone().unwrap_or_default();
+            // A task moved to another project starts in its first bucket
+            if task.project_id != demo_task.project_id {
+                demo_task.project_id = task.project_id;
+                demo_task.bucket = 0;
+            }
+        })?;
+        self.fetch_task_detail(task.id).await
+    }
+
+    async fn delete_task(&self, task_id: u64) -> ApiResult<()> {
+        let mut data = self.data.lock().unwrap();
+        let count = data.tasks.len();
+        data.tasks.retain(|task| task.id != task_id);
+        if data.tasks.len() == count {
+            return Err(format!("Error deleting task: no task {}", task_id).into());
+        }
+        data.comments.retain(|(task, _, _)| *task != task_id);
+        Ok(())
+    }
+
+    async fn fetch_labels(&self) -> ApiResult<Vec<Label>> {
+        Ok(self.data.lock().unwrap().labels.clone())
+    }
+
+    async fn create_label(&self, label: &Label) -> ApiResult<Label> {
+        let mut data = self.data.lock().unwrap();
+        let label = Label {
+            id: data.labels.iter().map(|l| l.id).max().unwrap_or(0) + 1,
+            ..label.clone()
+        };
+        data.labels.push(label.clone());
+        Ok(label)
+    }
+
+    async fn add_label(&self, task_id: u64, label_id: u64) -> ApiResult<()> {
+        let title = self.label_title(label_id, "adding")?;
+        self.with_task(task_id, |task| {
+            if !task.labels.contains(&title) {
+                task.labels.push(title);
+            }
+        })
+    }
+
+    async fn remove_label(&self, task_id: u64, label_id: u64) -> ApiResult<()> {
+        let title = self.label_title(label_id, "removing")?;
+        self.with_task(task_id, |task| task.labels.retain(|l| *l != title))
+    }
+
+    async fn search_users(&self, query: &str) -> ApiResult<Vec<User>> {
+        let query = query.to_lowercase();
+        Ok(USERS
+            .iter()
+            .filter(|(username, name)| {
+                username.contains(&query) || name.to_lowercase().contains(&query)
+            })
+            .filter_map(|(username, _)| user(username))
+            .collect())
+    }
+
+    async fn add_assignee(&self, task_id: u64, user_id: u64) -> ApiResult<()> {
+        let (username, _) = USERS
+            .get((user_id as usize).wrapping_sub(1))
+            .ok_or_else(|| format!("Error adding assignee: user {} not found", user_id))?;
+        self.with_task(task_id, |task| {
+            if !task.assignees.contains(username) {
+                task.assignees.push(username);
+            }
+        })
+    }
+
+    // The demo backend never rejects a session
+    async fn login(
+        &self,
+        _username: &str,
+        _password: &str,
+        _totp_passcode: Option<&str>,
+    ) -> ApiResult<String> {
+        Ok(String::new())
+    }
+
+    fn set_token(&self, _token: &str) {}
+
+    async fn move_task_to_bucket(
+        &self,
+        _project_id: u64,
+        _view_id: u64,
+        bucket_id: u64,
+        task_id: u64,
+    ) -> ApiResult<()> {
+        self.with_task(task_id, |task| task.bucket = bucket_id as usize - 1)
+    }
+
+    async fn set_task_position(&self, task_id: u64, _view_id: u64, position: f64) -> ApiResult<()> {
+        self.with_task(task_id, |task| task.position = position)
+    }
+
+    // Comment ids are the position in the list plus one
+    async fn fetch_comments(&self, task_id: u64) -> ApiResult<Vec<Comment>> {
+        let data = self.data.lock().unwrap();
+        Ok(data
+            .comments
+            .iter()
+            .enumerate()
+            .filter(|(_, (task, _, _))| *task == task_id)
+            .map(|(i, (_, author, text))| Comment {
+                id: i as u64 + 1,
+                comment: text.to_string(),
+                author: user(author).unwrap_or_default(),
+                created: Some("2024-05-01T10:00:00Z".to_string()),
+                reactions: Some(data.reactions(ReactionTarget::Comment(i as u64 + 1))),
+            })
+            .collect())
+    }
+
+    async fn add_comment(&self, task_id: u64, comment: &str) -> ApiResult<()> {
+        let mut data = self.data.lock().unwrap();
+        if !data.tasks.iter().any(|task| task.id == task_id) {
+            return Err(format!("Task {} not found", task_id).into());
+        }
+        data.comments
+            .push((task_id, CURRENT_USER, comment.to_string()));
+        Ok(())
+    }
+
+    async fn fetch_current_user(&self) -> ApiResult<User> {
+        user(CURRENT_USER).ok_or_else(|| "No current user".into())
+    }
+
+    async fn fetch_project_users(&self, project_id: u64) -> ApiResult<Vec<ProjectUser>> {
+        let data = self.data.lock().unwrap();
+        Ok(data
+            .user_shares
+            .iter()
+            .filter(|(project, _, _)| *project == project_id)
+            .filter_map(|(_, username, right)| {
+                Some(ProjectUser {
+                    user: user(username)?,
+                    right: *right,
+                })
+            })
+            .collect())
+    }
+
+    async fn fetch_project_teams(&self, project_id: u64) -> ApiResult<Vec<ProjectTeam>> {
+        let data = self.data.lock().unwrap();
+        Ok(data
+            .team_shares
+            .iter()
+            .filter(|(project, _, _)| *project == project_id)
+            .filter_map(|(_, team_id, right)| {
+                Some(ProjectTeam {
+                    team: data.teams.iter().find(|t| t.id == *team_id)?.clone(),
+                    right: *right,
+                })
+            })
+            .collect())
+    }
+
+    async fn fetch_teams(&self) -> ApiResult<Vec<Team>> {
+        Ok(self.data.lock().unwrap().teams.clone())
+    }
+
+    async fn create_team(&self, name: &str) -> ApiResult<Team> {
+        let mut data = self.data.lock().unwrap();
+        let team = team(data.teams.len(), name, &[CURRENT_USER]);
+        data.teams.push(team.clone());
+        Ok(team)
+    }
+
+    async fn add_team_member(&self, team_id: u64, username: &str) -> ApiResult<()> {
+        let Some(member) = user(username) else {
+            return Err(
+                format!("Error adding team member: user {} does not exist", username).into(),
+            );
+        };
+        let mut data = self.data.lock().unwrap();
+        let team = data
+            .teams
+            .iter_mut()
+            .find(|team| team.id == team_id)
+            .ok_or_else(|| format!("No team {}", team_id))?;
+        let members = team.members.get_or_insert_with(Vec::new);
+        if members.iter().any(|m| m.user.username == username) {
+            return Err(
+                format!("Error adding team member: {} is already a member", username).into(),
+            );
+        }
+        members.push(TeamMember {
+            user: member,
+            admin: false,
+        });
+        Ok(())
+    }
+
+    async fn share_with_user(&self, project_id: u64, username: &str, right: u8) -> ApiResult<()> {
+        if user(username).is_none() {
+            return Err(format!("Error sharing project: user {} does not exist", username).into());
+        }
+        let mut data = self.data.lock().unwrap();
+        let shares = &mut data.user_shares;
+        shares.retain(|(project, shared, _)| !(*project == project_id && shared == username));
+        shares.push((project_id, username.to_string(), right));
+        Ok(())
+    }
+
+    async fn share_with_team(&self, project_id: u64, team_id: u64, right: u8) -> ApiResult<()> {
+        let mut data = self.data.lock().unwrap();
+        let shares = &mut data.team_shares;
+        shares.retain(|(project, shared, _)| !(*project == project_id && *shared == team_id));
+        shares.push((project_id, team_id, right));
+        Ok(())
+    }
+
+    async fn unshare_with_user(&self, project_id: u64, username: &str) -> ApiResult<()> {
+        let mut data = self.data.lock().unwrap();
+        let shares = &mut data.user_shares;
+        shares.retain(|(project, shared, _)| !(*project == project_id && shared == username));
+        Ok(())
+    }
+
+    async fn unshare_with_team(&self, project_id: u64, team_id: u64) -> ApiResult<()> {
+        let mut data = self.data.lock().unwrap();
+        let shares = &mut data.team_shares;
+        shares.retain(|(project, shared, _)| !(*project == project_id && *shared == team_id));
+        Ok(())
+    }
+
+    async fn add_reaction(&self, target: ReactionTarget, value: &str) -> ApiResult<()> {
+        let mut data = self.data.lock().unwrap();
+        let exists = data
+            .reactions
+            .iter()
+            .any(|(t, v, u)| *t == target && v == value && *u == CURRENT_USER);
+        if !exists {
+            data.reactions
+                .push((target, value.to_string(), CURRENT_USER));
+        }
+        Ok(())
+    }
+
+    async fn remove_reaction(&self, target: ReactionTarget, value: &str) -> ApiResult<()> {
+        let mut data = self.data.lock().unwrap();
+        data.reactions
+            .retain(|(t, v, u)| !(*t == target && v == value && *u == CURRENT_USER));
+        Ok(())
+    }
+
+    async fn fetch_notifications(&self) -> ApiResult<Vec<Notification>> {
+        Ok(self.data.lock().unwrap().notifications.clone())
+    }
+
+    async fn mark_notification_read(&self, notification_id: u64) -> ApiResult<()> {
+        let mut data = self.data.lock().unwrap();
+        match data
+            .notifications
+            .iter_mut()
+            .find(|n| n.id == notification_id)
+        {
+            Some(notification) => {
+                notification.read_at = Some("2024-05-02T08:00:00Z".to_string());
+                Ok(())
+            }
+            None => Err(format!("Notification {} not found", notification_id).into()),
+        }
+    }
+}
+
+impl DemoApi {
+    fn with_task<T>(&self, task_id: u64, f: impl FnOnce(&mut DemoTask) -> T) -> ApiResult<T> {
+        let mut data = self.data.lock().unwrap();
+        match data.tasks.iter_mut().find(|task| task.id == task_id) {
+            Some(task) => {
+                task.revision += 1;
+                Ok(f(task))
+            }
+            None => Err(format!("Task {} not found", task_id).into()),
+        }
+    }
+
+    fn label_title(&self, label_id: u64, doing: &str) -> ApiResult<String> {
+        let data = self.data.lock().unwrap();
+        match data.labels.iter().find(|label| label.id == label_id) {
+            Some(label) => Ok(label.title.clone()),
+            None => Err(format!("Error {} label: label {} not found", doing, label_id).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fetch_tasks_applies_project_and_done_filter() {
+        let api = DemoApi::new();
+        let query = TaskQuery {
+            filter: Some("project = 3 && (done = false)".to_string()),
+            ..Default::default()
+        };
+        let tasks = api.fetch_tasks(1, &query).await.unwrap();
+        let titles: Vec<&str> = tasks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, vec!["Milk", "Coffee beans"]);
+    }
+
+    #[tokio::test]
+    async fn test_subtasks_are_related_to_their_parent() {
+        let api = DemoApi::new();
+        let tasks = api.fetch_tasks(1, &TaskQuery::default()).await.unwrap();
+        let parent = tasks.iter().find(|t| t.title == "Weekly shop").unwrap();
+        let titles: Vec<&str> = parent
+            .related("subtask")
+            .iter()
+            .map(|t| t.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["Milk", "Coffee beans", "Bread"]);
+        let milk = tasks.iter().find(|t| t.title == "Milk").unwrap();
+        assert_eq!(milk.related("parenttask")[0].id, parent.id);
+    }
+
+    #[tokio::test]
+    async fn test_created_task_is_listed() {
+        let api = DemoApi::new();
+        let task = api
+            .create_new_task(&NewTask {
+                title: "Water plants".to_string(),
+                priority: Some(2),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        api.add_label(task.id, 2).await.unwrap();
+        let query = TaskQuery {
+            filter: Some("priority = 2".to_string()),
+            sort: Some("id desc".to_string()),
+            ..Default::default()
+        };
+        let tasks = api.fetch_tasks(1, &query).await.unwrap();
+        assert_eq!(tasks[0].title, "Water plants");
+        assert_eq!(tasks[0].labels.as_ref().unwrap()[0].title, "home");
+    }
+}
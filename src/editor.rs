@@ -0,0 +1,119 @@
+use crate::screen;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus};
+use tokio::runtime::{Handle, RuntimeFlavor};
+use vikunja_client::api::ApiResult;
+
+// A file of our own in the temp directory. It is created fresh, so a file or
+// link someone else put at the name is never written through.
+fn create_temp_file(extension: &str) -> io::Result<(PathBuf, File)> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    for attempt in 0.. {
+        let name = format!(
+            "vikunja-tui-{}-{}.{}",
+            std::process::id(),
+            attempt,
+            extension
+        );
+        let path = std::env::temp_dir().join(name);
+        match options.open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    unreachable!()
+}
+
+// Waiting for the editor holds up the thread, which the runtime is told about
+// so its other tasks keep running elsewhere
+fn wait_for_editor(mut command: Command) -> io::Result<ExitStatus> {
+    match Handle::try_current().map(|handle| handle.runtime_flavor()) {
+        Ok(RuntimeFlavor::MultiThread) => tokio::task::block_in_place(|| command.status()),
+        _ => command.status(),
+    }
+}
+
+// Lets the text be written in $VISUAL or $EDITOR (vi when neither is set) and
+// returns what was saved. The terminal is handed over to the editor while it
+// runs, so the screen has to be redrawn in full afterwards.
+pub fn edit_text(text: &str, extension: &str) -> ApiResult<String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let (path, mut file) = create_temp_file(extension)?;
+    file.write_all(text.as_bytes())?;
+    drop(file);
+
+    screen::leave()?;
+    // The editor may come with arguments, like `code --wait`
+    let command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command
+            .arg("/C")
+            .arg(format!("{} {}", editor, path.display()));
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command
+            .args(["-c", &format!("{} \"$1\"", editor), "sh"])
+            .arg(&path);
+        command
+    };
+    let status = wait_for_editor(command);
+    screen::enter()?;
+
+    let result = match status {
+        Ok(status) if status.success() => Ok(fs::read_to_string(&path)?),
+        Ok(status) => Err(format!("{} exited with {}", editor, status).into()),
+        Err(err) => Err(format!("Failed to run {}: {}", editor, err).into()),
+    };
+    let _ = fs::remove_file(&path);
+    result
+}
+
+// Plain text as HTML for comments, a paragraph for each block of lines
+pub fn to_html(text: &str) -> String {
+    let escaped = text
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    escaped
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| {
+            format!(
+                "<p>{}</p>",
+                paragraph.lines().collect::<Vec<_>>().join("<br>")
+            )
+        })
+        .collect()
+}
+
+// Single line inputs take the text with its lines joined
+pub fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_html() {
+        assert_eq!(
+            to_html("Numbers <final>\nsee sheet\n\n\nThanks & bye\n"),
+            "<p>Numbers &lt;final&gt;<br>see sheet</p><p>Thanks &amp; bye</p>"
+        );
+        assert_eq!(single_line("Buy\n  milk !2\n"), "Buy milk !2");
+    }
+}
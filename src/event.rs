@@ -0,0 +1,209 @@
+use crossterm::event::{self as terminal, Event, KeyEventKind};
+
+// What the app reacts to. It is kept apart from the terminal's events, so the
+// app can be driven by a script of them without a terminal.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AppEvent {
+    Key(Key),
+    // A turn of the main loop, which takes in the results of finished requests
+    Tick,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: Modifiers,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeyCode {
+    Char(char),
+    Enter,
+    Esc,
+    Backspace,
+    Tab,
+    BackTab,
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Delete,
+    F(u8),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+}
+
+impl Key {
+    pub fn ctrl(code: KeyCode) -> Key {
+        Key {
+            code,
+            modifiers: Modifiers {
+                ctrl: true,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+// Reads keys written the way vim maps them, like "sWork<Enter>" or "<C-o>".
+// `<lt>` is a literal "<".
+pub fn parse_keys(script: &str) -> Result<Vec<Key>, String> {
+    let mut keys = Vec::new();
+    let mut rest = script;
+    while let Some(c) = rest.chars().next() {
+        if c != '<' {
+            keys.push(Key {
+                code: KeyCode::Char(c),
+                modifiers: Modifiers::default(),
+            });
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        let end = rest
+            .find('>')
+            .ok_or_else(|| format!("Unclosed '<' in {}", rest))?;
+        let name = &rest[1..end];
+        rest = &rest[end + 1..];
+        let (ctrl, name) = match name.strip_prefix("C-") {
+            Some(name) => (true, name),
+            None => (false, name),
+        };
+        // Function keys like <F12>
+        let function = name
+            .strip_prefix(['f', 'F'])
+            .and_then(|number| number.parse().ok());
+        let code = if let Some(number) = function {
+            KeyCode::F(number)
+        } else {
+            match name.to_lowercase().as_str() {
+                "enter" | "cr" => KeyCode::Enter,
+                "esc" => KeyCode::Esc,
+                "bs" => KeyCode::Backspace,
+                "tab" => KeyCode::Tab,
+                "s-tab" => KeyCode::BackTab,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "del" => KeyCode::Delete,
+                "lt" => KeyCode::Char('<'),
+                "space" => KeyCode::Char(' '),
+                _ if name.chars().count() == 1 => KeyCode::Char(name.chars().next().unwrap()),
+                _ => return Err(format!("Unknown key <{}>", name)),
+            }
+        };
+        keys.push(Key {
+            code,
+            modifiers: Modifiers {
+                ctrl,
+                ..Default::default()
+            },
+        });
+    }
+    Ok(keys)
+}
+
+impl AppEvent {
+    // The terminal's event as one for the app, None for the ones it has no
+    // use for like key releases or mouse moves
+    pub fn from_terminal(event: Event) -> Option<AppEvent> {
+        let Event::Key(key) = event else {
+            return None;
+        };
+        if key.kind == KeyEventKind::Release {
+            return None;
+        }
+        let code = match key.code {
+            terminal::KeyCode::Char(c) => KeyCode::Char(c),
+            terminal::KeyCode::Enter => KeyCode::Enter,
+            terminal::KeyCode::Esc => KeyCode::Esc,
+            terminal::KeyCode::Backspace => KeyCode::Backspace,
+            terminal::KeyCode::Tab => KeyCode::Tab,
+            terminal::KeyCode::BackTab => KeyCode::BackTab,
+            terminal::KeyCode::Up => KeyCode::Up,
+            terminal::KeyCode::Down => KeyCode::Down,
+            terminal::KeyCode::Left => KeyCode::Left,
+            terminal::KeyCode::Right => KeyCode::Right,
+            terminal::KeyCode::PageUp => KeyCode::PageUp,
+            terminal::KeyCode::PageDown => KeyCode::PageDown,
+            terminal::KeyCode::Home => KeyCode::Home,
+            terminal::KeyCode::End => KeyCode::End,
+            terminal::KeyCode::Delete => KeyCode::Delete,
+            terminal::KeyCode::F(n) => KeyCode::F(n),
+            _ => return None,
+        };
+        let modifiers = Modifiers {
+            ctrl: key.modifiers.contains(terminal::KeyModifiers::CONTROL),
+            alt: key.modifiers.contains(terminal::KeyModifiers::ALT),
+            shift: key.modifiers.contains(terminal::KeyModifiers::SHIFT),
+        };
+        Some(AppEvent::Key(Key { code, modifiers }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyEvent, KeyEventState, KeyModifiers};
+
+    #[test]
+    fn test_from_terminal() {
+        let key = |code, modifiers, kind| {
+            Event::Key(KeyEvent {
+                code,
+                modifiers,
+                kind,
+                state: KeyEventState::NONE,
+            })
+        };
+        assert_eq!(
+            AppEvent::from_terminal(key(
+                terminal::KeyCode::Char('o'),
+                KeyModifiers::CONTROL,
+                KeyEventKind::Press
+            )),
+            Some(AppEvent::Key(Key::ctrl(KeyCode::Char('o'))))
+        );
+        assert_eq!(
+            AppEvent::from_terminal(key(
+                terminal::KeyCode::Char('j'),
+                KeyModifiers::NONE,
+                KeyEventKind::Release
+            )),
+            None
+        );
+        assert_eq!(AppEvent::from_terminal(Event::FocusGained), None);
+    }
+
+    #[test]
+    fn test_parse_keys() {
+        let keys = parse_keys("j<Enter><C-o><lt><F12>").unwrap();
+        let codes: Vec<KeyCode> = keys.iter().map(|key| key.code).collect();
+        assert_eq!(
+            codes,
+            vec![
+                KeyCode::Char('j'),
+                KeyCode::Enter,
+                KeyCode::Char('o'),
+                KeyCode::Char('<'),
+                KeyCode::F(12)
+            ]
+        );
+        assert_eq!(keys[2], Key::ctrl(KeyCode::Char('o')));
+        assert!(parse_keys("<Nope>").is_err());
+        assert!(parse_keys("<Enter").is_err());
+    }
+}
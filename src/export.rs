@@ -0,0 +1,143 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Local};
+use std::path::PathBuf;
+use vikunja_client::models::{Comment, Task};
+
+// Where `:export` puts the task
+pub enum ExportTarget {
+    Clipboard,
+    File(PathBuf),
+}
+
+// Unset dates come from the server as year one
+fn set_date(date: Option<&String>) -> Option<String> {
+    let date = date.filter(|date| !date.starts_with("0001-"))?;
+    let date = DateTime::parse_from_rfc3339(date).ok()?;
+    Some(
+        date.with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string(),
+    )
+}
+
+// The task with its details and comments as Markdown, for meeting notes
+pub fn task_markdown(task: &Task, comments: &[Comment], project: Option<&str>) -> String {
+    let mut lines = Vec::new();
+    if task.identifier.is_empty() {
+        lines.push(format!("# {}", task.title));
+    } else {
+        lines.push(format!("# {} {}", task.identifier, task.title));
+    }
+    lines.push(String::new());
+    if let Some(project) = project {
+        lines.push(format!("- Project: {}", project));
+    }
+    match set_date(task.done_at.as_ref()) {
+        Some(done_at) if task.done => lines.push(format!("- Status: done {}", done_at)),
+        _ if task.done => lines.push("- Status: done".to_string()),
+        _ => lines.push("- Status: open".to_string()),
+    }
+    if let Some(priority) = task.priority.filter(|&p| p > 0) {
+        lines.push(format!("- Priority: {}", priority));
+    }
+    for (name, date) in [
+        ("Due", &task.due_date),
+        ("Start", &task.start_date),
+        ("End", &task.end_date),
+    ] {
+        if let Some(date) = set_date(date.as_ref()) {
+            lines.push(format!("- {}: {}", name, date));
+        }
+    }
+    let labels: Vec<&str> = task
+        .labels
+        .iter()
+        .flatten()
+        .map(|l| l.title.as_str())
+        .collect();
+    if !labels.is_empty() {
+        lines.push(format!("- Labels: {}", labels.join(", ")));
+    }
+    let assignees: Vec<&str> = task
+        .assignees
+        .iter()
+        .flatten()
+        .map(|user| user.username.as_str())
+        .collect();
+    if !assignees.is_empty() {
+        lines.push(format!("- Assignees: {}", assignees.join(", ")));
+    }
+
+    let description = task.description.as_deref().unwrap_or_default();
+    let description = html2text::from_read(description.as_bytes(), usize::MAX);
+    if !description.trim().is_empty() {
+        lines.push(String::new());
+        lines.push(description.trim_end().to_string());
+    }
+
+    if !comments.is_empty() {
+        lines.push(String::new());
+        lines.push("## Comments".to_string());
+    }
+    for comment in comments {
+        lines.push(String::new());
+        match set_date(comment.created.as_ref()) {
+            Some(created) => lines.push(format!("**{}** · {}", comment.author.username, created)),
+            None => lines.push(format!("**{}**", comment.author.username)),
+        }
+        lines.push(String::new());
+        let text = html2text::from_read(comment.comment.as_bytes(), usize::MAX);
+        lines.push(text.trim_end().to_string());
+    }
+    lines.join("\n") + "\n"
+}
+
+// The escape sequence that has the terminal put text on the clipboard, which
+// also works over ssh
+pub fn osc52(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", STANDARD.encode(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vikunja_client::models::{Label, User};
+
+    #[test]
+    fn test_task_markdown() {
+        let task = Task {
+            title: "Write quarterly report".to_string(),
+            identifier: "WRK-1".to_string(),
+            description: Some("<p>Numbers from finance</p><ul><li>Revenue</li></ul>".to_string()),
+            priority: Some(4),
+            due_date: Some("0001-01-01T00:00:00Z".to_string()),
+            labels: Some(vec![Label {
+                title: "reports".to_string(),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+        let comments = [Comment {
+            comment: "<p>Draft is up</p>".to_string(),
+            author: User {
+                username: "sam".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }];
+        assert_eq!(
+            task_markdown(&task, &comments, Some("Work")),
+            "# WRK-1 Write quarterly report\n\n\
+             - Project: Work\n\
+             - Status: open\n\
+             - Priority: 4\n\
+             - Labels: reports\n\n\
+             Numbers from finance\n\n\
+             * Revenue\n\n\
+             ## Comments\n\n\
+             **sam**\n\n\
+             Draft is up\n"
+        );
+        assert_eq!(osc52("hi"), "\x1b]52;c;aGk=\x07");
+    }
+}
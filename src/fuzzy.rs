@@ -0,0 +1,145 @@
+use vikunja_client::models::Task;
+
+// Returns a score when every character of the query appears in order in the
+// candidate (case-insensitive). Higher is better.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    fuzzy_match(query, candidate).map(|(score, _)| score)
+}
+
+// The score and the char indices of the candidate that matched, for
+// highlighting them
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<(i64, Vec<usize>)> {
+    let query: Vec<char> = query
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    if query.is_empty() {
+        return Some((0, Vec::new()));
+    }
+
+    // Lowercasing char by char keeps the indices lined up with the original
+    let candidate: Vec<char> = candidate
+        .chars()
+        .map(|c| c.to_lowercase().next().unwrap_or(c))
+        .collect();
+    let mut score = 0i64;
+    let mut query_index = 0;
+    let mut previous_match: Option<usize> = None;
+    let mut positions = Vec::new();
+
+    for (i, c) in candidate.iter().enumerate() {
+        if query_index == query.len() {
+            break;
+        }
+        if *c != query[query_index] {
+            continue;
+        }
+
+        score += 1;
+        // Consecutive characters and word starts count for more
+        if previous_match == Some(i.wrapping_sub(1)) {
+            score += 5;
+        }
+        if i == 0 || !candidate[i - 1].is_alphanumeric() {
+            score += 3;
+        }
+        previous_match = Some(i);
+        positions.push(i);
+        query_index += 1;
+    }
+
+    if query_index < query.len() {
+        return None;
+    }
+
+    // Prefer shorter candidates when the match quality is the same
+    Some((score * 100 - candidate.len() as i64, positions))
+}
+
+// Indices of the matching candidates, best match first
+pub fn fuzzy_filter<S: AsRef<str>>(query: &str, candidates: &[S]) -> Vec<usize> {
+    let mut scored: Vec<(usize, i64)> = candidates
+        .iter()
+        .enumerate()
+        .filter_map(|(i, c)| fuzzy_score(query, c.as_ref()).map(|score| (i, score)))
+        .collect();
+
+    if !query.trim().is_empty() {
+        scored.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    }
+
+    scored.into_iter().map(|(i, _)| i).collect()
+}
+
+// Indices of the tasks whose title or one of whose labels matches, in list
+// order so narrowing doesn't reshuffle the list
+pub fn filter_tasks(query: &str, tasks: &[Task]) -> Vec<usize> {
+    tasks
+        .iter()
+        .enumerate()
+        .filter(|(_, task)| {
+            fuzzy_score(query, &task.title).is_some()
+                || task
+                    .labels
+                    .iter()
+                    .flatten()
+                    .any(|label| fuzzy_score(query, &label.title).is_some())
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vikunja_client::models::Label;
+
+    #[test]
+    fn test_score_requires_all_characters_in_order() {
+        assert!(fuzzy_score("gro", "Groceries").is_some());
+        assert!(fuzzy_score("gcs", "Groceries").is_some());
+        assert!(fuzzy_score("sgc", "Groceries").is_none());
+    }
+
+    #[test]
+    fn test_filter_prefers_consecutive_matches() {
+        let candidates = ["Work Backlog", "Bookshelf", "Home"];
+        let result = fuzzy_filter("bo", &candidates);
+        assert_eq!(result, vec![1, 0]);
+    }
+
+    #[test]
+    fn test_filter_empty_query_keeps_order() {
+        let candidates = ["Inbox", "Work", "Home"];
+        let result = fuzzy_filter("", &candidates);
+        assert_eq!(result, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_match_positions_are_char_indices() {
+        let (_, positions) = fuzzy_match("co", "Über cool").unwrap();
+        assert_eq!(positions, vec![5, 6]);
+        let (_, positions) = fuzzy_match("BM", "buy milk").unwrap();
+        assert_eq!(positions, vec![0, 4]);
+    }
+
+    #[test]
+    fn test_filter_tasks_matches_labels_in_list_order() {
+        let task = |title: &str, label: &str| Task {
+            title: title.to_string(),
+            labels: Some(vec![Label {
+                title: label.to_string(),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+        let tasks = [
+            task("Call the dentist", "health"),
+            task("Buy milk", "errands"),
+            task("Renew passport", "errands"),
+        ];
+        assert_eq!(filter_tasks("err", &tasks), vec![1, 2]);
+        assert_eq!(filter_tasks("milk", &tasks), vec![1]);
+    }
+}
@@ -0,0 +1,110 @@
+use ratatui::style::{Color, Modifier, Style};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{FontStyle, Theme, ThemeSet};
+use syntect::parsing::SyntaxSet;
+
+// Loaded the first time a code block is shown
+fn syntaxes() -> &'static (SyntaxSet, Theme) {
+    static SYNTAXES: OnceLock<(SyntaxSet, Theme)> = OnceLock::new();
+    SYNTAXES.get_or_init(|| {
+        let mut themes = ThemeSet::load_defaults();
+        let theme = themes
+            .themes
+            .remove("base16-ocean.dark")
+            .unwrap_or_default();
+        (SyntaxSet::load_defaults_newlines(), theme)
+    })
+}
+
+type Highlighted = Vec<Vec<(Style, String)>>;
+
+// The details are drawn on every tick, so highlighting is done once
+type Cache = HashMap<(Option<String>, String), Highlighted>;
+
+// Code blocks already highlighted, by language and code
+static HIGHLIGHTED: OnceLock<Mutex<Cache>> = OnceLock::new();
+const CACHE_LIMIT: usize = 32;
+
+// The lines of a code block as styled pieces. Languages syntect doesn't know
+// are shown dimmed.
+pub fn highlight_code(code: &str, language: Option<&str>) -> Highlighted {
+    let key = (language.map(str::to_string), code.to_string());
+    let cache = HIGHLIGHTED.get_or_init(Mutex::default);
+    if let Some(lines) = cache.lock().unwrap().get(&key) {
+        return lines.clone();
+    }
+    let lines = highlight(code, language);
+    let mut cache = cache.lock().unwrap();
+    if cache.len() >= CACHE_LIMIT {
+        cache.clear();
+    }
+    cache.insert(key, lines.clone());
+    lines
+}
+
+fn highlight(code: &str, language: Option<&str>) -> Highlighted {
+    let dim = Style::default().add_modifier(Modifier::DIM);
+    let (syntaxes, theme) = syntaxes();
+    let Some(syntax) = language.and_then(|language| syntaxes.find_syntax_by_token(language)) else {
+        return code
+            .lines()
+            .map(|line| vec![(dim, line.to_string())])
+            .collect();
+    };
+    let mut highlighter = HighlightLines::new(syntax, theme);
+    code.lines()
+        .map(|line| {
+            let line = format!("{}\n", line);
+            match highlighter.highlight_line(&line, syntaxes) {
+                Ok(pieces) => pieces
+                    .into_iter()
+                    .map(|(style, text)| (to_style(style), text.trim_end_matches('\n').to_string()))
+                    .filter(|(_, text)| !text.is_empty())
+                    .collect(),
+                Err(_) => vec![(dim, line.trim_end().to_string())],
+            }
+        })
+        .collect()
+}
+
+// Only the foreground, the theme's background would clash with the terminal's
+fn to_style(style: syntect::highlighting::Style) -> Style {
+    let mut converted = Style::default().fg(Color::Rgb(
+        style.foreground.r,
+        style.foreground.g,
+        style.foreground.b,
+    ));
+    if style.font_style.contains(FontStyle::BOLD) {
+        converted = converted.add_modifier(Modifier::BOLD);
+    }
+    if style.font_style.contains(FontStyle::ITALIC) {
+        converted = converted.add_modifier(Modifier::ITALIC);
+    }
+    converted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_code() {
+        let code = "fn main() {\n    let x = 1;\n}";
+        let lines = highlight_code(code, Some("rust"));
+        assert_eq!(lines.len(), 3);
+        let text: String = lines[1].iter().map(|(_, text)| text.as_str()).collect();
+        assert_eq!(text, "    let x = 1;");
+        assert!(lines[0].iter().any(|(style, _)| style.fg.is_some()));
+
+        let lines = highlight_code(code, Some("no-such-language"));
+        assert_eq!(
+            lines[0],
+            vec![(
+                Style::default().add_modifier(Modifier::DIM),
+                "fn main() {".to_string()
+            )]
+        );
+    }
+}
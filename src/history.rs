@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+
+// How many places back are kept
+const HISTORY_LIMIT: usize = 50;
+// How many entries each input keeps
+const INPUT_HISTORY_LIMIT: usize = 100;
+
+// Back and forward stacks of places visited, like a browser's
+pub struct History<T> {
+    back: Vec<T>,
+    forward: Vec<T>,
+}
+
+impl<T> Default for History<T> {
+    fn default() -> History<T> {
+        History {
+            back: Vec::new(),
+            forward: Vec::new(),
+        }
+    }
+}
+
+impl<T: PartialEq> History<T> {
+    pub fn new() -> History<T> {
+        History::default()
+    }
+
+    // Called with the place being left. Going somewhere new drops the places
+    // ahead, and staying put records nothing.
+    pub fn record(&mut self, current: T) {
+        if self.back.last() != Some(&current) {
+            self.back.push(current);
+            if self.back.len() > HISTORY_LIMIT {
+                self.back.remove(0);
+            }
+        }
+        self.forward.clear();
+    }
+
+    // The place to go back to, remembering the current one to come forward to
+    pub fn back(&mut self, current: T) -> Option<T> {
+        let previous = self.back.pop()?;
+        self.forward.push(current);
+        Some(previous)
+    }
+
+    pub fn forward(&mut self, current: T) -> Option<T> {
+        let next = self.forward.pop()?;
+        self.back.push(current);
+        Some(next)
+    }
+
+    pub fn clear(&mut self) {
+        self.back.clear();
+        self.forward.clear();
+    }
+}
+
+// What was entered into one input, oldest first, walked through like a
+// shell's history
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(transparent)]
+pub struct InputHistory {
+    entries: Vec<String>,
+    // The entry shown and the text typed before walking back
+    #[serde(skip)]
+    browsing: Option<(usize, String)>,
+}
+
+impl InputHistory {
+    // Entering something again moves it to the newest place
+    pub fn add(&mut self, entry: &str) {
+        self.browsing = None;
+        let entry = entry.trim();
+        if entry.is_empty() {
+            return;
+        }
+        self.entries.retain(|e| e != entry);
+        self.entries.push(entry.to_string());
+        if self.entries.len() > INPUT_HISTORY_LIMIT {
+            self.entries.remove(0);
+        }
+    }
+
+    // The entry before the one shown, remembering what was typed to come
+    // back to
+    pub fn older(&mut self, typed: &str) -> Option<String> {
+        let i = match &self.browsing {
+            Some((i, _)) => i.checked_sub(1)?,
+            None => self.entries.len().checked_sub(1)?,
+        };
+        let typed = match self.browsing.take() {
+            Some((_, typed)) => typed,
+            None => typed.to_string(),
+        };
+        self.browsing = Some((i, typed));
+        Some(self.entries[i].clone())
+    }
+
+    // The entry after the one shown, past the newest what was typed
+    pub fn newer(&mut self) -> Option<String> {
+        let (i, typed) = self.browsing.take()?;
+        match self.entries.get(i + 1) {
+            Some(entry) => {
+                self.browsing = Some((i + 1, typed));
+                Some(entry.clone())
+            }
+            None => Some(typed),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.browsing = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_back_and_forward() {
+        let mut history = History::new();
+        history.record(1);
+        history.record(2);
+        assert_eq!(history.back(3), Some(2));
+        assert_eq!(history.back(2), Some(1));
+        assert_eq!(history.back(1), None);
+        assert_eq!(history.forward(1), Some(2));
+        assert_eq!(history.forward(2), Some(3));
+        assert_eq!(history.forward(3), None);
+    }
+
+    #[test]
+    fn test_record_drops_forward_places() {
+        let mut history = History::new();
+        history.record(1);
+        assert_eq!(history.back(2), Some(1));
+        history.record(1);
+        history.record(1);
+        assert_eq!(history.forward(4), None);
+        assert_eq!(history.back(4), Some(1));
+        assert_eq!(history.back(1), None);
+    }
+
+    #[test]
+    fn test_walk_through_input_history() {
+        let mut history = InputHistory::default();
+        assert_eq!(history.older("typed"), None);
+        history.add("done = false");
+        history.add("priority >= 3");
+        history.add("done = false");
+        assert_eq!(history.older("typed").as_deref(), Some("done = false"));
+        assert_eq!(history.older("").as_deref(), Some("priority >= 3"));
+        assert_eq!(history.older(""), None);
+        assert_eq!(history.newer().as_deref(), Some("done = false"));
+        assert_eq!(history.newer().as_deref(), Some("typed"));
+        assert_eq!(history.newer(), None);
+    }
+}
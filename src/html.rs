@@ -0,0 +1,330 @@
+use regex::Regex;
+
+// A piece of a description: HTML for html2text, or a code block or table
+// that is laid out here
+#[derive(Debug, PartialEq)]
+pub enum Section {
+    Html(String),
+    Code {
+        // From a `language-…` class on the code element
+        language: Option<String>,
+        code: String,
+    },
+    Table {
+        // Whether the first row is made of `<th>` cells
+        header: bool,
+        rows: Vec<Vec<String>>,
+    },
+}
+
+// Splits the `<pre>` blocks and tables out of an HTML description, in order
+pub fn split_sections(html: &str) -> Vec<Section> {
+    let block = Regex::new(concat!(
+        r"(?s)<pre[^>]*>\s*(?:<code(?P<attributes>[^>]*)>)?(?P<code>.*?)(?:</code>)?\s*</pre>",
+        r"|<table[^>]*>(?P<table>.*?)</table>"
+    ))
+    .unwrap();
+    let language = Regex::new(r"language-([\w+#-]+)").unwrap();
+    // Highlighters in the web editor leave spans behind
+    let tag = Regex::new(r"<[^>]+>").unwrap();
+    let row = Regex::new(r"(?s)<tr[^>]*>(.*?)</tr>").unwrap();
+    let cell = Regex::new(r"(?s)<t([hd])[^>]*>(.*?)</t[hd]>").unwrap();
+    let mut sections = Vec::new();
+    let mut last = 0;
+    for captures in block.captures_iter(html) {
+        let whole = captures.get(0).unwrap();
+        let before = &html[last..whole.start()];
+        if !before.trim().is_empty() {
+            sections.push(Section::Html(before.to_string()));
+        }
+        last = whole.end();
+        if let Some(table) = captures.name("table") {
+            let mut header = false;
+            let rows: Vec<Vec<String>> = row
+                .captures_iter(table.as_str())
+                .enumerate()
+                .map(|(i, row)| {
+                    let cells: Vec<_> = cell.captures_iter(&row[1]).collect();
+                    header |= i == 0 && !cells.is_empty() && cells.iter().all(|c| &c[1] == "h");
+                    cells
+                        .iter()
+                        .map(|c| {
+                            let text = decode_entities(&tag.replace_all(&c[2], " "));
+                            text.split_whitespace().collect::<Vec<_>>().join(" ")
+                        })
+                        .collect()
+                })
+                .collect();
+            sections.push(Section::Table { header, rows });
+            continue;
+        }
+        let language = captures
+            .name("attributes")
+            .and_then(|attributes| language.captures(attributes.as_str()))
+            .map(|found| found[1].to_lowercase());
+        let code = tag.replace_all(&captures["code"], "");
+        sections.push(Section::Code {
+            language,
+            code: decode_entities(&code),
+        });
+    }
+    if !html[last..].trim().is_empty() {
+        sections.push(Section::Html(html[last..].to_string()));
+    }
+    sections
+}
+
+// A table drawn with box characters in at most `width` columns. Cells wrap
+// when the columns don't fit, and when even that gets too narrow each row is
+// written out as `header: value` lines instead.
+pub fn layout_table(header: bool, rows: &[Vec<String>], width: usize) -> Vec<String> {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if columns == 0 {
+        return Vec::new();
+    }
+    let cell = |row: &[String], i: usize| row.get(i).cloned().unwrap_or_default();
+    let natural: Vec<usize> = (0..columns)
+        .map(|i| {
+            rows.iter()
+                .map(|row| cell(row, i).chars().count())
+                .max()
+                .unwrap_or(0)
+                .max(1)
+        })
+        .collect();
+    // A border on each side of every column
+    let available = width.saturating_sub(columns + 1);
+    if available < columns * MIN_COLUMN_WIDTH {
+        return stacked_rows(header, rows);
+    }
+    let widths = fit_columns(&natural, available);
+
+    let border = |left: &str, middle: &str, right: &str| {
+        let segments: Vec<String> = widths.iter().map(|w| "─".repeat(*w)).collect();
+        format!("{}{}{}", left, segments.join(middle), right)
+    };
+    let mut lines = vec![border("┌", "┬", "┐")];
+    for (r, row) in rows.iter().enumerate() {
+        let wrapped: Vec<Vec<String>> = (0..columns)
+            .map(|i| wrap_words(&cell(row, i), widths[i]))
+            .collect();
+        let height = wrapped.iter().map(Vec::len).max().unwrap_or(1);
+        for l in 0..height {
+            let cells: Vec<String> = wrapped
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| {
+                    let text = cell.get(l).map(String::as_str).unwrap_or_default();
+                    format!("{}{}", text, " ".repeat(width - text.chars().count()))
+                })
+                .collect();
+            lines.push(format!("│{}│", cells.join("│")));
+        }
+        if header && r == 0 && rows.len() > 1 {
+            lines.push(border("├", "┼", "┤"));
+        }
+    }
+    lines.push(border("└", "┴", "┘"));
+    lines
+}
+
+// Narrowest a column gets before the table is written out row by row
+const MIN_COLUMN_WIDTH: usize = 4;
+
+// Columns that fit keep their width, the rest share what is left evenly
+fn fit_columns(natural: &[usize], available: usize) -> Vec<usize> {
+    let mut widths = vec![0; natural.len()];
+    let mut open: Vec<usize> = (0..natural.len()).collect();
+    let mut left = available;
+    loop {
+        let share = left / open.len().max(1);
+        let (fits, rest): (Vec<usize>, Vec<usize>) =
+            open.iter().partition(|&&i| natural[i] <= share);
+        if fits.is_empty() {
+            let mut extra = left - share * rest.len();
+            for i in rest {
+                widths[i] = share + usize::from(extra > 0);
+                extra = extra.saturating_sub(1);
+            }
+            return widths;
+        }
+        for i in fits {
+            widths[i] = natural[i];
+            left -= natural[i];
+        }
+        open = rest;
+        if open.is_empty() {
+            return widths;
+        }
+    }
+}
+
+fn stacked_rows(header: bool, rows: &[Vec<String>]) -> Vec<String> {
+    let (names, body) = match (header, rows.split_first()) {
+        (true, Some((names, body))) => (names.as_slice(), body),
+        _ => (&[][..], rows),
+    };
+    let mut lines = Vec::new();
+    for row in body {
+        for (i, value) in row.iter().enumerate() {
+            lines.push(match names.get(i) {
+                Some(name) => format!("{}: {}", name, value),
+                None => value.clone(),
+            });
+        }
+        lines.push(String::new());
+    }
+    lines
+}
+
+// Breaks text into lines of at most `width` characters, at spaces where
+// possible
+fn wrap_words(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        let used = line.chars().count();
+        if used > 0 && used + 1 + word.len() <= width {
+            line.push(' ');
+            line.extend(&word);
+            continue;
+        }
+        if used > 0 {
+            lines.push(std::mem::take(&mut line));
+        }
+        while word.len() > width {
+            lines.push(word.drain(..width).collect());
+        }
+        line = word.into_iter().collect();
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+// The links in some HTML as (text, url): anchors, then bare urls in the text.
+// Each url is listed once.
+pub fn extract_links(html: &str) -> Vec<(String, String)> {
+    let anchor = Regex::new(r#"(?s)<a\s[^>]*href\s*=\s*["']([^"']+)["'][^>]*>(.*?)</a>"#).unwrap();
+    let bare = Regex::new(r#"https?://[^\s<>"']+"#).unwrap();
+    let tag = Regex::new(r"<[^>]+>").unwrap();
+    let mut links: Vec<(String, String)> = Vec::new();
+    for captures in anchor.captures_iter(html) {
+        let url = decode_entities(&captures[1]);
+        let text = decode_entities(&tag.replace_all(&captures[2], ""));
+        links.push((text.trim().to_string(), url));
+    }
+    let text = anchor.replace_all(html, "");
+    let text = tag.replace_all(&text, " ");
+    for found in bare.find_iter(&text) {
+        let url = decode_entities(found.as_str().trim_end_matches(['.', ',', ')', ';', ':']));
+        links.push((String::new(), url));
+    }
+    let mut seen = Vec::new();
+    links.retain(|(_, url)| {
+        let new = !seen.contains(url);
+        seen.push(url.clone());
+        new
+    });
+    links
+}
+
+pub fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_sections() {
+        let html = concat!(
+            "<p>Before</p>",
+            "<pre><code class=\"language-Rust\">fn main() {\n    <span>x</span> &amp;&amp; y\n}</code></pre>",
+            "<pre>plain</pre><p>After</p>"
+        );
+        assert_eq!(
+            split_sections(html),
+            vec![
+                Section::Html("<p>Before</p>".to_string()),
+                Section::Code {
+                    language: Some("rust".to_string()),
+                    code: "fn main() {\n    x && y\n}".to_string(),
+                },
+                Section::Code {
+                    language: None,
+                    code: "plain".to_string(),
+                },
+                Section::Html("<p>After</p>".to_string()),
+            ]
+        );
+        assert_eq!(
+            split_sections("<p>Only text</p>"),
+            vec![Section::Html("<p>Only text</p>".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_extract_links() {
+        let html = concat!(
+            "<p>See <a href=\"https://example.com/spec?a=1&amp;b=2\"><strong>the spec</strong></a>",
+            " and https://example.com/board, or https://example.com/spec?a=1&amp;b=2.</p>"
+        );
+        assert_eq!(
+            extract_links(html),
+            vec![
+                (
+                    "the spec".to_string(),
+                    "https://example.com/spec?a=1&b=2".to_string()
+                ),
+                (String::new(), "https://example.com/board".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tables_are_laid_out_to_fit() {
+        let sections = split_sections(concat!(
+            "<table><tr><th><p>Quarter</p></th><th>Revenue</th></tr>",
+            "<tr><td>Q2</td><td>1.5M, <strong>ahead</strong> of plan</td></tr></table>"
+        ));
+        let Some(Section::Table { header, rows }) = sections.first() else {
+            panic!("no table in {:?}", sections);
+        };
+        assert!(header);
+        assert_eq!(
+            layout_table(*header, rows, 40),
+            vec![
+                "┌───────┬───────────────────┐",
+                "│Quarter│Revenue            │",
+                "├───────┼───────────────────┤",
+                "│Q2     │1.5M, ahead of plan│",
+                "└───────┴───────────────────┘",
+            ]
+        );
+        assert_eq!(
+            layout_table(*header, rows, 20),
+            vec![
+                "┌───────┬──────────┐",
+                "│Quarter│Revenue   │",
+                "├───────┼──────────┤",
+                "│Q2     │1.5M,     │",
+                "│       │ahead of  │",
+                "│       │plan      │",
+                "└───────┴──────────┘",
+            ]
+        );
+        assert_eq!(
+            layout_table(*header, rows, 8),
+            vec!["Quarter: Q2", "Revenue: 1.5M, ahead of plan", ""]
+        );
+    }
+}
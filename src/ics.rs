@@ -0,0 +1,156 @@
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::Deserialize;
+use vikunja_client::ApiResult;
+
+// An iCal feed from another calendar, whose events are shown next to the
+// tasks in the date picker. Nothing is ever written back to it.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct CalendarFeed {
+    pub name: String,
+    pub url: String,
+}
+
+// An event of a feed, on the day it starts
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExternalEvent {
+    pub calendar: String,
+    pub summary: String,
+    pub date: NaiveDate,
+}
+
+pub async fn fetch_events(feed: &CalendarFeed) -> ApiResult<Vec<ExternalEvent>> {
+    let text = reqwest::get(&feed.url)
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    Ok(parse_events(&feed.name, &text))
+}
+
+// Reads the VEVENTs of an iCalendar file. Events without a start or a
+// summary, and lines that can't be read, are left out rather than failing
+// the whole feed.
+pub fn parse_events(calendar: &str, text: &str) -> Vec<ExternalEvent> {
+    let mut events = Vec::new();
+    let mut in_event = false;
+    let mut summary = None;
+    let mut date = None;
+    for line in unfold(text) {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        // Parameters like ;VALUE=DATE or ;TZID=... follow the property name
+        let (property, params) = name.split_once(';').unwrap_or((name, ""));
+        match property.to_ascii_uppercase().as_str() {
+            "BEGIN" if value.eq_ignore_ascii_case("VEVENT") => {
+                in_event = true;
+                summary = None;
+                date = None;
+            }
+            "END" if value.eq_ignore_ascii_case("VEVENT") => {
+                if let (Some(summary), Some(date)) = (summary.take(), date.take()) {
+                    events.push(ExternalEvent {
+                        calendar: calendar.to_string(),
+                        summary,
+                        date,
+                    });
+                }
+                in_event = false;
+            }
+            "SUMMARY" if in_event => summary = Some(unescape(value)),
+            "DTSTART" if in_event => date = start_date(value, params),
+            _ => {}
+        }
+    }
+    events.sort_by_key(|event| event.date);
+    events
+}
+
+// Long lines are folded onto the next ones, which start with a space or tab
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+fn unescape(value: &str) -> String {
+    let mut text = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => text.push(' '),
+            Some(other) => text.push(other),
+            None => {}
+        }
+    }
+    text.trim().to_string()
+}
+
+// All-day events give a date, others a time which is either UTC (ending in
+// Z) or local to the event. Times in UTC are moved to the local day.
+fn start_date(value: &str, params: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    if params.to_ascii_uppercase().contains("VALUE=DATE") && !value.contains('T') {
+        return NaiveDate::parse_from_str(value, "%Y%m%d").ok();
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let time = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(
+            Utc.from_utc_datetime(&time)
+                .with_timezone(&Local)
+                .date_naive(),
+        );
+    }
+    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .map(|time| time.date())
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y%m%d"))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_events_reads_dates_and_summaries() {
+        let text = "BEGIN:VCALENDAR\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART;TZID=Europe/Berlin:20261103T090000\r\n\
+            SUMMARY:Dentist\\, Dr. Lee\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART;VALUE=DATE:20261101\r\n\
+            SUMMARY:Tax return\r\n  due\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            SUMMARY:No start\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+        let events = parse_events("Home", text);
+        assert_eq!(
+            events,
+            vec![
+                ExternalEvent {
+                    calendar: "Home".to_string(),
+                    summary: "Tax return due".to_string(),
+                    date: NaiveDate::from_ymd_opt(2026, 11, 1).unwrap(),
+                },
+                ExternalEvent {
+                    calendar: "Home".to_string(),
+                    summary: "Dentist, Dr. Lee".to_string(),
+                    date: NaiveDate::from_ymd_opt(2026, 11, 3).unwrap(),
+                },
+            ]
+        );
+        assert!(parse_events("Home", "not a calendar").is_empty());
+    }
+}
@@ -1,83 +1,331 @@
-mod api;
 mod app;
-mod models;
+mod auth;
+mod board;
+mod cache;
+mod calendar;
+mod cli;
+mod color;
+mod config;
+mod demo;
+mod editor;
+mod event;
+mod export;
+mod fuzzy;
+mod highlight;
+mod history;
+mod html;
+mod ics;
+mod members;
 mod parser;
+mod picker;
+mod report;
+mod row_format;
+mod screen;
+mod state;
+mod teams;
+mod timer;
 mod ui;
+mod worker;
 
-use crate::api::fetch_tasks;
+use crate::demo::DemoApi;
+use vikunja_client::api::{HttpClient, VikunjaApi};
 
-use app::App;
-use crossterm::{
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
-use dirs::config_dir;
+use app::{App, ViewKind};
+use clap::{CommandFactory, Parser};
+use clap_complete::env::Shells;
+use clap_complete::CompleteEnv;
+use cli::{Args, Command};
+use config::{load_config, Config, NoConfigFile};
+use event::parse_keys;
 use ratatui::{backend::CrosstermBackend, Terminal};
-use serde::Deserialize;
-use std::fs;
 use std::io;
-use std::path::PathBuf;
-use ui::run_app;
+use std::process::ExitCode;
+use std::sync::Arc;
+use ui::{render_headless, run_app};
+use vikunja_client::auth::{parse_share_link, parse_task_link, same_instance};
+use vikunja_client::models::Project;
 
-#[derive(Deserialize)]
-struct VikunjaConfig {
-    instance_url: String,
-    api_key: String,
-}
+#[tokio::main]
+async fn main() -> Result<ExitCode, Box<dyn std::error::Error + Send + Sync>> {
+    // The shell runs the binary with COMPLETE set to complete a word
+    CompleteEnv::with_factory(Args::command).complete();
+    let args = Args::parse();
+    if let Some(Command::Completions { shell }) = &args.command {
+        // The script calls back into the binary, which is how --task gets
+        // the ids of open tasks
+        let shells = Shells::builtins();
+        let completer = shells
+            .completer(&shell.to_string())
+            .ok_or_else(|| format!("No completions for {}", shell))?;
+        completer.write_registration(
+            "COMPLETE",
+            "vikunja-tui",
+            "vikunja-tui",
+            "vikunja-tui",
+            &mut io::stdout(),
+        )?;
+        return Ok(ExitCode::SUCCESS);
+    }
 
-#[derive(Deserialize)]
-struct Config {
-    vikunja: VikunjaConfig,
-}
+    // Demo mode runs against built-in sample data and needs no config file
+    let mut share_session = None;
+    let mut profile_name = None;
+    let mut link_task = None;
+    let (api, config): (Arc<dyn VikunjaApi>, Config) = if args.demo {
+        (Arc::new(DemoApi::new()), Config::demo())
+    } else if let Some(share) = &args.share {
+        // A full share link carries the instance url, so no config is needed
+        let (instance_url, hash) = parse_share_link(share);
+        let config = match load_config(args.config.as_deref()) {
+            Ok(config) => Some(config),
+            Err(err) if err.is::<NoConfigFile>() => None,
+            Err(err) => {
+                eprintln!("{}", err);
+                return Ok(ExitCode::FAILURE);
+            }
+        };
+        let instance_url = match (instance_url, &config) {
+            (Some(url), _) => url,
+            (None, Some(config)) => config.vikunja.instance_url.clone(),
+            (None, None) => {
+                return Err("--share needs the full share link without a config file".into())
+            }
+        };
+        let api = HttpClient::new(&instance_url, "");
+        share_session = Some(vikunja_client::auth::share_session(&api, &hash).await?);
+        let config = config.unwrap_or_else(|| Config::for_instance(&instance_url));
+        (Arc::new(api), config)
+    } else {
+        let config = match load_config(args.config.as_deref()) {
+            Ok(config) => config,
+            Err(err) => {
+                eprintln!("{}", err);
+                return Ok(ExitCode::FAILURE);
+            }
+        };
+        // A task link picks the profile of its instance
+        let link = match &args.link {
+            Some(link) => Some(
+                parse_task_link(link)
+                    .ok_or_else(|| format!("Not a link to a Vikunja task: {}", link))?,
+            ),
+            None => None,
+        };
+        let profile = match (&args.profile, &link) {
+            (Some(name), _) => config
+                .all_profiles()
+                .into_iter()
+                .find(|p| p.name == *name)
+                .ok_or_else(|| format!("No profile named '{}' in the config", name))?,
+            (None, Some((instance_url, _))) => config
+                .all_profiles()
+                .into_iter()
+                .find(|p| same_instance(&p.vikunja.instance_url, instance_url))
+                .ok_or_else(|| format!("{} is not an instance in the config", instance_url))?,
+            (None, None) => config.all_profiles().remove(0),
+        };
+        if let Some((instance_url, task_id)) = link {
+            if !same_instance(&profile.vikunja.instance_url, &instance_url) {
+                return Err(format!(
+                    "The link is on {}, not on profile '{}'",
+                    instance_url, profile.name
+                )
+                .into());
+            }
+            link_task = Some(task_id);
+        }
+        let api_key = profile.vikunja.resolve_api_key()?;
+        let api = HttpClient::new(&profile.vikunja.instance_url, &api_key);
+        if let Some(provider) = &profile.vikunja.oidc_provider {
+            let info = api.fetch_info().await?;
+            let token =
+                auth::oidc_token(&api, &profile.vikunja.instance_url, &info, provider).await?;
+            api.set_token(&token);
+        }
+        profile_name = Some(profile.name);
+        (Arc::new(api), config)
+    };
 
-fn load_config() -> Result<Config, Box<dyn std::error::Error>> {
-    let mut config_path: PathBuf = config_dir().expect("Could not determine config directory");
-    config_path.push("vikunja-tui/config.toml");
+    if let Some(Command::Report { days }) = &args.command {
+        print!("{}", report::review_report(api, *days).await?);
+        return Ok(ExitCode::SUCCESS);
+    }
 
-    // Read the config file
-    let config_content = fs::read_to_string(config_path)?;
+    let profiles = config.all_profiles();
+    let mut app = App::new(api, config.filters);
+    app.set_project_settings(config.projects);
+    app.confirm_done_bucket = config.confirm_done_bucket;
+    app.show_done_at = config.show_done_at;
+    app.terminal_title = config.terminal_title;
+    app.confirm_new_task = config.confirm_new_task;
+    app.default_due_time = config.default_due_time;
+    app.done_review_days = config.done_review_days;
+    app.label_color = config.label_color.clone();
+    app.timesheet = config.timesheet.clone();
+    app.row_format = config.row_format.clone();
+    app.calendar_feeds = config.calendars.clone();
+    // Only the interface draws on the terminal, so only it asks for the
+    // background
+    app.theme = config.theme.resolve(config.themes, || {
+        args.render.is_none() && args.command.is_none() && screen::light_background()
+    });
+    app.plain = args.plain;
+    // Switching instances only makes sense for a normal session
+    if let Some(name) = profile_name {
+        app.profiles = profiles;
+        app.profile = name;
+    }
 
-    // Parse the TOML content
-    let config: Config = toml::from_str(&config_content)?;
+    // What the start can't do without is fetched side by side rather than
+    // one request after another
+    let wants_projects = args.project.is_some() || share_session.is_some();
+    let wants_user = args.filter.as_deref() == Some(config::MINE_FILTER);
+    let api = app.api.clone();
+    let (info, projects, user) = tokio::join!(
+        api.fetch_info(),
+        async {
+            if wants_projects {
+                Some(api.fetch_projects().await)
+            } else {
+                None
+            }
+        },
+        async {
+            if wants_user {
+                Some(api.fetch_current_user().await)
+            } else {
+                None
+            }
+        },
+    );
+    if let Ok(info) = info {
+        app.set_server_info(info);
+    }
+    // A share link still opens its project when the list can't be read
+    match projects {
+        Some(Ok(projects)) => app.projects = projects,
+        Some(Err(err)) if args.project.is_some() => return Err(err),
+        _ => {}
+    }
 
-    Ok(config)
-}
+    if let Some(name) = &args.project {
+        match app.find_project(name) {
+            Ok(project) => app.enter_project(Some(project)),
+            Err(err) => {
+                eprintln!("{}", err);
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+    }
+    if let Some(share) = &share_session {
+        let project = app.projects.iter().find(|p| p.id == share.project_id);
+        let project = project.cloned().unwrap_or(Project {
+            id: share.project_id,
+            title: "Shared Project".to_string(),
+            ..Default::default()
+        });
+        app.enter_project(Some(project));
+    }
+    if let Some(name) = &args.filter {
+        match user {
+            Some(Ok(user)) => app.current_user = Some(user),
+            // Not knowing who is signed in leaves the list unfiltered
+            Some(Err(err)) => {
+                app.message = Some(format!(
+                    "Error fetching the current user, showing all tasks: {}",
+                    err
+                ))
+            }
+            None => {}
+        }
+        if name != config::MINE_FILTER || app.current_user.is_some() {
+            app.active_filter = Some(app.resolve_filter(name));
+        }
+    }
+    app.view_kind = args.view;
+    if app.view_kind == ViewKind::Kanban && !app.capabilities().project_views {
+        app.view_kind = ViewKind::List;
+        app.message = Some("The board needs Vikunja 0.24 or newer, showing the list".to_string());
+    }
+    app.read_only = args.read_only
+        || config.read_only
+        || share_session.as_ref().is_some_and(|share| share.read_only);
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let config = load_config().expect("Failed to load config file");
+    if let Some(Command::Add {
+        create_labels,
+        words,
+    }) = &args.command
+    {
+        let lines = match &words[..] {
+            [dash] if dash == "-" => io::stdin().lines().collect::<io::Result<Vec<_>>>()?,
+            _ => vec![words.join(" ")],
+        };
+        let mut failed = false;
+        for (line, outcome) in app.add_lines(&lines, *create_labels).await {
+            match outcome {
+                Ok(None) => println!("Added: {}", line),
+                Ok(Some(note)) => println!("Added: {} ({})", line, note),
+                Err(reason) => {
+                    eprintln!("Not added: {} ({})", line, reason);
+                    failed = true;
+                }
+            }
+        }
+        return Ok(if failed {
+            ExitCode::FAILURE
+        } else {
+            ExitCode::SUCCESS
+        });
+    }
 
-    let instance_url = config.vikunja.instance_url;
-    let api_key = config.vikunja.api_key;
+    // The tasks load once the screen is up, failures show on the status line
+    // and an expired token opens the sign-in form
+    app.refresh_tasks();
+    if let Some(task_id) = args.task.or(link_task) {
+        app.open_task(task_id);
+    }
+    app.warm_up();
 
-    let show_done_tasks = false;
+    if let Some((width, height)) = args.render {
+        let keys = parse_keys(args.keys.as_deref().unwrap_or_default())?;
+        print!("{}", render_headless(app, width, height, &keys).await?);
+        return Ok(ExitCode::SUCCESS);
+    }
 
-    let tasks = fetch_tasks(&instance_url, &api_key, 1).await?;
-    let tasks = if show_done_tasks {
-        tasks
-    } else {
-        tasks.into_iter().filter(|task| !task.done).collect()
-    };
+    // Only the interface keeps a draft, adding from the command line leaves
+    // it alone
+    if let Some(path) = state::state_path() {
+        app.load_state(path);
+    }
+    // Sample tasks would only get in the way of completing real ones
+    if !args.demo {
+        if let Some(path) = state::task_index_path() {
+            app.load_task_index(path);
+        }
+    }
 
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
+    // Terminals that keep a stack of titles get theirs back on exit
+    let terminal_title = app.terminal_title;
+    if terminal_title {
+        print!("\x1b[22;0t");
+    }
+    screen::enter()?;
+    let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend)?;
 
     terminal.hide_cursor()?;
 
-    let app = App::new(tasks);
-
-    let res = run_app(&mut terminal, app, &instance_url, &api_key).await;
+    let res = run_app(&mut terminal, app).await;
 
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    screen::leave()?;
     terminal.show_cursor()?;
+    if terminal_title {
+        print!("\x1b[23;0t");
+    }
 
     if let Err(err) = res {
         eprintln!("Error: {:?}", err);
     }
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
@@ -0,0 +1,124 @@
+use crate::picker::{select_next, select_previous};
+use ratatui::widgets::ListState;
+use vikunja_client::models::{Project, ProjectTeam, ProjectUser, Team, PERMISSIONS};
+
+// Prefix of a team name in the add input, plain names are usernames
+pub const TEAM_PREFIX: &str = "team:";
+
+// Someone a project is shared with, a user by username or a team by id
+#[derive(Clone, Debug, PartialEq)]
+pub enum Member {
+    User(String),
+    Team(u64),
+}
+
+// The users and teams a project is shared with, listed users first
+pub struct Members {
+    pub project: Project,
+    pub users: Vec<ProjectUser>,
+    pub teams: Vec<ProjectTeam>,
+    // The teams of the signed in user, the ones the project can go to
+    pub your_teams: Vec<Team>,
+    pub state: ListState,
+    // The username or team being typed to add, and the permission they get
+    pub adding: Option<String>,
+    pub right: u8,
+}
+
+impl Members {
+    pub fn new(
+        project: Project,
+        users: Vec<ProjectUser>,
+        teams: Vec<ProjectTeam>,
+        your_teams: Vec<Team>,
+    ) -> Members {
+        let mut state = ListState::default();
+        if !users.is_empty() || !teams.is_empty() {
+            state.select(Some(0));
+        }
+        Members {
+            project,
+            users,
+            teams,
+            your_teams,
+            state,
+            adding: None,
+            right: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.users.len() + self.teams.len()
+    }
+
+    pub fn down(&mut self) {
+        let len = self.len();
+        select_next(&mut self.state, len);
+    }
+
+    pub fn up(&mut self) {
+        let len = self.len();
+        select_previous(&mut self.state, len);
+    }
+
+    // Keeps the selection on the same row after a reload, or on the last one
+    pub fn select(&mut self, row: Option<usize>) {
+        let last = self.len().checked_sub(1);
+        self.state
+            .select(row.zip(last).map(|(row, last)| row.min(last)));
+    }
+
+    pub fn selected(&self) -> Option<(Member, String)> {
+        let i = self.state.selected()?;
+        match self.users.get(i) {
+            Some(shared) => Some((
+                Member::User(shared.user.username.clone()),
+                shared.user.username.clone(),
+            )),
+            None => {
+                let shared = self.teams.get(i - self.users.len())?;
+                Some((Member::Team(shared.team.id), shared.team.name.clone()))
+            }
+        }
+    }
+
+    pub fn cycle_right(&mut self) {
+        self.right = (self.right + 1) % PERMISSIONS.len() as u8;
+    }
+
+    // The member the add input names, teams by their name among yours
+    pub fn typed_member(&self) -> Result<Member, String> {
+        let text = self.adding.as_deref().unwrap_or_default().trim();
+        match text.strip_prefix(TEAM_PREFIX) {
+            Some(name) => self
+                .your_teams
+                .iter()
+                .find(|team| team.name.eq_ignore_ascii_case(name.trim()))
+                .map(|team| Member::Team(team.id))
+                .ok_or_else(|| format!("You are in no team named {}", name.trim())),
+            None if text.is_empty() => Err("Type a username or team:name".to_string()),
+            None => Ok(Member::User(text.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typed_member() {
+        let design = Team {
+            id: 7,
+            name: "Design".to_string(),
+            ..Default::default()
+        };
+        let mut members = Members::new(Project::default(), Vec::new(), Vec::new(), vec![design]);
+        members.adding = Some(" sam ".to_string());
+        assert_eq!(members.typed_member(), Ok(Member::User("sam".to_string())));
+        members.adding = Some("team:design".to_string());
+        assert_eq!(members.typed_member(), Ok(Member::Team(7)));
+        members.adding = Some("team:Ops".to_string());
+        assert!(members.typed_member().is_err());
+    }
+}
@@ -1,41 +1,224 @@
+use crate::calendar::{parse_date_time, today, TypedDate};
 use regex::Regex;
+use std::ops::Range;
+use std::sync::OnceLock;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct ParsedTask {
     pub title: String,
     pub priority: Option<u8>,
+    pub labels: Vec<String>,
+    pub assignees: Vec<String>,
+    // The project to add the task to, as typed after the +
+    pub project: Option<String>,
+    // Text in braces, like {bring the old passport}
+    pub description: Option<String>,
+    pub start_date: Option<TypedDate>,
+    pub end_date: Option<TypedDate>,
+    pub due_date: Option<TypedDate>,
+    pub reminders: Vec<TypedDate>,
 }
 
 pub fn parse_task_input(input: &str) -> ParsedTask {
-    let priority_re = Regex::new(r"!(\d+)\s*").unwrap();
+    let mut parsed = ParsedTask::default();
+    let mut title = String::new();
+    let mut end = 0;
 
-    let mut priority = None;
-
-    // Priority
-    for caps in priority_re.captures_iter(input) {
-        if let Some(priority_match) = caps.get(1) {
-            if let Ok(p) = priority_match.as_str().parse::<u8>() {
-                if (1..=5).contains(&p) && priority.is_none() {
-                    priority = Some(p);
+    // Tokens are cut out of the title, the value follows the sigil
+    for (range, kind) in magic_tokens(input) {
+        title.push_str(&input[end..range.start]);
+        title.push(' ');
+        end = range.end;
+        let value = &input[range.start + 1..range.end];
+        match kind {
+            TokenKind::Priority => parsed.priority = value.parse().ok(),
+            TokenKind::Label => parsed.labels.push(value.trim_matches('"').to_string()),
+            TokenKind::Assignee => parsed.assignees.push(value.to_string()),
+            TokenKind::Project => parsed.project = Some(value.trim_matches('"').to_string()),
+            TokenKind::Description => {
+                let text = value.strip_suffix('}').unwrap_or(value).trim();
+                parsed.description = Some(match parsed.description.take() {
+                    Some(before) => format!("{}\n{}", before, text),
+                    None => text.to_string(),
+                });
+            }
+            TokenKind::Date => {
+                let (key, date) = input[range].split_once(':').unwrap_or_default();
+                let date = parse_date_time(date.trim_start(), today());
+                match key.to_lowercase().as_str() {
+                    "start" => parsed.start_date = date,
+                    "end" => parsed.end_date = date,
+                    "remind" => parsed.reminders.extend(date),
+                    _ => parsed.due_date = date,
                 }
             }
+            TokenKind::Ignored => {}
         }
     }
+    title.push_str(&input[end..]);
+
+    parsed.title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    parsed
+}
 
-    let title = priority_re.replace_all(input, "").into_owned();
+// What a magic token in the input does, for highlighting while typing
+#[derive(Clone, Debug, PartialEq)]
+pub enum TokenKind {
+    Priority,
+    // *label, or *"label with spaces"
+    Label,
+    // @username
+    Assignee,
+    // +project, or +"project with spaces"
+    Project,
+    // {description}
+    Description,
+    // start:<date>, end:<date>, due:<date> or remind:<date>, optionally
+    // followed by a time like "due:friday 9am". A space after the colon is
+    // fine when a date follows.
+    Date,
+    // Removed from the title without effect: out of range, or after the
+    // priority was already set
+    Ignored,
+}
 
-    let title = Regex::new(r"\s+")
-        .unwrap()
-        .replace_all(&title, " ")
-        .trim()
-        .to_string();
+struct TokenPatterns {
+    priority: Regex,
+    label: Regex,
+    assignee: Regex,
+    project: Regex,
+    description: Regex,
+    date: Regex,
+}
 
-    ParsedTask { title, priority }
+// Compiled once, as the quick add preview reads the input on every keystroke
+fn token_patterns() -> &'static TokenPatterns {
+    static PATTERNS: OnceLock<TokenPatterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| TokenPatterns {
+        priority: Regex::new(r"!(\d+)").unwrap(),
+        // Labels and assignees only start at a word, so emails stay intact
+        label: Regex::new(r#"(?:^|\s)(\*(?:"[^"]+"|\S+))"#).unwrap(),
+        assignee: Regex::new(r"(?:^|\s)(@\S+)").unwrap(),
+        project: Regex::new(r#"(?:^|\s)(\+(?:"[^"]+"|\S+))"#).unwrap(),
+        description: Regex::new(r"(\{[^}]*\}?)").unwrap(),
+        date: Regex::new(
+            r"(?i)(?:^|\s)((?:start|end|due|remind):(\s?)(\S+(?:\s+(?:\d{1,2}(?::\d{2})?\s?[ap]m|\d{1,2}:\d{2})\b)?))",
+        )
+        .unwrap(),
+    })
+}
+
+// Byte ranges of the magic tokens in the input, in order
+pub fn magic_tokens(input: &str) -> Vec<(Range<usize>, TokenKind)> {
+    let patterns = token_patterns();
+    let mut tokens: Vec<(Range<usize>, TokenKind)> = Vec::new();
+    for caps in patterns.priority.captures_iter(input) {
+        let valid = caps[1].parse::<u8>().is_ok_and(|p| (1..=5).contains(&p));
+        let kind = if valid {
+            TokenKind::Priority
+        } else {
+            TokenKind::Ignored
+        };
+        tokens.push((caps.get(0).unwrap().range(), kind));
+    }
+    for (re, kind) in [
+        (&patterns.label, TokenKind::Label),
+        (&patterns.assignee, TokenKind::Assignee),
+        (&patterns.project, TokenKind::Project),
+        (&patterns.description, TokenKind::Description),
+    ] {
+        for caps in re.captures_iter(input) {
+            tokens.push((caps.get(1).unwrap().range(), kind.clone()));
+        }
+    }
+    for caps in patterns.date.captures_iter(input) {
+        let kind = match parse_date_time(&caps[3], today()) {
+            Some(_) => TokenKind::Date,
+            // Like "remind: me later", which is just text
+            None if !caps[2].is_empty() => continue,
+            None => TokenKind::Ignored,
+        };
+        tokens.push((caps.get(1).unwrap().range(), kind));
+    }
+    tokens.sort_by_key(|(range, _)| range.start);
+
+    // Only the first priority counts, and a token inside another is dropped
+    let mut has_priority = false;
+    let mut end = 0;
+    let mut result = Vec::new();
+    for (range, mut kind) in tokens {
+        if range.start < end {
+            continue;
+        }
+        if kind == TokenKind::Priority {
+            if has_priority {
+                kind = TokenKind::Ignored;
+            }
+            has_priority = true;
+        }
+        end = range.end;
+        result.push((range, kind));
+    }
+    result
+}
+
+// The token being typed at the end of the input when it is a label, project
+// or assignee: its offset, the sigil and what follows the sigil so far
+pub fn completion_token(input: &str) -> Option<(usize, char, &str)> {
+    input.char_indices().rev().find_map(|(i, c)| {
+        let at_word_start = i == 0 || input[..i].ends_with(char::is_whitespace);
+        if !matches!(c, '*' | '+' | '@') || !at_word_start {
+            return None;
+        }
+        let rest = &input[i + 1..];
+        // An open quote allows spaces until it is closed
+        if let Some(quoted) = rest.strip_prefix('"') {
+            return (!quoted.contains('"')).then_some((i, c, quoted));
+        }
+        (!rest.contains(char::is_whitespace)).then_some((i, c, rest))
+    })
+}
+
+// A task as people write it: `#12` or `PRJ-12`
+#[derive(Clone, Debug, PartialEq)]
+pub enum TaskRef {
+    Index(u64),
+    // Project identifier and index within that project
+    Identifier(String, u64),
+}
+
+pub fn parse_task_ref(input: &str) -> Option<TaskRef> {
+    let input = input.trim();
+    let index = input.strip_prefix('#').unwrap_or(input);
+    if let Ok(index) = index.parse() {
+        return Some(TaskRef::Index(index));
+    }
+    let (project, index) = input.rsplit_once('-')?;
+    let valid = !project.is_empty() && project.chars().all(char::is_alphanumeric);
+    Some(TaskRef::Identifier(
+        project.to_string(),
+        index.parse().ok()?,
+    ))
+    .filter(|_| valid)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::{NaiveDate, NaiveTime};
+
+    #[test]
+    fn test_parse_task_ref() {
+        assert_eq!(parse_task_ref("#12"), Some(TaskRef::Index(12)));
+        assert_eq!(parse_task_ref(" 12 "), Some(TaskRef::Index(12)));
+        assert_eq!(
+            parse_task_ref("WRK-4"),
+            Some(TaskRef::Identifier("WRK".to_string(), 4))
+        );
+        assert_eq!(parse_task_ref("WRK-"), None);
+        assert_eq!(parse_task_ref("-4"), None);
+        assert_eq!(parse_task_ref("my task"), None);
+    }
 
     #[test]
     fn test_parse_with_priority_in_middle() {
@@ -43,6 +226,7 @@ mod tests {
         let expected = ParsedTask {
             title: "Update software documentation".to_string(),
             priority: Some(4),
+            ..Default::default()
         };
         let result = parse_task_input(input);
         assert_eq!(result, expected);
@@ -54,6 +238,7 @@ mod tests {
         let expected = ParsedTask {
             title: "Fix bugs in the code".to_string(),
             priority: Some(2),
+            ..Default::default()
         };
         let result = parse_task_input(input);
         assert_eq!(result, expected);
@@ -65,6 +250,7 @@ mod tests {
         let expected = ParsedTask {
             title: "Write tests for the parser".to_string(),
             priority: Some(3),
+            ..Default::default()
         };
         let result = parse_task_input(input);
         assert_eq!(result, expected);
@@ -76,6 +262,7 @@ mod tests {
         let expected = ParsedTask {
             title: "Deploy to production".to_string(),
             priority: Some(5),
+            ..Default::default()
         };
         let result = parse_task_input(input);
         assert_eq!(result, expected);
@@ -87,6 +274,7 @@ mod tests {
         let expected = ParsedTask {
             title: "Prepare presentation slides".to_string(),
             priority: Some(2),
+            ..Default::default()
         };
         let result = parse_task_input(input);
         assert_eq!(result, expected);
@@ -98,6 +286,7 @@ mod tests {
         let expected = ParsedTask {
             title: "Organize team building event".to_string(),
             priority: Some(1),
+            ..Default::default()
         };
         let result = parse_task_input(input);
         assert_eq!(result, expected);
@@ -109,8 +298,121 @@ mod tests {
         let expected = ParsedTask {
             title: "Check logs immediately".to_string(),
             priority: None,
+            ..Default::default()
         };
         let result = parse_task_input(input);
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn test_magic_tokens_mark_ignored_priorities() {
+        let tokens = magic_tokens("Call !9 mom !2 !3");
+        assert_eq!(
+            tokens,
+            vec![
+                (5..7, TokenKind::Ignored),
+                (12..14, TokenKind::Priority),
+                (15..17, TokenKind::Ignored),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_labels_and_assignees() {
+        let input = r#"Fix *bug the login *"In Review" @alex page, mail bob@example.com"#;
+        let result = parse_task_input(input);
+        assert_eq!(result.title, "Fix the login page, mail bob@example.com");
+        assert_eq!(result.labels, vec!["bug", "In Review"]);
+        assert_eq!(result.assignees, vec!["alex"]);
+    }
+
+    #[test]
+    fn test_parse_project() {
+        let result = parse_task_input(r#"Buy milk +groceries 1+1"#);
+        assert_eq!(result.title, "Buy milk 1+1");
+        assert_eq!(result.project.as_deref(), Some("groceries"));
+        let result = parse_task_input(r#"+"Day Job" Send invoice"#);
+        assert_eq!(result.project.as_deref(), Some("Day Job"));
+    }
+
+    #[test]
+    fn test_parse_start_and_end_dates() {
+        let result = parse_task_input("Offsite start:2024-06-12 END:2024-06-14 restart:x");
+        assert_eq!(result.title, "Offsite restart:x");
+        let date = |typed: Option<TypedDate>| typed.map(|typed| typed.date);
+        assert_eq!(
+            date(result.start_date),
+            NaiveDate::from_ymd_opt(2024, 6, 12)
+        );
+        assert_eq!(date(result.end_date), NaiveDate::from_ymd_opt(2024, 6, 14));
+        let tokens = magic_tokens("Offsite start:someday");
+        assert_eq!(tokens[0].1, TokenKind::Ignored);
+    }
+
+    #[test]
+    fn test_parse_due_with_time() {
+        let result = parse_task_input("Call the bank due:2024-05-01 9am about the loan");
+        assert_eq!(result.title, "Call the bank about the loan");
+        let due = result.due_date.unwrap();
+        assert_eq!(due.date, NaiveDate::from_ymd_opt(2024, 5, 1).unwrap());
+        assert_eq!(due.time, NaiveTime::from_hms_opt(9, 0, 0));
+
+        let result = parse_task_input("Ship it due:2024-05-01T17:30");
+        assert_eq!(result.title, "Ship it");
+        assert_eq!(
+            result.due_date.unwrap().time,
+            NaiveTime::from_hms_opt(17, 30, 0)
+        );
+
+        // Without a time, the configured default applies when the task is sent
+        let result = parse_task_input("Ship it due:2024-05-01 10 boxes");
+        assert_eq!(result.title, "Ship it 10 boxes");
+        assert_eq!(result.due_date.unwrap().time, None);
+    }
+
+    #[test]
+    fn test_parse_description_in_braces() {
+        let result = parse_task_input("Renew passport {bring old passport and photos} !3");
+        assert_eq!(result.title, "Renew passport");
+        assert_eq!(result.priority, Some(3));
+        assert_eq!(
+            result.description.as_deref(),
+            Some("bring old passport and photos")
+        );
+        // Still being typed
+        let result = parse_task_input("Call mum {about *sunday");
+        assert_eq!(result.description.as_deref(), Some("about *sunday"));
+        assert!(result.labels.is_empty());
+    }
+
+    #[test]
+    fn test_parse_reminders() {
+        let result =
+            parse_task_input("Pay rent remind: 2024-05-01 9am remind:2024-05-02 remind: me later");
+        assert_eq!(result.title, "Pay rent remind: me later");
+        let reminders: Vec<_> = result.reminders.iter().map(|r| (r.date, r.time)).collect();
+        assert_eq!(
+            reminders,
+            vec![
+                (
+                    NaiveDate::from_ymd_opt(2024, 5, 1).unwrap(),
+                    NaiveTime::from_hms_opt(9, 0, 0)
+                ),
+                (NaiveDate::from_ymd_opt(2024, 5, 2).unwrap(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_completion_token() {
+        assert_eq!(completion_token("Buy *gro"), Some((4, '*', "gro")));
+        assert_eq!(
+            completion_token(r#"Buy *"Side pro"#),
+            Some((4, '*', "Side pro"))
+        );
+        assert_eq!(completion_token("Buy +Wo"), Some((4, '+', "Wo")));
+        assert_eq!(completion_token("Ask @"), Some((4, '@', "")));
+        assert_eq!(completion_token("Ask @sam now"), None);
+        assert_eq!(completion_token("mail bob@example"), None);
+    }
 }
@@ -0,0 +1,86 @@
+use crate::fuzzy::fuzzy_filter;
+use ratatui::style::Color;
+use ratatui::widgets::ListState;
+
+// Moves the selection of a list `len` rows long one row down, from the last
+// row back to the first
+pub fn select_next(state: &mut ListState, len: usize) {
+    if let Some(i) = state.selected().filter(|_| len > 0) {
+        state.select(Some((i + 1) % len));
+    }
+}
+
+// One row up, from the first row to the last
+pub fn select_previous(state: &mut ListState, len: usize) {
+    if let Some(i) = state.selected().filter(|_| len > 0) {
+        state.select(Some((i + len - 1) % len));
+    }
+}
+
+// Fuzzy-matching selection list used by the project switcher and the task finder
+pub struct Picker {
+    pub query: String,
+    pub candidates: Vec<String>,
+    // A colored dot in front of each candidate, when not empty
+    pub colors: Vec<Option<Color>>,
+    pub matches: Vec<usize>,
+    pub state: ListState,
+}
+
+impl Picker {
+    pub fn new(candidates: Vec<String>) -> Picker {
+        let mut picker = Picker {
+            query: String::new(),
+            candidates,
+            colors: Vec::new(),
+            matches: Vec::new(),
+            state: ListState::default(),
+        };
+        picker.update_matches();
+        picker
+    }
+
+    pub fn with_colors(mut self, colors: Vec<Option<Color>>) -> Picker {
+        self.colors = colors;
+        self
+    }
+
+    pub fn set_query(&mut self, query: &str) {
+        if self.query != query {
+            self.query = query.to_string();
+            self.update_matches();
+        }
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.update_matches();
+    }
+
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.update_matches();
+    }
+
+    fn update_matches(&mut self) {
+        self.matches = fuzzy_filter(&self.query, &self.candidates);
+        if self.matches.is_empty() {
+            self.state.select(None);
+        } else {
+            self.state.select(Some(0));
+        }
+    }
+
+    pub fn next(&mut self) {
+        select_next(&mut self.state, self.matches.len());
+    }
+
+    pub fn previous(&mut self) {
+        select_previous(&mut self.state, self.matches.len());
+    }
+
+    // Index into the original candidate list
+    pub fn selected(&self) -> Option<usize> {
+        self.state.selected().map(|i| self.matches[i])
+    }
+}
@@ -0,0 +1,161 @@
+use chrono::{DateTime, Duration, Local};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use vikunja_client::api::{fetch_all_tasks, ApiResult, LoadProgress, TaskQuery, VikunjaApi};
+use vikunja_client::models::{Project, Task};
+
+// The date a report section sorts and lists its tasks by
+type TaskDate = fn(&Task) -> Option<DateTime<Local>>;
+
+fn filtered(filter: String) -> TaskQuery {
+    TaskQuery {
+        filter: Some(filter),
+        ..Default::default()
+    }
+}
+
+fn created_at(task: &Task) -> Option<DateTime<Local>> {
+    let created = DateTime::parse_from_rfc3339(task.created.as_deref()?).ok()?;
+    Some(created.with_timezone(&Local))
+}
+
+// Fetches what was done and created in the last `days` and what is overdue,
+// each query paged through side by side, and writes them up as Markdown
+pub async fn review_report(api: Arc<dyn VikunjaApi>, days: u32) -> ApiResult<String> {
+    let progress = || Arc::new(LoadProgress::default());
+    let (projects, done, created, overdue) = tokio::try_join!(
+        api.fetch_projects(),
+        fetch_all_tasks(
+            api.clone(),
+            filtered(format!("done = true && done_at > now-{}d", days)),
+            progress()
+        ),
+        fetch_all_tasks(
+            api.clone(),
+            filtered(format!("created > now-{}d", days)),
+            progress()
+        ),
+        fetch_all_tasks(
+            api.clone(),
+            filtered("done = false && due_date < now".to_string()),
+            progress()
+        ),
+    )?;
+    Ok(report_markdown(
+        &projects,
+        [done, created, overdue],
+        Local::now(),
+        days,
+    ))
+}
+
+// The dates are checked again here, servers that don't know a filter field
+// leave it out of the query instead of failing
+pub fn report_markdown(
+    projects: &[Project],
+    [done, created, overdue]: [Vec<Task>; 3],
+    now: DateTime<Local>,
+    days: u32,
+) -> String {
+    let since = now - Duration::days(days as i64);
+    let done: Vec<Task> = done
+        .into_iter()
+        .filter(|task| task.done && task.completed_at().is_some_and(|at| at > since))
+        .collect();
+    let created: Vec<Task> = created
+        .into_iter()
+        .filter(|task| created_at(task).is_some_and(|at| at > since))
+        .collect();
+    let overdue: Vec<Task> = overdue
+        .into_iter()
+        .filter(|task| task.is_overdue(now))
+        .collect();
+
+    let mut lines = vec![format!(
+        "# Review of {} to {}",
+        since.format("%Y-%m-%d"),
+        now.format("%Y-%m-%d")
+    )];
+    let sections: [(&str, &[Task], TaskDate, &str); 3] = [
+        ("Completed", &done, Task::completed_at, "done"),
+        ("Created", &created, created_at, "created"),
+        ("Overdue", &overdue, Task::due_at, "due"),
+    ];
+    for (name, tasks, date, date_name) in sections {
+        lines.push(String::new());
+        lines.push(format!("## {} ({})", name, tasks.len()));
+        if tasks.is_empty() {
+            lines.push(String::new());
+            lines.push("Nothing.".to_string());
+        }
+        let mut by_project: BTreeMap<String, Vec<&Task>> = BTreeMap::new();
+        for task in tasks {
+            let title = match projects.iter().find(|p| p.id == task.project_id) {
+                Some(project) => project.title.clone(),
+                None => format!("Project {}", task.project_id),
+            };
+            by_project.entry(title).or_default().push(task);
+        }
+        for (project, mut tasks) in by_project {
+            tasks.sort_by_key(|task| date(task));
+            lines.push(String::new());
+            lines.push(format!("### {}", project));
+            lines.push(String::new());
+            for task in tasks {
+                let mut line = if task.identifier.is_empty() {
+                    format!("- {}", task.title)
+                } else {
+                    format!("- {} {}", task.identifier, task.title)
+                };
+                if let Some(at) = date(task) {
+                    line.push_str(&format!(" ({} {})", date_name, at.format("%Y-%m-%d")));
+                }
+                lines.push(line);
+            }
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_report_markdown() {
+        let now = Local.with_ymd_and_hms(2024, 5, 8, 12, 0, 0).unwrap();
+        let task = |id, title: &str, project_id, date: &str| Task {
+            id,
+            title: title.to_string(),
+            project_id,
+            done: true,
+            done_at: Some(date.to_string()),
+            created: Some(date.to_string()),
+            ..Default::default()
+        };
+        let projects = [Project {
+            id: 1,
+            title: "Work".to_string(),
+            ..Default::default()
+        }];
+        let done = vec![
+            task(1, "Ship release", 1, "2024-05-06T10:00:00Z"),
+            task(2, "Old chore", 1, "2024-04-01T10:00:00Z"),
+            task(3, "Milk", 2, "2024-05-07T10:00:00Z"),
+        ];
+        assert_eq!(
+            report_markdown(&projects, [done, Vec::new(), Vec::new()], now, 7),
+            "# Review of 2024-05-01 to 2024-05-08\n\n\
+             ## Completed (2)\n\n\
+             ### Project 2\n\n\
+             - Milk (done 2024-05-07)\n\n\
+             ### Work\n\n\
+             - Ship release (done 2024-05-06)\n\n\
+             ## Created (0)\n\n\
+             Nothing.\n\n\
+             ## Overdue (0)\n\n\
+             Nothing.\n"
+        );
+    }
+}
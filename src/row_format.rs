@@ -0,0 +1,159 @@
+use chrono::format::{Item, StrftimeItems};
+use std::sync::OnceLock;
+
+// How due dates show when the template gives no format
+pub const DEFAULT_DUE_FORMAT: &str = "%Y-%m-%d";
+
+// The rows without a row_format. Plain mode spells out what the symbols show.
+const DEFAULT_ROW_FORMAT: &str = "{color} {done} {blocked} {identifier} {priority:badge} {title} \
+     {repeat} {progress} {counts}  {assignees}";
+const PLAIN_ROW_FORMAT: &str =
+    "{done} {priority} {blocked} {repeat} {identifier} {title} {assignees} {progress} {counts}";
+
+// What a `{field}` in a row format stands for
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Field {
+    Identifier,
+    Title,
+    Done,
+    Priority,
+    Blocked,
+    Repeat,
+    Due,
+    Labels,
+    Assignees,
+    Progress,
+    Counts,
+    Color,
+}
+
+const FIELDS: &[(&str, Field)] = &[
+    ("identifier", Field::Identifier),
+    ("title", Field::Title),
+    ("done", Field::Done),
+    ("priority", Field::Priority),
+    ("blocked", Field::Blocked),
+    ("repeat", Field::Repeat),
+    ("due", Field::Due),
+    ("labels", Field::Labels),
+    ("assignees", Field::Assignees),
+    ("progress", Field::Progress),
+    ("counts", Field::Counts),
+    ("color", Field::Color),
+];
+
+// A piece of a row format: text as written, or a field with the date format
+// after its colon, like `{due:%m-%d}`
+#[derive(Clone, Debug, PartialEq)]
+pub enum RowPart {
+    Text(String),
+    Field(Field, Option<String>),
+}
+
+// Parses a list row template like "{identifier} {title} {due:%m-%d}".
+// `{{` and `}}` are literal braces.
+pub fn parse_row_format(template: &str) -> Result<Vec<RowPart>, String> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let mut spec = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => spec.push(c),
+                        None => return Err(format!("'{{{}' is missing its '}}'", spec)),
+                    }
+                }
+                if !text.is_empty() {
+                    parts.push(RowPart::Text(std::mem::take(&mut text)));
+                }
+                parts.push(parse_field(&spec)?);
+            }
+            '}' => return Err("'}' without a '{' before it, write '}}' for a brace".to_string()),
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        parts.push(RowPart::Text(text));
+    }
+    Ok(parts)
+}
+
+pub fn default_row(plain: bool) -> &'static [RowPart] {
+    static DEFAULT: OnceLock<Vec<RowPart>> = OnceLock::new();
+    static PLAIN: OnceLock<Vec<RowPart>> = OnceLock::new();
+    let (row, template) = if plain {
+        (&PLAIN, PLAIN_ROW_FORMAT)
+    } else {
+        (&DEFAULT, DEFAULT_ROW_FORMAT)
+    };
+    row.get_or_init(|| parse_row_format(template).expect("the default rows parse"))
+}
+
+fn parse_field(spec: &str) -> Result<RowPart, String> {
+    let (name, format) = match spec.split_once(':') {
+        Some((name, format)) => (name.trim(), Some(format)),
+        None => (spec.trim(), None),
+    };
+    let Some(&(_, field)) = FIELDS.iter().find(|(known, _)| *known == name) else {
+        let known: Vec<&str> = FIELDS.iter().map(|(known, _)| *known).collect();
+        return Err(format!(
+            "unknown field '{{{}}}', the fields are {}",
+            name,
+            known.join(", ")
+        ));
+    };
+    if let Some(format) = format {
+        if field == Field::Priority && format == "badge" {
+            return Ok(RowPart::Field(field, Some(format.to_string())));
+        }
+        if field != Field::Due {
+            return Err(format!(
+                "'{{{}}}' takes no format, only due and priority:badge do",
+                name
+            ));
+        }
+        // A bad specifier would only fail once a row is drawn
+        if StrftimeItems::new(format).any(|item| item == Item::Error) {
+            return Err(format!("'{}' is not a date format like %m-%d", format));
+        }
+    }
+    Ok(RowPart::Field(field, format.map(str::to_string)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_row_format() {
+        assert_eq!(
+            parse_row_format("{identifier} {title} {due:%m-%d} {{x}}"),
+            Ok(vec![
+                RowPart::Field(Field::Identifier, None),
+                RowPart::Text(" ".to_string()),
+                RowPart::Field(Field::Title, None),
+                RowPart::Text(" ".to_string()),
+                RowPart::Field(Field::Due, Some("%m-%d".to_string())),
+                RowPart::Text(" {x}".to_string()),
+            ])
+        );
+        assert!(parse_row_format("{title").is_err());
+        assert!(parse_row_format("{name}").is_err());
+        assert!(parse_row_format("{title:%d}").is_err());
+        assert!(parse_row_format("{priority:badge}").is_ok());
+        assert!(parse_row_format("{priority:%d}").is_err());
+        assert!(parse_row_format("{due:%Q}").is_err());
+    }
+}
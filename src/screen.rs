@@ -0,0 +1,118 @@
+use crossterm::{
+    event::{KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags},
+    execute,
+    terminal::{
+        disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
+};
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Whether the terminal took the keyboard flags, they are popped again when the
+// screen is handed back
+static ENHANCED: AtomicBool = AtomicBool::new(false);
+
+// Takes over the terminal. Terminals speaking the kitty keyboard protocol are
+// asked to tell keys like Shift+Enter and Ctrl+Enter apart from Enter and to
+// report key releases, the others keep sending the legacy codes.
+pub fn enter() -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    if supports_keyboard_enhancement().unwrap_or(false) {
+        execute!(
+            io::stdout(),
+            PushKeyboardEnhancementFlags(
+                KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                    | KeyboardEnhancementFlags::REPORT_EVENT_TYPES
+            )
+        )?;
+        ENHANCED.store(true, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+// Gives the terminal back the way it was before enter
+pub fn leave() -> io::Result<()> {
+    if ENHANCED.swap(false, Ordering::Relaxed) {
+        execute!(io::stdout(), PopKeyboardEnhancementFlags)?;
+    }
+    execute!(io::stdout(), LeaveAlternateScreen)?;
+    disable_raw_mode()
+}
+
+// How long the terminal gets to tell its background color
+#[cfg(unix)]
+const BACKGROUND_ANSWER_MS: i32 = 100;
+
+// Whether the terminal has a light background. It is asked with OSC 11, and
+// terminals that don't answer in time fall back to COLORFGBG, then dark.
+pub fn light_background() -> bool {
+    #[cfg(unix)]
+    if let Some(answer) = query_background() {
+        if let Some(light) = crate::color::is_light_answer(&answer) {
+            return light;
+        }
+    }
+    // Like "15;0", the last number is the background's palette index
+    let colorfgbg = std::env::var("COLORFGBG").unwrap_or_default();
+    matches!(colorfgbg.rsplit(';').next(), Some("7" | "15"))
+}
+
+#[cfg(unix)]
+fn query_background() -> Option<String> {
+    use std::io::{IsTerminal, Read, Write};
+    use std::os::fd::AsRawFd;
+
+    if !io::stdout().is_terminal() {
+        return None;
+    }
+    let mut tty = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+    enable_raw_mode().ok()?;
+    let mut answer = Vec::new();
+    // The device attributes are asked for after the color. Terminals answer
+    // that one whether or not they know OSC 11, and in order, so once its
+    // answer is in no answer about the color can come after the UI starts.
+    let mut attributes = None;
+    if tty.write_all(b"\x1b]11;?\x07\x1b[c").is_ok() && tty.flush().is_ok() {
+        let mut poll = libc::pollfd {
+            fd: tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // Reading only once poll says there is something keeps a silent
+        // terminal from swallowing the first key
+        while attributes.is_none() {
+            // SAFETY: poll gets one valid pollfd for the duration of the call
+            if unsafe { libc::poll(&mut poll, 1, BACKGROUND_ANSWER_MS) } <= 0 {
+                break;
+            }
+            let mut buf = [0; 64];
+            match tty.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => answer.extend_from_slice(&buf[..n]),
+            }
+            attributes = attributes_answer(&answer);
+        }
+    }
+    if attributes.is_none() {
+        // Whatever part of an answer came is dropped rather than read as keys
+        // SAFETY: tcflush only takes the descriptor of the open tty
+        unsafe { libc::tcflush(tty.as_raw_fd(), libc::TCIFLUSH) };
+    }
+    disable_raw_mode().ok()?;
+    answer.truncate(attributes.unwrap_or(answer.len()));
+    Some(String::from_utf8_lossy(&answer).into_owned())
+}
+
+// Where the answer to the device attributes query, like "\x1b[?62;22c",
+// starts once it is complete
+#[cfg(unix)]
+fn attributes_answer(answer: &[u8]) -> Option<usize> {
+    let start = answer.windows(3).position(|w| w == b"\x1b[?")?;
+    answer[start..].contains(&b'c').then_some(start)
+}
@@ -0,0 +1,167 @@
+use crate::history::InputHistory;
+use dirs::data_dir;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use vikunja_client::models::Task;
+
+// How many open tasks the index for shell completion keeps
+const TASK_INDEX_LIMIT: usize = 500;
+
+// What the add popup held when it was closed without creating the task
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Draft {
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+// What the interface keeps between sessions, next to the login token
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct State {
+    #[serde(default)]
+    pub draft: Option<Draft>,
+    // What was typed into `/`, `:` and the add popup's title
+    #[serde(default)]
+    pub search_history: InputHistory,
+    #[serde(default)]
+    pub command_history: InputHistory,
+    #[serde(default)]
+    pub title_history: InputHistory,
+}
+
+// Open tasks seen in the list, newest first, which the shell completes
+// `--task` from without asking the server
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(transparent)]
+pub struct TaskIndex {
+    pub tasks: Vec<IndexedTask>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct IndexedTask {
+    pub id: u64,
+    pub title: String,
+}
+
+pub fn state_path() -> Option<PathBuf> {
+    let mut path = data_dir()?;
+    path.push("vikunja-tui/state.json");
+    Some(path)
+}
+
+pub fn task_index_path() -> Option<PathBuf> {
+    let mut path = data_dir()?;
+    path.push("vikunja-tui/tasks.json");
+    Some(path)
+}
+
+// Written next to the file and renamed over it, so a crash part way leaves
+// the last complete version behind
+fn replace_file(path: &Path, text: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let partial = path.with_extension("json.partial");
+    fs::write(&partial, text)?;
+    fs::rename(partial, path)
+}
+
+impl State {
+    // A missing or unreadable file starts over with nothing kept
+    pub fn load(path: &Path) -> State {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        replace_file(path, &serde_json::to_string_pretty(self)?)
+    }
+}
+
+impl TaskIndex {
+    pub fn load(path: &Path) -> TaskIndex {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        replace_file(path, &serde_json::to_string(self)?)
+    }
+
+    // Puts the open ones of the loaded tasks first and drops the done ones.
+    // Returns whether anything changed, so an unchanged page isn't written.
+    pub fn update(&mut self, loaded: &[Task]) -> bool {
+        let mut tasks: Vec<IndexedTask> = loaded
+            .iter()
+            .filter(|task| !task.done)
+            .map(|task| IndexedTask {
+                id: task.id,
+                title: task.title.clone(),
+            })
+            .collect();
+        tasks.extend(
+            self.tasks
+                .iter()
+                .filter(|indexed| loaded.iter().all(|task| task.id != indexed.id))
+                .cloned(),
+        );
+        tasks.truncate(TASK_INDEX_LIMIT);
+        let changed = tasks != self.tasks;
+        self.tasks = tasks;
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_replaces_the_state_whole() {
+        let dir = std::env::temp_dir().join(format!("vikunja-tui-state-{}", std::process::id()));
+        let path = dir.join("state.json");
+        let mut state = State {
+            draft: Some(Draft {
+                title: "Call the bank".to_string(),
+                description: String::new(),
+            }),
+            ..Default::default()
+        };
+        state.command_history.add("archive milk");
+        state.save(&path).unwrap();
+        state.draft = None;
+        state.save(&path).unwrap();
+
+        let loaded = State::load(&path);
+        assert_eq!(loaded.draft, None);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::write(&path, "{\"draft\": {\"title\"").unwrap();
+        assert!(State::load(&path).draft.is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_task_index_keeps_open_tasks_newest_first() {
+        let task = |id: u64, title: &str, done: bool| Task {
+            id,
+            title: title.to_string(),
+            done,
+            ..Default::default()
+        };
+        let mut index = TaskIndex::default();
+        assert!(index.update(&[task(1, "Buy milk", false), task(2, "Call mom", false)]));
+        assert!(!index.update(&[task(1, "Buy milk", false)]));
+
+        // Done tasks leave the index, renamed ones take the new title
+        assert!(index.update(&[task(3, "Pay rent", false), task(2, "Call dad", false)]));
+        assert!(index.update(&[task(1, "Buy milk", true)]));
+        let titles: Vec<&str> = index.tasks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, ["Pay rent", "Call dad"]);
+    }
+}
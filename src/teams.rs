@@ -0,0 +1,65 @@
+use crate::picker::{select_next, select_previous};
+use ratatui::widgets::ListState;
+use vikunja_client::models::Team;
+
+// What is being typed in the teams view
+pub enum TeamInput {
+    NewTeam(String),
+    // A username to add to the selected team
+    AddMember(String),
+}
+
+impl TeamInput {
+    pub fn text_mut(&mut self) -> &mut String {
+        match self {
+            TeamInput::NewTeam(text) | TeamInput::AddMember(text) => text,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        match self {
+            TeamInput::NewTeam(text) | TeamInput::AddMember(text) => text,
+        }
+    }
+}
+
+// The teams of the signed in user, with the members of the selected one
+pub struct Teams {
+    pub teams: Vec<Team>,
+    pub state: ListState,
+    pub input: Option<TeamInput>,
+}
+
+impl Teams {
+    pub fn new(teams: Vec<Team>) -> Teams {
+        let mut state = ListState::default();
+        if !teams.is_empty() {
+            state.select(Some(0));
+        }
+        Teams {
+            teams,
+            state,
+            input: None,
+        }
+    }
+
+    pub fn down(&mut self) {
+        select_next(&mut self.state, self.teams.len());
+    }
+
+    pub fn up(&mut self) {
+        select_previous(&mut self.state, self.teams.len());
+    }
+
+    pub fn selected(&self) -> Option<&Team> {
+        self.teams.get(self.state.selected()?)
+    }
+
+    // Selects the team after a reload, by id so a new team in between
+    // doesn't move the selection
+    pub fn select_team(&mut self, team_id: u64) {
+        if let Some(i) = self.teams.iter().position(|team| team.id == team_id) {
+            self.state.select(Some(i));
+        }
+    }
+}
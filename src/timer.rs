@@ -0,0 +1,108 @@
+use chrono::NaiveDate;
+use dirs::home_dir;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const TIMESHEET_HEADER: &str = "date,task_id,title,minutes";
+
+// Time being tracked on a task, posted as a comment when it stops
+pub struct Timer {
+    pub task_id: u64,
+    pub title: String,
+    pub started: Instant,
+    // The day it was started on, which the time is logged for
+    pub date: NaiveDate,
+    // The time tracked once it was stopped, kept until the comment is posted
+    pub stopped: Option<Duration>,
+    pub sending: bool,
+}
+
+impl Timer {
+    pub fn new(task_id: u64, title: &str, date: NaiveDate) -> Timer {
+        Timer {
+            task_id,
+            title: title.to_string(),
+            started: Instant::now(),
+            date,
+            stopped: None,
+            sending: false,
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.stopped.unwrap_or_else(|| self.started.elapsed())
+    }
+}
+
+// Hours and minutes like "1:05"
+pub fn format_elapsed(elapsed: Duration) -> String {
+    let minutes = elapsed.as_secs() / 60;
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
+// The comment a stopped timer leaves on its task, like "⏱ 0:42 on 2024-05-02"
+pub fn time_comment(elapsed: Duration, date: NaiveDate) -> String {
+    format!(
+        "⏱ {} on {}",
+        format_elapsed(elapsed),
+        date.format("%Y-%m-%d")
+    )
+}
+
+// A leading ~ in the configured path stands for the home directory
+pub fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+pub fn timesheet_row(timer: &Timer, elapsed: Duration) -> String {
+    format!(
+        "{},{},{},{}",
+        timer.date.format("%Y-%m-%d"),
+        timer.task_id,
+        csv_field(&timer.title),
+        elapsed.as_secs() / 60
+    )
+}
+
+// Adds a row to the timesheet, starting it with a header when it is new
+pub fn append_timesheet(path: &Path, row: &str) -> io::Result<()> {
+    let path = expand_home(path);
+    let new = !path.exists();
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    if new {
+        writeln!(file, "{}", TIMESHEET_HEADER)?;
+    }
+    writeln!(file, "{}", row)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_comment_and_timesheet_row() {
+        let date = NaiveDate::from_ymd_opt(2024, 5, 2).unwrap();
+        let elapsed = Duration::from_secs(42 * 60 + 30);
+        assert_eq!(time_comment(elapsed, date), "⏱ 0:42 on 2024-05-02");
+        assert_eq!(format_elapsed(Duration::from_secs(65 * 60)), "1:05");
+
+        let timer = Timer::new(7, "Call \"Sam\", then Alex", date);
+        assert_eq!(
+            timesheet_row(&timer, elapsed),
+            "2024-05-02,7,\"Call \"\"Sam\"\", then Alex\",42"
+        );
+    }
+}
@@ -1,22 +1,77 @@
-use crate::app::{ActiveInput, App, InputMode};
+use crate::app::{
+    ActiveInput, App, AuthField, BulkLabel, BulkMove, CardMove, CompletionKind, DateTarget,
+    InputMode, NewTaskPreview, TitleEdit, ViewKind,
+};
+use crate::board::bucket_tasks;
+use crate::calendar::{today, DatePicker};
+use crate::color::{
+    adapt_buffer, color_support, hex_color, label_style, ColorSupport, Theme, TASK_COLORS,
+};
+use crate::event::{AppEvent, Key};
+use crate::export::osc52;
+use crate::fuzzy::fuzzy_match;
+use crate::highlight::highlight_code;
+use crate::html::{layout_table, split_sections, Section};
+use crate::members::{Members, TEAM_PREFIX};
+use crate::parser::{magic_tokens, parse_task_input, TokenKind};
+use crate::row_format::{default_row, Field, RowPart, DEFAULT_DUE_FORMAT};
+use crate::teams::{TeamInput, Teams};
+use crate::timer::format_elapsed;
 use ansi_parser::{AnsiParser, Output};
-use crossterm::event::{self, Event as CEvent};
+use chrono::{DateTime, Datelike, Local, NaiveDate};
+use crossterm::event;
+use crossterm::{execute, terminal::SetTitle};
 use ratatui::{
-    backend::Backend,
+    backend::{Backend, TestBackend},
+    buffer::Buffer,
     layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
-    Terminal,
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Tabs, Wrap},
+    Frame, Terminal,
 };
-use std::io;
+use std::io::{self, Write};
+use std::sync::atomic::Ordering;
 use std::time::Duration;
+use unicode_width::UnicodeWidthStr;
+use vikunja_client::api::{average_latency, ApiCall};
+use vikunja_client::models::{Project, Reactions, Task, TeamMember, User, PERMISSIONS};
+
+// How many of the latest requests the average latency covers, and from
+// which average the server counts as slow
+const LATENCY_WINDOW: usize = 20;
+const SLOW_LATENCY: Duration = Duration::from_secs(1);
+
+// The most a cut off title takes up below the list
+const MAX_PREVIEW_LINES: usize = 3;
+
+// Put before tasks waiting on others that are not done
+const BLOCKED_SYMBOL: &str = "🔒 ";
+// Assignees get one of these by user id, so a person keeps their color
+const ASSIGNEE_COLORS: [Color; 6] = [
+    Color::Cyan,
+    Color::Magenta,
+    Color::Green,
+    Color::Blue,
+    Color::Yellow,
+    Color::LightRed,
+];
+// How completion times are shown, in local time
+const DONE_AT_FORMAT: &str = "%Y-%m-%d %H:%M";
+// Put before tasks that have a color, in that color
+const COLOR_MARKER: &str = "● ";
+// After the title of tasks that repeat
+const REPEAT_SYMBOL: &str = " ↻";
+// Marks the selected row of the task list
+const HIGHLIGHT_SYMBOL: &str = ">> ";
+// Cells of the progress bar on the status line while labeling
+const BULK_PROGRESS_WIDTH: usize = 20;
 
 fn get_cursor_position(input: &str, area: Rect) -> (u16, u16) {
     let lines: Vec<&str> = input.lines().collect();
     let last_line = lines.last().unwrap_or(&"");
     let x = area.x + last_line.chars().count() as u16 + 1;
-    let y = area.y + lines.len() as u16 - 1 + 1;
+    let y = area.y + lines.len().max(1) as u16;
     (x, y)
 }
 
@@ -26,7 +81,7 @@ fn centered_rect_absolute(width: u16, height: u16, r: Rect) -> Rect {
         .constraints([
             Constraint::Length((r.height.saturating_sub(height)) / 2u16),
             Constraint::Length(height),
-            Constraint::Length((r.height.saturating_sub(height) + 1u16) / 2u16),
+            Constraint::Length(r.height.saturating_sub(height).div_ceil(2u16)),
         ])
         .split(r);
 
@@ -35,7 +90,7 @@ fn centered_rect_absolute(width: u16, height: u16, r: Rect) -> Rect {
         .constraints([
             Constraint::Length((r.width.saturating_sub(width)) / 2u16),
             Constraint::Length(width),
-            Constraint::Length((r.width.saturating_sub(width) + 1u16) / 2u16),
+            Constraint::Length(r.width.saturating_sub(width).div_ceil(2u16)),
         ])
         .split(popup_layout[1])[1]
 }
@@ -58,316 +113,3092 @@ pub fn ansi_to_text(ansi_str: &str) -> Vec<Line<'static>> {
     lines
 }
 
-fn get_legend(input_mode: &InputMode) -> Text<'static> {
-    match input_mode {
-        InputMode::Normal => Text::from(Line::from(vec![
-            Span::styled(" q ", Style::default().fg(Color::Red)),
-            Span::raw(": Quit "),
-            Span::styled(" j ", Style::default().fg(Color::Red)),
-            Span::raw(": Down "),
-            Span::styled(" k ", Style::default().fg(Color::Red)),
-            Span::raw(": Up "),
-            Span::styled(" n ", Style::default().fg(Color::Red)),
-            Span::raw(": Next Page "),
-            Span::styled(" p ", Style::default().fg(Color::Red)),
-            Span::raw(": Previous Page "),
-            Span::styled(" t ", Style::default().fg(Color::Red)),
-            Span::raw(": Toggle Done "),
-            Span::styled(" Enter ", Style::default().fg(Color::Red)),
-            Span::raw(": View Details "),
-            Span::styled(" a ", Style::default().fg(Color::Red)),
-            Span::raw(": Add Task "),
-        ])),
+fn get_legend(app: &App) -> Text<'static> {
+    // Keys that change data are greyed out in read-only mode
+    let (mutating_key, mutating_label) = if app.read_only {
+        (
+            Style::default().fg(Color::DarkGray),
+            Style::default().fg(Color::DarkGray),
+        )
+    } else {
+        (Style::default().fg(Color::Red), Style::default())
+    };
+
+    match app.input_mode {
+        InputMode::Normal => {
+            let mut spans = vec![
+                Span::styled(" q ", Style::default().fg(Color::Red)),
+                Span::raw(": Quit "),
+                Span::styled(" j ", Style::default().fg(Color::Red)),
+                Span::raw(": Down "),
+                Span::styled(" k ", Style::default().fg(Color::Red)),
+                Span::raw(": Up "),
+                Span::styled(" n ", Style::default().fg(Color::Red)),
+                Span::raw(": Next Page "),
+                Span::styled(" p ", Style::default().fg(Color::Red)),
+                Span::raw(": Previous Page "),
+                Span::styled(" t ", Style::default().fg(Color::Red)),
+                Span::raw(": Toggle Done "),
+                Span::styled(" b ", Style::default().fg(Color::Red)),
+                Span::raw(": Toggle Board "),
+                Span::styled(" r ", Style::default().fg(Color::Red)),
+                Span::raw(": Refresh "),
+                Span::styled(" D ", Style::default().fg(Color::Red)),
+                Span::raw(": Done Review "),
+                Span::styled(" Enter ", Style::default().fg(Color::Red)),
+                Span::raw(": View Details "),
+                Span::styled(" / ", Style::default().fg(Color::Red)),
+                Span::raw(": Filter List "),
+                Span::styled(" : # ", Style::default().fg(Color::Red)),
+                Span::raw(": Go To Task "),
+                Span::styled(" Ctrl-o/Tab ", Style::default().fg(Color::Red)),
+                Span::raw(": Back/Forward "),
+                Span::styled(" gn/gt/gT/gc ", Style::default().fg(Color::Red)),
+                Span::raw(": New/Next/Previous/Close Tab "),
+                Span::styled(" a ", mutating_key),
+                Span::styled(": Add Task ", mutating_label),
+                Span::styled(" m ", mutating_key),
+                Span::styled(": Assign To Me ", mutating_label),
+                Span::styled(" x ", mutating_key),
+                Span::styled(": Delete ", mutating_label),
+                Span::styled(" s ", Style::default().fg(Color::Red)),
+                Span::raw(": Switch Project "),
+                Span::styled(" f ", Style::default().fg(Color::Red)),
+                Span::raw(": Find Task "),
+                Span::styled(" F ", Style::default().fg(Color::Red)),
+                Span::raw(": Filters "),
+                Span::styled(" N ", Style::default().fg(Color::Red)),
+                Span::raw(": Notifications "),
+            ];
+            if app.current_project.is_some() {
+                spans.push(Span::styled(" U ", Style::default().fg(Color::Red)));
+                spans.push(Span::raw(": Members "));
+            }
+            spans.push(Span::styled(" T ", Style::default().fg(Color::Red)));
+            spans.push(Span::raw(": Teams "));
+            spans.push(Span::styled(" F12 ", Style::default().fg(Color::Red)));
+            spans.push(Span::raw(": API Requests "));
+            if app.groups_completed() {
+                spans.push(Span::styled(" C ", Style::default().fg(Color::Red)));
+                spans.push(Span::raw(if app.collapse_completed {
+                    ": Show Completed On This Page "
+                } else {
+                    ": Fold Completed On This Page "
+                }));
+            }
+            if app.can_undo_delete() {
+                spans.push(Span::styled(" u ", mutating_key));
+                spans.push(Span::styled(": Undo Delete ", mutating_label));
+            }
+            spans.push(Span::styled(" W ", mutating_key));
+            spans.push(Span::styled(
+                match &app.timer {
+                    Some(timer) if timer.stopped.is_some() => ": Log Time ",
+                    Some(_) => ": Stop Timer ",
+                    None => ": Track Time ",
+                },
+                mutating_label,
+            ));
+            if app.task_detail.is_some() {
+                spans.push(Span::styled(" w ", Style::default().fg(Color::Red)));
+                spans.push(Span::raw(": Wrap Code "));
+                spans.push(Span::styled(" gx ", Style::default().fg(Color::Red)));
+                spans.push(Span::raw(": Links "));
+                if !app.detail_wrap {
+                    spans.push(Span::styled(" < > ", Style::default().fg(Color::Red)));
+                    spans.push(Span::raw(": Scroll Code "));
+                }
+                spans.push(Span::styled(" c ", mutating_key));
+                spans.push(Span::styled(": Comment ", mutating_label));
+                spans.push(Span::styled(" e ", mutating_key));
+                spans.push(Span::styled(": React ", mutating_label));
+            }
+            if app.view_kind != ViewKind::Kanban {
+                spans.push(Span::styled(" z ", Style::default().fg(Color::Red)));
+                spans.push(Span::raw(": Subtasks "));
+            }
+            if app.view_kind == ViewKind::List && app.list_view_id.is_some() {
+                spans.push(Span::styled(" J/K ", mutating_key));
+                spans.push(Span::styled(": Reorder Task ", mutating_label));
+            }
+            if app.view_kind == ViewKind::Done {
+                spans.push(Span::styled(" Space ", Style::default().fg(Color::Red)));
+                spans.push(Span::raw(": Mark "));
+                spans.push(Span::styled(" u ", mutating_key));
+                spans.push(Span::styled(": Reopen ", mutating_label));
+            }
+            if app.view_kind == ViewKind::Kanban {
+                spans.push(Span::styled(" H/L ", mutating_key));
+                spans.push(Span::styled(": Move Card ", mutating_label));
+                spans.push(Span::styled(" J/K ", mutating_key));
+                spans.push(Span::styled(": Reorder Card ", mutating_label));
+            }
+            if app.list_filter.is_some() {
+                spans.push(Span::styled(" Esc ", Style::default().fg(Color::Red)));
+                spans.push(Span::raw(": Clear Filter "));
+            }
+            if app.profiles.len() > 1 {
+                spans.push(Span::styled(" P ", Style::default().fg(Color::Red)));
+                spans.push(Span::raw(": Switch Instance "));
+            }
+            Text::from(Line::from(spans))
+        }
         InputMode::Editing => Text::from(Line::from(vec![
             Span::styled(" i ", Style::default().fg(Color::Red)),
             Span::raw(": Insert "),
+            Span::styled(" q ", Style::default().fg(Color::Red)),
+            Span::raw(": Quit "),
             Span::styled(" Tab ", Style::default().fg(Color::Red)),
             Span::raw(": Switch Input "),
+            Span::styled(" d ", Style::default().fg(Color::Red)),
+            Span::raw(": Due Date "),
+            Span::styled(" r ", Style::default().fg(Color::Red)),
+            Span::raw(": Reminder "),
+            Span::styled(" s/e ", Style::default().fg(Color::Red)),
+            Span::raw(": Start/End "),
+            Span::styled(" c ", Style::default().fg(Color::Red)),
+            Span::raw(": Color "),
+            Span::styled(" Ctrl-e ", Style::default().fg(Color::Red)),
+            Span::raw(": Editor "),
             Span::styled(" Enter ", Style::default().fg(Color::Red)),
             Span::raw(": Submit "),
             Span::styled(" Esc ", Style::default().fg(Color::Red)),
             Span::raw(": Cancel "),
         ])),
+        InputMode::Insert if app.completion.is_some() => Text::from(Line::from(vec![
+            Span::styled(" Up/Down ", Style::default().fg(Color::Red)),
+            Span::raw(": Move "),
+            Span::styled(" Tab/Enter ", Style::default().fg(Color::Red)),
+            Span::raw(": Complete "),
+            Span::styled(" Esc ", Style::default().fg(Color::Red)),
+            Span::raw(": Close "),
+        ])),
         InputMode::Insert => Text::from(Line::from(vec![
             Span::styled(" Esc ", Style::default().fg(Color::Red)),
             Span::raw(": Exit Insert Mode "),
+            Span::styled(" Ctrl-Enter ", Style::default().fg(Color::Red)),
+            Span::raw(": Submit "),
+            Span::styled(" Up/Down ", Style::default().fg(Color::Red)),
+            Span::raw(": History "),
+            Span::styled(" Ctrl-e ", Style::default().fg(Color::Red)),
+            Span::raw(": Editor "),
+            Span::styled(" * + @ ", Style::default().fg(Color::Red)),
+            Span::raw(": Label, Project, Assignee "),
+        ])),
+        InputMode::ProjectSwitcher
+        | InputMode::TaskFinder
+        | InputMode::FilterMenu
+        | InputMode::ProfileSwitcher
+        | InputMode::ReactionTargets
+        | InputMode::ReactionPicker
+        | InputMode::Links
+        | InputMode::ProjectChoice
+        | InputMode::DuplicateParent => {
+            let mut spans = vec![
+                Span::styled(" Up/Down ", Style::default().fg(Color::Red)),
+                Span::raw(": Move "),
+                Span::styled(" Enter ", Style::default().fg(Color::Red)),
+                Span::raw(": Select "),
+                Span::styled(" Esc ", Style::default().fg(Color::Red)),
+                Span::raw(": Cancel "),
+            ];
+            if let InputMode::ProjectSwitcher = app.input_mode {
+                spans.push(Span::styled(" Ctrl-d ", mutating_key));
+                spans.push(Span::styled(": Duplicate ", mutating_label));
+            }
+            Text::from(Line::from(spans))
+        }
+        InputMode::Notifications => Text::from(Line::from(vec![
+            Span::styled(" j/k ", Style::default().fg(Color::Red)),
+            Span::raw(": Move "),
+            Span::styled(" Enter ", Style::default().fg(Color::Red)),
+            Span::raw(": Open Task "),
+            Span::styled(" m ", mutating_key),
+            Span::styled(": Mark Read ", mutating_label),
+            Span::styled(" M ", mutating_key),
+            Span::styled(": Mark All Read ", mutating_label),
+            Span::styled(" Esc ", Style::default().fg(Color::Red)),
+            Span::raw(": Close "),
+        ])),
+        InputMode::RenameTask => Text::from(Line::from(vec![
+            Span::styled(" Enter ", Style::default().fg(Color::Red)),
+            Span::raw(": Save Title "),
+            Span::styled(" Left/Right/Home/End ", Style::default().fg(Color::Red)),
+            Span::raw(": Move Cursor "),
+            Span::styled(" Esc ", Style::default().fg(Color::Red)),
+            Span::raw(": Cancel "),
+        ])),
+        InputMode::Command => Text::from(Line::from(vec![
+            Span::styled(" Enter ", Style::default().fg(Color::Red)),
+            Span::raw(": Run "),
+            Span::styled(" Up/Down ", Style::default().fg(Color::Red)),
+            Span::raw(": History "),
+            Span::styled(" Ctrl-e ", Style::default().fg(Color::Red)),
+            Span::raw(": Editor "),
+            Span::styled(" Esc ", Style::default().fg(Color::Red)),
+            Span::raw(": Cancel "),
+            Span::raw(" goto WRK-12, #12: Open Task "),
+            Span::raw(" label/unlabel <name>: Label Every Task "),
+            Span::raw(" move <project>: Move Every Task "),
+            Span::raw(" archive <text>: Search Done Tasks "),
+            Span::raw(" export [file]: Copy/Save as Markdown "),
+        ])),
+        InputMode::ListFilter => Text::from(Line::from(vec![
+            Span::styled(" Up/Down ", Style::default().fg(Color::Red)),
+            Span::raw(": Move "),
+            Span::styled(" Enter ", Style::default().fg(Color::Red)),
+            Span::raw(": Keep Filter "),
+            Span::styled(" Ctrl-p/n ", Style::default().fg(Color::Red)),
+            Span::raw(": History "),
+            Span::styled(" Ctrl-e ", Style::default().fg(Color::Red)),
+            Span::raw(": Editor "),
+            Span::styled(" Esc ", Style::default().fg(Color::Red)),
+            Span::raw(": Show All "),
+        ])),
+        InputMode::DatePicker => Text::from(Line::from(vec![
+            Span::styled(" Arrows/hjkl ", Style::default().fg(Color::Red)),
+            Span::raw(": Move "),
+            Span::styled(" [ ] ", Style::default().fg(Color::Red)),
+            Span::raw(": Month "),
+            Span::styled(" t ", Style::default().fg(Color::Red)),
+            Span::raw(": Today "),
+            Span::styled(" Enter ", Style::default().fg(Color::Red)),
+            Span::raw(": Pick "),
+            Span::styled(" x ", Style::default().fg(Color::Red)),
+            Span::raw(": Clear "),
+            Span::styled(" Esc ", Style::default().fg(Color::Red)),
+            Span::raw(": Cancel "),
+        ])),
+        InputMode::Members => Text::from(Line::from(vec![
+            Span::styled(" j/k ", Style::default().fg(Color::Red)),
+            Span::raw(": Move "),
+            Span::styled(" a ", mutating_key),
+            Span::styled(": Add User Or Team ", mutating_label),
+            Span::styled(" d ", mutating_key),
+            Span::styled(": Remove ", mutating_label),
+            Span::styled(" Esc ", Style::default().fg(Color::Red)),
+            Span::raw(": Close "),
+        ])),
+        InputMode::AddMember => Text::from(Line::from(vec![
+            Span::styled(" Enter ", Style::default().fg(Color::Red)),
+            Span::raw(": Share "),
+            Span::styled(" Tab ", Style::default().fg(Color::Red)),
+            Span::raw(": Permission "),
+            Span::styled(" Esc ", Style::default().fg(Color::Red)),
+            Span::raw(": Cancel "),
+            Span::raw(" alex, team:Design: Who To Share With "),
+        ])),
+        InputMode::Teams => Text::from(Line::from(vec![
+            Span::styled(" j/k ", Style::default().fg(Color::Red)),
+            Span::raw(": Move "),
+            Span::styled(" n ", mutating_key),
+            Span::styled(": New Team ", mutating_label),
+            Span::styled(" a ", mutating_key),
+            Span::styled(": Add Member ", mutating_label),
+            Span::styled(" Esc ", Style::default().fg(Color::Red)),
+            Span::raw(": Close "),
+        ])),
+        InputMode::TeamInput => Text::from(Line::from(vec![
+            Span::styled(" Enter ", Style::default().fg(Color::Red)),
+            Span::raw(": Save "),
+            Span::styled(" Esc ", Style::default().fg(Color::Red)),
+            Span::raw(": Cancel "),
+        ])),
+        InputMode::ConfirmDelete => Text::from(Line::from(vec![
+            Span::styled(" y ", Style::default().fg(Color::Red)),
+            Span::raw(": Delete "),
+            Span::styled(" n ", Style::default().fg(Color::Red)),
+            Span::raw(": Keep "),
+        ])),
+        InputMode::ConfirmUnshare => Text::from(Line::from(vec![
+            Span::styled(" y ", Style::default().fg(Color::Red)),
+            Span::raw(": Remove "),
+            Span::styled(" n ", Style::default().fg(Color::Red)),
+            Span::raw(": Keep "),
+        ])),
+        InputMode::ConfirmQuit => Text::from(Line::from(vec![
+            Span::styled(" y ", Style::default().fg(Color::Red)),
+            Span::raw(": Quit "),
+            Span::styled(" n ", Style::default().fg(Color::Red)),
+            Span::raw(": Stay "),
+        ])),
+        InputMode::ConfirmNewLabels => Text::from(Line::from(vec![
+            Span::styled(" y ", Style::default().fg(Color::Red)),
+            Span::raw(": Create "),
+            Span::styled(" n ", Style::default().fg(Color::Red)),
+            Span::raw(": Back "),
+        ])),
+        InputMode::ConfirmNewTask => Text::from(Line::from(vec![
+            Span::styled(" Enter ", Style::default().fg(Color::Red)),
+            Span::raw(": Create "),
+            Span::styled(" Esc ", Style::default().fg(Color::Red)),
+            Span::raw(": Keep editing "),
+        ])),
+        InputMode::RestoreDraft => Text::from(Line::from(vec![
+            Span::styled(" y ", Style::default().fg(Color::Red)),
+            Span::raw(": Restore "),
+            Span::styled(" n ", Style::default().fg(Color::Red)),
+            Span::raw(": Start over "),
+            Span::styled(" Esc ", Style::default().fg(Color::Red)),
+            Span::raw(": Cancel "),
+        ])),
+        InputMode::ConfirmBulkLabel | InputMode::ConfirmBulkMove => Text::from(Line::from(vec![
+            Span::styled(" y ", Style::default().fg(Color::Red)),
+            Span::raw(": Apply "),
+            Span::styled(" n ", Style::default().fg(Color::Red)),
+            Span::raw(": Cancel "),
+        ])),
+        InputMode::ConfirmMove => Text::from(Line::from(vec![
+            Span::styled(" y ", Style::default().fg(Color::Red)),
+            Span::raw(": Move "),
+            Span::styled(" n ", Style::default().fg(Color::Red)),
+            Span::raw(": Cancel "),
+        ])),
+        InputMode::Reauth => Text::from(Line::from(vec![
+            Span::styled(" Tab ", Style::default().fg(Color::Red)),
+            Span::raw(": Next Field "),
+            Span::styled(" Enter ", Style::default().fg(Color::Red)),
+            Span::raw(": Sign In "),
+            Span::styled(" Esc ", Style::default().fg(Color::Red)),
+            Span::raw(": Cancel "),
         ])),
     }
 }
 
-pub async fn run_app<B: Backend>(
-    terminal: &mut Terminal<B>,
-    mut app: App,
-    instance_url: &str,
-    api_key: &str,
-) -> io::Result<()> {
-    loop {
-        terminal.draw(|f| {
-            let size = f.area();
+fn list_title(app: &App) -> String {
+    let scope = match &app.current_project {
+        Some(project) => project.title.as_str(),
+        None => "Tasks",
+    };
+    let filter_name = match &app.active_filter {
+        Some(quick_filter) => format!(" [{}]", quick_filter.name),
+        None => String::new(),
+    };
+    if let (ViewKind::Done, Some(text)) = (app.view_kind, &app.archive) {
+        if text.is_empty() {
+            format!("{}{} (Archive)", scope, filter_name)
+        } else {
+            format!("{}{} (Archive: \"{}\")", scope, filter_name, text)
+        }
+    } else if app.view_kind == ViewKind::Done {
+        format!(
+            "{}{} (Done in the last {} days)",
+            scope, filter_name, app.done_review_days
+        )
+    } else if app.show_done_tasks {
+        format!("{}{} (All)", scope, filter_name)
+    } else {
+        format!("{}{} (Undone)", scope, filter_name)
+    }
+}
 
-            // Split the main layout into body and footer
+// Counts for the list's title, like "37 open · 5 overdue · page 2/4". The
+// matching tasks and pages are the server's, the overdue ones are those of
+// the loaded page.
+fn list_summary(app: &App) -> String {
+    let now = Local::now();
+    let overdue = app.tasks.iter().filter(|task| task.is_overdue(now)).count();
+    let (count, exact) = app.matching_tasks();
+    let noun = if app.view_kind == ViewKind::Done {
+        "done"
+    } else if app.show_done_tasks {
+        "tasks"
+    } else {
+        "open"
+    };
+    let pages = app.total_pages.max(app.page);
+    let mut parts = vec![if exact {
+        format!("{} {}", count, noun)
+    } else {
+        format!("{}+ {}", count, noun)
+    }];
+    if overdue > 0 && pages > 1 {
+        parts.push(format!("{} overdue on this page", overdue));
+    } else if overdue > 0 {
+        parts.push(format!("{} overdue", overdue));
+    }
+    parts.push(format!("page {}/{}", app.page, pages));
+    parts.join(" · ")
+}
+
+fn draw_task_list(f: &mut Frame, app: &mut App, area: Rect) {
+    let task_title = format!("{} — {}", list_title(app), list_summary(app));
+
+    // The filter being typed goes on a line below the list, like a pager's
+    // search prompt
+    let area = match &app.list_filter {
+        Some(query) => {
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
-                .margin(0)
-                .constraints([Constraint::Min(0), Constraint::Length(2u16)])
-                .split(size);
+                .constraints([Constraint::Min(0), Constraint::Length(1)])
+                .split(area);
+            let prompt = format!("/{}", query);
+            if let InputMode::ListFilter = app.input_mode {
+                f.set_cursor_position((chunks[1].x + prompt.chars().count() as u16, chunks[1].y));
+            }
+            f.render_widget(
+                Paragraph::new(format!(
+                    "{} ({} of {})",
+                    prompt,
+                    app.visible_rows.len(),
+                    app.tasks.len()
+                )),
+                chunks[1],
+            );
+            chunks[0]
+        }
+        None => area,
+    };
 
-            let body_chunk = chunks[0];
-            let footer_chunk = chunks[1];
+    // A selected row cut off at the edge has its title in full below the list
+    let area = match cut_off_title(app, area.width) {
+        Some(title) => {
+            let height = calculate_wrapped_lines(&title, area.width).min(MAX_PREVIEW_LINES);
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(height as u16)])
+                .split(area);
+            let preview = Paragraph::new(title)
+                .style(Style::default().fg(Color::DarkGray))
+                .wrap(Wrap { trim: false });
+            f.render_widget(preview, chunks[1]);
+            chunks[0]
+        }
+        None => area,
+    };
 
-            match app.input_mode {
-                InputMode::Normal => {
-                    let chunks = Layout::default()
-                        .direction(Direction::Horizontal)
-                        .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
-                        .split(body_chunk);
-
-                    let task_title = if app.show_done_tasks {
-                        "Tasks (All)"
-                    } else {
-                        "Tasks (Undone)"
-                    };
-
-                    // Left panel: Task list
-                    let tasks_widget = if !app.tasks.is_empty() {
-                        let tasks: Vec<ListItem> = app
-                            .tasks
-                            .iter()
-                            .map(|task| {
-                                let content = if task.done {
-                                    vec![
-                                        Span::styled("DONE ", Style::default().fg(Color::Green)),
-                                        Span::raw(&task.title),
-                                    ]
-                                } else {
-                                    vec![Span::raw(&task.title)]
-                                };
-                                ListItem::new(Line::from(content))
-                            })
-                            .collect();
+    if !app.visible_rows.is_empty() {
+        // Only the rows in view are built, so long lists draw as fast as
+        // short ones
+        let height = area.height.saturating_sub(2) as usize;
+        let selected = app.state.selected();
+        let offset = list_offset(app.state.offset(), selected, height, app.visible_rows.len());
+        let end = (offset + height).min(app.visible_rows.len());
+        let mut tasks = Vec::with_capacity(end - offset);
+        for row in offset..end {
+            let i = app.visible_rows[row];
+            let mut lines = Vec::new();
+            if separator_before(app, row) {
+                lines.push(completed_separator(app));
+            }
+            if let Some(edit) = app
+                .title_edit
+                .as_ref()
+                .filter(|e| e.task_id == app.tasks[i].id)
+            {
+                lines.push(title_edit_line(app, &app.tasks[i], edit));
+            } else {
+                lines.push(cached_task_line(app, i).clone());
+                if app.expanded.contains(&app.tasks[i].id) {
+                    lines.extend(subtask_lines(app, &app.tasks[i]));
+                }
+            }
+            if separator_after(app, row) {
+                lines.push(completed_separator(app));
+            }
+            tasks.push(ListItem::new(lines));
+        }
 
-                        List::new(tasks)
-                            .block(Block::default().borders(Borders::ALL).title(task_title))
-                            .highlight_style(
-                                Style::default()
-                                    .fg(Color::Green)
-                                    .add_modifier(Modifier::BOLD),
-                            )
-                            .highlight_symbol(">> ")
-                    } else {
-                        List::new(vec![ListItem::new("No tasks available")])
-                            .block(Block::default().borders(Borders::ALL).title(task_title))
-                    };
+        let list = List::new(tasks)
+            .block(Block::default().borders(Borders::ALL).title(task_title))
+            .highlight_style(match app.selected_task() {
+                Some(task) => selection_style(&app.theme, task),
+                None => Style::default(),
+            })
+            .highlight_symbol(HIGHLIGHT_SYMBOL);
+        let mut window = ListState::default().with_selected(selected.map(|row| row - offset));
+        f.render_stateful_widget(list, area, &mut window);
+        // Expanded subtasks can push the selection further down than the rows
+        // in view allowed for
+        let offset = offset + window.offset();
+        // The cursor goes after the highlight symbol and what precedes the
+        // title in the row
+        if let (Some(edit), Some(row)) = (&app.title_edit, selected) {
+            if (offset..end).contains(&row) {
+                let task = &app.tasks[app.visible_rows[row]];
+                let before = title_edit_line(app, task, edit).width() - edit.text.width()
+                    + edit.text[..edit.cursor].width();
+                let lines_above: usize = (offset..row)
+                    .map(|r| {
+                        let task = &app.tasks[app.visible_rows[r]];
+                        let subtasks = if app.expanded.contains(&task.id) {
+                            app.subtasks(task).len()
+                        } else {
+                            0
+                        };
+                        1 + subtasks
+                            + separator_before(app, r) as usize
+                            + separator_after(app, r) as usize
+                    })
+                    .sum::<usize>()
+                    + separator_before(app, row) as usize;
+                let x = area.x + 1 + HIGHLIGHT_SYMBOL.width() as u16 + before as u16;
+                let y = area.y + 1 + lines_above as u16;
+                if x < area.right().saturating_sub(1) && y < area.bottom().saturating_sub(1) {
+                    f.set_cursor_position((x, y));
+                }
+            }
+        }
+        *app.state.offset_mut() = offset;
+        return;
+    }
 
-                    f.render_stateful_widget(tasks_widget, chunks[0], &mut app.state);
+    let tasks_widget = if app.completed_rows > 0 {
+        List::new(vec![
+            ListItem::new("No open tasks"),
+            ListItem::new(completed_separator(app)),
+        ])
+        .block(Block::default().borders(Borders::ALL).title(task_title))
+    } else if !app.tasks.is_empty() {
+        List::new(vec![ListItem::new("No matching tasks")])
+            .block(Block::default().borders(Borders::ALL).title(task_title))
+    } else if app.loading() {
+        List::new(vec![ListItem::new("Loading tasks...")])
+            .block(Block::default().borders(Borders::ALL).title(task_title))
+    } else {
+        List::new(vec![ListItem::new("No tasks available")])
+            .block(Block::default().borders(Borders::ALL).title(task_title))
+    };
 
-                    // Right panel: Task details
-                    let detail_block = Block::default().borders(Borders::ALL).title("Task Details");
+    f.render_stateful_widget(tasks_widget, area, &mut app.state);
+}
 
-                    if let Some(ref detail) = app.task_detail {
-                        let mut lines: Vec<Line<'static>> = Vec::new();
+fn cut_off_title(app: &mut App, width: u16) -> Option<String> {
+    if app.title_edit.is_some() {
+        return None;
+    }
+    let i = *app.visible_rows.get(app.state.selected()?)?;
+    let room = (width as usize).saturating_sub(2 + HIGHLIGHT_SYMBOL.width());
+    (cached_task_line(app, i).width() > room).then(|| app.tasks[i].title.clone())
+}
 
-                        // Due date
-                        let due_date = match &detail.due_date {
-                            Some(date) if date != "0001-01-01T00:00:00Z" => date.clone(),
-                            _ => "No due date".to_string(),
-                        };
-                        lines.push(Line::from(vec![
-                            Span::styled(
-                                "Due Date: ",
-                                Style::default().add_modifier(Modifier::BOLD),
-                            ),
-                            Span::raw(due_date),
-                        ]));
-
-                        // Priority
-                        let priority_str = match detail.priority {
-                            Some(p) => p.to_string(),
-                            None => "No priority".to_string(),
-                        };
-                        lines.push(Line::from(vec![
-                            Span::styled(
-                                "Priority: ",
-                                Style::default().add_modifier(Modifier::BOLD),
-                            ),
-                            Span::raw(priority_str),
-                        ]));
-
-                        // Labels
-                        lines.push(Line::from(vec![Span::styled(
-                            "Labels: ",
-                            Style::default().add_modifier(Modifier::BOLD),
-                        )]));
-
-                        match &detail.labels {
-                            Some(labels) if !labels.is_empty() => {
-                                let mut label_spans: Vec<Span<'static>> = Vec::new();
-                                for (i, label) in labels.iter().enumerate() {
-                                    if i > 0 {
-                                        label_spans.push(Span::raw(" "));
-                                    }
-                                    label_spans.push(Span::styled(
-                                        format!(" {} ", label.title),
-                                        Style::default().bg(Color::Yellow).fg(Color::Black),
-                                    ));
-                                }
-                                lines.push(Line::from(label_spans));
-                            }
-                            _ => {
-                                lines.push(Line::from(Span::raw("No labels".to_string())));
-                            }
-                        }
+// The row of the task at index i, built once until the list changes
+fn cached_task_line(app: &mut App, i: usize) -> &Line<'static> {
+    app.task_lines.resize(app.tasks.len(), None);
+    let line = match app.task_lines[i].take() {
+        Some(line) => line,
+        None => {
+            let line = task_line(app, &app.tasks[i]);
+            if app.groups_completed() && app.tasks[i].done {
+                line.patch_style(Style::default().add_modifier(Modifier::DIM))
+            } else {
+                line
+            }
+        }
+    };
+    app.task_lines[i].insert(line)
+}
 
-                        // Description
-                        lines.push(Line::from(vec![Span::styled(
-                            "Description: ",
-                            Style::default().add_modifier(Modifier::BOLD),
-                        )]));
-
-                        if let Some(desc) = &detail.description {
-                            if desc.trim() == "<p></p>" {
-                                lines.push(Line::from(Span::raw("No description".to_string())));
-                            } else {
-                                let width = (chunks[1].width - 2) as usize; // Adjust for borders
-                                let ansi_text = html2text::from_read(desc.as_bytes(), width);
-
-                                let mut desc_lines = ansi_to_text(&ansi_text);
-                                lines.append(&mut desc_lines);
-                            }
-                        } else {
-                            lines.push(Line::from(Span::raw("No description".to_string())));
-                        }
+fn selection_style(theme: &Theme, task: &Task) -> Style {
+    theme.selection_style(task.done, task.is_overdue(Local::now()))
+}
+
+// The "Completed" separator goes under the last open task, so the highlight
+// symbol stays on the task rows. Only when every task is done does it go
+// above the first one.
+fn separator_before(app: &App, row: usize) -> bool {
+    app.completed_rows > 0
+        && !app.collapse_completed
+        && row == 0
+        && app.completed_rows == app.visible_rows.len()
+}
+
+fn separator_after(app: &App, row: usize) -> bool {
+    let open = if app.collapse_completed {
+        app.visible_rows.len()
+    } else {
+        app.visible_rows.len() - app.completed_rows
+    };
+    app.completed_rows > 0 && row + 1 == open
+}
+
+fn completed_separator(app: &App) -> Line<'static> {
+    let count = app.completed_rows;
+    match (app.plain, app.collapse_completed) {
+        (true, true) => Line::raw(format!("-- Completed ({}), hidden, C to show --", count)),
+        (true, false) => Line::raw(format!("-- Completed ({}) --", count)),
+        (false, folded) => Line::styled(
+            format!(
+                "── Completed ({}) {}",
+                count,
+                if folded { "▸" } else { "▾" }
+            ),
+            Style::default().fg(Color::DarkGray),
+        ),
+    }
+}
+
+// The row of a task whose title is being edited, with the title as typed
+fn title_edit_line(app: &App, task: &Task, edit: &TitleEdit) -> Line<'static> {
+    let mut spans = Vec::new();
+    if app.view_kind == ViewKind::Done {
+        spans.push(Span::raw(if app.marked.contains(&task.id) {
+            "[x] "
+        } else {
+            "[ ] "
+        }));
+    }
+    if !task.identifier.is_empty() {
+        spans.push(Span::styled(
+            format!("{} ", task.identifier),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    spans.push(Span::styled(
+        edit.text.clone(),
+        Style::default().add_modifier(Modifier::UNDERLINED),
+    ));
+    Line::from(spans)
+}
 
-                        let paragraph = Paragraph::new(lines)
-                            .block(detail_block)
-                            .wrap(Wrap { trim: true });
-                        f.render_widget(paragraph, chunks[1]);
+fn task_line(app: &App, task: &Task) -> Line<'static> {
+    let reviewing = app.view_kind == ViewKind::Done;
+    let parts = match &app.row_format {
+        Some(parts) => parts.as_slice(),
+        None => default_row(app.plain),
+    };
+    let mut content = template_spans(app, task, parts);
+    if reviewing {
+        let mark = if app.marked.contains(&task.id) {
+            "[x] "
+        } else {
+            "[ ] "
+        };
+        content.insert(0, Span::raw(mark));
+    }
+    if let Some(completed) = task
+        .completed_at()
+        .filter(|_| app.show_done_at || reviewing)
+    {
+        content.push(Span::styled(
+            format!("  done {}", completed.format(DONE_AT_FORMAT)),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    Line::from(content)
+}
+
+// A row laid out by a template. A field the task doesn't have leaves
+// nothing, and the text between it and the field before goes too so gaps
+// don't pile up.
+fn template_spans(app: &App, task: &Task, parts: &[RowPart]) -> Vec<Span<'static>> {
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    let mut gap: Option<&str> = None;
+    let mut shown = true;
+    let push_gap = |spans: &mut Vec<Span<'static>>, gap: Option<&str>| {
+        let text = gap.unwrap_or_default();
+        let text = if spans.is_empty() {
+            text.trim_start()
+        } else {
+            text
+        };
+        if !text.is_empty() {
+            spans.push(Span::raw(text.to_string()));
+        }
+    };
+    for part in parts {
+        match part {
+            RowPart::Text(text) => gap = Some(text),
+            RowPart::Field(field, format) => {
+                let field_spans = field_spans(app, task, *field, format.as_deref());
+                shown = !field_spans.is_empty();
+                if shown {
+                    push_gap(&mut spans, gap);
+                    spans.extend(field_spans);
+                }
+                gap = None;
+            }
+        }
+    }
+    if shown {
+        push_gap(&mut spans, gap);
+    }
+    // Label badges keep their padding
+    if let Some(last) = spans.last_mut() {
+        if last.style == Style::default() && last.content.ends_with(' ') {
+            last.content = last.content.trim_end().to_string().into();
+        }
+    }
+    spans
+}
+
+fn field_spans(app: &App, task: &Task, field: Field, format: Option<&str>) -> Vec<Span<'static>> {
+    let dim = Style::default().fg(Color::DarkGray);
+    let styled = |text: String, style: Style| {
+        if app.plain {
+            Span::raw(text)
+        } else {
+            Span::styled(text, style)
+        }
+    };
+    let urgency = task
+        .priority
+        .and_then(|priority| app.theme.priority_style(priority))
+        .unwrap_or_default();
+    match field {
+        Field::Identifier if task.identifier.is_empty() => Vec::new(),
+        Field::Identifier => vec![styled(task.identifier.clone(), dim)],
+        Field::Title if app.plain => vec![Span::raw(task.title.clone())],
+        Field::Title => highlight_matches(&task.title, app.list_filter.as_deref(), urgency),
+        Field::Done if !task.done => Vec::new(),
+        Field::Done if app.plain => vec![Span::raw("[DONE]")],
+        Field::Done => vec![Span::styled("DONE", Style::default().fg(Color::Green))],
+        Field::Priority => match task.priority.filter(|p| *p > 0) {
+            Some(priority) if app.plain => vec![Span::raw(format!("[P{}]", priority))],
+            // Only the urgent ones stand out, the way the default rows show them
+            Some(4) if format == Some("badge") => vec![Span::styled("!!", urgency)],
+            Some(priority) if priority >= 5 && format == Some("badge") => {
+                let badge = Style::default()
+                    .bg(app.theme.do_now)
+                    .fg(Color::Black)
+                    .add_modifier(Modifier::BOLD);
+                vec![Span::styled(" DO NOW ", badge)]
+            }
+            Some(_) if format == Some("badge") => Vec::new(),
+            Some(priority) => vec![Span::styled(format!("P{}", priority), urgency)],
+            None => Vec::new(),
+        },
+        Field::Blocked if task.blocked_by().is_empty() => Vec::new(),
+        Field::Blocked if app.plain => vec![Span::raw("[BLOCKED]")],
+        Field::Blocked => vec![Span::styled(
+            BLOCKED_SYMBOL.trim_end().to_string(),
+            Style::default().fg(Color::Red),
+        )],
+        Field::Repeat if !task.repeats() => Vec::new(),
+        Field::Repeat if app.plain => vec![Span::raw("[REPEATS]")],
+        Field::Repeat => vec![Span::styled(
+            REPEAT_SYMBOL.trim_start().to_string(),
+            Style::default().fg(Color::Cyan),
+        )],
+        Field::Due => match task.due_at() {
+            Some(due) => {
+                let color = if task.is_overdue(Local::now()) {
+                    Color::Red
+                } else {
+                    Color::Cyan
+                };
+                let text = due.format(format.unwrap_or(DEFAULT_DUE_FORMAT)).to_string();
+                vec![styled(text, Style::default().fg(color))]
+            }
+            None => Vec::new(),
+        },
+        Field::Labels => {
+            let mut spans = Vec::new();
+            for (i, label) in task.labels.iter().flatten().enumerate() {
+                if i > 0 {
+                    spans.push(Span::raw(" "));
+                }
+                spans.push(styled(
+                    if app.plain {
+                        format!("[{}]", label.title)
                     } else {
-                        let paragraph = Paragraph::new("Press Enter to view task details")
-                            .block(detail_block)
-                            .wrap(Wrap { trim: true });
-                        f.render_widget(paragraph, chunks[1]);
-                    }
+                        format!(" {} ", label.title)
+                    },
+                    label_style(label.hex_color.as_deref()),
+                ));
+            }
+            spans
+        }
+        Field::Assignees => {
+            let mut spans = Vec::new();
+            for (i, user) in task.assignees.iter().flatten().enumerate() {
+                if i > 0 {
+                    spans.push(Span::raw(" "));
                 }
-                InputMode::Editing | InputMode::Insert => {
-                    let popup_width_percentage = 60u16;
-                    let popup_width =
-                        (size.width * popup_width_percentage / 100u16).saturating_sub(2u16);
-
-                    // Calculate the required heights for the input boxes
-                    let title_lines_required =
-                        calculate_wrapped_lines(&app.new_task_title, popup_width);
-                    let description_lines_required =
-                        calculate_wrapped_lines(&app.new_task_description, popup_width);
-
-                    let title_height = std::cmp::max(title_lines_required as u16, 1u16);
-                    let description_height = std::cmp::max(description_lines_required as u16, 2u16); // At least 2 lines tall
-
-                    let total_height = title_height + description_height + 6u16; // +6 for borders and titles
-
-                    let max_popup_height = size.height - 2u16;
-                    let popup_height = std::cmp::min(total_height, max_popup_height);
-
-                    let popup_area =
-                        centered_rect_absolute(popup_width + 2u16, popup_height, body_chunk);
-
-                    let popup_block = Block::default()
-                        .title("Enter New Task (Press Enter to Submit, Tab to Switch)")
-                        .borders(Borders::ALL)
-                        .style(Style::default().fg(Color::Green));
-
-                    // Split the popup area vertically for the two input boxes
-                    let input_chunks = Layout::default()
-                        .direction(Direction::Vertical)
-                        .constraints([
-                            Constraint::Length(title_height + 2u16), // +2 for borders
-                            Constraint::Length(description_height + 2u16), // +2 for borders
-                        ])
-                        .split(popup_area.inner(Margin {
-                            vertical: 1u16,
-                            horizontal: 1u16,
-                        })); // Adjust for popup_block borders
-
-                    // Title input box
-                    let title_block = Block::default().borders(Borders::ALL).title("Title").style(
-                        if app.active_input == ActiveInput::Title {
-                            Style::default().fg(Color::Yellow)
-                        } else {
-                            Style::default()
-                        },
-                    );
-
-                    let title_paragraph = Paragraph::new(app.new_task_title.as_str())
-                        .style(Style::default().fg(Color::White))
-                        .block(title_block)
-                        .wrap(Wrap { trim: false });
-
-                    // Description input box
-                    let description_block = Block::default()
-                        .borders(Borders::ALL)
-                        .title("Description")
-                        .style(if app.active_input == ActiveInput::Description {
-                            Style::default().fg(Color::Yellow)
-                        } else {
-                            Style::default()
-                        });
-
-                    let description_paragraph = Paragraph::new(app.new_task_description.as_str())
-                        .style(Style::default().fg(Color::White))
-                        .block(description_block)
-                        .wrap(Wrap { trim: false });
-
-                    f.render_widget(Clear, popup_area);
-                    f.render_widget(popup_block, popup_area);
-
-                    f.render_widget(title_paragraph, input_chunks[0]);
-                    f.render_widget(description_paragraph, input_chunks[1]);
-
-                    // Set cursor position
-                    match app.active_input {
-                        ActiveInput::Title => {
-                            // Calculate cursor position in title input
-                            let cursor_position =
-                                get_cursor_position(&app.new_task_title, input_chunks[0]);
-                            f.set_cursor(cursor_position.0, cursor_position.1);
-                        }
-                        ActiveInput::Description => {
-                            // Calculate cursor position in description input
-                            let cursor_position =
-                                get_cursor_position(&app.new_task_description, input_chunks[1]);
-                            f.set_cursor(cursor_position.0, cursor_position.1);
-                        }
-                    }
+                let color = ASSIGNEE_COLORS[user.id as usize % ASSIGNEE_COLORS.len()];
+                spans.push(if app.plain {
+                    Span::raw(format!("@{}", user.username))
+                } else {
+                    Span::styled(
+                        initials(user),
+                        Style::default().fg(color).add_modifier(Modifier::BOLD),
+                    )
+                });
+            }
+            spans
+        }
+        Field::Progress => match subtask_progress(app.subtasks(task)) {
+            Some((done, total)) => {
+                let color = if done == total {
+                    Color::Green
+                } else {
+                    Color::DarkGray
+                };
+                let text = format!("[{}/{}]", done, total);
+                vec![styled(text, Style::default().fg(color))]
+            }
+            None => Vec::new(),
+        },
+        Field::Counts => {
+            let counts = content_counts(task).into_iter().map(|(count, content)| {
+                if app.plain {
+                    format!("[{} {}]", count, content.noun())
+                } else {
+                    format!("{}{}", content.symbol(), count)
                 }
+            });
+            let text = counts.collect::<Vec<_>>().join(" ");
+            if text.is_empty() {
+                Vec::new()
+            } else {
+                vec![styled(text, dim)]
             }
+        }
+        Field::Color if app.plain => Vec::new(),
+        Field::Color => color_marker(task)
+            .map(|span| Span::styled(span.content.trim_end().to_string(), span.style))
+            .into_iter()
+            .collect(),
+    }
+}
 
-            // Render the legend in the footer
-            let legend = Paragraph::new(get_legend(&app.input_mode))
-                .style(Style::default().fg(Color::White))
-                .alignment(Alignment::Left)
-                .wrap(Wrap { trim: true });
+// What a task holds besides its fields, counted in the list rows
+#[derive(Clone, Copy)]
+enum Content {
+    Attachments,
+    Comments,
+}
 
-            f.render_widget(legend, footer_chunk);
-        })?;
+impl Content {
+    fn noun(self) -> &'static str {
+        match self {
+            Content::Attachments => "attachments",
+            Content::Comments => "comments",
+        }
+    }
 
-        // Handle input
-        if event::poll(Duration::from_millis(100))? {
-            if let CEvent::Key(key) = event::read()? {
-                let should_quit = app.handle_input(key, instance_url, api_key).await?;
-                if should_quit {
-                    return Ok(());
+    fn symbol(self) -> &'static str {
+        match self {
+            Content::Attachments => "📎",
+            Content::Comments => "💬",
+        }
+    }
+}
+
+// The attachments and comments on a task, those it has any of
+fn content_counts(task: &Task) -> Vec<(usize, Content)> {
+    let attachments = task.attachments.as_ref().map_or(0, Vec::len);
+    let comments = task.comment_count.unwrap_or(0) as usize;
+    [
+        (attachments, Content::Attachments),
+        (comments, Content::Comments),
+    ]
+    .into_iter()
+    .filter(|(count, _)| *count > 0)
+    .collect()
+}
+
+// How many of the subtasks are done, None without subtasks
+fn subtask_progress(subtasks: &[Task]) -> Option<(usize, usize)> {
+    let done = subtasks.iter().filter(|task| task.done).count();
+    (!subtasks.is_empty()).then_some((done, subtasks.len()))
+}
+
+// The subtasks of an expanded task, indented under its row
+fn subtask_lines(app: &App, task: &Task) -> Vec<Line<'static>> {
+    let subtasks = app.subtasks(task);
+    subtasks
+        .iter()
+        .enumerate()
+        .map(|(i, subtask)| {
+            let branch = if i + 1 == subtasks.len() {
+                "└ "
+            } else {
+                "├ "
+            };
+            let style = Style::default().fg(Color::DarkGray);
+            match (app.plain, subtask.done) {
+                (true, true) => Line::raw(format!("  - [DONE] {}", subtask.title)),
+                (true, false) => Line::raw(format!("  - {}", subtask.title)),
+                (false, true) => Line::from(vec![
+                    Span::styled(format!("  {}", branch), style),
+                    Span::styled("✓ ", Style::default().fg(Color::Green)),
+                    Span::styled(
+                        subtask.title.clone(),
+                        style.add_modifier(Modifier::CROSSED_OUT),
+                    ),
+                ]),
+                (false, false) => Line::from(vec![
+                    Span::styled(format!("  {}", branch), style),
+                    Span::raw(subtask.title.clone()),
+                ]),
+            }
+        })
+        .collect()
+}
+
+// The first row in view, moved as little as possible from the last one to
+// keep the selection visible
+fn list_offset(offset: usize, selected: Option<usize>, height: usize, len: usize) -> usize {
+    let height = height.max(1);
+    let offset = match selected {
+        Some(row) if row < offset => row,
+        Some(row) if row >= offset + height => row + 1 - height,
+        _ => offset,
+    };
+    offset.min(len.saturating_sub(height))
+}
+
+// The text with the characters matched by the `/` filter picked out
+fn highlight_matches(text: &str, query: Option<&str>, style: Style) -> Vec<Span<'static>> {
+    let positions = match query.and_then(|query| fuzzy_match(query, text)) {
+        Some((_, positions)) if !positions.is_empty() => positions,
+        _ => return vec![Span::styled(text.to_string(), style)],
+    };
+    // Underlined too, so matches still show on the selected row, whose
+    // highlight replaces the color
+    let matched = style
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    let mut run = String::new();
+    let mut run_matched = false;
+    for (i, c) in text.chars().enumerate() {
+        let is_match = positions.binary_search(&i).is_ok();
+        if is_match != run_matched && !run.is_empty() {
+            let run_style = if run_matched { matched } else { style };
+            spans.push(Span::styled(std::mem::take(&mut run), run_style));
+        }
+        run_matched = is_match;
+        run.push(c);
+    }
+    if !run.is_empty() {
+        spans.push(Span::styled(run, if run_matched { matched } else { style }));
+    }
+    spans
+}
+
+fn draw_board(f: &mut Frame, app: &mut App, area: Rect) {
+    let plain = app.plain;
+    let theme = app.theme.clone();
+    let Some(board) = app.board.as_mut() else {
+        let message = if app.current_project.is_some() {
+            "No kanban board available"
+        } else {
+            "Switch to a project (s) to view its board"
+        };
+        let paragraph = Paragraph::new(message)
+            .block(Block::default().borders(Borders::ALL).title("Board"))
+            .wrap(Wrap { trim: true });
+        f.render_widget(paragraph, area);
+        return;
+    };
+
+    if board.buckets.is_empty() {
+        let paragraph = Paragraph::new("No buckets in this board")
+            .block(Block::default().borders(Borders::ALL).title("Board"));
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let constraints: Vec<Constraint> = board
+        .buckets
+        .iter()
+        .map(|_| Constraint::Ratio(1, board.buckets.len() as u32))
+        .collect();
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(constraints)
+        .split(area);
+
+    for (i, bucket) in board.buckets.iter().enumerate() {
+        let tasks = bucket_tasks(bucket);
+        let items: Vec<ListItem> = tasks
+            .iter()
+            .map(|task| {
+                if plain {
+                    ListItem::new(plain_task(task))
+                } else if !task.blocked_by().is_empty() {
+                    ListItem::new(format!("{}{}", BLOCKED_SYMBOL, task.title))
+                } else {
+                    ListItem::new(task.title.clone())
                 }
+            })
+            .collect();
+        let border_style = if i == board.column {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        };
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(border_style)
+                    .title(match (board.is_done_bucket(bucket.id), plain) {
+                        (true, true) => format!("{} [done bucket]", bucket.title),
+                        (true, false) => format!("{} ✓", bucket.title),
+                        _ => bucket.title.clone(),
+                    }),
+            )
+            .highlight_style(
+                match board.states[i].selected().and_then(|row| tasks.get(row)) {
+                    Some(task) if i == board.column => selection_style(&theme, task),
+                    _ => Style::default(),
+                },
+            );
+        // Without colors the selection needs a marker
+        let list = if plain && i == board.column {
+            list.highlight_symbol("> ")
+        } else {
+            list
+        };
+        f.render_stateful_widget(list, columns[i], &mut board.states[i]);
+    }
+}
+
+// The title input with its magic tokens colored, and the ones without effect
+// marked as errors
+fn highlight_tokens(input: &str) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut end = 0;
+    for (range, kind) in magic_tokens(input) {
+        spans.push(Span::raw(input[end..range.start].to_string()));
+        let style = match kind {
+            TokenKind::Priority => Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+            TokenKind::Label => Style::default()
+                .fg(Color::Magenta)
+                .add_modifier(Modifier::BOLD),
+            TokenKind::Assignee => Style::default()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+            TokenKind::Project => Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+            TokenKind::Description => Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC),
+            TokenKind::Date => Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+            TokenKind::Ignored => Style::default()
+                .fg(Color::Red)
+                .add_modifier(Modifier::UNDERLINED),
+        };
+        spans.push(Span::styled(input[range.clone()].to_string(), style));
+        end = range.end;
+    }
+    spans.push(Span::raw(input[end..].to_string()));
+    Line::from(spans)
+}
+
+// What the title will be parsed into, shown while typing
+fn parse_preview(input: &str) -> Paragraph<'static> {
+    let parsed = parse_task_input(input);
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let mut spans = vec![
+        Span::styled("Title: ", bold),
+        Span::raw(if parsed.title.is_empty() {
+            "(empty)".to_string()
+        } else {
+            parsed.title
+        }),
+    ];
+    if let Some(priority) = parsed.priority {
+        spans.push(Span::raw(" · "));
+        spans.push(Span::styled("Priority: ", bold));
+        spans.push(Span::raw(priority.to_string()));
+    }
+    if let Some(description) = parsed.description {
+        spans.push(Span::raw(" · "));
+        spans.push(Span::styled("Description: ", bold));
+        spans.push(Span::raw(description));
+    }
+    if let Some(project) = parsed.project {
+        spans.push(Span::raw(" · "));
+        spans.push(Span::styled("Project: ", bold));
+        spans.push(Span::raw(project));
+    }
+    let dates = [
+        ("Start: ", parsed.start_date),
+        ("End: ", parsed.end_date),
+        ("Due: ", parsed.due_date),
+    ];
+    let reminders = parsed
+        .reminders
+        .into_iter()
+        .map(|r| ("Reminder: ", Some(r)));
+    for (name, date) in dates.into_iter().chain(reminders) {
+        if let Some(date) = date {
+            spans.push(Span::raw(" · "));
+            spans.push(Span::styled(name, bold));
+            let mut text = date.date.format("%Y-%m-%d").to_string();
+            if let Some(time) = date.time {
+                text.push_str(&time.format(" %H:%M").to_string());
             }
+            spans.push(Span::raw(text));
         }
     }
+    for (name, values) in [
+        ("Labels: ", parsed.labels),
+        ("Assignees: ", parsed.assignees),
+    ] {
+        if !values.is_empty() {
+            spans.push(Span::raw(" · "));
+            spans.push(Span::styled(name, bold));
+            spans.push(Span::raw(values.join(", ")));
+        }
+    }
+    let ignored: Vec<&str> = magic_tokens(input)
+        .into_iter()
+        .filter(|(_, kind)| *kind == TokenKind::Ignored)
+        .map(|(range, _)| &input[range])
+        .collect();
+    if !ignored.is_empty() {
+        spans.push(Span::raw(" · "));
+        spans.push(Span::styled(
+            format!("Ignored: {}", ignored.join(" ")),
+            Style::default().fg(Color::Red),
+        ));
+    }
+    Paragraph::new(Line::from(spans)).style(Style::default().fg(Color::White))
 }
 
-fn calculate_wrapped_lines(text: &str, max_width: u16) -> usize {
-    let mut line_count = 0;
-    for line in text.lines() {
-        let line_width = line.chars().count() as u16;
-        let total_width = line_width + max_width - 1u16;
-        line_count += (total_width / max_width) as usize;
+// A task with its state spelled out, like "[DONE] [P3] Write report"
+fn plain_task(task: &Task) -> String {
+    let mut text = String::new();
+    if task.done {
+        text.push_str("[DONE] ");
+    }
+    if let Some(priority) = task.priority.filter(|p| *p > 0) {
+        text.push_str(&format!("[P{}] ", priority));
+    }
+    if !task.blocked_by().is_empty() {
+        text.push_str("[BLOCKED] ");
+    }
+    if task.repeats() {
+        text.push_str("[REPEATS] ");
+    }
+    if !task.identifier.is_empty() {
+        text.push_str(&format!("{} ", task.identifier));
+    }
+    text.push_str(&task.title);
+    for user in task.assignees.iter().flatten() {
+        text.push_str(&format!(" @{}", user.username));
+    }
+    text
+}
+
+// The task's color as a dot, nothing when it has none
+fn color_marker(task: &Task) -> Option<Span<'static>> {
+    let color = hex_color(task.hex_color.as_deref()?)?;
+    Some(Span::styled(COLOR_MARKER, Style::default().fg(color)))
+}
+
+// The username, followed by the name when the user has one
+fn user_name(user: &User) -> String {
+    if user.name.is_empty() {
+        user.username.clone()
+    } else {
+        format!("{} ({})", user.username, user.name)
+    }
+}
+
+// Two letters for an assignee, from the first and last word of the name or
+// from the username when there is no name
+fn initials(user: &User) -> String {
+    let words: Vec<&str> = user.name.split_whitespace().collect();
+    let letters: String = match words.as_slice() {
+        [] => user.username.chars().take(2).collect(),
+        [word] => word.chars().take(2).collect(),
+        [first, .., last] => first.chars().take(1).chain(last.chars().take(1)).collect(),
+    };
+    letters.to_uppercase()
+}
+
+// What the status line reads out in plain mode when there is no message:
+// where the user is and what is selected
+fn announcement(app: &App) -> String {
+    let position = |i: usize, len: usize| format!("({} of {})", i + 1, len);
+    match app.input_mode {
+        InputMode::Normal => match (&app.view_kind, &app.board) {
+            (ViewKind::Kanban, Some(board)) => {
+                let Some(bucket) = board.buckets.get(board.column) else {
+                    return "Board: no buckets".to_string();
+                };
+                let tasks = bucket_tasks(bucket);
+                let column = position(board.column, board.buckets.len());
+                match board
+                    .selected_row()
+                    .and_then(|row| Some((row, tasks.get(row)?)))
+                {
+                    Some((row, task)) => format!(
+                        "{} {}: {} {}",
+                        bucket.title,
+                        column,
+                        plain_task(task),
+                        position(row, tasks.len())
+                    ),
+                    None => format!("{} {}: empty", bucket.title, column),
+                }
+            }
+            (ViewKind::Kanban, None) => "Board: not available".to_string(),
+            (ViewKind::List | ViewKind::Done, _) => {
+                let selected = app.state.selected();
+                let task = |i: usize| app.tasks.get(*app.visible_rows.get(i)?);
+                match selected.and_then(|i| Some((i, task(i)?))) {
+                    Some((i, task)) => format!(
+                        "{}, page {}: {} {}",
+                        list_title(app),
+                        app.page,
+                        plain_task(task),
+                        position(i, app.visible_rows.len())
+                    ),
+                    None => format!("{}, page {}: no tasks", list_title(app), app.page),
+                }
+            }
+        },
+        InputMode::ProjectSwitcher
+        | InputMode::TaskFinder
+        | InputMode::FilterMenu
+        | InputMode::ProfileSwitcher
+        | InputMode::ReactionTargets
+        | InputMode::ReactionPicker
+        | InputMode::Links
+        | InputMode::ProjectChoice
+        | InputMode::DuplicateParent => {
+            let title = picker_title(&app.input_mode);
+            let Some(picker) = &app.picker else {
+                return title.to_string();
+            };
+            match (picker.state.selected(), picker.selected()) {
+                (Some(i), Some(candidate)) => format!(
+                    "{}: {} {}",
+                    title,
+                    picker.candidates[candidate],
+                    position(i, picker.matches.len())
+                ),
+                _ => format!("{}: no matches", title),
+            }
+        }
+        InputMode::Editing | InputMode::Insert => {
+            let field = match app.active_input {
+                ActiveInput::Title => "title",
+                ActiveInput::Description => "description",
+            };
+            match app.input_mode {
+                InputMode::Insert => format!("New task, typing in the {}", field),
+                _ => format!("New task, {} field", field),
+            }
+        }
+        InputMode::Reauth => match &app.auth_form.error {
+            Some(error) => format!("Sign-in failed: {}", error),
+            None => format!(
+                "Session expired, {} field",
+                auth_field_title(app.auth_form.field)
+            ),
+        },
+        InputMode::ConfirmMove => match &app.pending_move {
+            Some(card_move) => move_question(card_move),
+            None => String::new(),
+        },
+        InputMode::ConfirmQuit => app.quit_question.clone().unwrap_or_default(),
+        InputMode::ConfirmNewLabels => new_labels_question(app),
+        InputMode::ConfirmNewTask => match &app.new_task_preview {
+            Some(preview) => format!("Create this task? {}", new_task_summary(preview).join(", ")),
+            None => String::new(),
+        },
+        InputMode::ConfirmUnshare => unshare_question(app),
+        InputMode::ConfirmDelete => delete_question(app),
+        InputMode::RestoreDraft => draft_question(app),
+        InputMode::Teams => match &app.teams {
+            Some(teams) => match (teams.state.selected(), teams.selected()) {
+                (Some(i), Some(team)) => format!(
+                    "Team {}: {} {}",
+                    team.name,
+                    team_member_rows(team.members.as_deref()).join(", "),
+                    position(i, teams.teams.len())
+                ),
+                _ => "Teams: none".to_string(),
+            },
+            None => String::new(),
+        },
+        InputMode::TeamInput => match app.teams.as_ref().and_then(|t| t.input.as_ref()) {
+            Some(input) => format!(
+                "{}: {}",
+                team_input_title(app.teams.as_ref(), input),
+                input.text()
+            ),
+            None => String::new(),
+        },
+        InputMode::Members => match &app.members {
+            Some(members) => match members.state.selected() {
+                Some(i) => format!(
+                    "Shared with: {} {}",
+                    member_rows(members)[i],
+                    position(i, members.len())
+                ),
+                None => "Shared with: nobody".to_string(),
+            },
+            None => String::new(),
+        },
+        InputMode::AddMember => match &app.members {
+            Some(members) => format!(
+                "Share with: {}, {}",
+                members.adding.as_deref().unwrap_or_default(),
+                PERMISSIONS[members.right as usize]
+            ),
+            None => String::new(),
+        },
+        InputMode::ConfirmBulkLabel => match &app.bulk_label {
+            Some(bulk) => bulk_label_question(app, bulk),
+            None => String::new(),
+        },
+        InputMode::ConfirmBulkMove => match &app.bulk_move {
+            Some(bulk) => bulk_move_question(app, bulk),
+            None => String::new(),
+        },
+        InputMode::Notifications => {
+            let selected = app.notification_state.selected();
+            match selected.and_then(|i| Some((i, app.notifications.get(i)?))) {
+                Some((i, notification)) => format!(
+                    "Notifications: {}{} {}",
+                    if notification.is_unread() {
+                        "[UNREAD] "
+                    } else {
+                        ""
+                    },
+                    notification.summary(),
+                    position(i, app.notifications.len())
+                ),
+                None => "Notifications: none".to_string(),
+            }
+        }
+        InputMode::Command => format!("Command: {}", app.command),
+        InputMode::RenameTask => match &app.title_edit {
+            Some(edit) => format!("Editing title: {}", edit.text),
+            None => String::new(),
+        },
+        InputMode::ListFilter => {
+            let query = app.list_filter.as_deref().unwrap_or_default();
+            let count = app.visible_rows.len();
+            match app
+                .state
+                .selected()
+                .and_then(|i| app.tasks.get(app.visible_rows[i]))
+            {
+                Some(task) => format!(
+                    "Filter \"{}\", {} matches: {}",
+                    query,
+                    count,
+                    plain_task(task)
+                ),
+                None => format!("Filter \"{}\", no matches", query),
+            }
+        }
+        InputMode::DatePicker => match &app.date_picker {
+            Some(picker) => {
+                let mut text = format!(
+                    "{}: {}",
+                    date_picker_title(app.date_target),
+                    picker.selected.format("%A %-d %B %Y")
+                );
+                for event in app.events_on(picker.selected) {
+                    text.push_str(&format!(", {}: {}", event.calendar, event.summary));
+                }
+                text
+            }
+            None => String::new(),
+        },
+    }
+}
+
+// Turns the frame into plain text for screen readers, with box drawing
+// replaced by spaces
+fn strip_box_drawing(buffer: &mut Buffer) {
+    for cell in buffer.content.iter_mut() {
+        if cell
+            .symbol()
+            .chars()
+            .all(|c| ('\u{2500}'..='\u{259f}').contains(&c))
+        {
+            cell.set_symbol(" ");
+        }
+    }
+}
+
+// The description as plain lines, empty when the project has none
+fn project_description(project: &Project, width: u16) -> Vec<Line<'static>> {
+    let text = html2text::from_read(project.description.as_bytes(), width.max(1) as usize);
+    let text = text.trim();
+    if text.is_empty() {
+        return Vec::new();
+    }
+    ansi_to_text(text)
+}
+
+fn draw_task_view(f: &mut Frame, app: &mut App, body_chunk: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+        .split(body_chunk);
+
+    // Left panel: the project description, then the task list or board
+    let description = app
+        .current_project
+        .as_ref()
+        .map(|project| project_description(project, chunks[0].width.saturating_sub(2)))
+        .unwrap_or_default();
+    let left = if description.is_empty() {
+        chunks[0]
+    } else {
+        // At most three lines, the tasks matter more
+        let height = std::cmp::min(description.len(), 3) as u16 + 2;
+        let left = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(height), Constraint::Min(0)])
+            .split(chunks[0]);
+        let paragraph = Paragraph::new(description)
+            .block(Block::default().borders(Borders::ALL).title("Project"));
+        f.render_widget(paragraph, left[0]);
+        left[1]
+    };
+    match app.view_kind {
+        ViewKind::List | ViewKind::Done => draw_task_list(f, app, left),
+        ViewKind::Kanban => draw_board(f, app, left),
+    }
+
+    // Right panel: Task details
+    let detail_block = Block::default().borders(Borders::ALL).title("Task Details");
+
+    if let Some(ref detail) = app.task_detail {
+        let mut lines: Vec<Line<'static>> = Vec::new();
+
+        // Title, with the filter matches picked out like in the list
+        let mut title = Vec::new();
+        if !app.plain {
+            title.extend(color_marker(detail));
+        }
+        if !detail.identifier.is_empty() {
+            title.push(Span::styled(
+                format!("{} ", detail.identifier),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        title.extend(highlight_matches(
+            &detail.title,
+            app.list_filter.as_deref(),
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+        lines.push(Line::from(title));
+
+        let me = app.current_user.as_ref().map(|u| u.id);
+        let reactions = reaction_spans(detail.reactions.as_ref(), me, app.plain);
+        if !reactions.is_empty() {
+            lines.push(Line::from(reactions));
+        }
+
+        // Relations that hold up work go first, so they aren't missed
+        let blocked_by = detail.blocked_by();
+        if !blocked_by.is_empty() {
+            let titles: Vec<&str> = blocked_by.iter().map(|t| t.title.as_str()).collect();
+            let style = Style::default().fg(Color::Red).add_modifier(Modifier::BOLD);
+            lines.push(Line::from(vec![
+                Span::styled("Blocked by: ", style),
+                Span::styled(titles.join(", "), Style::default().fg(Color::Red)),
+            ]));
+        }
+        let blocking = detail.related("blocking");
+        if !blocking.is_empty() {
+            let titles: Vec<&str> = blocking.iter().map(|t| t.title.as_str()).collect();
+            lines.push(Line::from(vec![
+                Span::styled("Blocking: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(titles.join(", ")),
+            ]));
+        }
+
+        if let Some(completed) = detail.completed_at() {
+            lines.push(Line::from(vec![
+                Span::styled("Done: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::styled(
+                    completed.format(DONE_AT_FORMAT).to_string(),
+                    Style::default().fg(Color::Green),
+                ),
+            ]));
+        }
+
+        // Due date
+        let due_date = match &detail.due_date {
+            Some(date) if date != "0001-01-01T00:00:00Z" => date.clone(),
+            _ => "No due date".to_string(),
+        };
+        lines.push(Line::from(vec![
+            Span::styled("Due Date: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(due_date),
+        ]));
+
+        // Start and end dates plan the task on the Gantt chart, only shown
+        // when set
+        for (name, date) in [
+            ("Start Date: ", &detail.start_date),
+            ("End Date: ", &detail.end_date),
+        ] {
+            if let Some(date) = date.as_ref().filter(|date| *date != "0001-01-01T00:00:00Z") {
+                lines.push(Line::from(vec![
+                    Span::styled(name, Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(date.clone()),
+                ]));
+            }
+        }
+
+        // Absolute reminders, relative ones depend on dates not shown here
+        let reminders: Vec<String> = detail
+            .reminders
+            .iter()
+            .flatten()
+            .filter_map(|reminder| reminder.reminder.clone())
+            .collect();
+        if !reminders.is_empty() {
+            lines.push(Line::from(vec![
+                Span::styled("Reminders: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(reminders.join(", ")),
+            ]));
+        }
+
+        // Priority
+        let priority_str = match detail.priority {
+            Some(p) => p.to_string(),
+            None => "No priority".to_string(),
+        };
+        lines.push(Line::from(vec![
+            Span::styled("Priority: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(priority_str),
+        ]));
+
+        // Labels
+        lines.push(Line::from(vec![Span::styled(
+            "Labels: ",
+            Style::default().add_modifier(Modifier::BOLD),
+        )]));
+
+        match &detail.labels {
+            Some(labels) if !labels.is_empty() => {
+                let mut label_spans: Vec<Span<'static>> = Vec::new();
+                for (i, label) in labels.iter().enumerate() {
+                    if i > 0 {
+                        label_spans.push(Span::raw(" "));
+                    }
+                    label_spans.push(if app.plain {
+                        Span::raw(format!("[{}]", label.title))
+                    } else {
+                        Span::styled(
+                            format!(" {} ", label.title),
+                            label_style(label.hex_color.as_deref()),
+                        )
+                    });
+                }
+                lines.push(Line::from(label_spans));
+            }
+            _ => {
+                lines.push(Line::from(Span::raw("No labels".to_string())));
+            }
+        }
+
+        // Description
+        lines.push(Line::from(vec![Span::styled(
+            "Description: ",
+            Style::default().add_modifier(Modifier::BOLD),
+        )]));
+
+        if let Some(desc) = &detail.description {
+            if desc.trim() == "<p></p>" {
+                lines.push(Line::from(Span::raw("No description".to_string())));
+            } else {
+                let width = chunks[1].width.saturating_sub(2).max(1) as usize; // Adjust for borders
+                lines.extend(description_lines(desc, width, app));
+            }
+        } else {
+            lines.push(Line::from(Span::raw("No description".to_string())));
+        }
+
+        if !app.task_comments.is_empty() {
+            lines.push(Line::from(vec![Span::styled(
+                "Comments: ",
+                Style::default().add_modifier(Modifier::BOLD),
+            )]));
+        }
+        for comment in &app.task_comments {
+            let created = comment
+                .created
+                .as_deref()
+                .and_then(|created| created.get(..10))
+                .unwrap_or_default();
+            lines.push(Line::from(vec![
+                Span::styled(
+                    comment.author.username.clone(),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!(" {}", created),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]));
+            let width = chunks[1].width.saturating_sub(2).max(1) as usize;
+            let text = html2text::from_read(comment.comment.as_bytes(), width);
+            lines.append(&mut ansi_to_text(&text));
+            let reactions = reaction_spans(comment.reactions.as_ref(), me, app.plain);
+            if !reactions.is_empty() {
+                lines.push(Line::from(reactions));
+            }
+        }
+
+        // Not trimmed, so code keeps its indentation
+        let paragraph = Paragraph::new(lines)
+            .block(detail_block)
+            .wrap(Wrap { trim: false });
+        f.render_widget(paragraph, chunks[1]);
+    } else {
+        let paragraph = Paragraph::new("Press Enter to view task details")
+            .block(detail_block)
+            .wrap(Wrap { trim: true });
+        f.render_widget(paragraph, chunks[1]);
+    }
+}
+
+// Prose is wrapped to the pane. Code blocks keep their lines, broken up at
+// the pane's edge or, with wrapping off, cut off and scrolled sideways.
+fn description_lines(html: &str, width: usize, app: &App) -> Vec<Line<'static>> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    for section in split_sections(html) {
+        match section {
+            Section::Html(html) => {
+                lines.extend(ansi_to_text(&html2text::from_read(html.as_bytes(), width)))
+            }
+            Section::Code { language, code } => {
+                for pieces in highlight_code(&code, language.as_deref()) {
+                    let chars: Vec<(char, Style)> = pieces
+                        .iter()
+                        .flat_map(|(style, text)| text.chars().map(move |c| (c, *style)))
+                        .collect();
+                    if app.detail_wrap {
+                        for chunk in chars.chunks(width) {
+                            lines.push(styled_line(chunk));
+                        }
+                        if chars.is_empty() {
+                            lines.push(Line::default());
+                        }
+                    } else {
+                        let visible: Vec<(char, Style)> = chars
+                            .into_iter()
+                            .skip(app.detail_scroll_x)
+                            .take(width)
+                            .collect();
+                        lines.push(styled_line(&visible));
+                    }
+                }
+                lines.push(Line::default());
+            }
+            Section::Table { header, rows } => {
+                lines.extend(
+                    layout_table(header, &rows, width)
+                        .into_iter()
+                        .map(Line::from),
+                );
+            }
+        }
+    }
+    lines
+}
+
+// Characters back into spans, one for each run of the same style
+fn styled_line(chars: &[(char, Style)]) -> Line<'static> {
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    for &(c, style) in chars {
+        match spans.last_mut() {
+            Some(span) if span.style == style => span.content.to_mut().push(c),
+            _ => spans.push(Span::styled(c.to_string(), style)),
+        }
+    }
+    Line::from(spans)
+}
+
+// Each emoji with how many reacted, the ones I gave reversed
+fn reaction_spans(
+    reactions: Option<&Reactions>,
+    me: Option<u64>,
+    plain: bool,
+) -> Vec<Span<'static>> {
+    let mut reactions: Vec<_> = reactions
+        .into_iter()
+        .flatten()
+        .filter(|(_, users)| !users.is_empty())
+        .collect();
+    reactions.sort_by(|a, b| a.0.cmp(b.0));
+    let mut spans = Vec::new();
+    for (value, users) in reactions {
+        let mine = users.iter().any(|u| Some(u.id) == me);
+        if !spans.is_empty() {
+            spans.push(Span::raw(" "));
+        }
+        spans.push(match (mine, plain) {
+            (true, true) => Span::raw(format!("[{} {}, yours]", value, users.len())),
+            (false, true) => Span::raw(format!("[{} {}]", value, users.len())),
+            (true, false) => Span::styled(
+                format!(" {} {} ", value, users.len()),
+                Style::default().add_modifier(Modifier::REVERSED),
+            ),
+            (false, false) => Span::raw(format!(" {} {} ", value, users.len())),
+        });
+    }
+    spans
+}
+
+// The completion list opens under the title input, over the preview
+fn draw_completion(f: &mut Frame, app: &mut App, title_area: Rect) {
+    let Some(completion) = app.completion.as_mut() else {
+        return;
+    };
+    let picker = &mut completion.picker;
+    let title = match completion.kind {
+        CompletionKind::Label => "Labels",
+        CompletionKind::Project => "Projects",
+        CompletionKind::User => "Users",
+    };
+    let height = std::cmp::min(picker.matches.len() as u16, 6u16) + 2;
+    let area = Rect {
+        x: title_area.x + 1,
+        y: title_area.y + title_area.height,
+        width: std::cmp::min(32u16, title_area.width.saturating_sub(2)),
+        height,
+    }
+    .intersection(f.area());
+
+    let items: Vec<ListItem> = picker
+        .matches
+        .iter()
+        .map(|&i| ListItem::new(picker.candidates[i].clone()))
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(Color::Cyan)),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("> ");
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut picker.state);
+}
+
+fn picker_title(input_mode: &InputMode) -> &'static str {
+    match input_mode {
+        InputMode::TaskFinder => "Find Task",
+        InputMode::FilterMenu => "Apply Filter",
+        InputMode::ProfileSwitcher => "Switch Instance",
+        InputMode::ReactionTargets => "React To",
+        InputMode::ReactionPicker => "Reaction",
+        InputMode::Links => "Open Link",
+        InputMode::ProjectChoice => "Add To Project",
+        InputMode::DuplicateParent => "Duplicate Into",
+        _ => "Switch Project",
+    }
+}
+
+// Unread notifications are bold with a dot, read ones greyed out
+fn draw_notifications(f: &mut Frame, app: &mut App, area: Rect) {
+    let plain = app.plain;
+    let items: Vec<ListItem> = app
+        .notifications
+        .iter()
+        .map(|notification| {
+            let created = notification
+                .created
+                .as_deref()
+                .and_then(|created| created.get(..10))
+                .unwrap_or_default();
+            let text = format!("{}  {}", notification.summary(), created);
+            match (notification.is_unread(), plain) {
+                (true, true) => ListItem::new(format!("[UNREAD] {}", text)),
+                (true, false) => ListItem::new(Line::from(vec![
+                    Span::styled("● ", Style::default().fg(Color::Cyan)),
+                    Span::styled(text, Style::default().add_modifier(Modifier::BOLD)),
+                ])),
+                (false, _) => ListItem::new(Line::from(vec![
+                    Span::raw("  "),
+                    Span::styled(text, Style::default().fg(Color::DarkGray)),
+                ])),
+            }
+        })
+        .collect();
+
+    let popup_width = std::cmp::min(80u16, area.width);
+    let popup_height = std::cmp::min(items.len() as u16 + 2, area.height).max(3);
+    let popup_area = centered_rect_absolute(popup_width, popup_height, area);
+    let title = format!("Notifications ({} unread)", app.unread_notifications());
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(Color::Green)),
+        )
+        .highlight_style(Style::default().fg(Color::Green))
+        .highlight_symbol(">> ");
+    f.render_widget(Clear, popup_area);
+    f.render_stateful_widget(list, popup_area, &mut app.notification_state);
+}
+
+// A row per user, then per team, with their permission
+fn member_rows(members: &Members) -> Vec<String> {
+    let users = members
+        .users
+        .iter()
+        .map(|shared| (user_name(&shared.user), shared.right));
+    let teams = members
+        .teams
+        .iter()
+        .map(|shared| (format!("{}{}", TEAM_PREFIX, shared.team.name), shared.right));
+    users
+        .chain(teams)
+        .map(|(name, right)| {
+            let permission = PERMISSIONS.get(right as usize).unwrap_or(&"Unknown");
+            format!("{:<32} {}", name, permission)
+        })
+        .collect()
+}
+
+fn draw_members(f: &mut Frame, app: &mut App, area: Rect) {
+    let Some(members) = app.members.as_mut() else {
+        return;
+    };
+    let rows = member_rows(members);
+    let adding_height = if members.adding.is_some() { 3 } else { 0 };
+    let popup_width = std::cmp::min(60u16, area.width);
+    let popup_height =
+        std::cmp::min(rows.len().max(1) as u16 + 2 + adding_height, area.height).max(3);
+    let popup_area = centered_rect_absolute(popup_width, popup_height, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(adding_height)])
+        .split(popup_area);
+
+    let items: Vec<ListItem> = if rows.is_empty() {
+        vec![ListItem::new(Span::styled(
+            "Not shared with anyone",
+            Style::default().fg(Color::DarkGray),
+        ))]
+    } else {
+        rows.into_iter().map(ListItem::new).collect()
+    };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("{} Is Shared With", members.project.title))
+                .border_style(Style::default().fg(Color::Green)),
+        )
+        .highlight_style(Style::default().fg(Color::Green))
+        .highlight_symbol(HIGHLIGHT_SYMBOL);
+    f.render_widget(Clear, popup_area);
+    f.render_stateful_widget(list, chunks[0], &mut members.state);
+
+    if let Some(adding) = &members.adding {
+        let input = Paragraph::new(adding.as_str())
+            .style(Style::default().fg(Color::White))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(
+                        "Share With, {} (Tab)",
+                        PERMISSIONS[members.right as usize]
+                    ))
+                    .style(Style::default().fg(Color::Yellow)),
+            );
+        f.render_widget(input, chunks[1]);
+        f.set_cursor_position((chunks[1].x + 1 + adding.width() as u16, chunks[1].y + 1));
+    }
+}
+
+fn team_member_rows(members: Option<&[TeamMember]>) -> Vec<String> {
+    members
+        .unwrap_or_default()
+        .iter()
+        .map(|member| {
+            let name = user_name(&member.user);
+            if member.admin {
+                format!("{} admin", name)
+            } else {
+                name
+            }
+        })
+        .collect()
+}
+
+fn team_input_title(teams: Option<&Teams>, input: &TeamInput) -> String {
+    match input {
+        TeamInput::NewTeam(_) => "New Team".to_string(),
+        TeamInput::AddMember(_) => match teams.and_then(Teams::selected) {
+            Some(team) => format!("Add Username To {}", team.name),
+            None => "Add Username".to_string(),
+        },
+    }
+}
+
+// The teams on the left, the members of the selected one on the right
+fn draw_teams(f: &mut Frame, app: &mut App, area: Rect) {
+    let Some(teams) = app.teams.as_mut() else {
+        return;
+    };
+    let input_height = if teams.input.is_some() { 3 } else { 0 };
+    let most_members = teams
+        .teams
+        .iter()
+        .map(|team| team.members.as_ref().map_or(0, Vec::len))
+        .max()
+        .unwrap_or(0);
+    let rows = teams.teams.len().max(most_members).max(1) as u16;
+    let popup_width = std::cmp::min(80u16, area.width);
+    let popup_height = std::cmp::min(rows + 2 + input_height, area.height).max(3);
+    let popup_area = centered_rect_absolute(popup_width, popup_height, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(input_height)])
+        .split(popup_area);
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(chunks[0]);
+
+    let items: Vec<ListItem> = if teams.teams.is_empty() {
+        vec![ListItem::new(Span::styled(
+            "In no teams yet",
+            Style::default().fg(Color::DarkGray),
+        ))]
+    } else {
+        teams
+            .teams
+            .iter()
+            .map(|team| {
+                let count = team.members.as_ref().map_or(0, Vec::len);
+                ListItem::new(format!("{} ({})", team.name, count))
+            })
+            .collect()
+    };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Teams")
+                .border_style(Style::default().fg(Color::Green)),
+        )
+        .highlight_style(Style::default().fg(Color::Green))
+        .highlight_symbol(HIGHLIGHT_SYMBOL);
+    let members: Vec<ListItem> =
+        team_member_rows(teams.selected().and_then(|team| team.members.as_deref()))
+            .into_iter()
+            .map(ListItem::new)
+            .collect();
+    let members = List::new(members).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Members")
+            .border_style(Style::default().fg(Color::Green)),
+    );
+    f.render_widget(Clear, popup_area);
+    f.render_stateful_widget(list, columns[0], &mut teams.state);
+    f.render_widget(members, columns[1]);
+
+    if let Some(input) = &teams.input {
+        let text = input.text();
+        let block = Paragraph::new(text)
+            .style(Style::default().fg(Color::White))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(team_input_title(Some(teams), input))
+                    .style(Style::default().fg(Color::Yellow)),
+            );
+        f.render_widget(block, chunks[1]);
+        f.set_cursor_position((chunks[1].x + 1 + text.width() as u16, chunks[1].y + 1));
+    }
+}
+
+// Undo only brings back the title, description, labels and due date
+fn delete_question(app: &App) -> String {
+    match &app.pending_delete {
+        Some(task) => format!(
+            "Delete \"{}\"? Its comments and attachments can't be brought back.",
+            task.title
+        ),
+        None => String::new(),
+    }
+}
+
+fn unshare_question(app: &App) -> String {
+    match app.members.as_ref().and_then(Members::selected) {
+        Some((_, name)) => format!("Stop sharing the project with {}?", name),
+        None => String::new(),
+    }
+}
+
+fn draw_picker(f: &mut Frame, app: &mut App, area: Rect) {
+    let title = picker_title(&app.input_mode);
+    let plain = app.plain;
+    let Some(picker) = app.picker.as_mut() else {
+        return;
+    };
+
+    let popup_width = std::cmp::min(60u16, area.width);
+    let popup_height = std::cmp::min(16u16, area.height);
+    let popup_area = centered_rect_absolute(popup_width, popup_height, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3u16), Constraint::Min(0)])
+        .split(popup_area);
+
+    let input = Paragraph::new(picker.query.as_str())
+        .style(Style::default().fg(Color::White))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .style(Style::default().fg(Color::Yellow)),
+        );
+
+    let items: Vec<ListItem> = picker
+        .matches
+        .iter()
+        .map(|&i| {
+            let title = Span::raw(picker.candidates[i].clone());
+            match picker.colors.get(i).filter(|_| !plain) {
+                Some(Some(color)) => {
+                    Line::from(vec![Span::styled("● ", Style::default().fg(*color)), title])
+                }
+                Some(None) => Line::from(vec![Span::raw("  "), title]),
+                None => Line::from(title),
+            }
+        })
+        .map(ListItem::new)
+        .collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL))
+        .highlight_style(
+            Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol(">> ");
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(input, chunks[0]);
+    f.render_stateful_widget(list, chunks[1], &mut picker.state);
+
+    let cursor_x = chunks[0].x + picker.query.chars().count() as u16 + 1;
+    f.set_cursor_position((cursor_x, chunks[0].y + 1));
+}
+
+fn move_question(card_move: &CardMove) -> String {
+    if card_move.done == Some(false) {
+        format!(
+            "Move \"{}\" out of the done bucket and mark it as not done?",
+            card_move.title
+        )
+    } else {
+        format!(
+            "Move \"{}\" to the done bucket and mark it as done?",
+            card_move.title
+        )
+    }
+}
+
+// A yes or no question in a popup over the view
+fn draw_confirm(f: &mut Frame, area: Rect, title: &str, question: String) {
+    let popup_area = centered_rect_absolute(std::cmp::min(60u16, area.width), 5u16, area);
+    let popup = Paragraph::new(question)
+        .style(Style::default().fg(Color::White))
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Green)),
+        );
+    f.render_widget(Clear, popup_area);
+    f.render_widget(popup, popup_area);
+}
+
+// The latest API requests over the view, newest first, to see what the app
+// asked the server and what came back
+fn draw_api_log(f: &mut Frame, app: &App, area: Rect) {
+    let area = area.inner(Margin {
+        horizontal: 2,
+        vertical: 1,
+    });
+    let lines = match app.api.call_log() {
+        // Inside the borders
+        Some(log) => {
+            log.with_recent(|calls| api_log_lines(calls, area.height.saturating_sub(2) as usize))
+        }
+        None => Vec::new(),
+    };
+    let lines = if lines.is_empty() {
+        vec![Line::raw("No requests yet")]
+    } else {
+        lines
+    };
+    let log = Paragraph::new(lines).block(
+        Block::default()
+            .title("API Requests (F12 to close)")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Magenta)),
+    );
+    f.render_widget(Clear, area);
+    f.render_widget(log, area);
+}
+
+// A line for each of the newest calls that fit
+fn api_log_lines(calls: &[ApiCall], rows: usize) -> Vec<Line<'static>> {
+    calls
+        .iter()
+        .rev()
+        .take(rows)
+        .map(|call| {
+            let (status, color) = match call.status {
+                Some(status) if status >= 400 => (status.to_string(), Color::Red),
+                Some(304) => ("304".to_string(), Color::Cyan),
+                Some(status) => (status.to_string(), Color::Green),
+                None => ("ERR".to_string(), Color::Red),
+            };
+            Line::from(vec![
+                Span::raw(format!("{:<6} ", call.method)),
+                Span::styled(format!("{} ", status), Style::default().fg(color)),
+                Span::styled(
+                    format!("{:>6} ms ", call.latency.as_millis()),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::raw(call.path.clone()),
+            ])
+        })
+        .collect()
+}
+
+fn new_labels_question(app: &App) -> String {
+    let titles: Vec<String> = app
+        .new_labels
+        .iter()
+        .map(|label| format!("\"{}\"", label.title))
+        .collect();
+    match titles.len() {
+        1 => format!("The label {} doesn't exist yet. Create it?", titles[0]),
+        _ => format!(
+            "The labels {} don't exist yet. Create them?",
+            titles.join(", ")
+        ),
+    }
+}
+
+fn draft_question(app: &App) -> String {
+    match &app.saved.draft {
+        Some(draft) if !draft.title.trim().is_empty() => format!(
+            "Restore the task you were adding, \"{}\"?",
+            draft.title.trim()
+        ),
+        _ => "Restore the task you were adding?".to_string(),
+    }
+}
+
+// One "Field: value" line for each part of the new task that is set
+fn new_task_summary(preview: &NewTaskPreview) -> Vec<String> {
+    let task = &preview.task;
+    let mut lines = vec![
+        format!(
+            "Project: {}",
+            preview.project.as_deref().unwrap_or("default project")
+        ),
+        format!("Title: {}", task.title),
+    ];
+    if let Some(priority) = task.priority.filter(|&p| p > 0) {
+        lines.push(format!("Priority: {}", priority));
+    }
+    let due = task
+        .due_date
+        .as_deref()
+        .and_then(|due| DateTime::parse_from_rfc3339(due).ok());
+    if let Some(due) = due {
+        let due = due.with_timezone(&Local);
+        lines.push(format!("Due: {}", due.format("%Y-%m-%d %H:%M")));
+    }
+    if !preview.labels.is_empty() {
+        lines.push(format!("Labels: {}", preview.labels.join(", ")));
+    }
+    if !preview.assignees.is_empty() {
+        lines.push(format!("Assignees: {}", preview.assignees.join(", ")));
+    }
+    lines
+}
+
+fn draw_new_task_preview(f: &mut Frame, area: Rect, preview: &NewTaskPreview) {
+    let lines = new_task_summary(preview);
+    let height = std::cmp::min(lines.len() as u16 + 2, area.height);
+    let popup_area = centered_rect_absolute(std::cmp::min(60u16, area.width), height, area);
+    let popup = Paragraph::new(lines.into_iter().map(Line::raw).collect::<Vec<_>>())
+        .style(Style::default().fg(Color::White))
+        .block(
+            Block::default()
+                .title("Create Task?")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Green)),
+        );
+    f.render_widget(Clear, popup_area);
+    f.render_widget(popup, popup_area);
+}
+
+fn bulk_label_question(app: &App, bulk: &BulkLabel) -> String {
+    let count = bulk.task_ids.len();
+    let tasks = if count == 1 { "task" } else { "tasks" };
+    if bulk.remove {
+        format!(
+            "Remove the label \"{}\" from {} {} in {}?",
+            bulk.label.title,
+            count,
+            tasks,
+            list_title(app)
+        )
+    } else {
+        format!(
+            "Add the label \"{}\" to {} {} in {}?",
+            bulk.label.title,
+            count,
+            tasks,
+            list_title(app)
+        )
+    }
+}
+
+fn bulk_move_question(app: &App, bulk: &BulkMove) -> String {
+    let count = bulk.task_ids.len();
+    let tasks = if count == 1 { "task" } else { "tasks" };
+    let from = if app.marked.is_empty() {
+        list_title(app)
+    } else {
+        "the marked tasks".to_string()
+    };
+    format!(
+        "Move {} {} from {} to {}?",
+        count, tasks, from, bulk.project.title
+    )
+}
+
+// A bar of blocks filled to the share done, like "████░░░░"
+fn progress_bar(done: usize, total: usize, width: usize) -> String {
+    let filled = (done * width).checked_div(total).unwrap_or(0).min(width);
+    format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))
+}
+
+// The add popup's title, naming where the task will go
+fn new_task_title(app: &App) -> String {
+    let target = match (&app.current_project, app.new_task_bucket()) {
+        (Some(project), Some(bucket)) => format!(" in {} › {}", project.title, bucket.title),
+        (Some(project), None) => format!(" in {}", project.title),
+        (None, _) => String::new(),
+    };
+    format!(
+        "Enter New Task{} (Press Enter to Submit, Tab to Switch)",
+        target
+    )
+}
+
+fn date_picker_title(target: DateTarget) -> &'static str {
+    match target {
+        DateTarget::Due => "Due Date",
+        DateTarget::Reminder => "Reminder",
+        DateTarget::Start => "Start Date",
+        DateTarget::End => "End Date",
+    }
+}
+
+// The dates and color picked for the new task, with the keys that change them
+fn new_task_dates(app: &App) -> Paragraph<'static> {
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let show = |date: Option<NaiveDate>| match date {
+        Some(date) => date.format("%Y-%m-%d").to_string(),
+        None => "none".to_string(),
+    };
+    let mut spans = vec![
+        Span::styled("Due: ", bold),
+        Span::raw(format!("{} (d)", show(app.new_task_due))),
+        Span::raw(" · "),
+        Span::styled("Reminder: ", bold),
+        Span::raw(format!("{} (r)", show(app.new_task_reminder))),
+        Span::raw(" · "),
+        Span::styled("Start: ", bold),
+        Span::raw(format!("{} (s)", show(app.new_task_start))),
+        Span::raw(" · "),
+        Span::styled("End: ", bold),
+        Span::raw(format!("{} (e)", show(app.new_task_end))),
+        Span::raw(" · "),
+        Span::styled("Color: ", bold),
+    ];
+    match app.new_task_color.map(|i| TASK_COLORS[i]) {
+        Some((name, hex)) => {
+            spans.extend(
+                hex_color(hex).map(|color| Span::styled(COLOR_MARKER, Style::default().fg(color))),
+            );
+            spans.push(Span::raw(format!("{} (c)", name)));
+        }
+        None => spans.push(Span::raw("none (c)")),
+    }
+    Paragraph::new(Line::from(spans)).wrap(Wrap { trim: true })
+}
+
+// A month calendar over the add popup, the selected day reversed and today
+// underlined. Days with events from the configured calendars are cyan, and
+// the selected day's events are listed under the month.
+fn draw_date_picker(f: &mut Frame, app: &App, area: Rect) {
+    let Some(picker) = &app.date_picker else {
+        return;
+    };
+    let weeks = picker.weeks();
+    let mut lines = vec![
+        Line::from(Span::styled(
+            picker.selected.format("%B %Y").to_string(),
+            Style::default().add_modifier(Modifier::BOLD),
+        ))
+        .alignment(Alignment::Center),
+        Line::from(Span::styled(
+            "Mo Tu We Th Fr Sa Su",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    for week in &weeks {
+        lines.push(calendar_week(app, picker, week));
+    }
+    let events = app.events_on(picker.selected);
+    let mut width = 24;
+    for event in &events {
+        let line = format!("{}: {}", event.calendar, event.summary);
+        width = width.max(line.width() + 2);
+        lines.push(Line::from(Span::styled(
+            line,
+            Style::default().fg(Color::Cyan),
+        )));
+    }
+
+    let popup_area = centered_rect_absolute(
+        std::cmp::min(width as u16, area.width),
+        std::cmp::min(lines.len() as u16 + 2, area.height),
+        area,
+    );
+    let popup = Paragraph::new(lines)
+        .style(Style::default().fg(Color::White))
+        .block(
+            Block::default()
+                .title(date_picker_title(app.date_target))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Green)),
+        );
+    f.render_widget(Clear, popup_area);
+    f.render_widget(popup, popup_area);
+}
+
+fn calendar_week(app: &App, picker: &DatePicker, week: &[Option<NaiveDate>; 7]) -> Line<'static> {
+    let today = today();
+    let mut spans = Vec::new();
+    for (i, day) in week.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::raw(" "));
+        }
+        let Some(day) = day else {
+            spans.push(Span::raw("  "));
+            continue;
+        };
+        let mut style = Style::default();
+        if !app.events_on(*day).is_empty() {
+            style = style.fg(Color::Cyan);
+        }
+        if *day == picker.selected {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
+        if *day == today {
+            style = style.add_modifier(Modifier::UNDERLINED);
+        }
+        spans.push(Span::styled(format!("{:>2}", day.day()), style));
+    }
+    Line::from(spans)
+}
+
+fn auth_field_title(field: AuthField) -> &'static str {
+    match field {
+        AuthField::Token => "API Token",
+        AuthField::Username => "Username",
+        AuthField::Password => "Password",
+        AuthField::Totp => "TOTP Passcode",
+    }
+}
+
+fn draw_auth_form(f: &mut Frame, app: &App, area: Rect) {
+    let form = &app.auth_form;
+    let popup_width = std::cmp::min(60u16, area.width);
+    let fields = form.fields();
+    let popup_height = std::cmp::min(6u16 + 3 * fields.len() as u16, area.height);
+    let popup_area = centered_rect_absolute(popup_width, popup_height, area);
+
+    let popup_block = Block::default()
+        .title("Session Expired")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Green));
+
+    // Help text, one box per field, then room for an error
+    let mut constraints = vec![Constraint::Length(2u16)];
+    constraints.extend(fields.iter().map(|_| Constraint::Length(3u16)));
+    constraints.push(Constraint::Min(0));
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(popup_area.inner(Margin {
+            vertical: 1u16,
+            horizontal: 1u16,
+        }));
+
+    let help = Paragraph::new("Paste a new API token, or sign in with your username and password")
+        .style(Style::default().fg(Color::White))
+        .wrap(Wrap { trim: true });
+
+    // The password is masked, the other fields are shown as typed
+    let masked_password = "*".repeat(form.password.chars().count());
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(popup_block, popup_area);
+    f.render_widget(help, chunks[0]);
+
+    for (i, field) in fields.iter().enumerate() {
+        let value = match field {
+            AuthField::Token => form.token.as_str(),
+            AuthField::Username => form.username.as_str(),
+            AuthField::Password => masked_password.as_str(),
+            AuthField::Totp => form.totp_passcode.as_deref().unwrap_or(""),
+        };
+        let title = auth_field_title(*field);
+        let chunk = chunks[i + 1];
+        let style = if form.field == *field {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        };
+        let input = Paragraph::new(value)
+            .style(Style::default().fg(Color::White))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(title)
+                    .style(style),
+            );
+        f.render_widget(input, chunk);
+
+        if form.field == *field {
+            let cursor_x = chunk.x + value.chars().count() as u16 + 1;
+            f.set_cursor_position((cursor_x, chunk.y + 1));
+        }
+    }
+
+    if let Some(error) = &form.error {
+        let error = Paragraph::new(error.as_str())
+            .style(Style::default().fg(Color::Red))
+            .wrap(Wrap { trim: true });
+        f.render_widget(error, chunks[fields.len() + 1]);
+    }
+}
+
+fn draw_status_line(f: &mut Frame, app: &App, area: Rect) {
+    // The command line takes the whole line while it is open
+    if let InputMode::Command = app.input_mode {
+        let prompt = format!(":{}", app.command);
+        f.set_cursor_position((area.x + prompt.chars().count() as u16, area.y));
+        f.render_widget(Paragraph::new(prompt), area);
+        return;
+    }
+    let mut spans = Vec::new();
+    if app.read_only {
+        spans.push(Span::styled(
+            " READ-ONLY ",
+            Style::default().bg(Color::DarkGray).fg(Color::White),
+        ));
+        spans.push(Span::raw(" "));
+    }
+    let bulk_progress = app
+        .bulk_label
+        .as_ref()
+        .and_then(|bulk| {
+            let doing = format!("Labeling {}", bulk.label.title);
+            Some((doing, bulk.task_ids.len(), bulk.progress.as_ref()?))
+        })
+        .or_else(|| {
+            let bulk = app.bulk_move.as_ref()?;
+            let doing = format!("Moving to {}", bulk.project.title);
+            Some((doing, bulk.task_ids.len(), bulk.progress.as_ref()?))
+        });
+    if let Some((doing, total, progress)) = bulk_progress {
+        let done = progress.load(Ordering::Relaxed);
+        let status = if app.plain {
+            format!("{}: {} of {} ", doing, done, total)
+        } else {
+            format!(
+                "{} {} {}/{} ",
+                doing,
+                progress_bar(done, total, BULK_PROGRESS_WIDTH),
+                done,
+                total
+            )
+        };
+        spans.push(Span::styled(status, Style::default().fg(Color::Cyan)));
+    } else if let Some(load) = &app.all_tasks_load {
+        let loaded = load.progress.loaded_pages.load(Ordering::Relaxed);
+        let total = load.progress.total_pages.load(Ordering::Relaxed);
+        let status = if total == 0 {
+            "Loading all tasks... ".to_string()
+        } else {
+            format!("Loading all tasks: page {}/{} ", loaded, total)
+        };
+        spans.push(Span::styled(status, Style::default().fg(Color::Cyan)));
+    } else if app.loading() {
+        spans.push(Span::styled(
+            "Loading... ",
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    if let Some(timer) = &app.timer {
+        let elapsed = format_elapsed(timer.elapsed());
+        let status = if app.plain {
+            format!("Tracking {}: {} ", timer.title, elapsed)
+        } else {
+            format!("⏱ {} {} ", elapsed, timer.title)
+        };
+        spans.push(Span::styled(status, Style::default().fg(Color::Magenta)));
+    }
+    let unread = app.unread_notifications();
+    if unread > 0 {
+        let badge = if app.plain {
+            format!("[{} unread notifications] ", unread)
+        } else {
+            format!(" ● {} unread ", unread)
+        };
+        spans.push(Span::styled(
+            badge,
+            Style::default().bg(Color::Blue).fg(Color::White),
+        ));
+        spans.push(Span::raw(" "));
+    }
+    if let Some(message) = &app.message {
+        spans.push(Span::styled(
+            message.clone(),
+            Style::default().fg(Color::Yellow),
+        ));
+    } else if app.plain {
+        spans.push(Span::raw(announcement(app)));
+    }
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
+
+    // The version would cover long announcements
+    if app.plain {
+        return;
+    }
+    // How old the view is, for shared projects others change meanwhile
+    let updated = app
+        .loaded_at
+        .map(|at| format!("updated {} · ", age(at.elapsed())))
+        .unwrap_or_default();
+    let mut right = Vec::new();
+    // Only requests to a real server are timed, so the demo shows none
+    let latency = app
+        .api
+        .call_log()
+        .and_then(|log| log.with_recent(|calls| average_latency(calls, LATENCY_WINDOW)));
+    if let Some(latency) = latency {
+        if latency >= SLOW_LATENCY {
+            right.push(Span::styled(
+                format!("server slow, avg {} ", short_duration(latency)),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        let sync = app
+            .last_sync
+            .map(|sync| format!("sync {} · ", short_duration(sync)))
+            .unwrap_or_default();
+        right.push(Span::raw(format!(
+            "{}avg {} · ",
+            sync,
+            short_duration(latency)
+        )));
+    }
+    if let Some(info) = &app.server_info {
+        // The profile name tells instances apart when there are several
+        let instance = if app.profiles.len() > 1 {
+            format!("{}{} · Vikunja {} ", updated, app.profile, info.version)
+        } else {
+            format!("{}Vikunja {} ", updated, info.version)
+        };
+        right.push(Span::raw(instance));
+    }
+    let version = Paragraph::new(Line::from(right))
+        .style(Style::default().fg(Color::DarkGray))
+        .alignment(Alignment::Right);
+    f.render_widget(version, area);
+}
+
+// Like "85ms", or "1.4s" from a second on
+fn short_duration(duration: Duration) -> String {
+    if duration < Duration::from_secs(1) {
+        format!("{}ms", duration.as_millis())
+    } else {
+        format!("{:.1}s", duration.as_secs_f64())
+    }
+}
+
+// Like "12s ago", or "just now" for the first few seconds
+fn age(elapsed: Duration) -> String {
+    match elapsed.as_secs() {
+        0..=9 => "just now".to_string(),
+        secs @ 10..=59 => format!("{}s ago", secs),
+        secs @ 60..=3599 => format!("{}m ago", secs / 60),
+        secs => format!("{}h ago", secs / 3600),
+    }
+}
+
+// Plays the keys to the app on a screen of the given size without a terminal
+// and returns what is on it at the end, one line per row. Each key waits for
+// the requests it sends.
+pub async fn render_headless(
+    mut app: App,
+    width: u16,
+    height: u16,
+    keys: &[Key],
+) -> io::Result<String> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+    app.settle().await;
+    for key in keys {
+        if app.handle_event(AppEvent::Key(*key))? {
+            break;
+        }
+        app.settle().await;
+    }
+    terminal.draw(|f| draw(f, &mut app))?;
+    Ok(buffer_text(terminal.backend().buffer()))
+}
+
+// The symbols in the buffer, leaving out the cells wide ones cover
+fn buffer_text(buffer: &Buffer) -> String {
+    let mut text = String::new();
+    let width = buffer.area.width as usize;
+    for y in 0..buffer.area.height as usize {
+        let row = &buffer.content[y * width..(y + 1) * width];
+        let mut covered = 0;
+        for cell in row {
+            if covered == 0 {
+                text.push_str(cell.symbol());
+            }
+            covered = covered.max(cell.symbol().width()).saturating_sub(1);
+        }
+        text.push('\n');
+    }
+    text
+}
+
+// Draws the whole screen
+pub fn draw(f: &mut Frame, app: &mut App) {
+    let size = f.area();
+
+    // Split the main layout into body, status line and footer
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(0)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(1u16),
+            Constraint::Length(2u16),
+        ])
+        .split(size);
+
+    let mut body_chunk = chunks[0];
+    let status_chunk = chunks[1];
+    let footer_chunk = chunks[2];
+
+    // The tab bar only takes room once there is more than one tab
+    let tab_titles = app.tab_titles();
+    if tab_titles.len() > 1 {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(body_chunk);
+        let tabs = Tabs::new(tab_titles)
+            .select(app.active_tab)
+            .highlight_style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD | Modifier::REVERSED),
+            );
+        f.render_widget(tabs, rows[0]);
+        body_chunk = rows[1];
+    }
+
+    match app.input_mode {
+        InputMode::Normal | InputMode::ListFilter | InputMode::Command | InputMode::RenameTask => {
+            draw_task_view(f, app, body_chunk)
+        }
+        InputMode::ProjectSwitcher
+        | InputMode::TaskFinder
+        | InputMode::FilterMenu
+        | InputMode::ProfileSwitcher
+        | InputMode::ReactionTargets
+        | InputMode::ReactionPicker
+        | InputMode::Links
+        | InputMode::ProjectChoice
+        | InputMode::DuplicateParent => {
+            draw_task_view(f, app, body_chunk);
+            draw_picker(f, app, body_chunk);
+        }
+        InputMode::Reauth => {
+            draw_task_view(f, app, body_chunk);
+            draw_auth_form(f, app, body_chunk);
+        }
+        InputMode::ConfirmMove => {
+            draw_task_view(f, app, body_chunk);
+            if let Some(card_move) = &app.pending_move {
+                draw_confirm(f, body_chunk, "Done Bucket", move_question(card_move));
+            }
+        }
+        InputMode::ConfirmNewLabels => {
+            draw_task_view(f, app, body_chunk);
+            draw_confirm(f, body_chunk, "New Labels", new_labels_question(app));
+        }
+        InputMode::ConfirmNewTask => {
+            draw_task_view(f, app, body_chunk);
+            if let Some(preview) = &app.new_task_preview {
+                draw_new_task_preview(f, body_chunk, preview);
+            }
+        }
+        InputMode::RestoreDraft => {
+            draw_task_view(f, app, body_chunk);
+            draw_confirm(f, body_chunk, "Draft", draft_question(app));
+        }
+        InputMode::ConfirmBulkLabel => {
+            draw_task_view(f, app, body_chunk);
+            if let Some(bulk) = &app.bulk_label {
+                draw_confirm(f, body_chunk, "Bulk Label", bulk_label_question(app, bulk));
+            }
+        }
+        InputMode::ConfirmBulkMove => {
+            draw_task_view(f, app, body_chunk);
+            if let Some(bulk) = &app.bulk_move {
+                draw_confirm(f, body_chunk, "Bulk Move", bulk_move_question(app, bulk));
+            }
+        }
+        InputMode::ConfirmQuit => {
+            draw_task_view(f, app, body_chunk);
+            if let Some(question) = &app.quit_question {
+                draw_confirm(f, body_chunk, "Quit", question.clone());
+            }
+        }
+        InputMode::Notifications => {
+            draw_task_view(f, app, body_chunk);
+            draw_notifications(f, app, body_chunk);
+        }
+        InputMode::Members | InputMode::AddMember => {
+            draw_task_view(f, app, body_chunk);
+            draw_members(f, app, body_chunk);
+        }
+        InputMode::Teams | InputMode::TeamInput => {
+            draw_task_view(f, app, body_chunk);
+            draw_teams(f, app, body_chunk);
+        }
+        InputMode::ConfirmDelete => {
+            draw_task_view(f, app, body_chunk);
+            draw_confirm(f, body_chunk, "Delete Task", delete_question(app));
+        }
+        InputMode::ConfirmUnshare => {
+            draw_task_view(f, app, body_chunk);
+            draw_members(f, app, body_chunk);
+            draw_confirm(f, body_chunk, "Remove Member", unshare_question(app));
+        }
+        InputMode::Editing | InputMode::Insert | InputMode::DatePicker => {
+            let popup_width_percentage = 60u16;
+            let popup_width = (size.width * popup_width_percentage / 100u16).saturating_sub(2u16);
+
+            // Calculate the required heights for the input boxes
+            let title_lines_required = calculate_wrapped_lines(&app.new_task_title, popup_width);
+            let description_lines_required =
+                calculate_wrapped_lines(&app.new_task_description, popup_width);
+
+            let title_height = std::cmp::max(title_lines_required as u16, 1u16);
+            let description_height = std::cmp::max(description_lines_required as u16, 2u16); // At least 2 lines tall
+
+            let total_height = title_height + description_height + 9u16; // +6 for borders and titles, +1 for the preview, +2 for the dates and color
+
+            let max_popup_height = size.height.saturating_sub(2u16);
+            let popup_height = std::cmp::min(total_height, max_popup_height);
+
+            let popup_area = centered_rect_absolute(popup_width + 2u16, popup_height, body_chunk);
+
+            let popup_block = Block::default()
+                .title(new_task_title(app))
+                .borders(Borders::ALL)
+                .style(Style::default().fg(Color::Green));
+
+            // Split the popup area vertically for the two input boxes
+            let input_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(title_height + 2u16), // +2 for borders
+                    Constraint::Length(1u16),
+                    Constraint::Length(description_height + 2u16), // +2 for borders
+                    Constraint::Length(2u16),
+                ])
+                .split(popup_area.inner(Margin {
+                    vertical: 1u16,
+                    horizontal: 1u16,
+                })); // Adjust for popup_block borders
+
+            // Title input box
+            let title_block = Block::default().borders(Borders::ALL).title("Title").style(
+                if app.active_input == ActiveInput::Title {
+                    Style::default().fg(Color::Yellow)
+                } else {
+                    Style::default()
+                },
+            );
+
+            let title_paragraph = Paragraph::new(highlight_tokens(&app.new_task_title))
+                .style(Style::default().fg(Color::White))
+                .block(title_block)
+                .wrap(Wrap { trim: false });
+
+            // Description input box
+            let description_block = Block::default()
+                .borders(Borders::ALL)
+                .title("Description")
+                .style(if app.active_input == ActiveInput::Description {
+                    Style::default().fg(Color::Yellow)
+                } else {
+                    Style::default()
+                });
+
+            let description_paragraph = Paragraph::new(app.new_task_description.as_str())
+                .style(Style::default().fg(Color::White))
+                .block(description_block)
+                .wrap(Wrap { trim: false });
+
+            f.render_widget(Clear, popup_area);
+            f.render_widget(popup_block, popup_area);
+
+            f.render_widget(title_paragraph, input_chunks[0]);
+            f.render_widget(parse_preview(&app.new_task_title), input_chunks[1]);
+            f.render_widget(description_paragraph, input_chunks[2]);
+            f.render_widget(new_task_dates(app), input_chunks[3]);
+            draw_completion(f, app, input_chunks[0]);
+
+            // The calendar covers the form and takes the cursor away
+            if let InputMode::DatePicker = app.input_mode {
+                draw_date_picker(f, app, body_chunk);
+            } else {
+                // Set cursor position
+                match app.active_input {
+                    ActiveInput::Title => {
+                        // Calculate cursor position in title input
+                        let cursor_position =
+                            get_cursor_position(&app.new_task_title, input_chunks[0]);
+                        f.set_cursor_position(cursor_position);
+                    }
+                    ActiveInput::Description => {
+                        // Calculate cursor position in description input
+                        let cursor_position =
+                            get_cursor_position(&app.new_task_description, input_chunks[2]);
+                        f.set_cursor_position(cursor_position);
+                    }
+                }
+            }
+        }
+    }
+
+    if app.show_api_log {
+        draw_api_log(f, app, body_chunk);
+    }
+
+    draw_status_line(f, app, status_chunk);
+
+    // Render the legend in the footer
+    let legend = Paragraph::new(get_legend(app))
+        .style(Style::default().fg(Color::White))
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(legend, footer_chunk);
+
+    if app.plain {
+        adapt_buffer(f.buffer_mut(), ColorSupport::Monochrome);
+        strip_box_drawing(f.buffer_mut());
+    } else {
+        adapt_buffer(f.buffer_mut(), color_support());
+    }
+}
+
+pub async fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<()> {
+    let mut window_title = String::new();
+    loop {
+        app.handle_event(AppEvent::Tick)?;
+        if app.terminal_title {
+            let title = app.window_title();
+            if title != window_title {
+                execute!(io::stdout(), SetTitle(&title))?;
+                window_title = title;
+            }
+        }
+        if app.redraw {
+            terminal.clear()?;
+            app.redraw = false;
+        }
+
+        terminal.draw(|f| draw(f, &mut app))?;
+        if let Some(text) = app.clipboard.take() {
+            let mut stdout = io::stdout();
+            stdout.write_all(osc52(&text).as_bytes())?;
+            stdout.flush()?;
+        }
+
+        // Handle input
+        if event::poll(Duration::from_millis(100))? {
+            if let Some(event) = AppEvent::from_terminal(event::read()?) {
+                let should_quit = app.handle_event(event)?;
+                if should_quit {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+fn calculate_wrapped_lines(text: &str, max_width: u16) -> usize {
+    let mut line_count = 0;
+    for line in text.lines() {
+        line_count += line.chars().count().div_ceil(max_width.max(1) as usize);
+    }
+    line_count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::demo::DemoApi;
+    use crate::event::parse_keys;
+    use crate::ics::ExternalEvent;
+    use std::sync::Arc;
+
+    fn demo_app() -> App {
+        let config = Config::demo();
+        let mut app = App::new(Arc::new(DemoApi::new()), config.filters);
+        app.set_project_settings(config.projects);
+        app.refresh_tasks();
+        app
+    }
+
+    // Compares with the file in tests/golden, UPDATE_GOLDEN=1 writes it instead
+    fn assert_golden(name: &str, screen: &str) {
+        let path = format!("{}/tests/golden/{}.txt", env!("CARGO_MANIFEST_DIR"), name);
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::write(&path, screen).unwrap();
+        }
+        let golden = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(screen, golden, "{} differs from {}", name, path);
+    }
+
+    #[test]
+    fn test_initials() {
+        let user = |username: &str, name: &str| User {
+            username: username.to_string(),
+            name: name.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(initials(&user("sam", "Sam de la Rivera")), "SR");
+        assert_eq!(initials(&user("alex", "")), "AL");
+        assert_eq!(initials(&user("jo", "Jo")), "JO");
+    }
+
+    #[test]
+    fn test_short_duration() {
+        assert_eq!(short_duration(Duration::from_millis(85)), "85ms");
+        assert_eq!(short_duration(Duration::from_millis(1440)), "1.4s");
+    }
+
+    #[tokio::test]
+    async fn test_row_format_drops_missing_fields() {
+        let mut app = App::new(Arc::new(DemoApi::new()), Vec::new());
+        app.plain = true;
+        let parts = crate::row_format::parse_row_format("{identifier} {due:%m-%d} {title} {done}");
+        app.row_format = Some(parts.unwrap());
+        let mut task = Task {
+            identifier: "WRK-3".to_string(),
+            title: "Ship it".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(task_line(&app, &task).to_string(), "WRK-3 Ship it");
+        task.due_date = Some("2024-05-02T12:00:00Z".to_string());
+        task.done = true;
+        let due = task.due_at().unwrap().format("%m-%d");
+        assert_eq!(
+            task_line(&app, &task).to_string(),
+            format!("WRK-3 {} Ship it [DONE]", due)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_api_log_opens_over_the_view() {
+        let keys = parse_keys("<F12>").unwrap();
+        let screen = render_headless(demo_app(), 80, 16, &keys).await.unwrap();
+        assert!(screen.contains("API Requests (F12 to close)"));
+        assert!(screen.contains("No requests yet"));
+    }
+
+    #[tokio::test]
+    async fn test_cut_off_title_shows_below_the_list() {
+        let keys = parse_keys("jj").unwrap();
+        let screen = render_headless(demo_app(), 50, 16, &keys).await.unwrap();
+        assert!(screen
+            .lines()
+            .any(|l| l.starts_with("Write quarterly report ")));
+
+        // Titles that fit leave the list its full height
+        let screen = render_headless(demo_app(), 50, 16, &[]).await.unwrap();
+        assert!(!screen.lines().any(|l| l.starts_with("Renew passport")));
+    }
+
+    #[tokio::test]
+    async fn test_date_picker_lists_external_events() {
+        let mut app = demo_app();
+        app.external_events = vec![ExternalEvent {
+            calendar: "Holidays".to_string(),
+            summary: "Office closed".to_string(),
+            date: today(),
+        }];
+        let keys = parse_keys("ad").unwrap();
+        let screen = render_headless(app, 80, 20, &keys).await.unwrap();
+        assert!(screen.contains("Holidays: Office closed"));
+    }
+
+    #[tokio::test]
+    async fn test_task_details_golden() {
+        let keys = parse_keys("sWork<Enter>j<Enter>").unwrap();
+        let screen = render_headless(demo_app(), 100, 16, &keys).await.unwrap();
+        assert_golden("task_details", &screen);
+    }
+
+    #[tokio::test]
+    async fn test_tiny_terminals() {
+        for keys in ["<Enter>", "a"] {
+            let keys = parse_keys(keys).unwrap();
+            for width in 0..6 {
+                for height in 0..6 {
+                    let screen = render_headless(demo_app(), width, height, &keys).await;
+                    assert_eq!(screen.unwrap().lines().count(), height as usize);
+                }
+            }
+        }
     }
-    line_count
 }
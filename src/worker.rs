@@ -0,0 +1,1037 @@
+use crate::app::{CardMove, Retry};
+use crate::config::Profile;
+use crate::fuzzy::fuzzy_filter;
+use crate::ics::{fetch_events, CalendarFeed, ExternalEvent};
+use crate::members::Member;
+use crate::parser::{ParsedTask, TaskRef};
+use futures::stream::{self, StreamExt};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+use vikunja_client::api::{
+    fetch_all_tasks, ApiResult, LoadProgress, NewTask, ReactionTarget, TaskPage, TaskQuery,
+    VikunjaApi,
+};
+use vikunja_client::models::{
+    Bucket, Comment, Label, Notification, Project, ProjectTeam, ProjectUser, ProjectView,
+    ServerInfo, Task, Team, User,
+};
+
+// Label changes of a bulk label or moves of a bulk move sent to the server
+// at a time
+const BULK_CONCURRENCY: usize = 8;
+
+// Work the key handler hands off. An action carries everything its API calls
+// need, so it can run while the app goes on taking keys.
+pub enum Action {
+    LoadServerInfo,
+    LoadTasks {
+        reload: Reload,
+        page: usize,
+        query: TaskQuery,
+        // Set when the list view of this project still has to be looked up
+        list_view_of: Option<u64>,
+    },
+    // The page after the one in view, loaded ahead so `n` shows it at once
+    PrefetchPage {
+        query: TaskQuery,
+        page: usize,
+    },
+    LoadBoard {
+        reload: Reload,
+        project_id: u64,
+    },
+    LoadAllTasks {
+        query: TaskQuery,
+        progress: Arc<LoadProgress>,
+    },
+    LoadTask(u64),
+    LoadProjects(ProjectsFor),
+    LoadLabels(LabelsFor),
+    LoadCurrentUser(UserFor),
+    LoadNotifications,
+    // The users and teams the project is shared with
+    LoadMembers(Project),
+    LoadTeams,
+    // Looks up a task by its index in a project
+    FindTask {
+        project: Project,
+        index: u64,
+    },
+    CreateTask {
+        parsed: ParsedTask,
+        task: NewTask,
+        labels: Vec<String>,
+        // Labels to create first, agreed to when the task was added
+        new_labels: Vec<Label>,
+        // Title of the bucket to put the task in, from the project settings
+        bucket: Option<String>,
+        // Users already seen on loaded tasks, the rest are searched for
+        known_users: Vec<User>,
+    },
+    Save {
+        change: Change,
+        then: Then,
+        retry: Retry,
+    },
+    MarkRead {
+        ids: Vec<u64>,
+        // The task a notification is about, opened once it is marked read
+        open: Option<u64>,
+    },
+    // Looks up the label and the tasks matching the query it would change
+    PlanBulkLabel {
+        query: TaskQuery,
+        name: String,
+        remove: bool,
+    },
+    BulkLabel {
+        label: Label,
+        remove: bool,
+        task_ids: Vec<u64>,
+        // Counts the tasks changed so far
+        progress: Arc<AtomicUsize>,
+    },
+    // Looks up the project and the tasks matching the query that would move
+    // there, only the marked ones when some are
+    PlanBulkMove {
+        query: TaskQuery,
+        name: String,
+        marked: Vec<u64>,
+    },
+    BulkMove {
+        project: Project,
+        task_ids: Vec<u64>,
+        // Counts the tasks moved so far
+        progress: Arc<AtomicUsize>,
+    },
+    Login {
+        username: String,
+        password: String,
+        passcode: Option<String>,
+    },
+    // The events of an iCal feed, from outside the Vikunja instance
+    LoadCalendar(CalendarFeed),
+    // The API key of a profile being switched to, which api_key_cmd may take
+    // a while to print
+    ResolveProfile(Profile),
+}
+
+// The results, in the order they arrive, which is not the order the actions
+// were sent in
+pub enum Message {
+    ServerInfo(ApiResult<ServerInfo>),
+    Tasks {
+        reload: Reload,
+        list_view_id: Option<u64>,
+        result: ApiResult<TaskPage>,
+    },
+    // None when the project has no kanban view
+    Board {
+        reload: Reload,
+        result: ApiResult<Option<(ProjectView, Vec<Bucket>)>>,
+    },
+    AllTasks(ApiResult<Vec<Task>>),
+    Task {
+        task_id: u64,
+        result: ApiResult<(Box<Task>, Vec<Comment>)>,
+    },
+    Projects {
+        wanted_for: ProjectsFor,
+        result: ApiResult<Vec<Project>>,
+    },
+    Labels {
+        wanted_for: LabelsFor,
+        result: ApiResult<Vec<Label>>,
+    },
+    CurrentUser {
+        wanted_for: UserFor,
+        result: ApiResult<User>,
+    },
+    Notifications(ApiResult<Vec<Notification>>),
+    // The project's users and teams, then the teams of the signed in user
+    Members {
+        project: Project,
+        result: ApiResult<(Vec<ProjectUser>, Vec<ProjectTeam>, Vec<Team>)>,
+    },
+    Teams(ApiResult<Vec<Team>>),
+    Found {
+        project: Project,
+        index: u64,
+        result: ApiResult<Option<u64>>,
+    },
+    // The outer result is the task itself, the inner one its labels and
+    // assignees, which come back as the ones that weren't found
+    Created {
+        parsed: ParsedTask,
+        task: NewTask,
+        new_labels: Vec<Label>,
+        result: ApiResult<ApiResult<Vec<String>>>,
+    },
+    Saved {
+        result: ApiResult<()>,
+        then: Then,
+        retry: Retry,
+    },
+    MarkedRead {
+        // The notifications marked before any failure
+        read: Vec<u64>,
+        open: Option<u64>,
+        result: ApiResult<()>,
+    },
+    // None when there is no label by that name
+    BulkLabelPlan {
+        name: String,
+        remove: bool,
+        result: ApiResult<Option<(Label, Vec<u64>)>>,
+    },
+    BulkLabeled {
+        label: Label,
+        remove: bool,
+        // The tasks changed before any failure
+        changed: usize,
+        result: ApiResult<()>,
+    },
+    // None when there is no project by that name
+    BulkMovePlan {
+        name: String,
+        result: ApiResult<Option<(Project, Vec<u64>)>>,
+    },
+    BulkMoved {
+        project: Project,
+        // The tasks moved before any failure
+        moved: usize,
+        result: ApiResult<()>,
+    },
+    Prefetched {
+        query: TaskQuery,
+        page: usize,
+        result: ApiResult<TaskPage>,
+    },
+    LoggedIn(ApiResult<String>),
+    Calendar {
+        feed: CalendarFeed,
+        result: ApiResult<Vec<ExternalEvent>>,
+    },
+    ProfileResolved {
+        profile: Profile,
+        result: ApiResult<String>,
+    },
+    // An action called off before it finished
+    Cancelled,
+}
+
+impl Action {
+    fn is_write(&self) -> bool {
+        matches!(
+            self,
+            Action::CreateTask { .. }
+                | Action::Save { .. }
+                | Action::MarkRead { .. }
+                | Action::BulkLabel { .. }
+                | Action::BulkMove { .. }
+        )
+    }
+
+    // Loads nobody waits on, which don't count as loading
+    fn is_background(&self) -> bool {
+        matches!(
+            self,
+            Action::PrefetchPage { .. }
+                | Action::LoadCalendar(_)
+                | Action::LoadProjects(ProjectsFor::Startup)
+                | Action::LoadCurrentUser(UserFor::Startup)
+        )
+    }
+}
+
+impl Message {
+    // Whether this is the result of an action that is a write
+    fn is_write(&self) -> bool {
+        matches!(
+            self,
+            Message::Created { .. }
+                | Message::Saved { .. }
+                | Message::MarkedRead { .. }
+                | Message::BulkLabeled { .. }
+                | Message::BulkMoved { .. }
+        )
+    }
+
+    fn is_background(&self) -> bool {
+        matches!(
+            self,
+            Message::Prefetched { .. }
+                | Message::Calendar { .. }
+                | Message::Projects {
+                    wanted_for: ProjectsFor::Startup,
+                    ..
+                }
+                | Message::CurrentUser {
+                    wanted_for: UserFor::Startup,
+                    ..
+                }
+        )
+    }
+}
+
+// Loads of the list or the board are numbered, only the latest one is shown.
+// They say what to select and open once they are in.
+#[derive(Clone, Copy, Default)]
+pub struct Reload {
+    pub generation: u64,
+    pub select: Option<u64>,
+    pub open: Option<u64>,
+    // Selects the last row instead, for a page entered from the one after
+    pub last_row: bool,
+}
+
+#[derive(Clone)]
+pub enum ProjectsFor {
+    Switcher,
+    Completion,
+    Goto(TaskRef),
+    // A +project in the new task's title to look up
+    NewTask,
+    // Fetched ahead on launch
+    Startup,
+}
+
+#[derive(Clone, Copy)]
+pub enum LabelsFor {
+    Completion,
+    // Finding the labels of a new task that don't exist yet
+    NewTask,
+    // Fetched ahead, for `add` on the command line
+    Startup,
+}
+
+// What the current user was fetched for
+#[derive(Clone, Copy)]
+pub enum UserFor {
+    Reactions,
+    AssignToMe(u64),
+    MineFilter,
+    Startup,
+}
+
+pub enum Change {
+    MoveCard {
+        project_id: u64,
+        view_id: u64,
+        card_move: CardMove,
+    },
+    SetPosition {
+        task_id: u64,
+        view_id: u64,
+        position: f64,
+    },
+    AddComment {
+        task_id: u64,
+        html: String,
+    },
+    React {
+        target: ReactionTarget,
+        value: String,
+        remove: bool,
+    },
+    // Marks done tasks undone
+    Reopen(Vec<u64>),
+    Rename {
+        task_id: u64,
+        title: String,
+    },
+    Assign {
+        task_id: u64,
+        user_id: u64,
+    },
+    // Shares the project with the member or changes their permission
+    Share {
+        project_id: u64,
+        member: Member,
+        right: u8,
+    },
+    Unshare {
+        project_id: u64,
+        member: Member,
+    },
+    Delete(u64),
+    // Copies a project with its tasks under the parent, 0 for the top level
+    DuplicateProject {
+        project_id: u64,
+        parent_id: u64,
+    },
+    CreateTeam(String),
+    AddTeamMember {
+        team_id: u64,
+        username: String,
+    },
+}
+
+// What the app does once a change is saved
+pub enum Then {
+    // Reloads the view keeping the task selected
+    ReloadAt(u64),
+    // Opens the task again to show what changed on it
+    Reopen(u64),
+    // Reloads the view from the top
+    Reload,
+    // Reloads the view after a repeating task was done, to tell when it is
+    // due next
+    NextOccurrence(u64),
+    // Lists the members of the project again
+    Members(Project),
+    // Lists the teams again
+    Teams,
+    // Tells the project was copied and lists the projects again
+    Duplicated(String),
+    // Keeps the deleted task to restore it on undo and reloads the view
+    Deleted(Box<Task>),
+    // Tells the time was logged on the task and adds the row to the
+    // timesheet
+    Tracked {
+        task_id: u64,
+        title: String,
+        logged: String,
+        row: String,
+    },
+}
+
+// How many actions are in flight, shared with the tasks running them
+#[derive(Default)]
+struct Counts {
+    in_flight: AtomicUsize,
+    // Those that change something on the server
+    writes: AtomicUsize,
+    // And those that load something ahead
+    background: AtomicUsize,
+}
+
+impl Counts {
+    fn change(&self, write: bool, background: bool, change: fn(&AtomicUsize, usize, Ordering)) {
+        change(&self.in_flight, 1, Ordering::Relaxed);
+        change(&self.writes, write as usize, Ordering::Relaxed);
+        change(&self.background, background as usize, Ordering::Relaxed);
+    }
+}
+
+// An action counted as in flight. Its message takes it off the counts when
+// it is received, and when the task running it ends without sending one,
+// by panicking or the app going away, dropping this does.
+struct Pending {
+    counts: Arc<Counts>,
+    write: bool,
+    background: bool,
+    answered: bool,
+}
+
+impl Pending {
+    fn new(counts: Arc<Counts>, action: &Action) -> Pending {
+        let (write, background) = (action.is_write(), action.is_background());
+        counts.change(write, background, |count, n, order| {
+            count.fetch_add(n, order);
+        });
+        Pending {
+            counts,
+            write,
+            background,
+            answered: false,
+        }
+    }
+}
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        if !self.answered {
+            self.counts
+                .change(self.write, self.background, |count, n, order| {
+                    count.fetch_sub(n, order);
+                });
+        }
+    }
+}
+
+type Queued = (Action, Option<oneshot::Receiver<()>>, Pending);
+
+// Runs the actions on the tokio runtime and hands back their messages. Each
+// action gets its own task, so a slow request doesn't hold up the others.
+pub struct Worker {
+    actions: mpsc::UnboundedSender<Queued>,
+    messages: mpsc::UnboundedReceiver<Message>,
+    counts: Arc<Counts>,
+    // Dropped to call off the task details being loaded
+    detail_load: Option<oneshot::Sender<()>>,
+}
+
+impl Worker {
+    // Dropping the worker drops the results still on their way
+    pub fn spawn(api: Arc<dyn VikunjaApi>) -> Worker {
+        let (actions, mut received) = mpsc::unbounded_channel::<Queued>();
+        let (sender, messages) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Some((action, cancel, pending)) = received.recv().await {
+                let api = api.clone();
+                let sender = sender.clone();
+                tokio::spawn(async move {
+                    // Moved in whole, to be dropped when the task ends
+                    let mut pending = pending;
+                    // A cancelled action drops its request, and still answers
+                    // so it isn't counted as in flight anymore
+                    let message = match cancel {
+                        Some(cancel) => tokio::select! {
+                            biased;
+                            _ = cancel => Message::Cancelled,
+                            message = perform(api, action) => message,
+                        },
+                        None => perform(api, action).await,
+                    };
+                    pending.answered = sender.send(message).is_ok();
+                });
+            }
+        });
+        Worker {
+            actions,
+            messages,
+            counts: Arc::new(Counts::default()),
+            detail_load: None,
+        }
+    }
+
+    pub fn send(&mut self, action: Action) {
+        let pending = Pending::new(self.counts.clone(), &action);
+        // Loading other task details calls off the last ones
+        let cancel = match action {
+            Action::LoadTask(_) => {
+                let (cancel, cancelled) = oneshot::channel();
+                self.detail_load = Some(cancel);
+                Some(cancelled)
+            }
+            _ => None,
+        };
+        // Not counted anymore when it can't be sent
+        let _ = self.actions.send((action, cancel, pending));
+    }
+
+    pub fn try_recv(&mut self) -> Option<Message> {
+        let message = self.messages.try_recv().ok()?;
+        self.counts.change(
+            message.is_write(),
+            message.is_background(),
+            |count, n, order| {
+                count.fetch_sub(n, order);
+            },
+        );
+        Some(message)
+    }
+
+    // Calls off the task details being loaded, when they aren't wanted anymore
+    pub fn cancel_detail_load(&mut self) {
+        self.detail_load = None;
+    }
+
+    // Whether any action is still running
+    pub fn busy(&self) -> bool {
+        self.counts.in_flight.load(Ordering::Relaxed) > 0
+    }
+
+    // Whether something the view waits for is still running
+    pub fn loading(&self) -> bool {
+        self.counts.in_flight.load(Ordering::Relaxed)
+            > self.counts.background.load(Ordering::Relaxed)
+    }
+
+    // Changes still on their way to the server, lost when quitting now
+    pub fn saving(&self) -> usize {
+        self.counts.writes.load(Ordering::Relaxed)
+    }
+}
+
+async fn perform(api: Arc<dyn VikunjaApi>, action: Action) -> Message {
+    match action {
+        Action::LoadServerInfo => Message::ServerInfo(api.fetch_info().await),
+        Action::LoadTasks {
+            reload,
+            page,
+            mut query,
+            list_view_of,
+        } => {
+            let mut list_view_id = None;
+            let result = async {
+                if let Some(project_id) = list_view_of {
+                    let views = api.fetch_project_views(project_id).await?;
+                    list_view_id = views.iter().find(|v| v.view_kind == "list").map(|v| v.id);
+                    query.view = list_view_id.map(|view_id| (project_id, view_id));
+                }
+                api.fetch_task_page(page, &query).await
+            }
+            .await;
+            Message::Tasks {
+                reload,
+                list_view_id,
+                result,
+            }
+        }
+        Action::PrefetchPage { query, page } => {
+            let result = api.fetch_task_page(page, &query).await;
+            Message::Prefetched {
+                query,
+                page,
+                result,
+            }
+        }
+        Action::LoadBoard { reload, project_id } => {
+            let result = async {
+                let views = api.fetch_project_views(project_id).await?;
+                let Some(view) = views.into_iter().find(|v| v.view_kind == "kanban") else {
+                    return Ok(None);
+                };
+                let buckets = api.fetch_buckets(project_id, view.id).await?;
+                Ok(Some((view, buckets)))
+            }
+            .await;
+            Message::Board { reload, result }
+        }
+        Action::LoadAllTasks { query, progress } => {
+            Message::AllTasks(fetch_all_tasks(api, query, progress).await)
+        }
+        Action::LoadTask(task_id) => {
+            let result = async {
+                let task = Box::new(api.fetch_task_detail(task_id).await?);
+                // Comments are extra, link shares without access still see the task
+                let comments = api.fetch_comments(task_id).await.unwrap_or_default();
+                Ok((task, comments))
+            }
+            .await;
+            Message::Task { task_id, result }
+        }
+        Action::LoadProjects(wanted_for) => Message::Projects {
+            wanted_for,
+            result: api.fetch_projects().await,
+        },
+        Action::LoadLabels(wanted_for) => Message::Labels {
+            wanted_for,
+            result: api.fetch_labels().await,
+        },
+        Action::LoadCurrentUser(wanted_for) => Message::CurrentUser {
+            wanted_for,
+            result: api.fetch_current_user().await,
+        },
+        Action::LoadNotifications => Message::Notifications(api.fetch_notifications().await),
+        Action::LoadMembers(project) => {
+            let result = async {
+                let users = api.fetch_project_users(project.id).await?;
+                let teams = api.fetch_project_teams(project.id).await?;
+                Ok((users, teams, api.fetch_teams().await?))
+            }
+            .await;
+            Message::Members { project, result }
+        }
+        Action::LoadTeams => Message::Teams(api.fetch_teams().await),
+        Action::LoadCalendar(feed) => {
+            let result = fetch_events(&feed).await;
+            Message::Calendar { feed, result }
+        }
+        Action::ResolveProfile(profile) => {
+            let vikunja = profile.vikunja.clone();
+            let result = tokio::task::spawn_blocking(move || vikunja.resolve_api_key())
+                .await
+                .unwrap_or_else(|err| Err(err.into()));
+            Message::ProfileResolved { profile, result }
+        }
+        Action::FindTask { project, index } => {
+            let query = TaskQuery {
+                filter: Some(format!("project = {}", project.id)),
+                search: Some(format!("#{}", index)),
+                ..Default::default()
+            };
+            let result = api.fetch_tasks(1, &query).await.map(|tasks| {
+                tasks
+                    .iter()
+                    .find(|t| t.project_id == project.id && t.index == index)
+                    .map(|t| t.id)
+            });
+            Message::Found {
+                project,
+                index,
+                result,
+            }
+        }
+        Action::CreateTask {
+            parsed,
+            mut task,
+            labels,
+            new_labels,
+            bucket,
+            known_users,
+        } => {
+            // Without the bucket the task goes to the default one
+            if let (Some(title), Some(project_id)) = (&bucket, task.project_id) {
+                task.bucket_id = find_bucket(api.as_ref(), project_id, title).await;
+            }
+            let result = match api.create_new_task(&task).await {
+                Ok(created) => Ok(tag_task(
+                    api.as_ref(),
+                    created.id,
+                    &parsed,
+                    labels,
+                    &new_labels,
+                    known_users,
+                )
+                .await),
+                Err(err) => Err(err),
+            };
+            Message::Created {
+                parsed,
+                task,
+                new_labels,
+                result,
+            }
+        }
+        Action::Save {
+            change,
+            then,
+            retry,
+        } => Message::Saved {
+            result: save(api.as_ref(), change).await,
+            then,
+            retry,
+        },
+        Action::MarkRead { ids, open } => {
+            let mut read = Vec::new();
+            let mut result = Ok(());
+            for id in ids {
+                if let Err(err) = api.mark_notification_read(id).await {
+                    result = Err(err);
+                    break;
+                }
+                read.push(id);
+            }
+            Message::MarkedRead { read, open, result }
+        }
+        Action::PlanBulkLabel {
+            query,
+            name,
+            remove,
+        } => {
+            let result = async {
+                let labels = api.fetch_labels().await?;
+                let Some(label) = labels
+                    .into_iter()
+                    .find(|l| l.title.eq_ignore_ascii_case(&name))
+                else {
+                    return Ok(None);
+                };
+                // Tasks that already are the way the label would leave them
+                // are skipped
+                let tasks = fetch_all_tasks(api.clone(), query, Arc::default()).await?;
+                let task_ids = tasks
+                    .iter()
+                    .filter(|task| {
+                        let labeled = task.labels.iter().flatten().any(|l| l.id == label.id);
+                        labeled == remove
+                    })
+                    .map(|task| task.id)
+                    .collect();
+                Ok(Some((label, task_ids)))
+            }
+            .await;
+            Message::BulkLabelPlan {
+                name,
+                remove,
+                result,
+            }
+        }
+        Action::BulkLabel {
+            label,
+            remove,
+            task_ids,
+            progress,
+        } => {
+            let label_id = label.id;
+            let results: Vec<ApiResult<()>> = stream::iter(task_ids)
+                .map(|task_id| {
+                    let api = api.clone();
+                    let progress = progress.clone();
+                    async move {
+                        let result = if remove {
+                            api.remove_label(task_id, label_id).await
+                        } else {
+                            api.add_label(task_id, label_id).await
+                        };
+                        if result.is_ok() {
+                            progress.fetch_add(1, Ordering::Relaxed);
+                        }
+                        result
+                    }
+                })
+                .buffer_unordered(BULK_CONCURRENCY)
+                .collect()
+                .await;
+            Message::BulkLabeled {
+                label,
+                remove,
+                changed: progress.load(Ordering::Relaxed),
+                result: results.into_iter().find(Result::is_err).unwrap_or(Ok(())),
+            }
+        }
+        Action::PlanBulkMove {
+            query,
+            name,
+            marked,
+        } => {
+            let result = async {
+                let projects = api.fetch_projects().await?;
+                let titles: Vec<&str> = projects.iter().map(|p| p.title.as_str()).collect();
+                let found = projects
+                    .iter()
+                    .position(|p| p.title.eq_ignore_ascii_case(&name))
+                    .or_else(|| fuzzy_filter(&name, &titles).first().copied());
+                let Some(project) = found.map(|i| projects[i].clone()) else {
+                    return Ok(None);
+                };
+                // Tasks already in the project stay where they are
+                let tasks = fetch_all_tasks(api.clone(), query, Arc::default()).await?;
+                let task_ids = tasks
+                    .iter()
+                    .filter(|task| task.project_id != project.id)
+                    .filter(|task| marked.is_empty() || marked.contains(&task.id))
+                    .map(|task| task.id)
+                    .collect();
+                Ok(Some((project, task_ids)))
+            }
+            .await;
+            Message::BulkMovePlan { name, result }
+        }
+        Action::BulkMove {
+            project,
+            task_ids,
+            progress,
+        } => {
+            let project_id = project.id;
+            let results: Vec<ApiResult<()>> = stream::iter(task_ids)
+                .map(|task_id| {
+                    let api = api.clone();
+                    let progress = progress.clone();
+                    async move {
+                        let mut task = api.fetch_task_detail(task_id).await?;
+                        task.project_id = project_id;
+                        api.update_task(&task).await?;
+                        progress.fetch_add(1, Ordering::Relaxed);
+                        Ok(())
+                    }
+                })
+                .buffer_unordered(BULK_CONCURRENCY)
+                .collect()
+                .await;
+            Message::BulkMoved {
+                project,
+                moved: progress.load(Ordering::Relaxed),
+                result: results.into_iter().find(Result::is_err).unwrap_or(Ok(())),
+            }
+        }
+        Action::Login {
+            username,
+            password,
+            passcode,
+        } => Message::LoggedIn(api.login(&username, &password, passcode.as_deref()).await),
+    }
+}
+
+// The bucket of the project's kanban view with the title, None when there is
+// no such bucket or it can't be looked up
+async fn find_bucket(api: &dyn VikunjaApi, project_id: u64, title: &str) -> Option<u64> {
+    let views = api.fetch_project_views(project_id).await.ok()?;
+    let view = views.into_iter().find(|v| v.view_kind == "kanban")?;
+    let buckets = api.fetch_buckets(project_id, view.id).await.ok()?;
+    buckets
+        .into_iter()
+        .find(|b| b.title.eq_ignore_ascii_case(title))
+        .map(|b| b.id)
+}
+
+// Adds the labels and the assignees from quick add to a new task
+async fn tag_task(
+    api: &dyn VikunjaApi,
+    task_id: u64,
+    parsed: &ParsedTask,
+    labels: Vec<String>,
+    new_labels: &[Label],
+    known_users: Vec<User>,
+) -> ApiResult<Vec<String>> {
+    let mut missing = Vec::new();
+    if !labels.is_empty() {
+        let existing = api.fetch_labels().await?;
+        for title in labels {
+            let named = |l: &&Label| l.title.eq_ignore_ascii_case(&title);
+            let label = match existing.iter().find(named) {
+                Some(label) => Some(label.clone()),
+                None => match new_labels.iter().find(named) {
+                    Some(label) => Some(api.create_label(label).await?),
+                    None => None,
+                },
+            };
+            match label {
+                Some(label) => api.add_label(task_id, label.id).await?,
+                None => missing.push(format!("*{}", title)),
+            }
+        }
+    }
+    for username in &parsed.assignees {
+        let matches = |user: &User| user.username.eq_ignore_ascii_case(username);
+        let user = match known_users.iter().find(|u| matches(u)) {
+            Some(user) => Some(user.clone()),
+            None => api.search_users(username).await?.into_iter().find(matches),
+        };
+        match user {
+            Some(user) => api.add_assignee(task_id, user.id).await?,
+            None => missing.push(format!("@{}", username)),
+        }
+    }
+    Ok(missing)
+}
+
+async fn save(api: &dyn VikunjaApi, change: Change) -> ApiResult<()> {
+    match change {
+        Change::MoveCard {
+            project_id,
+            view_id,
+            card_move,
+        } => {
+            // A repeating task comes back undone with its dates moved on, the
+            // due date from before tells that apart from an unhandled move
+            let due_before = match card_move.done {
+                Some(true) => api.fetch_task_detail(card_move.task_id).await?.due_date,
+                _ => None,
+            };
+            api.move_task_to_bucket(project_id, view_id, card_move.bucket_id, card_move.task_id)
+                .await?;
+            // Vikunja flips the done state itself on these moves, this catches
+            // servers that leave it to the client like the web UI does
+            if let Some(done) = card_move.done {
+                let mut task = api.fetch_task_detail(card_move.task_id).await?;
+                let repeated = task.repeats() && task.due_date != due_before;
+                if task.done != done && !repeated {
+                    task.done = done;
+                    api.update_task(&task).await?;
+                }
+            }
+            Ok(())
+        }
+        Change::SetPosition {
+            task_id,
+            view_id,
+            position,
+        } => api.set_task_position(task_id, view_id, position).await,
+        Change::AddComment { task_id, html } => api.add_comment(task_id, &html).await,
+        Change::React {
+            target,
+            value,
+            remove: true,
+        } => api.remove_reaction(target, &value).await,
+        Change::React { target, value, .. } => api.add_reaction(target, &value).await,
+        Change::Reopen(task_ids) => {
+            for task_id in task_ids {
+                let mut task = api.fetch_task_detail(task_id).await?;
+                task.done = false;
+                api.update_task(&task).await?;
+            }
+            Ok(())
+        }
+        Change::Rename { task_id, title } => {
+            let mut task = api.fetch_task_detail(task_id).await?;
+            task.title = title;
+            api.update_task(&task).await.map(|_| ())
+        }
+        Change::Assign { task_id, user_id } => api.add_assignee(task_id, user_id).await,
+        Change::Share {
+            project_id,
+            member: Member::User(username),
+            right,
+        } => api.share_with_user(project_id, &username, right).await,
+        Change::Share {
+            project_id,
+            member: Member::Team(team_id),
+            right,
+        } => api.share_with_team(project_id, team_id, right).await,
+        Change::Unshare {
+            project_id,
+            member: Member::User(username),
+        } => api.unshare_with_user(project_id, &username).await,
+        Change::Unshare {
+            project_id,
+            member: Member::Team(team_id),
+        } => api.unshare_with_team(project_id, team_id).await,
+        Change::Delete(task_id) => api.delete_task(task_id).await,
+        Change::DuplicateProject {
+            project_id,
+            parent_id,
+        } => api
+            .duplicate_project(project_id, parent_id)
+            .await
+            .map(|_| ()),
+        Change::CreateTeam(name) => api.create_team(&name).await.map(|_| ()),
+        Change::AddTeamMember { team_id, username } => {
+            api.add_team_member(team_id, &username).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::demo::DemoApi;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_results_come_back_as_messages() {
+        let mut worker = Worker::spawn(Arc::new(DemoApi::new()));
+        worker.send(Action::FindTask {
+            project: Project {
+                id: 3,
+                ..Default::default()
+            },
+            index: 2,
+        });
+        assert!(worker.busy());
+        let message = loop {
+            if let Some(message) = worker.try_recv() {
+                break message;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        };
+        let Message::Found { index, result, .. } = message else {
+            panic!("expected the found task");
+        };
+        assert_eq!(index, 2);
+        assert!(result.unwrap().is_some());
+        assert!(!worker.busy());
+    }
+
+    #[tokio::test]
+    async fn test_an_action_that_panics_stops_counting() {
+        let worker = Worker::spawn(Arc::new(DemoApi::new()));
+        let pending = Pending::new(worker.counts.clone(), &Action::LoadServerInfo);
+        assert!(worker.loading());
+        let task = tokio::spawn(async move {
+            let _pending = pending;
+            panic!("lost the action");
+        });
+        assert!(task.await.is_err());
+        assert!(!worker.busy());
+    }
+
+    #[tokio::test]
+    async fn test_detail_load_can_be_called_off() {
+        let mut worker = Worker::spawn(Arc::new(DemoApi::new()));
+        worker.send(Action::LoadTask(1));
+        worker.cancel_detail_load();
+        let message = loop {
+            if let Some(message) = worker.try_recv() {
+                break message;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        };
+        assert!(matches!(message, Message::Cancelled));
+        assert!(!worker.busy());
+    }
+}
@@ -0,0 +1,1044 @@
+use crate::models::{
+    parse_buckets, parse_tasks, Bucket, Comment, Label, Notification, Project, ProjectTeam,
+    ProjectUser, ProjectView, ServerInfo, Task, Team, User,
+};
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+use reqwest::header::{HeaderMap, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+pub type ApiResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+
+// Returned for a 401 so the app can ask for new credentials and try again
+#[derive(Debug)]
+pub struct Unauthorized;
+
+impl fmt::Display for Unauthorized {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "The session has expired or the API token was rejected")
+    }
+}
+
+impl Error for Unauthorized {}
+
+// Returned by login when the account has two-factor authentication and the
+// TOTP passcode was missing or wrong
+#[derive(Debug)]
+pub struct TotpRequired;
+
+impl fmt::Display for TotpRequired {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "A valid TOTP passcode is required")
+    }
+}
+
+impl Error for TotpRequired {}
+
+// Error code Vikunja uses for a missing or invalid TOTP passcode
+const TOTP_ERROR_CODE: i64 = 1017;
+
+pub fn is_unauthorized(err: &(dyn Error + Send + Sync + 'static)) -> bool {
+    err.downcast_ref::<Unauthorized>().is_some()
+}
+
+// Pages fetched at the same time when loading every task
+const ALL_PAGES_CONCURRENCY: usize = 4;
+
+// One page of tasks along with the page count reported by the server
+pub struct TaskPage {
+    pub tasks: Vec<Task>,
+    pub total_pages: usize,
+    // Tasks on this page as the server counts them
+    pub result_count: usize,
+    // Tasks in the response that couldn't be read
+    pub skipped: usize,
+}
+
+// The fields a task is created with. Dates are RFC 3339.
+#[derive(Clone, Debug, Default)]
+pub struct NewTask {
+    pub title: String,
+    pub description: Option<String>,
+    pub priority: Option<u8>,
+    pub due_date: Option<String>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub reminders: Vec<String>,
+    // Six hex digits without the #
+    pub hex_color: Option<String>,
+    // Project 1 when none is given
+    pub project_id: Option<u64>,
+    // The bucket of the project's kanban view to put the task in
+    pub bucket_id: Option<u64>,
+}
+
+// What a reaction is put on
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReactionTarget {
+    Task(u64),
+    Comment(u64),
+}
+
+impl ReactionTarget {
+    fn path(self) -> String {
+        match self {
+            ReactionTarget::Task(id) => format!("/tasks/{}/reactions", id),
+            ReactionTarget::Comment(id) => format!("/comments/{}/reactions", id),
+        }
+    }
+}
+
+// What to ask /tasks/all for. The sort is a field name optionally followed by
+// asc or desc, like "due_date desc".
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TaskQuery {
+    pub filter: Option<String>,
+    pub sort: Option<String>,
+    // Project and list view ids to read the tasks from instead, which come in
+    // the view's manual order unless sorted otherwise
+    pub view: Option<(u64, u64)>,
+    // Text to search for, which also finds tasks by `#index`
+    pub search: Option<String>,
+}
+
+impl TaskQuery {
+    fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        if let Some(filter) = &self.filter {
+            params.push(("filter", filter.clone()));
+        }
+        if let Some(search) = &self.search {
+            params.push(("s", search.clone()));
+        }
+        if let Some(sort) = &self.sort {
+            let mut parts = sort.split_whitespace();
+            if let Some(field) = parts.next() {
+                params.push(("sort_by", field.to_string()));
+                params.push(("order_by", parts.next().unwrap_or("asc").to_string()));
+            }
+        }
+        params
+    }
+}
+
+// Shared counters so the UI can show how far a full load has got
+#[derive(Default)]
+pub struct LoadProgress {
+    pub loaded_pages: AtomicUsize,
+    pub total_pages: AtomicUsize,
+}
+
+// How many requests the call log keeps
+const CALL_LOG_LIMIT: usize = 100;
+
+// How many GET responses are kept for revalidation, and for how long
+const RESPONSE_CACHE_LIMIT: usize = 200;
+const RESPONSE_CACHE_MAX_AGE: Duration = Duration::from_secs(15 * 60);
+
+// A request as the debug log lists it
+#[derive(Clone, Debug)]
+pub struct ApiCall {
+    pub method: String,
+    // The path under /api/v1, with the query
+    pub path: String,
+    // None when no response came back
+    pub status: Option<u16>,
+    pub latency: Duration,
+}
+
+// The average time the latest `count` answered calls took, None before any
+pub fn average_latency(calls: &[ApiCall], count: usize) -> Option<Duration> {
+    let answered: Vec<Duration> = calls
+        .iter()
+        .rev()
+        .filter(|call| call.status.is_some())
+        .take(count)
+        .map(|call| call.latency)
+        .collect();
+    let total: Duration = answered.iter().sum();
+    total.checked_div(answered.len() as u32)
+}
+
+// The latest requests, oldest first
+#[derive(Default)]
+pub struct CallLog {
+    calls: Mutex<VecDeque<ApiCall>>,
+}
+
+impl CallLog {
+    pub fn record(&self, call: ApiCall) {
+        let mut calls = self.calls.lock().unwrap();
+        if calls.len() == CALL_LOG_LIMIT {
+            calls.pop_front();
+        }
+        calls.push_back(call);
+    }
+
+    // Lends the calls out rather than copying them, the debug log and the
+    // status bar read them every frame
+    pub fn with_recent<R>(&self, read: impl FnOnce(&[ApiCall]) -> R) -> R {
+        read(self.calls.lock().unwrap().make_contiguous())
+    }
+}
+
+// Everything the app needs from a Vikunja instance, implemented over HTTP and
+// by the in-memory demo backend
+#[async_trait]
+pub trait VikunjaApi: Send + Sync {
+    async fn fetch_info(&self) -> ApiResult<ServerInfo>;
+
+    // The latest requests made, for the debug log. None when they aren't
+    // kept.
+    fn call_log(&self) -> Option<&CallLog> {
+        None
+    }
+
+    async fn fetch_task_page(&self, page: usize, query: &TaskQuery) -> ApiResult<TaskPage>;
+
+    async fn fetch_tasks(&self, page: usize, query: &TaskQuery) -> ApiResult<Vec<Task>> {
+        Ok(self.fetch_task_page(page, query).await?.tasks)
+    }
+
+    async fn fetch_projects(&self) -> ApiResult<Vec<Project>>;
+
+    async fn fetch_project_views(&self, project_id: u64) -> ApiResult<Vec<ProjectView>>;
+
+    // Copies the project with its tasks under the parent, 0 for the top
+    // level, and returns the copy
+    async fn duplicate_project(&self, project_id: u64, parent_id: u64) -> ApiResult<Project>;
+
+    async fn fetch_buckets(&self, project_id: u64, view_id: u64) -> ApiResult<Vec<Bucket>>;
+
+    async fn fetch_task_detail(&self, task_id: u64) -> ApiResult<Task>;
+
+    async fn create_new_task(&self, task: &NewTask) -> ApiResult<Task>;
+
+    // Saves every field of the task and returns it as stored
+    async fn update_task(&self, task: &Task) -> ApiResult<Task>;
+
+    async fn delete_task(&self, task_id: u64) -> ApiResult<()>;
+
+    async fn fetch_labels(&self) -> ApiResult<Vec<Label>>;
+
+    // Creates a label with the title and color, and returns it with its id
+    async fn create_label(&self, label: &Label) -> ApiResult<Label>;
+
+    async fn add_label(&self, task_id: u64, label_id: u64) -> ApiResult<()>;
+
+    async fn remove_label(&self, task_id: u64, label_id: u64) -> ApiResult<()>;
+
+    async fn add_assignee(&self, task_id: u64, user_id: u64) -> ApiResult<()>;
+
+    // Users matching a name or username
+    async fn search_users(&self, query: &str) -> ApiResult<Vec<User>>;
+
+    // Puts a task into a bucket of a kanban view
+    async fn move_task_to_bucket(
+        &self,
+        project_id: u64,
+        view_id: u64,
+        bucket_id: u64,
+        task_id: u64,
+    ) -> ApiResult<()>;
+
+    // Tasks in a view are ordered by their position in it
+    async fn set_task_position(&self, task_id: u64, view_id: u64, position: f64) -> ApiResult<()>;
+
+    // The newest notifications of the user, read and unread
+    async fn fetch_notifications(&self) -> ApiResult<Vec<Notification>>;
+
+    async fn mark_notification_read(&self, notification_id: u64) -> ApiResult<()>;
+
+    async fn fetch_comments(&self, task_id: u64) -> ApiResult<Vec<Comment>>;
+
+    // The comment is HTML
+    async fn add_comment(&self, task_id: u64, comment: &str) -> ApiResult<()>;
+
+    // The signed in user
+    async fn fetch_current_user(&self) -> ApiResult<User>;
+
+    // The users and teams a project is shared with
+    async fn fetch_project_users(&self, project_id: u64) -> ApiResult<Vec<ProjectUser>>;
+
+    async fn fetch_project_teams(&self, project_id: u64) -> ApiResult<Vec<ProjectTeam>>;
+
+    // The teams the signed in user is in, with their members
+    async fn fetch_teams(&self) -> ApiResult<Vec<Team>>;
+
+    // Creates a team with the signed in user as its admin
+    async fn create_team(&self, name: &str) -> ApiResult<Team>;
+
+    async fn add_team_member(&self, team_id: u64, username: &str) -> ApiResult<()>;
+
+    // Permissions go by their index in PERMISSIONS
+    async fn share_with_user(&self, project_id: u64, username: &str, right: u8) -> ApiResult<()>;
+
+    async fn share_with_team(&self, project_id: u64, team_id: u64, right: u8) -> ApiResult<()>;
+
+    async fn unshare_with_user(&self, project_id: u64, username: &str) -> ApiResult<()>;
+
+    async fn unshare_with_team(&self, project_id: u64, team_id: u64) -> ApiResult<()>;
+
+    // Reactions need Vikunja 0.24
+    async fn add_reaction(&self, target: ReactionTarget, value: &str) -> ApiResult<()>;
+
+    async fn remove_reaction(&self, target: ReactionTarget, value: &str) -> ApiResult<()>;
+
+    // Exchanges a username and password, plus a TOTP passcode for accounts
+    // with two-factor authentication, for a JWT
+    async fn login(
+        &self,
+        username: &str,
+        password: &str,
+        totp_passcode: Option<&str>,
+    ) -> ApiResult<String>;
+
+    // Replaces the token sent with every following request
+    fn set_token(&self, token: &str);
+}
+
+fn total_pages(headers: &HeaderMap) -> usize {
+    count_header(headers, "x-pagination-total-pages").unwrap_or(1)
+}
+
+fn count_header(headers: &HeaderMap, name: &str) -> Option<usize> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok())
+}
+
+// Loads every page of tasks. The first page tells how many there are, the rest
+// are fetched a few at a time and kept in page order.
+pub async fn fetch_all_tasks(
+    api: Arc<dyn VikunjaApi>,
+    query: TaskQuery,
+    progress: Arc<LoadProgress>,
+) -> ApiResult<Vec<Task>> {
+    let first = api.fetch_task_page(1, &query).await?;
+    progress
+        .total_pages
+        .store(first.total_pages, Ordering::Relaxed);
+    progress.loaded_pages.store(1, Ordering::Relaxed);
+
+    let mut tasks = first.tasks;
+    let pages: Vec<ApiResult<Vec<Task>>> = stream::iter(2..=first.total_pages)
+        .map(|page| {
+            let api = api.clone();
+            let query = query.clone();
+            let progress = progress.clone();
+            async move {
+                let page_tasks = api.fetch_tasks(page, &query).await;
+                progress.loaded_pages.fetch_add(1, Ordering::Relaxed);
+                page_tasks
+            }
+        })
+        .buffered(ALL_PAGES_CONCURRENCY)
+        .collect()
+        .await;
+
+    for page in pages {
+        tasks.extend(page?);
+    }
+    Ok(tasks)
+}
+
+// A GET response kept for revalidation with If-None-Match / If-Modified-Since
+#[derive(Clone)]
+struct CachedResponse {
+    headers: HeaderMap,
+    body: String,
+}
+
+// The responses by url, with when they came in. Old ones are dropped, and
+// a write drops them all, as a change within the second can keep
+// Last-Modified the same.
+#[derive(Default)]
+struct ResponseCache {
+    entries: Mutex<HashMap<String, (Instant, CachedResponse)>>,
+}
+
+impl ResponseCache {
+    fn get(&self, url: &str) -> Option<CachedResponse> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(url) {
+            Some((stored, response)) if stored.elapsed() < RESPONSE_CACHE_MAX_AGE => {
+                Some(response.clone())
+            }
+            Some(_) => {
+                entries.remove(url);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert(&self, url: &str, response: CachedResponse) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, (stored, _)| stored.elapsed() < RESPONSE_CACHE_MAX_AGE);
+        if entries.len() >= RESPONSE_CACHE_LIMIT && !entries.contains_key(url) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, (stored, _))| *stored)
+                .map(|(url, _)| url.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(url.to_string(), (Instant::now(), response));
+    }
+
+    // Writes reach further than their path, like a task created in a
+    // project showing up in /tasks/all or a bucket move changing the task,
+    // so any of them drops everything
+    fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+#[derive(Deserialize)]
+struct LoginResponse {
+    token: String,
+}
+
+// Response of /shares/{hash}/auth
+#[derive(Deserialize)]
+pub struct LinkShareAuth {
+    pub token: String,
+    pub project_id: u64,
+}
+
+// Response of /projects/{id}/duplicate
+#[derive(Deserialize)]
+struct DuplicateResponse {
+    duplicated_project: Project,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    code: i64,
+    message: String,
+}
+
+pub struct HttpClient {
+    instance_url: String,
+    // Either the API token from the config or a JWT from logging in again
+    token: RwLock<String>,
+    client: Client,
+    cache: ResponseCache,
+    calls: CallLog,
+}
+
+impl HttpClient {
+    pub fn new(instance_url: &str, api_key: &str) -> HttpClient {
+        HttpClient {
+            instance_url: instance_url.to_string(),
+            token: RwLock::new(api_key.to_string()),
+            client: Client::new(),
+            cache: ResponseCache::default(),
+            calls: CallLog::default(),
+        }
+    }
+
+    // Sends a request and notes it in the call log with its status and how
+    // long it took
+    async fn send(&self, request: RequestBuilder) -> ApiResult<Response> {
+        let request = request.build()?;
+        let url = request.url();
+        let mut path = url.path().trim_start_matches("/api/v1").to_string();
+        let query: Vec<String> = url
+            .query_pairs()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        if !query.is_empty() {
+            path = format!("{}?{}", path, query.join("&"));
+        }
+        let method = request.method().to_string();
+        let started = Instant::now();
+        let response = self.client.execute(request).await;
+        if method != Method::GET.as_str() {
+            self.cache.clear();
+        }
+        self.calls.record(ApiCall {
+            method,
+            path,
+            status: response.as_ref().ok().map(|res| res.status().as_u16()),
+            latency: started.elapsed(),
+        });
+        Ok(response?)
+    }
+
+    fn authorization(&self) -> String {
+        format!("Bearer {}", self.token.read().unwrap())
+    }
+
+    // GETs a path, sending the validators of a previous response so the server
+    // can answer 304 Not Modified instead of transferring the body again
+    async fn get(&self, path: &str, query: &[(&str, String)]) -> ApiResult<CachedResponse> {
+        let url = Url::parse_with_params(&format!("{}/api/v1{}", self.instance_url, path), query)?;
+        let cached = self.cache.get(url.as_str());
+
+        let mut request = self
+            .client
+            .get(url.clone())
+            .header("Authorization", self.authorization());
+        if let Some(cached) = &cached {
+            if let Some(etag) = cached.headers.get(ETAG) {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = cached.headers.get(LAST_MODIFIED) {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let res = self.send(request).await?;
+        if res.status() == StatusCode::UNAUTHORIZED {
+            return Err(Unauthorized.into());
+        }
+        if res.status() == StatusCode::NOT_MODIFIED {
+            if let Some(cached) = cached {
+                return Ok(cached);
+            }
+        }
+        if !res.status().is_success() {
+            let error_text = res.text().await?;
+            return Err(format!("Error fetching {}: {}", path, error_text).into());
+        }
+
+        let response = CachedResponse {
+            headers: res.headers().clone(),
+            body: res.text().await?,
+        };
+        let has_validators =
+            response.headers.contains_key(ETAG) || response.headers.contains_key(LAST_MODIFIED);
+        if has_validators {
+            self.cache.insert(url.as_str(), response.clone());
+        }
+        Ok(response)
+    }
+
+    // POSTs to one of the endpoints that answer with a fresh JWT
+    async fn post_for_token(&self, path: &str, body: &serde_json::Value) -> ApiResult<String> {
+        Ok(self.post_auth::<LoginResponse>(path, body).await?.token)
+    }
+
+    async fn post_auth<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &serde_json::Value,
+    ) -> ApiResult<T> {
+        let url = format!("{}/api/v1{}", self.instance_url, path);
+        let request = self
+            .client
+            .post(&url)
+            .header("Authorization", self.authorization())
+            .json(body);
+        let res = self.send(request).await?;
+
+        if res.status() == StatusCode::UNAUTHORIZED {
+            Err(Unauthorized.into())
+        } else if res.status().is_success() {
+            Ok(res.json::<T>().await?)
+        } else {
+            let error_text = res.text().await?;
+            match serde_json::from_str::<ErrorResponse>(&error_text) {
+                Ok(error) if error.code == TOTP_ERROR_CODE => Err(TotpRequired.into()),
+                Ok(error) => Err(format!("Error logging in: {}", error.message).into()),
+                Err(_) => Err(format!("Error logging in: {}", error_text).into()),
+            }
+        }
+    }
+
+    // Trades the code from an OpenID Connect redirect for a JWT. The redirect
+    // url must be the one the code was requested with, minus the provider key.
+    pub async fn oidc_callback(
+        &self,
+        provider_key: &str,
+        code: &str,
+        redirect_url: &str,
+    ) -> ApiResult<String> {
+        let path = format!("/auth/openid/{}/callback", provider_key);
+        let body = json!({
+            "code": code,
+            "redirect_url": redirect_url
+        });
+        self.post_for_token(&path, &body).await
+    }
+
+    // Signs in with the hash of a project link share instead of a user account
+    pub async fn share_auth(&self, hash: &str) -> ApiResult<LinkShareAuth> {
+        self.post_auth(&format!("/shares/{}/auth", hash), &json!({}))
+            .await
+    }
+
+    // Exchanges the current, still valid JWT for one with a new expiry
+    pub async fn renew_token(&self) -> ApiResult<String> {
+        self.post_for_token("/user/token", &json!({})).await
+    }
+
+    // Sends a change to the server and returns the response body
+    async fn send_json(
+        &self,
+        method: Method,
+        path: &str,
+        body: &serde_json::Value,
+        action: &str,
+    ) -> ApiResult<String> {
+        let url = format!("{}/api/v1{}", self.instance_url, path);
+        let request = self
+            .client
+            .request(method, &url)
+            .header("Authorization", self.authorization())
+            .json(body);
+        let res = self.send(request).await?;
+
+        if res.status() == StatusCode::UNAUTHORIZED {
+            Err(Unauthorized.into())
+        } else if res.status().is_success() {
+            Ok(res.text().await?)
+        } else {
+            let error_text = res.text().await?;
+            Err(format!("Error {}: {}", action, error_text).into())
+        }
+    }
+
+    async fn get_json<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> ApiResult<T> {
+        let response = self.get(path, query).await?;
+        Ok(serde_json::from_str(&response.body)?)
+    }
+}
+
+#[async_trait]
+impl VikunjaApi for HttpClient {
+    async fn fetch_info(&self) -> ApiResult<ServerInfo> {
+        self.get_json("/info", &[]).await
+    }
+
+    fn call_log(&self) -> Option<&CallLog> {
+        Some(&self.calls)
+    }
+
+    async fn fetch_task_page(&self, page: usize, query: &TaskQuery) -> ApiResult<TaskPage> {
+        let mut params = vec![("page", page.to_string())];
+        params.extend(query.params());
+
+        let path = match query.view {
+            Some((project_id, view_id)) => {
+                format!("/projects/{}/views/{}/tasks", project_id, view_id)
+            }
+            None => "/tasks/all".to_string(),
+        };
+        let res = self.get(&path, &params).await?;
+        let (tasks, skipped) = parse_tasks(&res.body)?;
+        Ok(TaskPage {
+            total_pages: total_pages(&res.headers),
+            result_count: count_header(&res.headers, "x-pagination-result-count")
+                .unwrap_or(tasks.len() + skipped),
+            tasks,
+            skipped,
+        })
+    }
+
+    async fn fetch_projects(&self) -> ApiResult<Vec<Project>> {
+        let mut projects = Vec::new();
+        let mut page = 1;
+        loop {
+            let res = self.get("/projects", &[("page", page.to_string())]).await?;
+            let total_pages = total_pages(&res.headers);
+
+            projects.extend(serde_json::from_str::<Vec<Project>>(&res.body)?);
+
+            if page >= total_pages {
+                break;
+            }
+            page += 1;
+        }
+
+        Ok(projects)
+    }
+
+    async fn fetch_project_views(&self, project_id: u64) -> ApiResult<Vec<ProjectView>> {
+        self.get_json(&format!("/projects/{}/views", project_id), &[])
+            .await
+    }
+
+    async fn duplicate_project(&self, project_id: u64, parent_id: u64) -> ApiResult<Project> {
+        let path = format!("/projects/{}/duplicate", project_id);
+        let body = json!({ "parent_project_id": parent_id });
+        let body = self
+            .send_json(Method::PUT, &path, &body, "duplicating project")
+            .await?;
+        Ok(serde_json::from_str::<DuplicateResponse>(&body)?.duplicated_project)
+    }
+
+    // For kanban views the tasks endpoint returns the buckets with their tasks
+    async fn fetch_buckets(&self, project_id: u64, view_id: u64) -> ApiResult<Vec<Bucket>> {
+        let path = format!("/projects/{}/views/{}/tasks", project_id, view_id);
+        let res = self.get(&path, &[]).await?;
+        Ok(parse_buckets(&res.body)?)
+    }
+
+    async fn fetch_task_detail(&self, task_id: u64) -> ApiResult<Task> {
+        self.get_json(&format!("/tasks/{}", task_id), &[]).await
+    }
+
+    async fn create_new_task(&self, task: &NewTask) -> ApiResult<Task> {
+        let mut task_data = json!({
+            "title": task.title
+        });
+
+        if let Some(desc) = &task.description {
+            task_data["description"] = json!(desc);
+        }
+
+        if let Some(priority_value) = task.priority {
+            task_data["priority"] = json!(priority_value);
+        }
+
+        for (field, date) in [
+            ("due_date", &task.due_date),
+            ("start_date", &task.start_date),
+            ("end_date", &task.end_date),
+        ] {
+            if let Some(date) = date {
+                task_data[field] = json!(date);
+            }
+        }
+
+        if let Some(hex_color) = &task.hex_color {
+            task_data["hex_color"] = json!(hex_color);
+        }
+
+        if let Some(bucket_id) = task.bucket_id {
+            task_data["bucket_id"] = json!(bucket_id);
+        }
+
+        if !task.reminders.is_empty() {
+            let reminders: Vec<_> = task
+                .reminders
+                .iter()
+                .map(|reminder| json!({ "reminder": reminder }))
+                .collect();
+            task_data["reminders"] = json!(reminders);
+        }
+
+        let path = format!("/projects/{}/tasks", task.project_id.unwrap_or(1));
+        let body = self
+            .send_json(Method::PUT, &path, &task_data, "creating task")
+            .await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    async fn update_task(&self, task: &Task) -> ApiResult<Task> {
+        let path = format!("/tasks/{}", task.id);
+        let body = self
+            .send_json(
+                Method::POST,
+                &path,
+                &serde_json::to_value(task)?,
+                "updating task",
+            )
+            .await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    async fn delete_task(&self, task_id: u64) -> ApiResult<()> {
+        let path = format!("/tasks/{}", task_id);
+        self.send_json(Method::DELETE, &path, &json!({}), "deleting task")
+            .await?;
+        Ok(())
+    }
+
+    async fn fetch_labels(&self) -> ApiResult<Vec<Label>> {
+        let mut labels = Vec::new();
+        let mut page = 1;
+        loop {
+            let res = self.get("/labels", &[("page", page.to_string())]).await?;
+            labels.extend(serde_json::from_str::<Vec<Label>>(&res.body)?);
+            if page >= total_pages(&res.headers) {
+                break;
+            }
+            page += 1;
+        }
+        Ok(labels)
+    }
+
+    async fn create_label(&self, label: &Label) -> ApiResult<Label> {
+        let body = json!({ "title": label.title, "hex_color": label.hex_color });
+        let body = self
+            .send_json(Method::PUT, "/labels", &body, "creating label")
+            .await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    async fn add_label(&self, task_id: u64, label_id: u64) -> ApiResult<()> {
+        let path = format!("/tasks/{}/labels", task_id);
+        let body = json!({ "label_id": label_id });
+        self.send_json(Method::PUT, &path, &body, "adding label")
+            .await?;
+        Ok(())
+    }
+
+    async fn remove_label(&self, task_id: u64, label_id: u64) -> ApiResult<()> {
+        let path = format!("/tasks/{}/labels/{}", task_id, label_id);
+        self.send_json(Method::DELETE, &path, &json!({}), "removing label")
+            .await?;
+        Ok(())
+    }
+
+    async fn search_users(&self, query: &str) -> ApiResult<Vec<User>> {
+        self.get_json("/users", &[("s", query.to_string())]).await
+    }
+
+    async fn add_assignee(&self, task_id: u64, user_id: u64) -> ApiResult<()> {
+        let path = format!("/tasks/{}/assignees", task_id);
+        let body = json!({ "user_id": user_id });
+        self.send_json(Method::PUT, &path, &body, "adding assignee")
+            .await?;
+        Ok(())
+    }
+
+    async fn move_task_to_bucket(
+        &self,
+        project_id: u64,
+        view_id: u64,
+        bucket_id: u64,
+        task_id: u64,
+    ) -> ApiResult<()> {
+        let path = format!(
+            "/projects/{}/views/{}/buckets/{}/tasks",
+            project_id, view_id, bucket_id
+        );
+        let body = json!({ "task_id": task_id });
+        self.send_json(Method::POST, &path, &body, "moving task")
+            .await?;
+        Ok(())
+    }
+
+    async fn set_task_position(&self, task_id: u64, view_id: u64, position: f64) -> ApiResult<()> {
+        let path = format!("/tasks/{}/position", task_id);
+        let body = json!({ "position": position, "project_view_id": view_id });
+        self.send_json(Method::POST, &path, &body, "moving task")
+            .await?;
+        Ok(())
+    }
+
+    async fn fetch_notifications(&self) -> ApiResult<Vec<Notification>> {
+        self.get_json("/notifications", &[]).await
+    }
+
+    async fn mark_notification_read(&self, notification_id: u64) -> ApiResult<()> {
+        let path = format!("/notifications/{}", notification_id);
+        let body = json!({ "read": true });
+        self.send_json(Method::POST, &path, &body, "marking notification as read")
+            .await?;
+        Ok(())
+    }
+
+    async fn fetch_comments(&self, task_id: u64) -> ApiResult<Vec<Comment>> {
+        self.get_json(&format!("/tasks/{}/comments", task_id), &[])
+            .await
+    }
+
+    async fn add_comment(&self, task_id: u64, comment: &str) -> ApiResult<()> {
+        self.send_json(
+            Method::PUT,
+            &format!("/tasks/{}/comments", task_id),
+            &json!({ "comment": comment }),
+            "adding comment",
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn fetch_current_user(&self) -> ApiResult<User> {
+        self.get_json("/user", &[]).await
+    }
+
+    async fn fetch_project_users(&self, project_id: u64) -> ApiResult<Vec<ProjectUser>> {
+        self.get_json(&format!("/projects/{}/users", project_id), &[])
+            .await
+    }
+
+    async fn fetch_project_teams(&self, project_id: u64) -> ApiResult<Vec<ProjectTeam>> {
+        self.get_json(&format!("/projects/{}/teams", project_id), &[])
+            .await
+    }
+
+    async fn fetch_teams(&self) -> ApiResult<Vec<Team>> {
+        self.get_json("/teams", &[]).await
+    }
+
+    async fn create_team(&self, name: &str) -> ApiResult<Team> {
+        let body = json!({ "name": name });
+        let body = self
+            .send_json(Method::PUT, "/teams", &body, "creating team")
+            .await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    async fn add_team_member(&self, team_id: u64, username: &str) -> ApiResult<()> {
+        let path = format!("/teams/{}/members", team_id);
+        let body = json!({ "username": username });
+        self.send_json(Method::PUT, &path, &body, "adding team member")
+            .await?;
+        Ok(())
+    }
+
+    // Vikunja takes the username as user_id here. The level goes under both
+    // its old and its new name.
+    async fn share_with_user(&self, project_id: u64, username: &str, right: u8) -> ApiResult<()> {
+        let path = format!("/projects/{}/users", project_id);
+        let body = json!({ "user_id": username, "right": right, "permission": right });
+        self.send_json(Method::PUT, &path, &body, "sharing project")
+            .await?;
+        Ok(())
+    }
+
+    async fn share_with_team(&self, project_id: u64, team_id: u64, right: u8) -> ApiResult<()> {
+        let path = format!("/projects/{}/teams", project_id);
+        let body = json!({ "team_id": team_id, "right": right, "permission": right });
+        self.send_json(Method::PUT, &path, &body, "sharing project")
+            .await?;
+        Ok(())
+    }
+
+    async fn unshare_with_user(&self, project_id: u64, username: &str) -> ApiResult<()> {
+        let path = format!("/projects/{}/users/{}", project_id, username);
+        self.send_json(Method::DELETE, &path, &json!({}), "unsharing project")
+            .await?;
+        Ok(())
+    }
+
+    async fn unshare_with_team(&self, project_id: u64, team_id: u64) -> ApiResult<()> {
+        let path = format!("/projects/{}/teams/{}", project_id, team_id);
+        self.send_json(Method::DELETE, &path, &json!({}), "unsharing project")
+            .await?;
+        Ok(())
+    }
+
+    async fn add_reaction(&self, target: ReactionTarget, value: &str) -> ApiResult<()> {
+        let body = json!({ "value": value });
+        self.send_json(Method::PUT, &target.path(), &body, "adding reaction")
+            .await?;
+        Ok(())
+    }
+
+    async fn remove_reaction(&self, target: ReactionTarget, value: &str) -> ApiResult<()> {
+        let path = format!("{}/delete", target.path());
+        let body = json!({ "value": value });
+        self.send_json(Method::POST, &path, &body, "removing reaction")
+            .await?;
+        Ok(())
+    }
+
+    async fn login(
+        &self,
+        username: &str,
+        password: &str,
+        totp_passcode: Option<&str>,
+    ) -> ApiResult<String> {
+        let mut body = json!({
+            "username": username,
+            "password": password,
+            "long_token": true
+        });
+        if let Some(passcode) = totp_passcode {
+            body["totp_passcode"] = json!(passcode);
+        }
+        self.post_for_token("/login", &body).await
+    }
+
+    fn set_token(&self, token: &str) {
+        *self.token.write().unwrap() = token.to_string();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_call_log_keeps_the_latest() {
+        let log = CallLog::default();
+        for i in 0..CALL_LOG_LIMIT + 5 {
+            log.record(ApiCall {
+                method: "GET".to_string(),
+                path: format!("/tasks/{}", i),
+                status: Some(200),
+                latency: Duration::from_millis(20),
+            });
+        }
+        log.with_recent(|calls| {
+            assert_eq!(calls.len(), CALL_LOG_LIMIT);
+            assert_eq!(calls[0].path, "/tasks/5");
+            assert_eq!(
+                calls.last().unwrap().path,
+                format!("/tasks/{}", CALL_LOG_LIMIT + 4)
+            );
+        });
+    }
+
+    #[test]
+    fn test_response_cache_hit_miss_and_clear() {
+        let cache = ResponseCache::default();
+        let response = |body: &str| CachedResponse {
+            headers: HeaderMap::new(),
+            body: body.to_string(),
+        };
+        cache.insert("https://v.io/api/v1/tasks/42", response("task"));
+        cache.insert(
+            "https://v.io/api/v1/projects/3/views/5/tasks",
+            response("list"),
+        );
+        assert_eq!(
+            cache.get("https://v.io/api/v1/tasks/42").unwrap().body,
+            "task"
+        );
+        assert!(cache.get("https://v.io/api/v1/tasks/43").is_none());
+
+        cache.clear();
+        assert!(cache
+            .get("https://v.io/api/v1/projects/3/views/5/tasks")
+            .is_none());
+
+        for i in 0..RESPONSE_CACHE_LIMIT + 1 {
+            cache.insert(&format!("https://v.io/{}", i), response("x"));
+        }
+        assert_eq!(cache.entries.lock().unwrap().len(), RESPONSE_CACHE_LIMIT);
+        assert!(cache
+            .get(&format!("https://v.io/{}", RESPONSE_CACHE_LIMIT))
+            .is_some());
+    }
+
+    #[test]
+    fn test_average_latency_of_the_latest_answered_calls() {
+        let call = |status, millis| ApiCall {
+            method: "GET".to_string(),
+            path: "/tasks/all".to_string(),
+            status,
+            latency: Duration::from_millis(millis),
+        };
+        let calls = [
+            call(Some(200), 900),
+            call(Some(200), 100),
+            call(None, 5000),
+            call(Some(304), 300),
+        ];
+        assert_eq!(average_latency(&calls, 2), Some(Duration::from_millis(200)));
+        assert_eq!(average_latency(&[], 2), None);
+    }
+}
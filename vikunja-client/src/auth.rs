@@ -0,0 +1,125 @@
+use crate::api::{ApiResult, HttpClient, VikunjaApi};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use reqwest::Url;
+
+// Link share right that only allows reading
+const SHARE_RIGHT_READ: i64 = 0;
+
+// A project opened through a link share
+pub struct ShareSession {
+    pub project_id: u64,
+    pub read_only: bool,
+}
+
+// Splits a share into the instance url, when it is a full link
+// like https://example.com/share/<hash>/auth, and the share hash
+pub fn parse_share_link(share: &str) -> (Option<String>, String) {
+    if let Ok(url) = Url::parse(share) {
+        if let Some(i) = url.path().find("/share/") {
+            let instance_url =
+                format!("{}{}", url.origin().ascii_serialization(), &url.path()[..i]);
+            let hash = url.path()[i + "/share/".len()..]
+                .split('/')
+                .next()
+                .unwrap_or_default();
+            return (Some(instance_url), hash.to_string());
+        }
+    }
+    (None, share.to_string())
+}
+
+// The instance url and task id of a task link like
+// https://example.com/tasks/42, as copied from the web interface
+pub fn parse_task_link(link: &str) -> Option<(String, u64)> {
+    let url = Url::parse(link).ok()?;
+    let i = url.path().find("/tasks/")?;
+    let instance_url = format!("{}{}", url.origin().ascii_serialization(), &url.path()[..i]);
+    let task_id = url.path()[i + "/tasks/".len()..]
+        .split('/')
+        .next()?
+        .parse()
+        .ok()?;
+    Some((instance_url, task_id))
+}
+
+// Whether two instance urls point at the same instance, ignoring case in
+// the host and a trailing slash
+pub fn same_instance(a: &str, b: &str) -> bool {
+    let normalize = |url: &str| match Url::parse(url.trim_end_matches('/')) {
+        Ok(url) => format!(
+            "{}{}",
+            url.origin().ascii_serialization(),
+            url.path().trim_end_matches('/')
+        ),
+        Err(_) => url.trim_end_matches('/').to_string(),
+    };
+    normalize(a) == normalize(b)
+}
+
+// Signs in with a link share. What the share allows is only in its token.
+pub async fn share_session(api: &HttpClient, hash: &str) -> ApiResult<ShareSession> {
+    let auth = api.share_auth(hash).await?;
+    api.set_token(&auth.token);
+    let right = token_claims(&auth.token)
+        .and_then(|claims| claims.get("right")?.as_i64())
+        .unwrap_or(SHARE_RIGHT_READ);
+    Ok(ShareSession {
+        project_id: auth.project_id,
+        read_only: right == SHARE_RIGHT_READ,
+    })
+}
+
+// The payload of a JWT. The signature isn't checked, the claims only decide
+// what the interface offers and the server enforces them anyway.
+fn token_claims(token: &str) -> Option<serde_json::Value> {
+    let payload = token.split('.').nth(1)?;
+    let bytes = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_share_link() {
+        assert_eq!(
+            parse_share_link("https://tasks.example.com/share/abc123/auth"),
+            (
+                Some("https://tasks.example.com".to_string()),
+                "abc123".to_string()
+            )
+        );
+        assert_eq!(parse_share_link("abc123"), (None, "abc123".to_string()));
+    }
+
+    #[test]
+    fn test_parse_task_link() {
+        assert_eq!(
+            parse_task_link("https://Tasks.example.com/vikunja/tasks/4521#comment-3"),
+            Some(("https://tasks.example.com/vikunja".to_string(), 4521))
+        );
+        assert_eq!(
+            parse_task_link("https://tasks.example.com/projects/3"),
+            None
+        );
+        assert_eq!(parse_task_link("4521"), None);
+        assert!(same_instance(
+            "https://tasks.example.com/",
+            "https://TASKS.example.com"
+        ));
+        assert!(!same_instance(
+            "https://tasks.example.com",
+            "https://other.example.com"
+        ));
+    }
+
+    #[test]
+    fn test_token_claims_reads_share_right() {
+        let payload = URL_SAFE_NO_PAD.encode(r#"{"type":2,"project_id":3,"right":1}"#);
+        let token = format!("eyJhbGciOiJIUzI1NiJ9.{}.signature", payload);
+        let claims = token_claims(&token).unwrap();
+        assert_eq!(claims["right"], 1);
+    }
+}
@@ -0,0 +1,26 @@
+//! A client for the [Vikunja](https://vikunja.io) REST API.
+//!
+//! [`HttpClient`] talks to an instance, [`VikunjaApi`] is the interface it
+//! implements so other backends (like an in-memory one for tests) can stand in
+//! for it. The types the API sends and takes are in [`models`].
+//!
+//! ```no_run
+//! use vikunja_client::{HttpClient, TaskQuery, VikunjaApi};
+//!
+//! # async fn run() -> vikunja_client::ApiResult<()> {
+//! let api = HttpClient::new("https://vikunja.example.com", "tk_...");
+//! for task in api.fetch_tasks(1, &TaskQuery::default()).await? {
+//!     println!("{} {}", task.identifier, task.title);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod api;
+pub mod auth;
+pub mod models;
+
+pub use api::{
+    fetch_all_tasks, is_unauthorized, ApiResult, HttpClient, LoadProgress, NewTask, ReactionTarget,
+    TaskPage, TaskQuery, TotpRequired, Unauthorized, VikunjaApi,
+};
@@ -0,0 +1,567 @@
+use chrono::{DateTime, Datelike, Local};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+// Task struct, covering the full task object so it can be sent back on updates
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Task {
+    pub id: u64,
+    pub title: String,
+    pub description: Option<String>,
+    pub done: bool,
+    pub done_at: Option<String>,
+    pub due_date: Option<String>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub reminders: Option<Vec<Reminder>>,
+    pub repeat_after: i64,
+    pub repeat_mode: i32,
+    pub priority: Option<i32>,
+    pub percent_done: f64,
+    pub hex_color: Option<String>,
+    pub identifier: String,
+    pub index: u64,
+    pub project_id: u64,
+    pub bucket_id: u64,
+    pub position: f64,
+    pub is_favorite: bool,
+    pub assignees: Option<Vec<User>>,
+    pub labels: Option<Vec<Label>>,
+    pub attachments: Option<Vec<Attachment>>,
+    // Left out by servers older than 0.24
+    pub comment_count: Option<u64>,
+    pub created: Option<String>,
+    pub updated: Option<String>,
+    pub created_by: Option<User>,
+    // Related tasks by relation kind, like "blocked" or "subtask"
+    pub related_tasks: Option<HashMap<String, Vec<Task>>>,
+    pub reactions: Option<Reactions>,
+    // Anything not modelled above, kept so updates don't drop fields
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Task {
+    // Tasks of a relation kind
+    pub fn related(&self, kind: &str) -> &[Task] {
+        self.related_tasks
+            .as_ref()
+            .and_then(|related| related.get(kind))
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    // The tasks this one is blocked by that are not done yet
+    pub fn blocked_by(&self) -> Vec<&Task> {
+        self.related("blocked")
+            .iter()
+            .filter(|task| !task.done)
+            .collect()
+    }
+
+    // When a done task was completed. Vikunja sends a zero date for tasks
+    // that never were.
+    pub fn completed_at(&self) -> Option<DateTime<Local>> {
+        if !self.done {
+            return None;
+        }
+        let done_at = DateTime::parse_from_rfc3339(self.done_at.as_deref()?).ok()?;
+        (done_at.year() > 1).then(|| done_at.with_timezone(&Local))
+    }
+
+    // The due date, None for the zero date Vikunja sends when there is none
+    pub fn due_at(&self) -> Option<DateTime<Local>> {
+        let due = DateTime::parse_from_rfc3339(self.due_date.as_deref()?).ok()?;
+        (due.year() > 1).then(|| due.with_timezone(&Local))
+    }
+
+    // Repeat mode 1 repeats monthly without an interval
+    pub fn repeats(&self) -> bool {
+        self.repeat_after > 0 || self.repeat_mode == 1
+    }
+
+    pub fn is_overdue(&self, now: DateTime<Local>) -> bool {
+        !self.done && self.due_at().is_some_and(|due| due < now)
+    }
+}
+
+// Reads a list of tasks one by one, so a task in a shape this doesn't expect
+// is left out instead of failing the list. Returns how many were left out.
+pub fn parse_tasks(body: &str) -> serde_json::Result<(Vec<Task>, usize)> {
+    let items: Option<Vec<Value>> = serde_json::from_str(body)?;
+    let items = items.unwrap_or_default();
+    let count = items.len();
+    let tasks: Vec<Task> = items
+        .into_iter()
+        .filter_map(|item| serde_json::from_value(item).ok())
+        .collect();
+    let skipped = count - tasks.len();
+    Ok((tasks, skipped))
+}
+
+// Reads the buckets of a kanban view, with their tasks read like parse_tasks.
+// A bucket that can't be read is left out, and its tasks are counted as
+// skipped in one of the others.
+pub fn parse_buckets(body: &str) -> serde_json::Result<Vec<Bucket>> {
+    let items: Option<Vec<Value>> = serde_json::from_str(body)?;
+    let mut buckets: Vec<Bucket> = Vec::new();
+    let mut lost = 0;
+    for mut item in items.unwrap_or_default() {
+        let tasks = item.get_mut("tasks").map(Value::take);
+        let tasks = match tasks {
+            Some(tasks @ Value::Array(_)) => Some(parse_tasks(&tasks.to_string())?),
+            _ => None,
+        };
+        match serde_json::from_value::<Bucket>(item) {
+            Ok(mut bucket) => {
+                if let Some((tasks, skipped)) = tasks {
+                    bucket.tasks = Some(tasks);
+                    bucket.skipped = skipped;
+                }
+                bucket.skipped += std::mem::take(&mut lost);
+                buckets.push(bucket);
+            }
+            Err(_) => lost += tasks.map_or(0, |(tasks, skipped)| tasks.len() + skipped),
+        }
+    }
+    if let Some(last) = buckets.last_mut() {
+        last.skipped += lost;
+    }
+    Ok(buckets)
+}
+
+// The users who reacted, by emoji
+pub type Reactions = HashMap<String, Vec<User>>;
+
+// Comment struct, the text is HTML like task descriptions
+#[derive(Clone, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Comment {
+    pub id: u64,
+    pub comment: String,
+    pub author: User,
+    pub created: Option<String>,
+    pub reactions: Option<Reactions>,
+}
+
+// Label struct
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Label {
+    pub id: u64,
+    pub title: String,
+    pub description: Option<String>,
+    pub hex_color: Option<String>,
+}
+
+// User struct, as used for assignees and creators
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct User {
+    pub id: u64,
+    pub name: String,
+    pub username: String,
+}
+
+// Reminder struct, either absolute or relative to one of the task dates
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Reminder {
+    pub reminder: Option<String>,
+    pub relative_period: i64,
+    pub relative_to: Option<String>,
+}
+
+// Attachment struct
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Attachment {
+    pub id: u64,
+    pub task_id: u64,
+    pub file: AttachmentFile,
+    pub created: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct AttachmentFile {
+    pub id: u64,
+    pub name: String,
+    pub mime: String,
+    pub size: u64,
+}
+
+// Project struct
+#[derive(Clone, Deserialize, Debug, Default)]
+pub struct Project {
+    pub id: u64,
+    pub title: String,
+    // HTML, like task descriptions
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub hex_color: String,
+    // Prefix of the task identifiers, like WRK in WRK-12
+    #[serde(default)]
+    pub identifier: String,
+}
+
+// ProjectView struct, one of the list/gantt/table/kanban views of a project
+#[derive(Clone, Deserialize, Debug)]
+pub struct ProjectView {
+    pub id: u64,
+    pub view_kind: String,
+    // Tasks moved into this bucket are marked done, 0 when there is none
+    #[serde(default)]
+    pub done_bucket_id: u64,
+}
+
+// Permission levels on a shared project, by their number in the API
+pub const PERMISSIONS: [&str; 3] = ["Read only", "Read & write", "Admin"];
+
+// A user a project is shared with. Newer servers call the level permission
+// rather than right.
+#[derive(Clone, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct ProjectUser {
+    #[serde(flatten)]
+    pub user: User,
+    #[serde(alias = "permission")]
+    pub right: u8,
+}
+
+// Team struct
+#[derive(Clone, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Team {
+    pub id: u64,
+    pub name: String,
+    pub description: String,
+    pub members: Option<Vec<TeamMember>>,
+}
+
+// A member of a team, admins can change it
+#[derive(Clone, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct TeamMember {
+    #[serde(flatten)]
+    pub user: User,
+    pub admin: bool,
+}
+
+// A team a project is shared with
+#[derive(Clone, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct ProjectTeam {
+    #[serde(flatten)]
+    pub team: Team,
+    #[serde(alias = "permission")]
+    pub right: u8,
+}
+
+// Bucket struct, a kanban column with its tasks
+#[derive(Clone, Deserialize, Debug)]
+pub struct Bucket {
+    pub id: u64,
+    pub title: String,
+    pub tasks: Option<Vec<Task>>,
+    // Tasks of the bucket that couldn't be read and are left out
+    #[serde(skip)]
+    pub skipped: usize,
+}
+
+// Notification struct, what happened is in the notification object, shaped
+// by the name
+#[derive(Clone, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Notification {
+    pub id: u64,
+    pub name: String,
+    pub notification: Value,
+    pub read_at: Option<String>,
+    pub created: Option<String>,
+}
+
+impl Notification {
+    pub fn is_unread(&self) -> bool {
+        self.read_at
+            .as_deref()
+            .is_none_or(|read_at| read_at.starts_with("0001-01-01"))
+    }
+
+    // The task it is about, for the kinds that have one
+    pub fn task_id(&self) -> Option<u64> {
+        self.notification.get("task")?.get("id")?.as_u64()
+    }
+
+    // One line describing the notification, like the web interface's
+    pub fn summary(&self) -> String {
+        let field = |object: &str, key: &str| {
+            self.notification
+                .get(object)
+                .and_then(|value| value.get(key))
+                .and_then(Value::as_str)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        let person = |object: &str| {
+            field(object, "name")
+                .or_else(|| field(object, "username"))
+                .unwrap_or_else(|| "Someone".to_string())
+        };
+        let task = field("task", "title").unwrap_or_else(|| "a task".to_string());
+        match self.name.as_str() {
+            "task.comment" => format!("{} commented on {}", person("doer"), task),
+            "task.mentioned" => format!("{} mentioned you in {}", person("doer"), task),
+            "task.assigned" => format!(
+                "{} assigned {} to {}",
+                person("doer"),
+                person("assignee"),
+                task
+            ),
+            "task.reminder" => format!("Reminder: {}", task),
+            "task.deleted" => format!("{} deleted {}", person("doer"), task),
+            "project.created" => format!(
+                "{} created the project {}",
+                person("doer"),
+                field("project", "title").unwrap_or_default()
+            ),
+            "team.member.added" => format!(
+                "{} added you to the team {}",
+                person("doer"),
+                field("team", "name").unwrap_or_default()
+            ),
+            name => name.to_string(),
+        }
+    }
+}
+
+// ServerInfo struct from /info
+#[derive(Clone, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct ServerInfo {
+    pub version: String,
+    pub auth: AuthInfo,
+}
+
+// Login methods enabled on the instance
+#[derive(Clone, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct AuthInfo {
+    pub openid_connect: OpenIdInfo,
+}
+
+#[derive(Clone, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct OpenIdInfo {
+    pub enabled: bool,
+    pub providers: Vec<OpenIdProvider>,
+}
+
+// OpenIdProvider struct, as listed in /info
+#[derive(Clone, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct OpenIdProvider {
+    pub name: String,
+    pub key: String,
+    pub auth_url: String,
+    pub client_id: String,
+    pub scope: String,
+}
+
+// Features that depend on the server version
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Capabilities {
+    pub project_views: bool,
+    pub reactions: bool,
+}
+
+// Oldest release with the filter query syntax and project views used here
+pub const MIN_SUPPORTED_VERSION: (u64, u64, u64) = (0, 24, 0);
+
+impl ServerInfo {
+    // "v0.24.1" or "0.24.1-42-gabc" -> (0, 24, 1). Unparsable versions (for
+    // example "unstable" builds) are None and assumed to be current.
+    pub fn version_tuple(&self) -> Option<(u64, u64, u64)> {
+        let version = self.version.trim_start_matches('v');
+        let mut parts = version
+            .split(|c: char| !c.is_ascii_digit())
+            .map(|part| part.parse::<u64>().ok());
+        Some((parts.next()??, parts.next()??, parts.next()??))
+    }
+
+    pub fn is_supported(&self) -> bool {
+        self.version_tuple()
+            .is_none_or(|version| version >= MIN_SUPPORTED_VERSION)
+    }
+
+    pub fn capabilities(&self) -> Capabilities {
+        let version = self.version_tuple();
+        Capabilities {
+            project_views: version.is_none_or(|v| v >= (0, 24, 0)),
+            reactions: version.is_none_or(|v| v >= (0, 24, 0)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_tasks_leaves_out_malformed_ones() {
+        let body = json!([
+            {"id": 1, "title": "Fine"},
+            {"id": 2, "title": ["not", "a", "string"]},
+            {"id": 3, "title": "Also fine", "priority": 2},
+        ]);
+        let (tasks, skipped) = parse_tasks(&body.to_string()).unwrap();
+        let ids: Vec<u64> = tasks.iter().map(|task| task.id).collect();
+        assert_eq!(ids, [1, 3]);
+        assert_eq!(skipped, 1);
+        assert_eq!(parse_tasks("null").unwrap().0.len(), 0);
+    }
+
+    #[test]
+    fn test_parse_buckets_leaves_out_malformed_tasks_and_buckets() {
+        let body = json!([
+            {"id": 1, "title": "To do", "tasks": [{"id": 1, "title": "Fine"}, {"id": 2, "title": 5}]},
+            {"id": "two", "title": "Broken", "tasks": [{"id": 3, "title": "Lost"}]},
+            {"id": 3, "title": "Done", "tasks": null},
+        ]);
+        let buckets = parse_buckets(&body.to_string()).unwrap();
+        let titles: Vec<&str> = buckets.iter().map(|b| b.title.as_str()).collect();
+        assert_eq!(titles, ["To do", "Done"]);
+        assert_eq!(buckets[0].tasks.as_ref().unwrap().len(), 1);
+        let skipped: usize = buckets.iter().map(|b| b.skipped).sum();
+        assert_eq!(skipped, 2);
+    }
+
+    #[test]
+    fn test_task_round_trip_keeps_unknown_fields() {
+        let input = json!({
+            "id": 42,
+            "title": "Write report",
+            "done": false,
+            "due_date": "2024-05-03T12:00:00Z",
+            "labels": [{"id": 1, "title": "work", "hex_color": "e8e8e8"}],
+            "assignees": null,
+            "attachments": [{"id": 7, "task_id": 42, "file": {"name": "notes.pdf", "size": 1024}}],
+            "percent_done": 0.5,
+            "identifier": "WRK-12",
+            "subscription": {"id": 3}
+        });
+        let task: Task = serde_json::from_value(input).unwrap();
+        assert_eq!(task.identifier, "WRK-12");
+        assert_eq!(task.attachments.as_ref().unwrap().len(), 1);
+        assert_eq!(
+            task.labels.as_ref().unwrap()[0].hex_color.as_deref(),
+            Some("e8e8e8")
+        );
+
+        let output = serde_json::to_value(&task).unwrap();
+        assert_eq!(output["subscription"], json!({"id": 3}));
+        assert_eq!(output["percent_done"], json!(0.5));
+    }
+
+    #[test]
+    fn test_blocked_by_skips_done_tasks() {
+        let task: Task = serde_json::from_value(json!({
+            "id": 7,
+            "related_tasks": {
+                "blocked": [{"id": 4, "title": "Report", "done": false}, {"id": 5, "done": true}],
+                "subtask": [{"id": 9}]
+            }
+        }))
+        .unwrap();
+        let blockers: Vec<u64> = task.blocked_by().iter().map(|t| t.id).collect();
+        assert_eq!(blockers, vec![4]);
+        let task: Task = serde_json::from_value(json!({"id": 8, "related_tasks": null})).unwrap();
+        assert!(task.blocked_by().is_empty());
+    }
+
+    #[test]
+    fn test_completed_at() {
+        let task: Task = serde_json::from_value(json!({
+            "done": true,
+            "done_at": "2024-05-03T14:30:00Z"
+        }))
+        .unwrap();
+        let completed = task.completed_at().unwrap();
+        assert_eq!(completed.to_utc().to_rfc3339(), "2024-05-03T14:30:00+00:00");
+        let task: Task = serde_json::from_value(json!({
+            "done": true,
+            "done_at": "0001-01-01T00:00:00Z"
+        }))
+        .unwrap();
+        assert!(task.completed_at().is_none());
+        let task: Task = serde_json::from_value(json!({
+            "done": false,
+            "done_at": "2024-05-03T14:30:00Z"
+        }))
+        .unwrap();
+        assert!(task.completed_at().is_none());
+    }
+
+    #[test]
+    fn test_is_overdue() {
+        let now = DateTime::parse_from_rfc3339("2024-05-10T09:00:00Z")
+            .unwrap()
+            .with_timezone(&Local);
+        let task = |value: Value| serde_json::from_value::<Task>(value).unwrap();
+        assert!(task(json!({"due_date": "2024-05-03T12:00:00Z"})).is_overdue(now));
+        assert!(!task(json!({"due_date": "2024-05-13T12:00:00Z"})).is_overdue(now));
+        assert!(!task(json!({"due_date": "2024-05-03T12:00:00Z", "done": true})).is_overdue(now));
+        assert!(!task(json!({"due_date": "0001-01-01T00:00:00Z"})).is_overdue(now));
+    }
+
+    #[test]
+    fn test_notification_summary() {
+        let notification: Notification = serde_json::from_value(json!({
+            "id": 1,
+            "name": "task.assigned",
+            "notification": {
+                "doer": {"username": "alex", "name": ""},
+                "assignee": {"username": "sam", "name": "Sam Rivera"},
+                "task": {"id": 4, "title": "Write quarterly report"}
+            },
+            "read_at": null
+        }))
+        .unwrap();
+        assert_eq!(
+            notification.summary(),
+            "alex assigned Sam Rivera to Write quarterly report"
+        );
+        assert_eq!(notification.task_id(), Some(4));
+        assert!(notification.is_unread());
+    }
+
+    #[test]
+    fn test_server_version_parsing() {
+        let info = |version: &str| ServerInfo {
+            version: version.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(info("v0.24.1").version_tuple(), Some((0, 24, 1)));
+        assert_eq!(
+            info("v0.22.0-83-g1e9c4a1").version_tuple(),
+            Some((0, 22, 0))
+        );
+        assert_eq!(info("unstable").version_tuple(), None);
+        assert!(!info("v0.22.0").capabilities().project_views);
+        assert!(info("unstable").is_supported());
+    }
+
+    #[test]
+    fn test_project_user_reads_either_permission_name() {
+        let user: ProjectUser =
+            serde_json::from_value(json!({"id": 2, "username": "sam", "right": 1})).unwrap();
+        assert_eq!((user.user.username.as_str(), user.right), ("sam", 1));
+        let user: ProjectUser =
+            serde_json::from_value(json!({"id": 2, "username": "sam", "permission": 2})).unwrap();
+        assert_eq!(user.right, 2);
+    }
+}
@@ -0,0 +1,99 @@
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use vikunja_client::{
+    fetch_all_tasks, is_unauthorized, HttpClient, LoadProgress, NewTask, TaskQuery, VikunjaApi,
+};
+
+const TOKEN: &str = "tk_test";
+const TASK_PAGES: usize = 3;
+
+// A stand-in instance on localhost that serves TASK_PAGES pages of one task
+// each, answers 304 when a page is asked for again with its ETag, takes new
+// tasks in project 1 and turns away any other token. Returns its url.
+async fn serve() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0; 8192];
+            let read = stream.read(&mut buffer).await.unwrap();
+            let request = String::from_utf8_lossy(&buffer[..read]).to_lowercase();
+            let target = request.split_whitespace().nth(1).unwrap_or_default();
+
+            let authorized = request.contains(&format!("authorization: bearer {}", TOKEN));
+            let (status, body) = if !authorized {
+                (
+                    "401 Unauthorized",
+                    r#"{"code":11,"message":"invalid token"}"#.to_string(),
+                )
+            } else if target.starts_with("/api/v1/tasks/all?") && request.contains("if-none-match")
+            {
+                ("304 Not Modified", String::new())
+            } else if request.starts_with("put /api/v1/projects/1/tasks ") {
+                ("200 OK", r#"{"id":9,"title":"New"}"#.to_string())
+            } else if let Some(query) = target.strip_prefix("/api/v1/tasks/all?") {
+                let page = query
+                    .split('&')
+                    .find_map(|param| param.strip_prefix("page="))
+                    .unwrap_or("1");
+                ("200 OK", format!(r#"[{{"id":{},"title":"Task"}}]"#, page))
+            } else {
+                ("404 Not Found", "{}".to_string())
+            };
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nETag: \"v1\"\r\nx-pagination-total-pages: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                TASK_PAGES,
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        }
+    });
+    url
+}
+
+#[tokio::test]
+async fn test_fetch_all_tasks_reads_every_page() {
+    let api = Arc::new(HttpClient::new(&serve().await, TOKEN));
+    let progress = Arc::new(LoadProgress::default());
+    let tasks = fetch_all_tasks(api, TaskQuery::default(), progress.clone())
+        .await
+        .unwrap();
+    let ids: Vec<u64> = tasks.iter().map(|task| task.id).collect();
+    assert_eq!(ids, vec![1, 2, 3]);
+    assert_eq!(progress.loaded_pages.load(Ordering::Relaxed), TASK_PAGES);
+}
+
+#[tokio::test]
+async fn test_writes_drop_cached_lists() {
+    let api = HttpClient::new(&serve().await, TOKEN);
+    let statuses = |api: &HttpClient| -> Vec<Option<u16>> {
+        api.call_log()
+            .unwrap()
+            .with_recent(|calls| calls.iter().map(|call| call.status).collect())
+    };
+    api.fetch_tasks(1, &TaskQuery::default()).await.unwrap();
+    api.fetch_tasks(1, &TaskQuery::default()).await.unwrap();
+    assert_eq!(statuses(&api), [Some(200), Some(304)]);
+
+    // The new task is in another path, and still /tasks/all is loaded anew
+    let task = NewTask {
+        title: "New".to_string(),
+        project_id: Some(1),
+        ..Default::default()
+    };
+    api.create_new_task(&task).await.unwrap();
+    api.fetch_tasks(1, &TaskQuery::default()).await.unwrap();
+    assert_eq!(statuses(&api)[3], Some(200));
+}
+
+#[tokio::test]
+async fn test_rejected_token_is_unauthorized() {
+    let api = HttpClient::new(&serve().await, "tk_expired");
+    let err = api.fetch_tasks(1, &TaskQuery::default()).await.unwrap_err();
+    assert!(is_unauthorized(err.as_ref()));
+}